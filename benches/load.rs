@@ -0,0 +1,117 @@
+//! Loading benchmarks over a synthetic Claude Code history.
+//!
+//! Run with `cargo bench --bench load`. The history mimics real logs: every message is streamed
+//! as several lines, resumed sessions repeat the messages of the session they continue, so
+//! deduplication has work both within and across files, and each file was last modified when
+//! its last message was logged.
+
+use ccost::report::UsageReport;
+use criterion::{Criterion, criterion_group, criterion_main};
+use std::fmt::Write as _;
+use std::fs::File;
+use std::hint::black_box;
+use std::path::Path;
+use std::time::{Duration, UNIX_EPOCH};
+use tempfile::TempDir;
+
+const PROJECTS: usize = 8;
+const SESSIONS_PER_PROJECT: usize = 25;
+const MESSAGES_PER_SESSION: usize = 120;
+/// Lines Claude Code writes per message (one per content block).
+const LINES_PER_MESSAGE: usize = 3;
+/// Minutes between messages of a project.
+const MESSAGE_INTERVAL_MINUTES: usize = 7;
+/// 2026-01-01T00:00:00Z, where the history starts.
+const HISTORY_START_SECS: u64 = 1_767_225_600;
+const MODELS: [&str; 3] = [
+    "claude-opus-4-20250514",
+    "claude-sonnet-4-20250514",
+    "claude-haiku-4-5-20251001",
+];
+
+/// Minutes after the start of the history at which `message` of `project` is logged.
+fn message_minutes(project: usize, message: usize) -> usize {
+    project * 60 + message * MESSAGE_INTERVAL_MINUTES
+}
+
+fn message_lines(output: &mut String, session: &str, project: usize, message: usize) {
+    let model = MODELS[message % MODELS.len()];
+    let minutes = message_minutes(project, message);
+    let (day, hour, minute) = (1 + minutes / 1440, minutes / 60 % 24, minutes % 60);
+    for line in 0..LINES_PER_MESSAGE {
+        let _ = writeln!(
+            output,
+            r#"{{"type":"assistant","timestamp":"2026-01-{day:02}T{hour:02}:{minute:02}:{line:02}Z","sessionId":"{session}","uuid":"{session}-{message}-{line}","requestId":"req_{project}_{message}","message":{{"id":"msg_{project}_{message}","model":"{model}","content":[{{"type":"tool_use","id":"tool_{project}_{message}_{line}"}}],"usage":{{"input_tokens":{input},"output_tokens":{output_tokens},"cache_creation_input_tokens":1200,"cache_read_input_tokens":24000}}}}}}"#,
+            input = 40 + message % 7,
+            output_tokens = 100 + line * 50,
+        );
+    }
+}
+
+/// Writes the history and returns the number of log lines.
+fn write_history(root: &Path) -> usize {
+    let mut lines = 0;
+    for project in 0..PROJECTS {
+        let dir = root.join(format!("projects/-home-me-project-{project}"));
+        std::fs::create_dir_all(&dir).expect("create project directory");
+        for session in 0..SESSIONS_PER_PROJECT {
+            let id = format!("{project:04}{session:04}-0000-4000-8000-000000000000");
+            let mut log = String::new();
+            let first = session * MESSAGES_PER_SESSION;
+            // Every other session is resumed from the previous one and repeats its tail.
+            let repeated = if session % 2 == 1 {
+                MESSAGES_PER_SESSION / 4
+            } else {
+                0
+            };
+            for message in first - repeated..first + MESSAGES_PER_SESSION {
+                message_lines(&mut log, &id, project, message);
+                lines += LINES_PER_MESSAGE;
+            }
+            let path = dir.join(format!("{id}.jsonl"));
+            std::fs::write(&path, log).expect("write session log");
+            let last = message_minutes(project, first + MESSAGES_PER_SESSION - 1);
+            let modified = UNIX_EPOCH + Duration::from_secs(HISTORY_START_SECS + last as u64 * 60);
+            File::options()
+                .write(true)
+                .open(&path)
+                .and_then(|file| file.set_modified(modified))
+                .expect("set modification time");
+        }
+    }
+    lines
+}
+
+fn load(c: &mut Criterion) {
+    let dir = TempDir::new().expect("temporary directory");
+    let lines = write_history(dir.path());
+    let report = UsageReport::new()
+        .claude_path(dir.path())
+        .timezone(chrono_tz::UTC);
+
+    let mut group = c.benchmark_group(format!("load {lines} lines"));
+    group.sample_size(20);
+    group.bench_function("entries", |b| {
+        b.iter(|| black_box(report.entries().expect("entries")))
+    });
+    group.bench_function("daily", |b| {
+        b.iter(|| black_box(report.daily().expect("daily")))
+    });
+    group.bench_function("daily by project", |b| {
+        let report = report.clone().group_by_project(true);
+        b.iter(|| black_box(report.daily().expect("daily")))
+    });
+    group.bench_function("monthly", |b| {
+        b.iter(|| black_box(report.monthly().expect("monthly")))
+    });
+    // The last three days, where most files can be skipped without parsing.
+    group.bench_function("daily since", |b| {
+        let since = chrono::NaiveDate::from_ymd_opt(2026, 1, 13).expect("date");
+        let report = report.clone().since(since);
+        b.iter(|| black_box(report.daily().expect("daily")))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, load);
+criterion_main!(benches);
@@ -0,0 +1,163 @@
+use anyhow::{Result, anyhow};
+use serde_json::Value;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+const ARCHIVE_COMPRESSION_LEVEL: i32 = 19;
+
+/// A Claude log picked by `ccost archive`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArchivedLog {
+    pub file: PathBuf,
+    /// Path inside the archive and the archive index: `projects/` and the log's path below its
+    /// data root's projects directory.
+    pub name: PathBuf,
+}
+
+impl ArchivedLog {
+    pub fn new(file: PathBuf, projects_dir: &Path) -> Self {
+        let relative = file.strip_prefix(projects_dir).unwrap_or(&file);
+        let name = Path::new(crate::data_loader::CLAUDE_PROJECTS_DIR_NAME).join(relative);
+        Self { file, name }
+    }
+}
+
+/// Writes `logs` into a new zstd-compressed tar at `to`, which appears only once complete.
+/// Fails rather than replace an existing file.
+pub fn write_archive(logs: &[ArchivedLog], to: &Path) -> Result<()> {
+    let dir = match to.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let temp = tempfile::Builder::new()
+        .prefix(".ccost-archive-")
+        .tempfile_in(dir)
+        .map_err(|error| anyhow!("failed to create {}: {error}", to.display()))?;
+    let encoder = zstd::Encoder::new(temp.as_file(), ARCHIVE_COMPRESSION_LEVEL)?;
+    let mut tar = tar::Builder::new(encoder);
+    for log in logs {
+        tar.append_path_with_name(&log.file, &log.name)
+            .map_err(|error| anyhow!("failed to archive {}: {error}", log.file.display()))?;
+    }
+    tar.into_inner()?.finish()?.sync_all()?;
+    temp.persist_noclobber(to)
+        .map_err(|error| anyhow!("failed to write {}: {}", to.display(), error.error))?;
+    Ok(())
+}
+
+/// Copies the lines reports read from each of `logs` into the Claude data root `index`, under
+/// the log's [`ArchivedLog::name`], so its usage stays in reports once the log is removed.
+pub fn record_usage(logs: &[ArchivedLog], index: &Path) -> Result<()> {
+    for log in logs {
+        let content = std::fs::read_to_string(&log.file)
+            .map_err(|error| anyhow!("failed to read {}: {error}", log.file.display()))?;
+        let path = index.join(&log.name);
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|error| anyhow!("failed to create {}: {error}", dir.display()))?;
+        }
+        // Logs of two data roots can share a name; their lines go into one file, where
+        // deduplication still counts each request once.
+        let mut file = File::options()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|error| anyhow!("failed to write {}: {error}", path.display()))?;
+        file.write_all(usage_lines(&content).as_bytes())
+            .and_then(|()| file.sync_all())
+            .map_err(|error| anyhow!("failed to write {}: {error}", path.display()))?;
+    }
+    Ok(())
+}
+
+/// The lines of a Claude log that reports read: those carrying usage (a subagent's included),
+/// session summaries, and typed prompts, which name sessions. Tool output, which makes up most
+/// of a log, is dropped.
+pub fn usage_lines(content: &str) -> String {
+    let mut output = String::new();
+    for line in content.lines() {
+        let Ok(value) = serde_json::from_str::<Value>(line) else {
+            continue;
+        };
+        let keep = value.pointer("/message/usage").is_some()
+            || value.pointer("/data/message/message/usage").is_some()
+            || match value["type"].as_str() {
+                Some("summary") => true,
+                Some("user") => is_prompt(&value["message"]["content"]),
+                _ => false,
+            };
+        if keep {
+            output.push_str(line);
+            output.push('\n');
+        }
+    }
+    output
+}
+
+/// Whether a user message's content is typed text rather than tool results.
+fn is_prompt(content: &Value) -> bool {
+    match content {
+        Value::String(_) => true,
+        Value::Array(blocks) => blocks.iter().any(|block| block["type"] == "text"),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    const LOG: &str = concat!(
+        r#"{"type":"user","message":{"content":"Fix the login bug"},"sessionId":"s"}"#,
+        "\n",
+        r#"{"type":"assistant","message":{"id":"m1","usage":{"input_tokens":5}},"requestId":"r1"}"#,
+        "\n",
+        r#"{"type":"user","message":{"content":[{"type":"tool_result","content":"big file"}]}}"#,
+        "\n",
+        r#"{"type":"progress","data":{"message":{"message":{"usage":{"output_tokens":2}}}}}"#,
+        "\n",
+        r#"{"type":"summary","summary":"Login fix","leafUuid":"u1"}"#,
+        "\n",
+        "not json\n",
+    );
+
+    #[test]
+    fn usage_lines_keep_usage_summaries_and_prompts() {
+        let kept = usage_lines(LOG);
+        let lines = kept.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].contains("Fix the login bug"));
+        assert!(lines[1].contains(r#""requestId":"r1""#));
+        assert!(lines[2].contains("output_tokens"));
+        assert!(lines[3].contains("Login fix"));
+        assert!(!kept.contains("big file"));
+    }
+
+    #[test]
+    fn archives_logs_and_records_their_usage() {
+        let dir = tempfile::tempdir().unwrap();
+        let projects = dir.path().join("claude").join("projects");
+        std::fs::create_dir_all(projects.join("app")).unwrap();
+        let file = projects.join("app").join("s.jsonl");
+        std::fs::write(&file, LOG).unwrap();
+        let logs = [ArchivedLog::new(file.clone(), &projects)];
+        assert_eq!(logs[0].name, Path::new("projects/app/s.jsonl"));
+
+        let to = dir.path().join("old.tar.zst");
+        write_archive(&logs, &to).unwrap();
+        assert!(write_archive(&logs, &to).is_err());
+        let mut archive = tar::Archive::new(zstd::Decoder::new(File::open(&to).unwrap()).unwrap());
+        let mut entry = archive.entries().unwrap().next().unwrap().unwrap();
+        assert_eq!(entry.path().unwrap(), Path::new("projects/app/s.jsonl"));
+        let mut archived = String::new();
+        entry.read_to_string(&mut archived).unwrap();
+        assert_eq!(archived, LOG);
+
+        let index = dir.path().join("index");
+        record_usage(&logs, &index).unwrap();
+        let recorded = std::fs::read_to_string(index.join("projects/app/s.jsonl")).unwrap();
+        assert_eq!(recorded, usage_lines(LOG));
+    }
+}
@@ -0,0 +1,177 @@
+use crate::data_loader::UsageEntry;
+use chrono::{DateTime, Duration, DurationRound, Utc};
+
+/// Length of a Claude subscription rate-limit window.
+pub const BLOCK_HOURS: i64 = 5;
+
+/// Usage in one 5-hour billing window.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UsageBlock {
+    /// Hour the window opened: the first entry's timestamp rounded down to the hour.
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub first_timestamp: DateTime<Utc>,
+    pub last_timestamp: DateTime<Utc>,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub total_tokens: u64,
+    pub total_cost: f64,
+    pub entry_count: usize,
+    pub models_used: Vec<String>,
+    /// The window is still open at the time the blocks were built.
+    pub is_active: bool,
+}
+
+impl UsageBlock {
+    fn new(entry: &UsageEntry) -> Self {
+        let start = entry
+            .timestamp
+            .duration_trunc(Duration::hours(1))
+            .unwrap_or(entry.timestamp);
+        Self {
+            start,
+            end: start + Duration::hours(BLOCK_HOURS),
+            first_timestamp: entry.timestamp,
+            last_timestamp: entry.timestamp,
+            input_tokens: 0,
+            output_tokens: 0,
+            cache_creation_tokens: 0,
+            cache_read_tokens: 0,
+            total_tokens: 0,
+            total_cost: 0.0,
+            entry_count: 0,
+            models_used: Vec::new(),
+            is_active: false,
+        }
+    }
+
+    fn add_entry(&mut self, entry: &UsageEntry) {
+        self.last_timestamp = self.last_timestamp.max(entry.timestamp);
+        self.input_tokens += entry.input_tokens;
+        self.output_tokens += entry.output_tokens;
+        self.cache_creation_tokens += entry.cache_creation_tokens;
+        self.cache_read_tokens += entry.cache_read_tokens;
+        self.total_tokens += entry.total_tokens;
+        self.total_cost += entry.cost;
+        if let Some(model) = entry.model.as_deref()
+            && model != "<synthetic>"
+            && !self.models_used.iter().any(|used| used == model)
+        {
+            self.models_used.push(model.to_string());
+        }
+        self.entry_count += 1;
+    }
+
+    /// Time left before an active window closes.
+    pub fn remaining(&self, now: DateTime<Utc>) -> Option<Duration> {
+        self.is_active.then(|| self.end - now)
+    }
+
+    /// Cost per hour between the first and last entry of the block.
+    pub fn cost_per_hour(&self) -> Option<f64> {
+        let hours = (self.last_timestamp - self.first_timestamp).num_seconds() as f64 / 3600.0;
+        (hours > 0.0).then(|| self.total_cost / hours)
+    }
+
+    /// Cost of an active block by the time it closes if spending continues at
+    /// [`Self::cost_per_hour`].
+    pub fn projected_cost(&self, now: DateTime<Utc>) -> Option<f64> {
+        let remaining = self.remaining(now)?.num_seconds() as f64 / 3600.0;
+        Some(self.total_cost + self.cost_per_hour()? * remaining.max(0.0))
+    }
+}
+
+/// Splits entries into 5-hour billing blocks, oldest first. A block opens at the hour of its
+/// first entry, and the first entry at or after its end opens the next one, so idle stretches
+/// produce no blocks. The block containing `now` is marked active.
+pub fn group_entries_into_blocks<'a>(
+    entries: impl IntoIterator<Item = &'a UsageEntry>,
+    now: DateTime<Utc>,
+) -> Vec<UsageBlock> {
+    let mut sorted = entries.into_iter().collect::<Vec<_>>();
+    sorted.sort_by_key(|entry| entry.timestamp);
+
+    let mut blocks: Vec<UsageBlock> = Vec::new();
+    for entry in sorted {
+        if blocks
+            .last()
+            .is_none_or(|block| entry.timestamp >= block.end)
+        {
+            blocks.push(UsageBlock::new(entry));
+        }
+        if let Some(block) = blocks.last_mut() {
+            block.add_entry(entry);
+        }
+    }
+    for block in &mut blocks {
+        block.is_active = block.start <= now && now < block.end;
+    }
+    blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(timestamp: &str, cost: f64) -> UsageEntry {
+        let timestamp = DateTime::parse_from_rfc3339(timestamp)
+            .unwrap()
+            .with_timezone(&Utc);
+        UsageEntry {
+            timestamp,
+            date: timestamp.format("%Y-%m-%d").to_string(),
+            model: Some("claude-sonnet-4".to_string()),
+            input_tokens: 10,
+            output_tokens: 5,
+            total_tokens: 15,
+            cost,
+            ..Default::default()
+        }
+    }
+
+    fn at(timestamp: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(timestamp)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn blocks_open_at_the_hour_and_last_five_hours() {
+        let entries = vec![
+            entry("2026-01-10T14:00:00Z", 1.0),
+            entry("2026-01-10T09:30:00Z", 1.0),
+            entry("2026-01-10T11:45:00Z", 2.0),
+            entry("2026-01-10T20:10:00Z", 0.5),
+            entry("2026-01-10T21:10:00Z", 0.5),
+        ];
+
+        let blocks = group_entries_into_blocks(&entries, at("2026-01-10T22:10:00Z"));
+
+        assert_eq!(
+            blocks
+                .iter()
+                .map(|block| (block.start, block.entry_count, block.total_cost))
+                .collect::<Vec<_>>(),
+            vec![
+                (at("2026-01-10T09:00:00Z"), 2, 3.0),
+                (at("2026-01-10T14:00:00Z"), 1, 1.0),
+                (at("2026-01-10T20:00:00Z"), 2, 1.0),
+            ]
+        );
+        assert_eq!(blocks[0].end, at("2026-01-10T14:00:00Z"));
+        assert!(!blocks[0].is_active);
+        assert!(blocks[2].is_active);
+        assert_eq!(
+            blocks[2].remaining(at("2026-01-10T22:10:00Z")),
+            Some(Duration::minutes(170))
+        );
+        assert_eq!(blocks[2].cost_per_hour(), Some(1.0));
+        assert_eq!(
+            blocks[2].projected_cost(at("2026-01-10T22:00:00Z")),
+            Some(4.0)
+        );
+        assert_eq!(blocks[0].projected_cost(at("2026-01-10T22:00:00Z")), None);
+    }
+}
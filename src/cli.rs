@@ -1,25 +1,90 @@
+use crate::archive::{ArchivedLog, record_usage, write_archive};
+use crate::blocks::{UsageBlock, group_entries_into_blocks};
+use crate::config::{
+    Config, archive_dir, config_path, imports_dir, load_config, parse_cache_path, parse_config,
+    pricing_path, snapshots_dir,
+};
 use crate::data_loader::{
-    DailyUsage, LoadOptions, ModelBreakdown, MonthlyUsage, UsageTotals, calculate_totals_daily,
-    calculate_totals_monthly, group_daily_by_project, load_daily_usage_data,
-    load_monthly_usage_data,
+    CLAUDE_PROJECTS_DIR_NAME, DailyUsage, DedupPolicy, HourlyUsage, LoadOptions, ModelBreakdown,
+    MonthlyUsage, ParseCache, ReadLimits, UsageEntry, UsageSource, UsageTotals,
+    calculate_totals_daily, calculate_totals_entries, calculate_totals_monthly, claude_log_files,
+    get_claude_paths, group_daily_by_project, group_entries_by_hour, load_daily_usage_data,
+    load_monthly_usage_data, load_session_titles, load_usage_entries, parse_byte_size,
+    usage_log_roots, weekday_hour_costs, widen_activity,
+};
+use crate::diff::{UsageChange, compare_periods, preceding_period};
+use crate::directories::{DirectoryUsage, directory_usage};
+use crate::distribution::{TokenHistogram, request_size_distribution};
+use crate::duplicates::{enable_duplicate_report, print_duplicate_report};
+use crate::email::{EmailMessage, compose_message, send_message};
+use crate::error::{ErrorCode, coded_error, json_error};
+use crate::forecast::{DEFAULT_WINDOW_DAYS, forecast_month};
+use crate::ignore::IgnorePatterns;
+use crate::import::{
+    DEFAULT_IMPORT_PROJECT, claude_usage_line, imported_ids, parse_ccusage_export,
+    parse_csv_export, parse_csv_mapping, parse_openrouter_export, project_dir_name, record_ids,
+};
+use crate::init::{ask_init_answers, init_config_toml};
+use crate::model_filter::ModelFilter;
+use crate::models::{MixPeriod, ModelUsage, group_entries_by_model, premium_mix};
+use crate::notify::{
+    NotifySummary, post_webhook, summary_text, top_projects_by_cost, webhook_payload,
+};
+use crate::pager::write_paged;
+use crate::pdf::monthly_report_pdf;
+use crate::pricing::{CostMode, PricingFetcher, load_pricing_overrides, set_pricing_overrides};
+use crate::projects::{ProjectUsage, group_entries_by_project};
+use crate::query::{ENTRIES_SCHEMA, run_query};
+use crate::recap::recap_markdown;
+use crate::schedule::ScheduledReport;
+use crate::sessions::{
+    CacheEfficiency, ModelSwitchSummary, SessionUsage, active_sessions, analyze_cache_efficiency,
+    group_entries_by_session, summarize_model_switches,
 };
-use crate::pricing::CostMode;
+use crate::snapshot::{
+    Drift, DriftKind, Snapshot, SnapshotDay, detect_drift, latest_snapshot_path, load_snapshot,
+    save_snapshot,
+};
+use crate::stop_reasons::{StopReasonPeriod, stop_reason_counts, stop_reason_totals};
 use crate::table::{
-    ModelBreakdownRow, TableMode, TokenFormat, UsageDataRow, build_breakdown_rows,
-    build_totals_row, build_usage_row,
+    BREAKDOWN_PREFIX, ExtraColumn, ModelBreakdownRow, TableMode, TokenFormat, UsageDataRow,
+    build_breakdown_rows, build_extra_cells, build_totals_row, build_usage_row, cost_header,
+    currency_code, extra_column_headers, format_currency, format_currency_rate, format_duration,
+    format_model_name, format_models_display_truncated, format_number, format_tokens,
+    set_display_currency, set_model_name_rules,
+};
+use crate::template::Template;
+use crate::tiers::{TierPeriod, TierUsage, service_tier_usage, tier_totals};
+use crate::time_utils::{
+    RelativeRange, SortOrder, format_date_compact, format_timestamp, parse_last_days,
+    today_in_timezone,
 };
-use crate::time_utils::{SortOrder, format_date_compact};
+use crate::timings::{enable_timings, print_timings};
+use crate::trend::{PROJECTION_DAYS, cost_trend, z_scores};
+use crate::warnings::{print_warnings, warn};
+use crate::watch::LogWatcher;
+use crate::whatif::{Scenario, WhatIfModel, parse_model_map, simulate};
 use anyhow::{Result, anyhow};
-use clap::{Args, Parser, Subcommand, ValueEnum};
-use comfy_table::Table;
+use chrono::{DateTime, Datelike, Local, Months, NaiveDate, Utc};
+use clap::{Args, CommandFactory, Parser, Subcommand, ValueEnum};
+use comfy_table::{Cell, Color, Table};
 use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::fmt::Write as _;
+use std::io::{IsTerminal, Write as _};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use terminal_size::terminal_size;
 
+const TABLE_PRESET: &str = "││──╞═╪╡│─┼├┤┬┴┌┐└┘";
+
 #[derive(Parser)]
 #[command(
     name = "ccost",
     version,
-    about = "Claude Code / Codex / OpenCode usage report (daily/monthly)"
+    about = "Claude Code / Codex / OpenCode usage report (daily/monthly)",
+    args_override_self = true
 )]
 pub struct Cli {
     #[command(subcommand)]
@@ -28,13 +93,88 @@ pub struct Cli {
 
 #[derive(Subcommand)]
 pub enum Command {
+    #[command(visible_alias = "d")]
     Daily(DailyArgs),
+    #[command(visible_alias = "m")]
     Monthly(MonthlyArgs),
+    /// Break one day's usage down by hour to line up spend spikes with work sessions
+    Hourly(HourlyArgs),
+    /// Print a one-line summary of today's and this month's usage
+    Summary(SummaryArgs),
+    /// Project this month's spend from the recent daily run-rate
+    Forecast(ForecastArgs),
+    /// Compare tokens and cost per model with an earlier period (by default the one just before)
+    Diff(DiffArgs),
+    /// Show the cost of a single conversation, message by message
+    Conversation(ConversationArgs),
+    /// Report usage per session with duration and hourly rates
+    #[command(visible_alias = "sessions")]
+    Session(SessionArgs),
+    /// List sessions with activity in the last few minutes
+    Active(ActiveArgs),
+    /// Group usage into 5-hour billing blocks and show the time left in the active one
+    Blocks(BlocksArgs),
+    /// Redraw today's sessions and the active block every few seconds until interrupted
+    Live(LiveArgs),
+    /// Browse daily, monthly, project, and session tables in an interactive dashboard
+    Tui(TuiArgs),
+    /// One row per project with totals, first and last activity, and the models used
+    Projects(ProjectsArgs),
+    /// List the most expensive days, sessions, or projects
+    Top(TopArgs),
+    /// Rank models by cost per message, cost per output token, and cache-read share
+    Models(ModelsArgs),
+    /// Track the ratio of premium (Opus) to standard (Sonnet/Haiku) tokens per day or month
+    Mix(MixArgs),
+    /// Break down requests, tokens, and cost by API service tier (standard, priority, batch)
+    Tiers(TiersArgs),
+    /// Count why responses ended (end_turn, max_tokens, refusal, ...) per day or month
+    StopReasons(StopReasonsArgs),
+    /// Histogram of per-request prompt and output token sizes
+    Distribution(DistributionArgs),
+    /// Report model switches within sessions and the cost on either side of them
+    #[command(visible_alias = "switches")]
+    ModelSwitches(ModelSwitchesArgs),
+    /// Post today's and this month's totals to a Slack or Discord webhook
+    Notify(NotifyArgs),
+    /// Email the monthly report through an SMTP server
+    Email(EmailArgs),
+    /// Run the reports configured under [[schedule]] at their times until interrupted
+    Schedule(ScheduleArgs),
+    /// Render a report from a user template (Tera syntax)
+    Render(RenderArgs),
+    /// Print shields.io endpoint JSON for a README badge
+    Badge(BadgeArgs),
+    /// Write a Markdown recap of one month for engineering reports
+    Recap(RecapArgs),
+    /// Import usage exported by other tools (ccusage JSON or CSV)
+    Import(ImportArgs),
+    /// Detect data directories, ask for a timezone and budgets, and write the config file
+    Init(InitArgs),
+    /// Run a SQL query against the usage entries (table `entries`)
+    Query(QueryArgs),
+    /// Save the current daily totals for later comparison with `drift`
+    Snapshot(SnapshotArgs),
+    /// Compare current data with the last snapshot and list days whose totals changed
+    Drift(DriftArgs),
+    /// Report prompt-cache writes per session and estimate the ones never read back
+    Cache(CacheArgs),
+    /// Show cost by weekday and hour of day as a 7x24 grid
+    Matrix(MatrixArgs),
+    /// Compare calculated costs with a hypothetical scenario, such as no prompt caching
+    Whatif(WhatifArgs),
+    /// Write one daily report file per project (CSV, JSON, or Markdown) into a directory, or
+    /// push usage to an OpenTelemetry collector with `export otlp`
+    Export(ExportArgs),
+    /// Compress old Claude Code logs into a .tar.zst, optionally removing them while keeping
+    /// their usage in reports
+    Archive(ArchiveArgs),
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq, ValueEnum)]
 enum Agent {
     Codex,
+    #[value(alias = "claude")]
     Claudecode,
     Opencode,
     All,
@@ -63,12 +203,68 @@ pub struct CommonArgs {
     since: Option<String>,
     #[arg(short, long, help = "Filter until date (YYYYMMDD format)")]
     until: Option<String>,
+    #[arg(
+        long,
+        value_name = "SPAN",
+        value_parser = parse_last_days,
+        group = "relative_range",
+        conflicts_with_all = ["since", "until"],
+        help = "Only the last SPAN, ending today: days or weeks, e.g. 7d or 2w"
+    )]
+    last: Option<u32>,
+    #[arg(
+        long,
+        group = "relative_range",
+        conflicts_with_all = ["since", "until"],
+        help = "Only yesterday"
+    )]
+    yesterday: bool,
+    #[arg(
+        long,
+        group = "relative_range",
+        conflicts_with_all = ["since", "until"],
+        help = "Only this week, from Monday"
+    )]
+    this_week: bool,
+    #[arg(
+        long,
+        group = "relative_range",
+        conflicts_with_all = ["since", "until"],
+        help = "Only this month"
+    )]
+    this_month: bool,
     #[arg(short = 'j', long, help = "Output in JSON format")]
     json: bool,
-    #[arg(short, long, default_value = "auto", help = "Cost calculation mode")]
-    mode: String,
-    #[arg(short, long, default_value = "asc", help = "Sort order: asc or desc")]
-    order: String,
+    #[arg(
+        long,
+        value_enum,
+        default_value = "table",
+        conflicts_with = "json",
+        help = "Output format: table, json, csv, md, ndjson, or pdf (monthly only); --json is the same as --format json"
+    )]
+    format: OutputFormat,
+    #[arg(
+        long,
+        value_name = "FIELDS",
+        value_delimiter = ',',
+        help = "Keep only these JSON fields of each record and of the totals, e.g. date,totalCost; with --format csv, one column per field"
+    )]
+    fields: Vec<String>,
+    #[arg(
+        long,
+        help = "Print only the totals object of the JSON report (one row with --format csv)"
+    )]
+    totals_only: bool,
+    #[arg(
+        short,
+        long,
+        value_enum,
+        default_value = "auto",
+        help = "Cost calculation mode"
+    )]
+    mode: CostMode,
+    #[arg(short, long, value_enum, default_value = "asc", help = "Sort order")]
+    order: SortOrder,
     #[arg(short, long, help = "Show per-model cost breakdown")]
     breakdown: bool,
     #[arg(
@@ -80,21 +276,209 @@ pub struct CommonArgs {
     offline: bool,
     #[arg(short, long, help = "Timezone for date grouping")]
     timezone: Option<String>,
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Per-model rates overriding the built-in pricing (default: pricing.toml next to the config file)"
+    )]
+    pricing_file: Option<std::path::PathBuf>,
     #[arg(long, default_value_t = false, help = "Force compact mode")]
     compact: bool,
     #[arg(long, help = "Format table token counts with K, M, or B suffixes")]
     kmb: bool,
     #[arg(
         long,
+        visible_alias = "source",
         value_enum,
         value_delimiter = ',',
-        default_value = "all",
-        help = "Usage data source: all, codex, claudecode, or opencode"
+        help = "Usage data source: all (default), codex, claudecode (or claude), or opencode"
     )]
     agent: Vec<Agent>,
+    #[arg(long, help = "Never pipe long output through a pager")]
+    no_pager: bool,
+    #[arg(
+        long,
+        value_name = "N",
+        help = "Show at most N models per table row (JSON keeps the full list)"
+    )]
+    max_models: Option<usize>,
+    #[arg(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        help = "Extra columns to show: messages, subagents, tools, errors, sessions, activity, hours, z-score"
+    )]
+    columns: Vec<ExtraColumn>,
+    #[arg(
+        long,
+        value_enum,
+        default_value = "auto",
+        help = "Duplicate handling: auto, off, keep-first, or keep-latest (or keep-last)"
+    )]
+    dedup: DedupPolicy,
+    #[arg(
+        long,
+        help = "Print to stderr how many duplicate records deduplication skipped in each file"
+    )]
+    report_duplicates: bool,
+    #[arg(
+        long,
+        help = "Also deduplicate Claude entries without a requestId by message id, timestamp, and token counts"
+    )]
+    dedup_fallback: bool,
+    #[arg(
+        long,
+        help = "Leave out Claude Code's placeholder entries for failed API requests (isApiErrorMessage)"
+    )]
+    skip_errors: bool,
+    #[arg(
+        long,
+        help = "Keep <synthetic> entries in model lists and breakdowns instead of hiding them"
+    )]
+    include_synthetic: bool,
+    #[arg(
+        long,
+        value_name = "BOOL",
+        action = clap::ArgAction::Set,
+        num_args = 0..=1,
+        default_value_t = true,
+        default_missing_value = "true",
+        help = "Follow symlinked directories while discovering usage files"
+    )]
+    follow_symlinks: bool,
+    #[arg(
+        long,
+        value_name = "BOOL",
+        action = clap::ArgAction::Set,
+        num_args = 0..=1,
+        default_value_t = true,
+        default_missing_value = "true",
+        help = "Include Claude Code-format logs found in Claude Desktop's app data directory"
+    )]
+    claude_desktop: bool,
+    #[arg(
+        long,
+        value_name = "PATTERN",
+        help = "Skip files or directories matching a gitignore-style pattern (repeatable)"
+    )]
+    ignore: Vec<String>,
+    #[arg(
+        long = "model",
+        value_name = "PATTERN",
+        help = "Only count usage of models matching PATTERN: a glob such as 'opus*' or a substring (repeatable)"
+    )]
+    models: Vec<String>,
+    #[arg(
+        long = "exclude-model",
+        value_name = "PATTERN",
+        help = "Leave out usage of models matching PATTERN (repeatable)"
+    )]
+    exclude_models: Vec<String>,
+    #[arg(
+        long,
+        value_name = "SIZE",
+        value_parser = parse_byte_size,
+        help = "Skip JSONL lines longer than SIZE (e.g. 64M; default 64M)"
+    )]
+    max_line_size: Option<u64>,
+    #[arg(
+        long,
+        value_name = "SIZE",
+        value_parser = parse_byte_size,
+        help = "Skip log files larger than SIZE (e.g. 4G; default 4G)"
+    )]
+    max_file_size: Option<u64>,
+    #[arg(
+        long,
+        value_name = "NAME",
+        help = "Use a named profile from the config file"
+    )]
+    profile: Option<String>,
+    #[arg(
+        long = "path",
+        visible_alias = "claude-path",
+        value_name = "DIR",
+        help = "Claude Code data directory containing projects/ (repeatable; replaces the default locations)"
+    )]
+    paths: Vec<std::path::PathBuf>,
+    #[arg(
+        long,
+        help = "Report which data directories were checked and which matched"
+    )]
+    verbose: bool,
+    #[arg(
+        short = 'q',
+        long,
+        help = "Print only the table or JSON: no title, hints, footnotes, or no-data messages"
+    )]
+    quiet: bool,
+    #[arg(
+        long,
+        help = "Print to stderr how long discovery, parsing, dedup, aggregation, and rendering took"
+    )]
+    timings: bool,
+    #[arg(
+        long,
+        help = "Parse every Claude Code log instead of reusing results cached from earlier runs"
+    )]
+    no_parse_cache: bool,
+    #[arg(
+        long,
+        help = "Parse every log, even those --since/--until rule out by modification time or first timestamp"
+    )]
+    scan_all_files: bool,
+    #[arg(long, help = "Exit with an error when the report finds no usage data")]
+    fail_empty: bool,
+    /// File the report is written to instead of stdout, set by `export`.
+    #[arg(skip)]
+    output_file: Option<std::path::PathBuf>,
 }
 
 impl CommonArgs {
+    /// Folds `--json` into `--format` and sets the flags report paths branch on: `json` for
+    /// the JSON-based formats and for CSV, which is built from the JSON records, and `quiet`
+    /// for CSV and Markdown, which carry only the data. Resolving twice changes nothing.
+    fn resolve_format(&mut self) {
+        if self.json && self.format == OutputFormat::Table {
+            self.format = OutputFormat::Json;
+        }
+        self.json = self.format.is_json() || self.format == OutputFormat::Csv;
+        self.quiet |= self.format.is_plain_table();
+    }
+
+    /// Whether `--fields` or `--totals-only` trims the JSON report.
+    fn selects_json(&self) -> bool {
+        !self.fields.is_empty() || self.totals_only
+    }
+
+    fn relative_range(&self) -> Option<RelativeRange> {
+        if let Some(days) = self.last {
+            Some(RelativeRange::LastDays(days))
+        } else if self.yesterday {
+            Some(RelativeRange::Yesterday)
+        } else if self.this_week {
+            Some(RelativeRange::ThisWeek)
+        } else if self.this_month {
+            Some(RelativeRange::ThisMonth)
+        } else {
+            None
+        }
+    }
+
+    /// Turns `--last` and the other relative ranges into `--since` and `--until`, counting
+    /// days in the report timezone.
+    fn resolve_relative_range(&mut self) -> Result<()> {
+        let Some(range) = self.relative_range() else {
+            return Ok(());
+        };
+        let today = today_in_timezone(self.timezone.as_deref())
+            .ok_or_else(|| invalid_timezone(self.timezone.as_deref().unwrap_or_default()))?;
+        let (since, until) = range.bounds(today);
+        self.since = Some(since.format("%Y%m%d").to_string());
+        self.until = Some(until.format("%Y%m%d").to_string());
+        Ok(())
+    }
+
     fn agent_flags(&self) -> AgentFlags {
         if self.agent.is_empty() || self.agent.contains(&Agent::All) {
             return AgentFlags::all();
@@ -116,300 +500,5618 @@ pub struct DailyArgs {
     instances: bool,
     #[arg(short = 'p', long, help = "Filter to specific project name")]
     project: Option<String>,
+    #[arg(
+        long,
+        help = "Fit a trend line through daily cost and project the next 30 days"
+    )]
+    trend: bool,
+    #[arg(long, help = BY_DIRECTORY_HELP)]
+    by_directory: bool,
+    #[arg(
+        long,
+        value_name = "USD",
+        value_parser = parse_budget,
+        help = "Highlight days costing more than USD and warn about them (default: budgets.daily in the config)"
+    )]
+    budget: Option<f64>,
+    #[arg(long, help = FAIL_ON_OVER_BUDGET_HELP)]
+    fail_on_over_budget: bool,
+    #[arg(
+        long,
+        value_enum,
+        conflicts_with_all = ["instances", "trend", "by_directory"],
+        help = GROUP_BY_HELP
+    )]
+    group_by: Option<GroupBy>,
 }
 
-#[derive(Args, Clone)]
-pub struct MonthlyArgs {
-    #[command(flatten)]
-    common: CommonArgs,
+/// Primary grouping of `daily` and `monthly` rows other than the period itself.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, ValueEnum)]
+enum GroupBy {
+    /// One row per model in each period
+    Model,
 }
 
-#[derive(Debug, Serialize)]
-#[serde(rename_all = "camelCase")]
-struct TotalsOutput {
-    input_tokens: u64,
-    output_tokens: u64,
-    cache_creation_tokens: u64,
-    cache_read_tokens: u64,
-    total_tokens: u64,
-    total_cost: f64,
-}
+const GROUP_BY_HELP: &str =
+    "Make each row one model in one period, with its share of the period's cost";
+
+const BY_DIRECTORY_HELP: &str =
+    "Also break totals down by data directory (Claude data root, Codex home, OpenCode directory)";
+
+const FAIL_ON_OVER_BUDGET_HELP: &str =
+    "Exit with an error when any row is over budget, for CI and cron cost guards";
 
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
-struct DailyMetadataOutput {
-    agents: Vec<String>,
+struct BudgetOutput {
+    amount: f64,
+    over_budget: Vec<OverBudgetOutput>,
 }
 
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
-struct DailyEntryOutput {
-    agent: String,
-    cache_creation_tokens: u64,
-    cache_read_tokens: u64,
-    input_tokens: u64,
-    metadata: DailyMetadataOutput,
-    model_breakdowns: Vec<ModelBreakdownOutput>,
-    models_used: Vec<String>,
-    output_tokens: u64,
+struct OverBudgetOutput {
     period: String,
-    total_cost: f64,
-    total_tokens: u64,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    project: Option<String>,
+    cost: f64,
 }
 
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
-struct MonthlyEntryOutput {
-    month: String,
+struct DirectoryUsageOutput {
+    directory: String,
+    request_count: u64,
     input_tokens: u64,
     output_tokens: u64,
     cache_creation_tokens: u64,
     cache_read_tokens: u64,
     total_tokens: u64,
     total_cost: f64,
-    models_used: Vec<String>,
-    model_breakdowns: Vec<ModelBreakdownOutput>,
 }
 
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
-struct ModelBreakdownOutput {
-    model_name: String,
-    input_tokens: u64,
-    output_tokens: u64,
-    cache_creation_tokens: u64,
-    cache_read_tokens: u64,
-    cost: f64,
-}
-
-pub fn run() -> Result<()> {
-    let mut args = std::env::args_os().collect::<Vec<_>>();
-    let needs_default = match args.get(1).and_then(|arg| arg.to_str()) {
-        None => true,
-        Some(arg) => {
-            if arg.starts_with('-') {
-                !matches!(arg, "-h" | "--help" | "-V" | "--version")
-            } else {
-                false
-            }
-        }
-    };
-    if needs_default {
-        args.insert(1, std::ffi::OsString::from("daily"));
-    }
-    let cli = Cli::parse_from(args);
-    match cli.command {
-        Command::Daily(args) => run_daily(args),
-        Command::Monthly(args) => run_monthly(args),
-    }
+struct CostTrendOutput {
+    days: u32,
+    slope_per_day: f64,
+    current_daily_cost: f64,
+    projection_days: u32,
+    projected_cost: f64,
+    r_squared: f64,
 }
 
-fn parse_cost_mode(value: &str) -> Result<CostMode> {
-    value
-        .parse::<CostMode>()
-        .map_err(|_| anyhow!("Invalid cost mode: {value}"))
+#[derive(Args, Clone)]
+pub struct HourlyArgs {
+    #[command(flatten)]
+    common: CommonArgs,
+    #[arg(
+        long,
+        value_name = "YYYYMMDD",
+        value_parser = parse_day,
+        conflicts_with_all = ["since", "until", "relative_range"],
+        help = "Day to break down (default: today, unless a date range is given)"
+    )]
+    date: Option<NaiveDate>,
 }
 
-fn parse_sort_order(value: &str) -> Result<SortOrder> {
-    value
-        .parse::<SortOrder>()
-        .map_err(|_| anyhow!("Invalid sort order: {value}"))
+#[derive(Args, Clone)]
+pub struct MonthlyArgs {
+    #[command(flatten)]
+    common: CommonArgs,
+    #[arg(short = 'p', long, help = "Filter to specific project name")]
+    project: Option<String>,
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Write the report to FILE (PDF goes to stdout only when it is not a terminal)"
+    )]
+    output: Option<std::path::PathBuf>,
+    #[arg(long, help = BY_DIRECTORY_HELP)]
+    by_directory: bool,
+    #[arg(
+        long,
+        value_name = "USD",
+        value_parser = parse_budget,
+        help = "Highlight months costing more than USD and warn about them (default: budgets.monthly in the config)"
+    )]
+    budget: Option<f64>,
+    #[arg(long, help = FAIL_ON_OVER_BUDGET_HELP)]
+    fail_on_over_budget: bool,
+    #[arg(long, value_enum, conflicts_with = "by_directory", help = GROUP_BY_HELP)]
+    group_by: Option<GroupBy>,
 }
 
-fn common_options(args: &CommonArgs) -> Result<LoadOptions> {
-    let agents = args.agent_flags();
-    Ok(LoadOptions {
-        mode: parse_cost_mode(&args.mode)?,
-        order: parse_sort_order(&args.order)?,
-        offline: args.offline,
-        codex: agents.codex,
-        claudecode: agents.claudecode,
-        opencode: agents.opencode,
-        since: args.since.clone(),
-        until: args.until.clone(),
-        timezone: args.timezone.clone(),
-        ..LoadOptions::default()
-    })
+#[derive(Clone, Copy, Debug, Eq, PartialEq, ValueEnum)]
+enum OutputFormat {
+    Table,
+    Json,
+    /// The table's cells as comma-separated values
+    Csv,
+    /// The table as a GitHub-flavored Markdown table
+    #[value(alias = "markdown")]
+    Md,
+    /// The JSON report's records, one object per line
+    Ndjson,
+    /// Paginated PDF with breakdown tables and a cost-per-month chart (monthly only)
+    Pdf,
 }
 
-fn run_daily(args: DailyArgs) -> Result<()> {
-    let mut options = common_options(&args.common)?;
-    options.group_by_project = args.instances;
-    options.project = args.project.clone();
-
-    let daily = load_daily_usage_data(options)?;
-    if daily.is_empty() {
-        if args.common.json {
-            println!("[]");
-        } else {
-            eprintln!("No usage data found.");
-        }
-        return Ok(());
+impl OutputFormat {
+    /// JSON and NDJSON, which serialize the report instead of drawing a table.
+    fn is_json(self) -> bool {
+        matches!(self, Self::Json | Self::Ndjson)
     }
 
-    let totals = calculate_totals_daily(&daily);
-
-    if args.common.json {
-        if args.instances && daily.iter().any(|d| d.project.is_some()) {
-            let grouped = group_daily_by_project(&daily);
-            let mut projects_output = std::collections::HashMap::new();
-            for (project, entries) in grouped {
-                let mapped = entries
-                    .into_iter()
-                    .map(|entry| daily_entry_output(entry, false))
-                    .collect::<Vec<_>>();
-                projects_output.insert(project, mapped);
-            }
-            let json = serde_json::json!({
-                "projects": projects_output,
-                "totals": totals_output(totals)
-            });
-            println!("{}", serde_json::to_string_pretty(&json)?);
-        } else {
-            let json = serde_json::json!({
-                "daily": daily.into_iter().map(|entry| daily_entry_output(entry, true)).collect::<Vec<_>>(),
-                "totals": totals_output(totals)
-            });
-            println!("{}", serde_json::to_string_pretty(&json)?);
-        }
-        return Ok(());
+    /// CSV and Markdown, which carry the table's cells without borders or styling.
+    fn is_plain_table(self) -> bool {
+        matches!(self, Self::Csv | Self::Md)
     }
+}
 
-    println!("{}", report_title("Daily", &args.common));
+#[derive(Args, Clone)]
+pub struct ForecastArgs {
+    #[command(flatten)]
+    common: CommonArgs,
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = DEFAULT_WINDOW_DAYS,
+        value_parser = clap::value_parser!(u32).range(1..=90),
+        help = "Average the daily cost of the last N days, including today, for the run-rate"
+    )]
+    days: u32,
+    #[arg(short = 'p', long, help = "Filter to specific project name")]
+    project: Option<String>,
+    #[arg(
+        long,
+        value_name = "USD",
+        value_parser = parse_budget,
+        help = "Compare the projection with this monthly budget (default: budgets.monthly in the config)"
+    )]
+    budget: Option<f64>,
+    #[arg(
+        long,
+        help = "Exit with an error when the projection is over budget, for CI and cron cost guards"
+    )]
+    fail_on_over_budget: bool,
+}
 
-    let mode = table_mode(args.common.compact);
-    let token_format = token_format(args.common.kmb);
-    let mut table = usage_table("Date", mode);
+#[derive(Args, Clone)]
+pub struct DiffArgs {
+    #[command(flatten)]
+    common: CommonArgs,
+    #[arg(
+        long,
+        value_name = "YYYYMMDD",
+        value_parser = parse_day,
+        requires = "vs_until",
+        help = "First day of the period to compare with (default: the days just before --since, as many as the period spans)"
+    )]
+    vs_since: Option<NaiveDate>,
+    #[arg(
+        long,
+        value_name = "YYYYMMDD",
+        value_parser = parse_day,
+        requires = "vs_since",
+        help = "Last day of the period to compare with"
+    )]
+    vs_until: Option<NaiveDate>,
+    #[arg(short = 'p', long, help = "Filter to specific project name")]
+    project: Option<String>,
+}
+
+#[derive(Args, Clone)]
+pub struct SummaryArgs {
+    #[command(flatten)]
+    common: CommonArgs,
+}
+
+#[derive(Args, Clone)]
+pub struct ConversationArgs {
+    #[arg(help = "Session ID (or a unique prefix of it)")]
+    session_id: String,
+    #[command(flatten)]
+    common: CommonArgs,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, ValueEnum)]
+enum SessionSort {
+    LastActivity,
+    Cost,
+    Tokens,
+    Duration,
+}
+
+#[derive(Args, Clone)]
+pub struct SessionArgs {
+    #[command(flatten)]
+    common: CommonArgs,
+    #[arg(short = 'p', long, help = "Filter to specific project name")]
+    project: Option<String>,
+    #[arg(
+        long,
+        value_enum,
+        default_value = "last-activity",
+        help = "Sort sessions; cost, tokens, and duration rank the largest first"
+    )]
+    sort: SessionSort,
+    #[arg(
+        long,
+        value_name = "N",
+        help = "Show only the first N sessions after sorting"
+    )]
+    top: Option<usize>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, ValueEnum)]
+enum ModelSort {
+    /// Cheapest per 1K output tokens first
+    Efficiency,
+    /// Most expensive overall first
+    Cost,
+    /// Cheapest per message first
+    PerMessage,
+    /// Highest cache-read share first
+    CacheRead,
+}
+
+#[derive(Args, Clone)]
+pub struct ModelsArgs {
+    #[command(flatten)]
+    common: CommonArgs,
+    #[arg(short = 'p', long, help = "Filter to specific project name")]
+    project: Option<String>,
+    #[arg(
+        long,
+        value_enum,
+        default_value = "efficiency",
+        help = "Rank models by cost per 1K output tokens, total cost, cost per message, or cache-read share"
+    )]
+    sort: ModelSort,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ModelUsageOutput {
+    rank: usize,
+    model: String,
+    message_count: u64,
+    input_tokens: u64,
+    output_tokens: u64,
+    cache_creation_tokens: u64,
+    cache_read_tokens: u64,
+    total_tokens: u64,
+    total_cost: f64,
+    cost_per_message: Option<f64>,
+    cost_per_1k_output_tokens: Option<f64>,
+    cache_read_share: Option<f64>,
+    /// Fraction of the total cost of all models.
+    cost_share: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ForecastOutput {
+    month: String,
+    days_in_month: u32,
+    days_elapsed: u32,
+    days_remaining: u32,
+    month_to_date_cost: f64,
+    window_days: u32,
+    daily_run_rate: f64,
+    daily_std_dev: f64,
+    projected_cost: f64,
+    projected_low: f64,
+    projected_high: f64,
+    budget: Option<ForecastBudgetOutput>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ForecastBudgetOutput {
+    amount: f64,
+    /// Projected cost as a fraction of the budget.
+    projected_share: Option<f64>,
+    over_budget: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DiffPeriodOutput {
+    since: String,
+    until: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DiffModelOutput {
+    model: String,
+    baseline_tokens: u64,
+    tokens: u64,
+    token_change: i64,
+    /// Token change as a fraction of the baseline; `null` when the baseline had none.
+    token_change_ratio: Option<f64>,
+    baseline_cost: f64,
+    cost: f64,
+    cost_change: f64,
+    cost_change_ratio: Option<f64>,
+}
+
+impl From<&UsageChange> for DiffModelOutput {
+    fn from(change: &UsageChange) -> Self {
+        Self {
+            model: change.model.clone(),
+            baseline_tokens: change.baseline.total_tokens,
+            tokens: change.current.total_tokens,
+            token_change: change.token_change(),
+            token_change_ratio: change.token_change_ratio(),
+            baseline_cost: change.baseline.cost,
+            cost: change.current.cost,
+            cost_change: change.cost_change(),
+            cost_change_ratio: change.cost_change_ratio(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PeriodModelOutput {
+    /// Day (`YYYY-MM-DD`) or month (`YYYY-MM`) of the row.
+    period: String,
+    model: String,
+    input_tokens: u64,
+    output_tokens: u64,
+    cache_creation_tokens: u64,
+    cache_read_tokens: u64,
+    total_tokens: u64,
+    cost: f64,
+    /// Fraction of the period's cost.
+    cost_share: Option<f64>,
+}
+
+#[derive(Args, Clone)]
+pub struct MixArgs {
+    #[command(flatten)]
+    common: CommonArgs,
+    #[arg(short = 'p', long, help = "Filter to specific project name")]
+    project: Option<String>,
+    #[arg(long, help = "Group by month instead of by day")]
+    monthly: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MixPeriodOutput {
+    period: String,
+    premium_tokens: u64,
+    standard_tokens: u64,
+    ratio: Option<f64>,
+    premium_share: Option<f64>,
+    /// Change in premium share from the previous period, in percentage points.
+    change: Option<f64>,
+}
+
+#[derive(Args, Clone)]
+pub struct TiersArgs {
+    #[command(flatten)]
+    common: CommonArgs,
+    #[arg(short = 'p', long, help = "Filter to specific project name")]
+    project: Option<String>,
+    #[arg(long, help = "Group by month instead of by day")]
+    monthly: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TierUsageOutput {
+    period: String,
+    tier: String,
+    request_count: u64,
+    /// Fraction of the period's requests.
+    request_share: Option<f64>,
+    total_tokens: u64,
+    total_cost: f64,
+}
+
+#[derive(Args, Clone)]
+pub struct StopReasonsArgs {
+    #[command(flatten)]
+    common: CommonArgs,
+    #[arg(short = 'p', long, help = "Filter to specific project name")]
+    project: Option<String>,
+    #[arg(long, help = "Group by month instead of by day")]
+    monthly: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct StopReasonOutput {
+    period: String,
+    stop_reason: String,
+    request_count: u64,
+    /// Fraction of the period's requests.
+    request_share: Option<f64>,
+    output_tokens: u64,
+    total_cost: f64,
+}
+
+#[derive(Args, Clone)]
+pub struct DistributionArgs {
+    #[command(flatten)]
+    common: CommonArgs,
+    #[arg(short = 'p', long, help = "Filter to specific project name")]
+    project: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TokenHistogramOutput {
+    request_count: u64,
+    median: u64,
+    p90: u64,
+    max: u64,
+    buckets: Vec<TokenBucketOutput>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TokenBucketOutput {
+    range: String,
+    min_tokens: u64,
+    /// Exclusive; `None` for the open-ended last bucket.
+    max_tokens: Option<u64>,
+    request_count: u64,
+    request_share: f64,
+    total_tokens: u64,
+    total_cost: f64,
+}
+
+impl From<&TokenHistogram> for TokenHistogramOutput {
+    fn from(histogram: &TokenHistogram) -> Self {
+        Self {
+            request_count: histogram.request_count,
+            median: histogram.median,
+            p90: histogram.p90,
+            max: histogram.max,
+            buckets: histogram
+                .buckets
+                .iter()
+                .map(|bucket| TokenBucketOutput {
+                    range: bucket.label(),
+                    min_tokens: bucket.min,
+                    max_tokens: bucket.max,
+                    request_count: bucket.request_count,
+                    request_share: bucket.request_count as f64 / histogram.request_count as f64,
+                    total_tokens: bucket.total_tokens,
+                    total_cost: bucket.total_cost,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Width of the longest histogram bar, in characters.
+const HISTOGRAM_BAR_WIDTH: usize = 30;
+
+#[derive(Args, Clone)]
+pub struct ActiveArgs {
+    #[command(flatten)]
+    common: CommonArgs,
+    #[arg(
+        long,
+        default_value_t = 30,
+        help = "Treat sessions with entries in the last N minutes as active"
+    )]
+    minutes: i64,
+}
+
+#[derive(Args, Clone)]
+pub struct BlocksArgs {
+    #[command(flatten)]
+    common: CommonArgs,
+    #[arg(short = 'p', long, help = "Filter to specific project name")]
+    project: Option<String>,
+    #[arg(long, help = "Show only the block that is still open")]
+    active: bool,
+}
+
+#[derive(Args, Clone)]
+pub struct LiveArgs {
+    #[command(flatten)]
+    common: CommonArgs,
+    #[arg(short = 'p', long, help = "Filter to specific project name")]
+    project: Option<String>,
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        default_value_t = 5,
+        help = "Seconds between refreshes"
+    )]
+    interval: u64,
+}
+
+#[derive(Args, Clone)]
+pub struct TuiArgs {
+    #[command(flatten)]
+    common: CommonArgs,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, ValueEnum)]
+enum ProjectSort {
+    /// Most expensive first
+    Cost,
+    /// Most tokens first
+    Tokens,
+}
+
+#[derive(Args, Clone)]
+pub struct ProjectsArgs {
+    #[command(flatten)]
+    common: CommonArgs,
+    #[arg(
+        long,
+        value_enum,
+        default_value = "cost",
+        help = "Rank projects by total cost or total tokens"
+    )]
+    sort: ProjectSort,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, ValueEnum)]
+enum TopKind {
+    Days,
+    Sessions,
+    Projects,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, ValueEnum)]
+enum TopMetric {
+    Cost,
+    Tokens,
+}
+
+#[derive(Args, Clone)]
+pub struct TopArgs {
+    #[command(flatten)]
+    common: CommonArgs,
+    #[arg(value_enum, default_value = "sessions", help = "What to rank")]
+    kind: TopKind,
+    #[arg(
+        long,
+        value_enum,
+        default_value = "cost",
+        help = "Rank by total cost or total tokens"
+    )]
+    by: TopMetric,
+    #[arg(
+        short = 'n',
+        long,
+        visible_alias = "n",
+        value_name = "N",
+        default_value_t = 10,
+        help = "How many to list"
+    )]
+    limit: usize,
+    #[arg(short = 'p', long, help = "Filter to specific project name")]
+    project: Option<String>,
+}
+
+/// A day, session, or project listed by `ccost top`.
+#[derive(Debug, Clone, PartialEq)]
+struct TopRow {
+    name: String,
+    project: Option<String>,
+    title: Option<String>,
+    total_tokens: u64,
+    total_cost: f64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TopRowOutput {
+    rank: usize,
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    project: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    title: Option<String>,
+    total_tokens: u64,
+    total_cost: f64,
+    /// Share of the range's total cost or tokens, whichever the ranking uses.
+    share: f64,
+}
+
+#[derive(Args, Clone)]
+pub struct ModelSwitchesArgs {
+    #[command(flatten)]
+    common: CommonArgs,
+    #[arg(short = 'p', long, help = "Filter to specific project name")]
+    project: Option<String>,
+}
+
+#[derive(Args, Clone)]
+pub struct NotifyArgs {
+    #[command(flatten)]
+    common: CommonArgs,
+    #[arg(
+        long,
+        value_name = "URL",
+        required_unless_present = "dry_run",
+        help = "Slack or Discord incoming webhook URL"
+    )]
+    webhook: Option<String>,
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 3,
+        help = "Number of top projects to include"
+    )]
+    top: usize,
+    #[arg(long, help = "Print the webhook payload instead of sending it")]
+    dry_run: bool,
+}
+
+#[derive(Args, Clone)]
+pub struct EmailArgs {
+    #[command(flatten)]
+    common: CommonArgs,
+    #[arg(
+        long,
+        value_name = "ADDRESS",
+        value_delimiter = ',',
+        required = true,
+        help = "Recipient address (repeatable or comma-separated)"
+    )]
+    to: Vec<String>,
+    #[arg(long, value_name = "ADDRESS", help = "Sender address")]
+    from: String,
+    #[arg(
+        long,
+        value_name = "URL",
+        required_unless_present = "dry_run",
+        help = "SMTP server, e.g. smtp://smtp.example.com:587 or smtps://smtp.example.com"
+    )]
+    smtp: Option<String>,
+    #[arg(
+        long,
+        value_name = "USER",
+        help = "SMTP user name; the password is read from CCOST_SMTP_PASSWORD"
+    )]
+    smtp_user: Option<String>,
+    #[arg(long, default_value = "ccost monthly report", help = "Email subject")]
+    subject: String,
+    #[arg(long, help = "Send an HTML email instead of plain text")]
+    html: bool,
+    #[arg(long, help = "Print the composed email instead of sending it")]
+    dry_run: bool,
+}
+
+#[derive(Args, Clone)]
+pub struct ScheduleArgs {
+    #[arg(
+        long,
+        help = "List the configured reports and their next run time, then exit"
+    )]
+    list: bool,
+}
+
+#[derive(Args, Clone)]
+pub struct RenderArgs {
+    #[command(flatten)]
+    common: CommonArgs,
+    #[arg(long, value_name = "FILE", help = "Template file to render")]
+    template: std::path::PathBuf,
+    #[arg(long, value_name = "FILE", help = "Write the rendered report to FILE")]
+    output: Option<std::path::PathBuf>,
+}
+
+#[derive(Args, Clone)]
+pub struct BadgeArgs {
+    #[command(flatten)]
+    common: CommonArgs,
+    #[arg(
+        long,
+        value_enum,
+        default_value = "month-cost",
+        help = "Value shown on the badge"
+    )]
+    metric: BadgeMetric,
+    #[arg(long, default_value = "claude spend", help = "Badge label")]
+    label: String,
+    #[arg(
+        long,
+        default_value = "blue",
+        help = "Badge color (shields.io color name or hex)"
+    )]
+    color: String,
+}
+
+#[derive(Args, Clone)]
+pub struct RecapArgs {
+    #[command(flatten)]
+    common: CommonArgs,
+    #[arg(
+        long,
+        value_name = "YYYY-MM",
+        help = "Month to summarize (default: the current month)"
+    )]
+    month: Option<String>,
+}
+
+#[derive(Args, Clone)]
+pub struct InitArgs {
+    #[arg(long, help = "Replace an existing config file")]
+    force: bool,
+}
+
+#[derive(Args, Clone)]
+pub struct ImportArgs {
+    #[arg(help = "Export file to import")]
+    file: std::path::PathBuf,
+    #[arg(
+        long,
+        value_enum,
+        default_value = "auto",
+        help = "Export format: ccusage, csv, or openrouter; auto picks csv for .csv files and ccusage otherwise"
+    )]
+    format: ImportFormat,
+    #[arg(
+        long,
+        value_name = "FIELD=COLUMN",
+        help = "Read FIELD (date, project, model, input_tokens, output_tokens, cache_creation_tokens, cache_read_tokens, cost) from CSV column COLUMN (repeatable)"
+    )]
+    map: Vec<String>,
+    #[arg(
+        short = 'p',
+        long,
+        help = "Assign every imported record to this project"
+    )]
+    project: Option<String>,
+    #[arg(
+        long,
+        value_name = "NAME",
+        help = "Name of the import (default: the file name); re-importing a name replaces it"
+    )]
+    name: Option<String>,
+    #[arg(long, help = "Show what would be imported without writing anything")]
+    dry_run: bool,
+}
+
+#[derive(Args, Clone)]
+pub struct QueryArgs {
+    #[arg(
+        required_unless_present = "schema",
+        help = "SQL statement, e.g. \"SELECT project, sum(cost) FROM entries GROUP BY 1\""
+    )]
+    sql: Option<String>,
+    #[arg(long, help = "Print the schema of the entries table and exit")]
+    schema: bool,
+    #[command(flatten)]
+    common: CommonArgs,
+}
+
+#[derive(Args, Clone)]
+pub struct CacheArgs {
+    #[command(flatten)]
+    common: CommonArgs,
+    #[arg(short = 'p', long, help = "Filter to specific project name")]
+    project: Option<String>,
+    #[arg(
+        long,
+        value_name = "MINUTES",
+        default_value_t = 5,
+        help = "Cache lifetime; a write not read within this many minutes counts as wasted"
+    )]
+    ttl: i64,
+    #[arg(
+        long,
+        value_name = "N",
+        help = "Show only the N sessions with the most wasted cache writes"
+    )]
+    top: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CacheEfficiencyOutput {
+    session_id: String,
+    project: Option<String>,
+    first_activity: String,
+    writes: u64,
+    unread_writes: u64,
+    cache_creation_tokens: u64,
+    cache_read_tokens: u64,
+    read_ratio: Option<f64>,
+    wasted_creation_tokens: u64,
+    wasted_cost: f64,
+}
+
+#[derive(Args, Clone)]
+pub struct MatrixArgs {
+    #[command(flatten)]
+    common: CommonArgs,
+    #[arg(short = 'p', long, help = "Filter to specific project name")]
+    project: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MatrixRowOutput {
+    weekday: &'static str,
+    hours: Vec<f64>,
+    total_cost: f64,
+}
+
+const WEEKDAYS: [&str; 7] = [
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+    "Sunday",
+];
+
+#[derive(Args, Clone)]
+pub struct WhatifArgs {
+    #[command(flatten)]
+    common: CommonArgs,
+    #[arg(short = 'p', long, help = "Filter to specific project name")]
+    project: Option<String>,
+    #[arg(
+        long,
+        help = "Price cache reads as regular input and drop cache writes, showing what caching saved"
+    )]
+    no_cache: bool,
+    #[arg(
+        long = "map",
+        value_name = "FROM=TO",
+        help = "Price models whose name starts with FROM as model TO (repeatable)"
+    )]
+    model_map: Vec<String>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, ValueEnum)]
+enum SplitBy {
+    Project,
+}
+
+#[derive(Args, Clone)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+pub struct ExportArgs {
+    #[command(subcommand)]
+    target: Option<ExportTarget>,
+    #[command(flatten)]
+    common: CommonArgs,
+    #[arg(
+        long,
+        value_enum,
+        default_value = "project",
+        help = "Write one file per project"
+    )]
+    split_by: SplitBy,
+    #[arg(
+        long,
+        value_name = "DIR",
+        required = true,
+        help = "Directory to write the files to (created if missing)"
+    )]
+    dir: Option<std::path::PathBuf>,
+}
+
+#[derive(Subcommand, Clone)]
+enum ExportTarget {
+    /// Push usage records to an OpenTelemetry collector over OTLP/HTTP (needs the `otlp` feature)
+    Otlp(Box<OtlpArgs>),
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, ValueEnum)]
+enum OtlpSignal {
+    Logs,
+    Metrics,
+}
+
+#[derive(Args, Clone)]
+pub struct OtlpArgs {
+    #[command(flatten)]
+    common: CommonArgs,
+    #[arg(
+        long,
+        value_name = "URL",
+        help = "Collector base URL; /v1/logs or /v1/metrics is appended (default: OTEL_EXPORTER_OTLP_ENDPOINT or http://localhost:4318)"
+    )]
+    endpoint: Option<String>,
+    #[arg(
+        long,
+        value_enum,
+        default_value = "logs",
+        help = "Send one log record per request, or hourly token and cost sums as metrics"
+    )]
+    signal: OtlpSignal,
+    #[arg(
+        long = "header",
+        value_name = "NAME=VALUE",
+        help = "Extra request header, e.g. an API key; repeat for more (added to OTEL_EXPORTER_OTLP_HEADERS)"
+    )]
+    headers: Vec<String>,
+    #[arg(
+        long,
+        value_name = "NAME",
+        default_value = "ccost",
+        help = "service.name resource attribute"
+    )]
+    service_name: String,
+    #[arg(
+        long,
+        help = "Print the request bodies as NDJSON instead of sending them"
+    )]
+    dry_run: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct WhatIfModelOutput {
+    model: String,
+    priced_as: Option<String>,
+    message_count: u64,
+    actual_cost: f64,
+    simulated_cost: f64,
+    difference: f64,
+}
+
+#[derive(Args, Clone)]
+pub struct SnapshotArgs {
+    #[command(flatten)]
+    common: CommonArgs,
+}
+
+#[derive(Args, Clone)]
+pub struct DriftArgs {
+    #[command(flatten)]
+    common: CommonArgs,
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Compare with this snapshot instead of the most recent one"
+    )]
+    snapshot: Option<std::path::PathBuf>,
+    #[arg(long, help = "Exit with an error when any drift is found")]
+    fail_on_drift: bool,
+}
+
+#[derive(Args, Clone)]
+pub struct ArchiveArgs {
+    #[command(flatten)]
+    common: CommonArgs,
+    #[arg(
+        long,
+        value_name = "SPAN",
+        value_parser = parse_last_days,
+        help = "Archive logs last modified more than this long ago, e.g. 90d or 12w"
+    )]
+    older_than: u32,
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Archive to write, e.g. archive.tar.zst"
+    )]
+    to: std::path::PathBuf,
+    #[arg(
+        long,
+        help = "Remove the archived logs, keeping the lines reports read in the archive index"
+    )]
+    remove: bool,
+    #[arg(
+        long,
+        help = "List the logs that would be archived without writing anything"
+    )]
+    dry_run: bool,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, ValueEnum)]
+enum ImportFormat {
+    Auto,
+    /// `ccusage daily --json` output
+    Ccusage,
+    Csv,
+    /// OpenRouter activity export (CSV or JSON) or generation API responses
+    Openrouter,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, ValueEnum)]
+enum BadgeMetric {
+    MonthCost,
+    TodayCost,
+    MonthTokens,
+    TodayTokens,
+}
+
+/// shields.io endpoint badge schema: https://shields.io/badges/endpoint-badge
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BadgeOutput {
+    schema_version: u8,
+    label: String,
+    message: String,
+    color: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TotalsOutput {
+    input_tokens: u64,
+    output_tokens: u64,
+    cache_creation_tokens: u64,
+    cache_read_tokens: u64,
+    total_tokens: u64,
+    total_cost: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message_count: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    main_cost: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    subagent_cost: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_calls: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    api_errors: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sessions: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    first_activity: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_activity: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    active_hours: Option<u32>,
+    /// Fraction of entries whose cost came from the log (auto and display modes).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cost_coverage: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DailyMetadataOutput {
+    agents: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DailyEntryOutput {
+    agent: String,
+    cache_creation_tokens: u64,
+    cache_read_tokens: u64,
+    input_tokens: u64,
+    metadata: DailyMetadataOutput,
+    model_breakdowns: Vec<ModelBreakdownOutput>,
+    models_used: Vec<String>,
+    output_tokens: u64,
+    period: String,
+    total_cost: f64,
+    total_tokens: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    project: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message_count: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    main_cost: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    subagent_cost: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_calls: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    api_errors: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sessions: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    first_activity: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_activity: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    active_hours: Option<u32>,
+    /// Month-to-date project cost as a percentage of its monthly budget.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    budget_used_percent: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    z_score: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MonthlyEntryOutput {
+    month: String,
+    input_tokens: u64,
+    output_tokens: u64,
+    cache_creation_tokens: u64,
+    cache_read_tokens: u64,
+    total_tokens: u64,
+    total_cost: f64,
+    models_used: Vec<String>,
+    model_breakdowns: Vec<ModelBreakdownOutput>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message_count: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    main_cost: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    subagent_cost: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_calls: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    api_errors: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sessions: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    first_activity: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_activity: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    active_hours: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    z_score: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SummaryPeriodOutput {
+    period: String,
+    total_tokens: u64,
+    total_cost: f64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SummaryOutput {
+    today: SummaryPeriodOutput,
+    month: SummaryPeriodOutput,
+    top_model: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SessionEntryOutput {
+    session_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    title: Option<String>,
+    project: Option<String>,
+    first_activity: String,
+    last_activity: String,
+    duration_seconds: i64,
+    input_tokens: u64,
+    output_tokens: u64,
+    cache_creation_tokens: u64,
+    cache_read_tokens: u64,
+    total_tokens: u64,
+    total_cost: f64,
+    subagent_cost: f64,
+    tool_calls: u64,
+    cost_per_hour: Option<f64>,
+    tokens_per_hour: Option<f64>,
+    models_used: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ModelSwitchOutput {
+    from_model: String,
+    to_model: String,
+    switches: usize,
+    sessions: usize,
+    cost_before: f64,
+    messages_before: u64,
+    cost_after: f64,
+    messages_after: u64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BlockOutput {
+    start: String,
+    end: String,
+    first_activity: String,
+    last_activity: String,
+    is_active: bool,
+    request_count: usize,
+    input_tokens: u64,
+    output_tokens: u64,
+    cache_creation_tokens: u64,
+    cache_read_tokens: u64,
+    total_tokens: u64,
+    total_cost: f64,
+    models_used: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    remaining_minutes: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    projected_cost: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ProjectUsageOutput {
+    project: String,
+    first_activity: String,
+    last_activity: String,
+    request_count: u64,
+    session_count: usize,
+    input_tokens: u64,
+    output_tokens: u64,
+    cache_creation_tokens: u64,
+    cache_read_tokens: u64,
+    total_tokens: u64,
+    total_cost: f64,
+    models_used: Vec<String>,
+}
+
+impl From<&ProjectUsage> for ProjectUsageOutput {
+    fn from(usage: &ProjectUsage) -> Self {
+        Self {
+            project: usage.project.clone(),
+            first_activity: usage.first_date.clone(),
+            last_activity: usage.last_date.clone(),
+            request_count: usage.request_count,
+            session_count: usage.session_count,
+            input_tokens: usage.input_tokens,
+            output_tokens: usage.output_tokens,
+            cache_creation_tokens: usage.cache_creation_tokens,
+            cache_read_tokens: usage.cache_read_tokens,
+            total_tokens: usage.total_tokens,
+            total_cost: usage.total_cost,
+            models_used: usage.models_used.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ActiveSessionOutput {
+    session_id: String,
+    project: Option<String>,
+    last_activity: String,
+    current_model: Option<String>,
+    total_tokens: u64,
+    total_cost: f64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ConversationEntryOutput {
+    timestamp: String,
+    model: Option<String>,
+    input_tokens: u64,
+    output_tokens: u64,
+    cache_creation_tokens: u64,
+    cache_read_tokens: u64,
+    total_tokens: u64,
+    cost: f64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct HourlyEntryOutput {
+    date: String,
+    hour: u32,
+    input_tokens: u64,
+    output_tokens: u64,
+    cache_creation_tokens: u64,
+    cache_read_tokens: u64,
+    total_tokens: u64,
+    total_cost: f64,
+    message_count: u64,
+    session_count: usize,
+    models_used: Vec<String>,
+    model_breakdowns: Vec<ModelBreakdownOutput>,
+}
+
+impl From<HourlyUsage> for HourlyEntryOutput {
+    fn from(usage: HourlyUsage) -> Self {
+        Self {
+            date: usage.date,
+            hour: usage.hour,
+            input_tokens: usage.input_tokens,
+            output_tokens: usage.output_tokens,
+            cache_creation_tokens: usage.cache_creation_tokens,
+            cache_read_tokens: usage.cache_read_tokens,
+            total_tokens: usage.total_tokens,
+            total_cost: usage.total_cost,
+            message_count: usage.message_count,
+            session_count: usage.session_count,
+            models_used: usage.models_used,
+            model_breakdowns: usage
+                .model_breakdowns
+                .into_iter()
+                .map(model_breakdown_output)
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ModelBreakdownOutput {
+    model_name: String,
+    input_tokens: u64,
+    output_tokens: u64,
+    cache_creation_tokens: u64,
+    cache_read_tokens: u64,
+    cost: f64,
+}
+
+const DEFAULT_COMMAND: &str = "daily";
+
+/// Set once the command line is parsed, so [`print_error`] answers in JSON for `--json` runs.
+static JSON_ERRORS: AtomicBool = AtomicBool::new(false);
+
+impl Command {
+    fn common_mut(&mut self) -> Option<&mut CommonArgs> {
+        match self {
+            Command::Daily(args) => Some(&mut args.common),
+            Command::Monthly(args) => Some(&mut args.common),
+            Command::Hourly(args) => Some(&mut args.common),
+            Command::Summary(args) => Some(&mut args.common),
+            Command::Forecast(args) => Some(&mut args.common),
+            Command::Diff(args) => Some(&mut args.common),
+            Command::Conversation(args) => Some(&mut args.common),
+            Command::Session(args) => Some(&mut args.common),
+            Command::Active(args) => Some(&mut args.common),
+            Command::Blocks(args) => Some(&mut args.common),
+            Command::Live(args) => Some(&mut args.common),
+            Command::Tui(args) => Some(&mut args.common),
+            Command::Projects(args) => Some(&mut args.common),
+            Command::Top(args) => Some(&mut args.common),
+            Command::Models(args) => Some(&mut args.common),
+            Command::Mix(args) => Some(&mut args.common),
+            Command::Tiers(args) => Some(&mut args.common),
+            Command::StopReasons(args) => Some(&mut args.common),
+            Command::Distribution(args) => Some(&mut args.common),
+            Command::ModelSwitches(args) => Some(&mut args.common),
+            Command::Notify(args) => Some(&mut args.common),
+            Command::Email(args) => Some(&mut args.common),
+            Command::Render(args) => Some(&mut args.common),
+            Command::Badge(args) => Some(&mut args.common),
+            Command::Recap(args) => Some(&mut args.common),
+            Command::Query(args) => Some(&mut args.common),
+            Command::Snapshot(args) => Some(&mut args.common),
+            Command::Drift(args) => Some(&mut args.common),
+            Command::Cache(args) => Some(&mut args.common),
+            Command::Matrix(args) => Some(&mut args.common),
+            Command::Whatif(args) => Some(&mut args.common),
+            Command::Archive(args) => Some(&mut args.common),
+            Command::Export(args) => Some(match &mut args.target {
+                Some(ExportTarget::Otlp(otlp)) => &mut otlp.common,
+                None => &mut args.common,
+            }),
+            Command::Schedule(_) | Command::Import(_) | Command::Init(_) => None,
+        }
+    }
+}
+
+/// Prints a failed run's error to stderr: as a `{"error": {"code", "message"}}` object when
+/// the command asked for JSON, otherwise as plain text.
+pub fn print_error(error: &anyhow::Error) {
+    if JSON_ERRORS.load(Ordering::Relaxed) {
+        eprintln!("{}", json_error(error));
+    } else {
+        eprintln!("{error}");
+    }
+}
+
+fn invalid_timezone(timezone: &str) -> anyhow::Error {
+    coded_error(
+        ErrorCode::InvalidTimezone,
+        format!("Invalid timezone: {timezone}"),
+    )
+}
+
+pub fn run() -> Result<()> {
+    let (config, args) = configure(std::env::args_os().collect(), load_config)?;
+    let mut cli = Cli::parse_from(args);
+    prepare_run(&mut cli.command, &config)?;
+    let pricing_file = cli
+        .command
+        .common_mut()
+        .and_then(|common| common.pricing_file.clone());
+    match pricing_file {
+        Some(path) => set_pricing_overrides(load_pricing_overrides(&path)?),
+        None => {
+            if let Some(path) = pricing_path().filter(|path| path.is_file()) {
+                set_pricing_overrides(load_pricing_overrides(&path)?);
+            }
+        }
+    }
+    if let Some(common) = cli.command.common_mut() {
+        if common.timings {
+            enable_timings();
+        }
+        if common.report_duplicates {
+            enable_duplicate_report();
+        }
+    }
+    let started = std::time::Instant::now();
+    let result = dispatch(cli.command, &config);
+    print_warnings();
+    print_duplicate_report();
+    print_timings(started.elapsed());
+    result
+}
+
+/// Expands argument files and applies the config from `load` to the command line. A JSON
+/// output flag is looked for in the raw arguments first, so that a bad argument file or config
+/// file is already reported in JSON.
+fn configure(
+    args: Vec<std::ffi::OsString>,
+    load: impl FnOnce() -> Result<Config>,
+) -> Result<(Config, Vec<std::ffi::OsString>)> {
+    JSON_ERRORS.store(asks_for_json(&args), Ordering::Relaxed);
+    let args = expand_arg_files(args)?;
+    JSON_ERRORS.store(asks_for_json(&args), Ordering::Relaxed);
+    match configured_args(args.clone(), load) {
+        Ok(configured) => Ok(configured),
+        Err(error) if works_without_config(&args) => {
+            eprintln!("Warning: continuing without the config file: {error}");
+            Ok((Config::default(), args))
+        }
+        Err(error) => Err(error),
+    }
+}
+
+/// Whether the command line asks for JSON or NDJSON output, read before it is parsed.
+fn asks_for_json(args: &[std::ffi::OsString]) -> bool {
+    let is_json =
+        |format: &str| OutputFormat::from_str(format, true).is_ok_and(OutputFormat::is_json);
+    let mut args = args
+        .iter()
+        .skip(1)
+        .map_while(|arg| arg.to_str().filter(|arg| *arg != "--"));
+    let mut json = false;
+    while let Some(arg) = args.next() {
+        json = match arg {
+            "-j" | "--json" => true,
+            "--format" => args.next().is_some_and(is_json),
+            _ => arg.strip_prefix("--format=").map_or(json, is_json),
+        };
+    }
+    json
+}
+
+/// Loads the config with `load` and applies its display settings, `default_command`, and
+/// `default_args` to the command line.
+fn configured_args(
+    args: Vec<std::ffi::OsString>,
+    load: impl FnOnce() -> Result<Config>,
+) -> Result<(Config, Vec<std::ffi::OsString>)> {
+    let config = load()?;
+    set_model_name_rules(config.model_name_rules()?);
+    if let Some(currency) = &config.currency {
+        set_display_currency(currency.display_currency());
+    }
+    let default_command = config.default_command.as_deref().unwrap_or(DEFAULT_COMMAND);
+    if Cli::command().find_subcommand(default_command).is_none() {
+        return Err(coded_error(
+            ErrorCode::InvalidConfig,
+            format!("Invalid default_command in config: {default_command}"),
+        ));
+    }
+    let args = with_default_args(with_default_subcommand(args, default_command), &config)?;
+    Ok((config, args))
+}
+
+/// Whether the command line asks for help, the version, or `ccost init`, which still run
+/// (with a warning) when the config file is broken, so it can be looked up or replaced.
+fn works_without_config(args: &[std::ffi::OsString]) -> bool {
+    let mut args = args.iter().skip(1).filter_map(|arg| arg.to_str());
+    let first = args.clone().next();
+    matches!(first, Some("init" | "help"))
+        || args.any(|arg| matches!(arg, "-h" | "--help" | "-V" | "--version"))
+}
+
+/// Picks the error format before anything can fail, so errors raised while preparing the
+/// command are already reported in JSON for `--json` runs.
+fn prepare_run(command: &mut Command, config: &Config) -> Result<()> {
+    if let Some(common) = command.common_mut() {
+        common.resolve_format();
+        JSON_ERRORS.store(common.format.is_json(), Ordering::Relaxed);
+    }
+    prepare_command(command, config)
+}
+
+/// Settles options that depend on several flags or on the config, before dispatch.
+fn prepare_command(command: &mut Command, config: &Config) -> Result<()> {
+    let is_monthly = matches!(command, Command::Monthly(_));
+    if let Some(common) = command.common_mut() {
+        common.resolve_format();
+        if common.timezone.is_none() {
+            common.timezone = config.timezone.clone();
+        }
+        common.resolve_relative_range()?;
+        if common.selects_json() && !common.json {
+            return Err(coded_error(
+                ErrorCode::InvalidArgument,
+                "--fields and --totals-only need --json, --format ndjson, or --format csv",
+            ));
+        }
+        if common.format == OutputFormat::Pdf && !is_monthly {
+            return Err(coded_error(
+                ErrorCode::InvalidArgument,
+                "--format pdf is only supported by `ccost monthly`",
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn dispatch(command: Command, config: &Config) -> Result<()> {
+    match command {
+        Command::Daily(args) => run_daily(args, config),
+        Command::Monthly(args) => run_monthly(args, config),
+        Command::Hourly(args) => run_hourly(args, config),
+        Command::Summary(args) => run_summary(args, config),
+        Command::Forecast(args) => run_forecast(args, config),
+        Command::Diff(args) => run_diff(args, config),
+        Command::Conversation(args) => run_conversation(args, config),
+        Command::Session(args) => run_session(args, config),
+        Command::Projects(args) => run_projects(args, config),
+        Command::Top(args) => run_top(args, config),
+        Command::Models(args) => run_models(args, config),
+        Command::Mix(args) => run_mix(args, config),
+        Command::Tiers(args) => run_tiers(args, config),
+        Command::StopReasons(args) => run_stop_reasons(args, config),
+        Command::Distribution(args) => run_distribution(args, config),
+        Command::Active(args) => run_active(args, config),
+        Command::Blocks(args) => run_blocks(args, config),
+        Command::Live(args) => run_live(args, config),
+        Command::Tui(args) => run_tui(args, config),
+        Command::ModelSwitches(args) => run_model_switches(args, config),
+        Command::Notify(args) => run_notify(args, config),
+        Command::Email(args) => run_email(args, config),
+        Command::Schedule(args) => run_schedule(args, config),
+        Command::Render(args) => run_render(args, config),
+        Command::Badge(args) => run_badge(args, config),
+        Command::Recap(args) => run_recap(args, config),
+        Command::Import(args) => run_import(args),
+        Command::Init(args) => run_init(args),
+        Command::Query(args) => run_query_command(args, config),
+        Command::Snapshot(args) => run_snapshot(args, config),
+        Command::Drift(args) => run_drift(args, config),
+        Command::Cache(args) => run_cache(args, config),
+        Command::Matrix(args) => run_matrix(args, config),
+        Command::Whatif(args) => run_whatif(args, config),
+        Command::Export(args) => run_export(args, config),
+        Command::Archive(args) => run_archive(args, config),
+    }
+}
+
+/// Inserts `default_command` when no subcommand is given (bare `ccost` or only flags).
+fn with_default_subcommand(
+    mut args: Vec<std::ffi::OsString>,
+    default_command: &str,
+) -> Vec<std::ffi::OsString> {
+    let needs_default = match args.get(1).and_then(|arg| arg.to_str()) {
+        None => true,
+        Some(arg) => {
+            if arg.starts_with('-') {
+                !matches!(arg, "-h" | "--help" | "-V" | "--version")
+            } else {
+                false
+            }
+        }
+    };
+    if needs_default {
+        args.insert(1, std::ffi::OsString::from(default_command));
+    }
+    args
+}
+
+/// Inserts the config's `default_args` for the chosen subcommand (looked up by its name, so
+/// aliases share them) right after it, where flags given later on the command line override
+/// them.
+fn with_default_args(
+    mut args: Vec<std::ffi::OsString>,
+    config: &Config,
+) -> Result<Vec<std::ffi::OsString>> {
+    let cli = Cli::command();
+    for name in config.default_args.keys() {
+        if cli.find_subcommand(name).is_none() {
+            return Err(coded_error(
+                ErrorCode::InvalidConfig,
+                format!("Unknown subcommand in default_args: {name}"),
+            ));
+        }
+    }
+    let subcommand = args
+        .get(1)
+        .and_then(|arg| arg.to_str())
+        .and_then(|arg| cli.find_subcommand(arg))
+        .map(|subcommand| subcommand.get_name());
+    if let Some(defaults) = subcommand.and_then(|name| config.default_args.get(name)) {
+        args.splice(2..2, defaults.iter().map(std::ffi::OsString::from));
+    }
+    Ok(args)
+}
+
+/// Replaces each `@FILE` argument with the arguments listed in FILE: whitespace-separated,
+/// with single or double quotes around arguments that contain spaces, and `#` starting a
+/// comment line. Arguments read from a file are not expanded again.
+fn expand_arg_files(args: Vec<std::ffi::OsString>) -> Result<Vec<std::ffi::OsString>> {
+    let mut expanded = Vec::with_capacity(args.len());
+    for (index, arg) in args.into_iter().enumerate() {
+        let Some(path) = arg
+            .to_str()
+            .and_then(|arg| arg.strip_prefix('@'))
+            .filter(|path| index > 0 && !path.is_empty())
+        else {
+            expanded.push(arg);
+            continue;
+        };
+        let content = std::fs::read_to_string(path).map_err(|error| {
+            coded_error(
+                ErrorCode::InvalidArgument,
+                format!("failed to read argument file {path}: {error}"),
+            )
+        })?;
+        let words = split_arg_file(&content)
+            .map_err(|error| coded_error(ErrorCode::InvalidArgument, format!("{path}: {error}")))?;
+        expanded.extend(words.into_iter().map(std::ffi::OsString::from));
+    }
+    Ok(expanded)
+}
+
+fn split_arg_file(content: &str) -> Result<Vec<String>> {
+    let mut words = Vec::new();
+    for line in content.lines() {
+        if line.trim_start().starts_with('#') {
+            continue;
+        }
+        let mut word: Option<String> = None;
+        let mut quote = None;
+        for ch in line.chars() {
+            match (quote, ch) {
+                (Some(open), _) if ch == open => quote = None,
+                (Some(_), _) => word.get_or_insert_with(String::new).push(ch),
+                (None, '"' | '\'') => {
+                    quote = Some(ch);
+                    word.get_or_insert_with(String::new);
+                }
+                (None, _) if ch.is_whitespace() => words.extend(word.take()),
+                (None, _) => word.get_or_insert_with(String::new).push(ch),
+            }
+        }
+        if quote.is_some() {
+            return Err(anyhow!("unterminated quote in: {line}"));
+        }
+        words.extend(word);
+    }
+    Ok(words)
+}
+
+fn common_options(args: &CommonArgs, config: &Config) -> Result<LoadOptions> {
+    if let Some(timezone) = &args.timezone {
+        chrono_tz::Tz::from_str(timezone).map_err(|_| invalid_timezone(timezone))?;
+    }
+    let agents = args.agent_flags();
+    let mut options = LoadOptions {
+        mode: args.mode,
+        order: args.order,
+        codex: agents.codex,
+        claudecode: agents.claudecode,
+        opencode: agents.opencode,
+        since: args.since.clone(),
+        until: args.until.clone(),
+        timezone: args.timezone.clone(),
+        dedup: args.dedup,
+        skip_errors: args.skip_errors,
+        cost_field: config.cost_field.clone(),
+        fallback_dedup: args.dedup_fallback,
+        include_synthetic: args.include_synthetic,
+        follow_symlinks: args.follow_symlinks,
+        claude_desktop: args.claude_desktop,
+        ignore: IgnorePatterns::new(
+            &config
+                .ignore
+                .iter()
+                .chain(&args.ignore)
+                .map(String::as_str)
+                .collect::<Vec<_>>(),
+        )?,
+        models: ModelFilter::new(&args.models, &args.exclude_models)
+            .map_err(|error| coded_error(ErrorCode::InvalidArgument, error.to_string()))?,
+        read_limits: read_limits(args, config)?,
+        claude_search_paths: config.claude_search_paths(),
+        verbose: args.verbose,
+        import_path: imports_dir(),
+        archive_path: archive_dir(),
+        cache_path: parse_cache_path().filter(|_| !args.no_parse_cache),
+        scan_all_files: args.scan_all_files,
+        ..LoadOptions::default()
+    };
+    if let Some(name) = &args.profile {
+        let profile = config.profile(name)?;
+        options.claude_paths = profile.claude_path.iter().cloned().collect();
+        options.codex_path = profile.codex_path.clone();
+        options.opencode_path = profile.opencode_path.clone();
+        options.project = profile.project.clone();
+    }
+    if !args.paths.is_empty() {
+        options.claude_paths = args.paths.clone();
+    }
+    Ok(options)
+}
+
+fn read_limits(args: &CommonArgs, config: &Config) -> Result<ReadLimits> {
+    let configured = |value: &Option<String>, key: &str| {
+        value
+            .as_deref()
+            .map(|value| {
+                parse_byte_size(value).map_err(|error| anyhow!("Invalid {key} in config: {error}"))
+            })
+            .transpose()
+    };
+    let defaults = ReadLimits::default();
+    Ok(ReadLimits {
+        max_line_bytes: match args.max_line_size {
+            Some(size) => size,
+            None => configured(&config.max_line_size, "max_line_size")?
+                .unwrap_or(defaults.max_line_bytes),
+        },
+        max_file_bytes: match args.max_file_size {
+            Some(size) => size,
+            None => configured(&config.max_file_size, "max_file_size")?
+                .unwrap_or(defaults.max_file_bytes),
+        },
+    })
+}
+
+fn run_daily(args: DailyArgs, config: &Config) -> Result<()> {
+    let mut options = common_options(&args.common, config)?;
+    options.group_by_project = args.instances;
+    options.project = args.project.clone().or(options.project);
+
+    let directories = if args.by_directory {
+        Some(directory_usage(&load_usage_entries(&options)?))
+    } else {
+        None
+    };
+    let daily = load_daily_usage_data(options)?;
+    if daily.is_empty() {
+        return report_no_data(&args.common, config, "No usage data found.");
+    }
+
+    let totals = calculate_totals_daily(&daily);
+    let coverage = cost_coverage(&totals, args.common.mode);
+    let trend = args.trend.then(|| cost_trend(&daily)).flatten();
+    let budget = args.budget.or(config.budgets.daily);
+    let over_budget = budget
+        .map(|budget| {
+            over_budget_periods(
+                daily
+                    .iter()
+                    .map(|entry| (entry.date.as_str(), entry.total_cost)),
+                budget,
+            )
+        })
+        .unwrap_or_default();
+
+    if args.group_by == Some(GroupBy::Model) {
+        let periods = daily
+            .into_iter()
+            .map(|entry| (entry.date, entry.total_cost, entry.model_breakdowns))
+            .collect();
+        emit_model_rows("daily", "Daily", "Date", periods, totals, &args.common)?;
+        return check_budget(&over_budget, budget, "day", args.fail_on_over_budget);
+    }
+
+    if args.common.json {
+        let trend_output = trend.as_ref().map(|trend| CostTrendOutput {
+            days: trend.days,
+            slope_per_day: trend.slope_per_day,
+            current_daily_cost: trend.current_daily_cost,
+            projection_days: PROJECTION_DAYS,
+            projected_cost: trend.projected_cost,
+            r_squared: trend.r_squared,
+        });
+        if args.instances && daily.iter().any(|d| d.project.is_some()) {
+            let grouped = group_daily_by_project(&daily);
+            let mut projects_output = std::collections::HashMap::new();
+            for (project, entries) in grouped {
+                let budget_used = config
+                    .budgets
+                    .projects
+                    .get(&project)
+                    .map(|budget| budget_used_percent(&entries, *budget));
+                let scores = cost_z_scores(&entries, &args.common.columns);
+                let mapped = entries
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, entry)| DailyEntryOutput {
+                        budget_used_percent: budget_used.as_ref().map(|used| used[index]),
+                        z_score: scores[index],
+                        ..daily_entry_output(entry, false, &args.common.columns)
+                    })
+                    .collect::<Vec<_>>();
+                projects_output.insert(project, mapped);
+            }
+            let mut json = serde_json::json!({
+                "projects": projects_output,
+                "totals": TotalsOutput {
+                    cost_coverage: coverage,
+                    ..totals_output(totals, &args.common.columns)
+                }
+            });
+            if args.trend {
+                json["trend"] = serde_json::to_value(&trend_output)?;
+            }
+            if let Some(directories) = &directories {
+                json["directories"] = directories_output(directories);
+            }
+            if let Some(budget) = budget {
+                json["budget"] = budget_output(budget, &over_budget);
+            }
+            emit_json(&json, &args.common)?;
+        } else {
+            let scores = cost_z_scores(&daily, &args.common.columns);
+            let mut json = serde_json::json!({
+                "totals": TotalsOutput {
+                    cost_coverage: coverage,
+                    ..totals_output(totals, &args.common.columns)
+                }
+            });
+            if args.trend {
+                json["trend"] = serde_json::to_value(&trend_output)?;
+            }
+            if let Some(directories) = &directories {
+                json["directories"] = directories_output(directories);
+            }
+            if let Some(budget) = budget {
+                json["budget"] = budget_output(budget, &over_budget);
+            }
+            let records = daily
+                .into_iter()
+                .zip(scores)
+                .map(|(entry, z_score)| DailyEntryOutput {
+                    z_score,
+                    ..daily_entry_output(entry, true, &args.common.columns)
+                });
+            emit_json_records("daily", records, json, &args.common)?;
+        }
+        return check_budget(&over_budget, budget, "day", args.fail_on_over_budget);
+    }
+
+    let mut output = String::new();
+    write_title(&mut output, "Daily", &args.common)?;
+
+    let mode = table_mode(&args.common);
+    let token_format = token_format(args.common.kmb);
+    let max_models = args.common.max_models.or(config.max_models);
+    let grouped_by_project = args.instances && daily.iter().any(|d| d.project.is_some());
+    let budgets = &config.budgets.projects;
+    let budget_column = grouped_by_project && !budgets.is_empty();
+    let mut table = usage_table_with(
+        "Date",
+        mode,
+        &args.common,
+        if budget_column { &["Budget Used"] } else { &[] },
+    );
+
+    if grouped_by_project {
+        let grouped = group_daily_by_project(&daily);
+        let mut first = true;
+        for (project, entries) in grouped {
+            if !first {
+                table.add_row(vec![String::new(); table.column_count()]);
+            }
+            let budget = budgets.get(&project).copied();
+            let mut header_row = vec![String::new(); table.column_count()];
+            header_row[0] = match budget {
+                Some(budget) => format!(
+                    "Project: {project} (budget {}/month)",
+                    format_currency(budget)
+                ),
+                None => format!("Project: {project}"),
+            };
+            table.add_row(header_row);
+            let budget_used = budget.map(|budget| budget_used_percent(&entries, budget));
+            let scores = cost_z_scores(&entries, &args.common.columns);
+            for (index, entry) in entries.into_iter().enumerate() {
+                let first_col = date_cell(&entry.date, &args.common);
+                let data = UsageDataRow {
+                    z_score: scores[index],
+                    ..usage_row_from_daily(&entry)
+                };
+                let row = build_usage_row(&first_col, &data, mode, token_format, max_models);
+                let used = budget_used.as_ref().map(|used| used[index]);
+                table.add_data_row_with(
+                    row,
+                    &data,
+                    if budget_column {
+                        vec![used.map_or_else(String::new, |used| format!("{used:.1}%"))]
+                    } else {
+                        Vec::new()
+                    },
+                    used.and_then(budget_color),
+                );
+                if args.common.breakdown {
+                    let breakdowns = breakdown_rows_from_breakdowns(&entry.model_breakdowns);
+                    for breakdown in build_breakdown_rows(&breakdowns, mode, token_format) {
+                        table.add_row(breakdown);
+                    }
+                }
+            }
+            first = false;
+        }
+    } else {
+        let scores = cost_z_scores(&daily, &args.common.columns);
+        for (entry, z_score) in daily.iter().zip(scores) {
+            let first_col = date_cell(&entry.date, &args.common);
+            let data = UsageDataRow {
+                z_score,
+                ..usage_row_from_daily(entry)
+            };
+            let row = build_usage_row(&first_col, &data, mode, token_format, max_models);
+            let color = budget.and_then(|budget| budget_color(entry.total_cost / budget * 100.0));
+            table.add_data_row_with(row, &data, Vec::new(), color);
+            if args.common.breakdown {
+                let breakdowns = breakdown_rows_from_breakdowns(&entry.model_breakdowns);
+                for breakdown in build_breakdown_rows(&breakdowns, mode, token_format) {
+                    table.add_row(breakdown);
+                }
+            }
+        }
+    }
+
+    let totals_data = usage_row_from_totals(&totals);
+    table.add_data_row(
+        build_totals_row(&totals_data, mode, token_format),
+        &totals_data,
+    );
+    write_table(&mut output, &table.table, &args.common)?;
+    write_cost_coverage(&mut output, &totals, &args.common)?;
+    if let Some(directories) = &directories {
+        write_directory_table(&mut output, directories, &args.common)?;
+    }
+    if args.trend {
+        match &trend {
+            Some(trend) => writeln!(
+                output,
+                "Trend: {}/day over {} days (R² {:.2}); fitted daily cost {}, \
+                 projected next {PROJECTION_DAYS} days {}",
+                format_signed_currency(trend.slope_per_day),
+                trend.days,
+                trend.r_squared,
+                format_currency(trend.current_daily_cost),
+                format_currency(trend.projected_cost),
+            )?,
+            None => writeln!(output, "Trend: needs at least two days of data")?,
+        }
+    }
+
+    if matches!(mode, TableMode::Compact) && !args.common.quiet {
+        writeln!(output, "\nRunning in Compact Mode")?;
+        writeln!(
+            output,
+            "Expand terminal width to see cache metrics and total tokens"
+        )?;
+    }
+
+    emit_output(&output, &args.common)?;
+    check_budget(&over_budget, budget, "day", args.fail_on_over_budget)
+}
+
+fn run_monthly(args: MonthlyArgs, config: &Config) -> Result<()> {
+    let mut options = common_options(&args.common, config)?;
+    options.project = args.project.clone().or(options.project);
+    let directories = if args.by_directory {
+        Some(directory_usage(&load_usage_entries(&options)?))
+    } else {
+        None
+    };
+    let monthly = load_monthly_usage_data(options)?;
+    if args.common.format == OutputFormat::Pdf {
+        let pdf = monthly_report_pdf(
+            &report_title("Monthly", &args.common),
+            &Local::now().format("%Y-%m-%d %H:%M").to_string(),
+            &monthly,
+            &calculate_totals_monthly(&monthly),
+            args.common.breakdown,
+        );
+        return write_binary_output(&pdf, args.output.as_deref());
+    }
+    if monthly.is_empty() {
+        if args.common.json {
+            let empty = serde_json::json!({
+                "monthly": [],
+                "totals": totals_output(UsageTotals::default(), &args.common.columns)
+            });
+            emit_json(&empty, &args.common)?;
+            if args.common.fail_empty || config.fail_empty {
+                return Err(coded_error(ErrorCode::NoData, "No usage data found."));
+            }
+            return Ok(());
+        }
+        return report_no_data(&args.common, config, "No usage data found.");
+    }
+    let budget = args.budget.or(config.budgets.monthly);
+    let over_budget = budget
+        .map(|budget| {
+            over_budget_periods(
+                monthly
+                    .iter()
+                    .map(|entry| (entry.month.as_str(), entry.total_cost)),
+                budget,
+            )
+        })
+        .unwrap_or_default();
+
+    if args.group_by == Some(GroupBy::Model) {
+        let totals = calculate_totals_monthly(&monthly);
+        let periods = monthly
+            .into_iter()
+            .map(|entry| (entry.month, entry.total_cost, entry.model_breakdowns))
+            .collect();
+        emit_model_rows("monthly", "Monthly", "Month", periods, totals, &args.common)?;
+        return check_budget(&over_budget, budget, "month", args.fail_on_over_budget);
+    }
+
+    if args.common.json {
+        let totals = calculate_totals_monthly(&monthly);
+        let coverage = cost_coverage(&totals, args.common.mode);
+        let costs = monthly
+            .iter()
+            .map(|entry| entry.total_cost)
+            .collect::<Vec<_>>();
+        let scores = if args.common.columns.contains(&ExtraColumn::ZScore) {
+            z_scores(&costs)
+        } else {
+            vec![None; costs.len()]
+        };
+        let mut json = serde_json::json!({
+            "totals": TotalsOutput {
+                cost_coverage: coverage,
+                ..totals_output(totals, &args.common.columns)
+            }
+        });
+        if let Some(directories) = &directories {
+            json["directories"] = directories_output(directories);
+        }
+        if let Some(budget) = budget {
+            json["budget"] = budget_output(budget, &over_budget);
+        }
+        let records = monthly
+            .into_iter()
+            .zip(scores)
+            .map(|(entry, z_score)| MonthlyEntryOutput {
+                z_score,
+                ..monthly_entry_output(entry, &args.common.columns)
+            });
+        emit_json_records("monthly", records, json, &args.common)?;
+        return check_budget(&over_budget, budget, "month", args.fail_on_over_budget);
+    }
+
+    let mode = table_mode(&args.common);
+    let mut output = render_monthly_report(&monthly, mode, &args.common, config, budget)?;
+    if let Some(directories) = &directories {
+        write_directory_table(&mut output, directories, &args.common)?;
+    }
+    emit_output(&output, &args.common)?;
+    check_budget(&over_budget, budget, "month", args.fail_on_over_budget)
+}
+
+/// Writes a `--group-by model` report: one row per model in each period (`key` names the
+/// periods in JSON), with the model's share of the period's cost.
+fn emit_model_rows(
+    key: &str,
+    title: &str,
+    first_column: &str,
+    periods: Vec<(String, f64, Vec<ModelBreakdown>)>,
+    totals: UsageTotals,
+    args: &CommonArgs,
+) -> Result<()> {
+    let share = |cost: f64, period_cost: f64| (period_cost > 0.0).then(|| cost / period_cost);
+    if args.json {
+        let json = serde_json::json!({
+            "totals": TotalsOutput {
+                cost_coverage: cost_coverage(&totals, args.mode),
+                ..totals_output(totals, &args.columns)
+            }
+        });
+        let records = periods
+            .into_iter()
+            .flat_map(|(period, period_cost, breakdowns)| {
+                breakdowns
+                    .into_iter()
+                    .map(move |breakdown| PeriodModelOutput {
+                        period: period.clone(),
+                        cost_share: share(breakdown.cost, period_cost),
+                        model: breakdown.model_name,
+                        input_tokens: breakdown.input_tokens,
+                        output_tokens: breakdown.output_tokens,
+                        cache_creation_tokens: breakdown.cache_creation_tokens,
+                        cache_read_tokens: breakdown.cache_read_tokens,
+                        total_tokens: breakdown.total_tokens,
+                        cost: breakdown.cost,
+                    })
+            });
+        return emit_json_records(key, records, json, args);
+    }
+
+    let mut output = String::new();
+    write_title(&mut output, title, args)?;
+    let mode = table_mode(args);
+    let token_format = token_format(args.kmb);
+    let percent =
+        |share: Option<f64>| share.map_or_else(String::new, |s| format!("{:.1}%", s * 100.0));
+    let mut table = Table::new();
+    table.load_preset(TABLE_PRESET);
+    let cost = cost_header("Cost");
+    table.set_header(match mode {
+        TableMode::Full => vec![
+            first_column,
+            "Model",
+            "Input",
+            "Output",
+            "Cache Create",
+            "Cache Read",
+            "Total Tokens",
+            cost.as_str(),
+            "Share",
+        ],
+        TableMode::Compact => vec![
+            first_column,
+            "Model",
+            "Input",
+            "Output",
+            cost.as_str(),
+            "Share",
+        ],
+    });
+    let row = |first: String, model: String, tokens: [u64; 5], cost: f64, share: String| {
+        let [input, output, cache_creation, cache_read, total] =
+            tokens.map(|count| format_tokens(count, token_format));
+        match mode {
+            TableMode::Full => vec![
+                first,
+                model,
+                input,
+                output,
+                cache_creation,
+                cache_read,
+                total,
+                format_currency(cost),
+                share,
+            ],
+            TableMode::Compact => vec![first, model, input, output, format_currency(cost), share],
+        }
+    };
+    for (period, period_cost, breakdowns) in &periods {
+        for (index, breakdown) in breakdowns.iter().enumerate() {
+            // On screen the period is shown once, above its models; CSV and Markdown repeat it.
+            let first = if index == 0 || args.format.is_plain_table() {
+                period.clone()
+            } else {
+                String::new()
+            };
+            table.add_row(row(
+                first,
+                format_model_name(&breakdown.model_name),
+                [
+                    breakdown.input_tokens,
+                    breakdown.output_tokens,
+                    breakdown.cache_creation_tokens,
+                    breakdown.cache_read_tokens,
+                    breakdown.total_tokens,
+                ],
+                breakdown.cost,
+                percent(share(breakdown.cost, *period_cost)),
+            ));
+        }
+    }
+    table.add_row(row(
+        "Total".to_string(),
+        String::new(),
+        [
+            totals.input_tokens,
+            totals.output_tokens,
+            totals.cache_creation_tokens,
+            totals.cache_read_tokens,
+            totals.total_tokens(),
+        ],
+        totals.total_cost,
+        String::new(),
+    ));
+    write_table(&mut output, &table, args)?;
+    write_cost_coverage(&mut output, &totals, args)?;
+    emit_output(&output, args)
+}
+
+fn directories_output(directories: &[DirectoryUsage]) -> serde_json::Value {
+    serde_json::json!(
+        directories
+            .iter()
+            .map(|usage| DirectoryUsageOutput {
+                directory: usage.directory.clone(),
+                request_count: usage.request_count,
+                input_tokens: usage.input_tokens,
+                output_tokens: usage.output_tokens,
+                cache_creation_tokens: usage.cache_creation_tokens,
+                cache_read_tokens: usage.cache_read_tokens,
+                total_tokens: usage.total_tokens,
+                total_cost: usage.total_cost,
+            })
+            .collect::<Vec<_>>()
+    )
+}
+
+/// Appends the per-data-directory totals of `--by-directory` as a second table.
+fn write_directory_table(
+    output: &mut String,
+    directories: &[DirectoryUsage],
+    common: &CommonArgs,
+) -> std::fmt::Result {
+    let token_format = token_format(common.kmb);
+    let mut table = Table::new();
+    table.load_preset(TABLE_PRESET);
+    table.set_header(vec![
+        "Directory",
+        "Requests",
+        "Input",
+        "Output",
+        "Cache Create",
+        "Cache Read",
+        "Total Tokens",
+        cost_header("Cost").as_str(),
+    ]);
+    for usage in directories {
+        table.add_row(vec![
+            usage.directory.clone(),
+            format_number(usage.request_count as f64),
+            format_tokens(usage.input_tokens, token_format),
+            format_tokens(usage.output_tokens, token_format),
+            format_tokens(usage.cache_creation_tokens, token_format),
+            format_tokens(usage.cache_read_tokens, token_format),
+            format_tokens(usage.total_tokens, token_format),
+            format_currency(usage.total_cost),
+        ]);
+    }
+    writeln!(output)?;
+    write_table(output, &table, common)
+}
+
+fn write_binary_output(bytes: &[u8], output: Option<&std::path::Path>) -> Result<()> {
+    if let Some(path) = output {
+        return std::fs::write(path, bytes)
+            .map_err(|error| anyhow!("failed to write {}: {error}", path.display()));
+    }
+    let mut stdout = std::io::stdout().lock();
+    if stdout.is_terminal() {
+        return Err(anyhow!(
+            "Refusing to write binary output to a terminal; use --output FILE or redirect stdout"
+        ));
+    }
+    stdout.write_all(bytes)?;
+    stdout.flush()?;
+    Ok(())
+}
+
+/// Monthly table; with a `budget`, months near or over it are colored like project budgets.
+fn render_monthly_report(
+    monthly: &[MonthlyUsage],
+    mode: TableMode,
+    common: &CommonArgs,
+    config: &Config,
+    budget: Option<f64>,
+) -> Result<String> {
+    let totals = calculate_totals_monthly(monthly);
+    let mut output = String::new();
+    write_title(&mut output, "Monthly", common)?;
+
+    let token_format = token_format(common.kmb);
+    let max_models = common.max_models.or(config.max_models);
+    let mut table = usage_table("Month", mode, common);
+
+    let costs = monthly
+        .iter()
+        .map(|entry| entry.total_cost)
+        .collect::<Vec<_>>();
+    let scores = if common.columns.contains(&ExtraColumn::ZScore) {
+        z_scores(&costs)
+    } else {
+        vec![None; costs.len()]
+    };
+    for (entry, z_score) in monthly.iter().zip(scores) {
+        let data = UsageDataRow {
+            z_score,
+            ..usage_row_from_monthly(entry)
+        };
+        let row = build_usage_row(&entry.month, &data, mode, token_format, max_models);
+        let color = budget.and_then(|budget| budget_color(entry.total_cost / budget * 100.0));
+        table.add_data_row_with(row, &data, Vec::new(), color);
+        if common.breakdown {
+            let breakdowns = breakdown_rows_from_breakdowns(&entry.model_breakdowns);
+            for breakdown in build_breakdown_rows(&breakdowns, mode, token_format) {
+                table.add_row(breakdown);
+            }
+        }
+    }
+
+    let totals_data = usage_row_from_totals(&totals);
+    table.add_data_row(
+        build_totals_row(&totals_data, mode, token_format),
+        &totals_data,
+    );
+    write_table(&mut output, &table.table, common)?;
+    write_cost_coverage(&mut output, &totals, common)?;
+
+    if matches!(mode, TableMode::Compact) && !common.quiet {
+        writeln!(output, "\nRunning in Compact Mode")?;
+        writeln!(
+            output,
+            "Expand terminal width to see cache metrics and total tokens"
+        )?;
+    }
+    Ok(output)
+}
+
+fn run_hourly(args: HourlyArgs, config: &Config) -> Result<()> {
+    let mut options = common_options(&args.common, config)?;
+    let date = match args.date {
+        Some(date) => Some(date),
+        None if options.since.is_none() && options.until.is_none() => Some(
+            today_in_timezone(args.common.timezone.as_deref()).ok_or_else(|| {
+                invalid_timezone(args.common.timezone.as_deref().unwrap_or_default())
+            })?,
+        ),
+        None => None,
+    };
+    if let Some(date) = date {
+        options.since = Some(date.format("%Y%m%d").to_string());
+        options.until = options.since.clone();
+    }
+    let entries = load_usage_entries(&options)?;
+    let mut hours = group_entries_by_hour(&entries);
+    if hours.is_empty() {
+        let message = match date {
+            Some(date) => format!("No usage data found for {}.", date.format("%Y-%m-%d")),
+            None => "No usage data found.".to_string(),
+        };
+        return report_no_data(&args.common, config, &message);
+    }
+    if args.common.order == SortOrder::Desc && date.is_none() {
+        hours.reverse();
+    }
+    let totals = calculate_totals_entries(&entries);
+
+    if args.common.json {
+        let json = serde_json::json!({
+            "totals": TotalsOutput {
+                cost_coverage: cost_coverage(&totals, args.common.mode),
+                ..totals_output(totals, &args.common.columns)
+            }
+        });
+        let records = hours.into_iter().map(HourlyEntryOutput::from);
+        return emit_json_records("hourly", records, json, &args.common);
+    }
+
+    let mut output = String::new();
+    let period = match date {
+        Some(date) => format!("Hourly {}", date.format("%Y-%m-%d")),
+        None => "Hourly".to_string(),
+    };
+    write_title(&mut output, &period, &args.common)?;
+    let mode = table_mode(&args.common);
+    let token_format = token_format(args.common.kmb);
+    let max_models = args.common.max_models.or(config.max_models);
+    let mut table = usage_table_with("Hour", mode, &args.common, &[]);
+    for hour in &hours {
+        let first_col = if date.is_some() {
+            format!("{:02}:00", hour.hour)
+        } else {
+            format!("{} {:02}:00", hour.date, hour.hour)
+        };
+        let data = UsageDataRow {
+            input_tokens: hour.input_tokens,
+            output_tokens: hour.output_tokens,
+            cache_creation_tokens: hour.cache_creation_tokens,
+            cache_read_tokens: hour.cache_read_tokens,
+            total_tokens: hour.total_tokens,
+            total_cost: hour.total_cost,
+            models_used: hour.models_used.clone(),
+            message_count: hour.message_count,
+            subagent_cost: hour.subagent_cost,
+            tool_calls: hour.tool_calls,
+            api_errors: hour.api_errors,
+            sessions: hour.session_count as u64,
+            first_timestamp: hour.first_timestamp,
+            last_timestamp: hour.last_timestamp,
+            active_hours: 1,
+            z_score: None,
+        };
+        let row = build_usage_row(&first_col, &data, mode, token_format, max_models);
+        table.add_data_row(row, &data);
+        if args.common.breakdown {
+            let breakdowns = breakdown_rows_from_breakdowns(&hour.model_breakdowns);
+            for breakdown in build_breakdown_rows(&breakdowns, mode, token_format) {
+                table.add_row(breakdown);
+            }
+        }
+    }
+    let totals_data = usage_row_from_totals(&totals);
+    table.add_data_row(
+        build_totals_row(&totals_data, mode, token_format),
+        &totals_data,
+    );
+    write_table(&mut output, &table.table, &args.common)?;
+    write_cost_coverage(&mut output, &totals, &args.common)?;
+    emit_output(&output, &args.common)
+}
+
+fn run_forecast(args: ForecastArgs, config: &Config) -> Result<()> {
+    let timezone = args.common.timezone.as_deref();
+    let today = today_in_timezone(timezone)
+        .ok_or_else(|| invalid_timezone(timezone.unwrap_or_default()))?;
+    let window_start = today - chrono::Duration::days(i64::from(args.days) - 1);
+    let since = window_start.min(today.with_day(1).unwrap_or(today));
+
+    let mut options = common_options(&args.common, config)?;
+    options.project = args.project.clone().or(options.project);
+    options.since = Some(since.format("%Y%m%d").to_string());
+    options.until = Some(today.format("%Y%m%d").to_string());
+    let daily = load_daily_usage_data(options)?;
+    if daily.is_empty() {
+        return report_no_data(&args.common, config, "No usage data found.");
+    }
+    let forecast = forecast_month(&daily, today, args.days);
+    let budget = args.budget.or(config.budgets.monthly);
+    let projected_share = |budget: f64| (budget > 0.0).then(|| forecast.projected_cost / budget);
+    let over_budget = budget.filter(|budget| forecast.projected_cost > *budget);
+
+    if args.common.json {
+        let output = ForecastOutput {
+            month: forecast.month.clone(),
+            days_in_month: forecast.days_in_month,
+            days_elapsed: forecast.days_elapsed,
+            days_remaining: forecast.days_remaining(),
+            month_to_date_cost: forecast.month_to_date_cost,
+            window_days: forecast.window_days,
+            daily_run_rate: forecast.daily_run_rate,
+            daily_std_dev: forecast.daily_std_dev,
+            projected_cost: forecast.projected_cost,
+            projected_low: forecast.projected_low,
+            projected_high: forecast.projected_high,
+            budget: budget.map(|amount| ForecastBudgetOutput {
+                amount,
+                projected_share: projected_share(amount),
+                over_budget: over_budget.is_some(),
+            }),
+        };
+        emit_json(&output, &args.common)?;
+    } else {
+        let mut output = String::new();
+        write_title(&mut output, "Forecast", &args.common)?;
+        let mut headers = vec![
+            "Month",
+            "Days Left",
+            "Month to Date",
+            "Run-Rate/Day",
+            "Projected",
+            "Range",
+        ];
+        let mut row = vec![
+            Cell::new(&forecast.month),
+            Cell::new(forecast.days_remaining()),
+            Cell::new(format_currency(forecast.month_to_date_cost)),
+            Cell::new(format_currency(forecast.daily_run_rate)),
+            Cell::new(format_currency(forecast.projected_cost)),
+            Cell::new(format!(
+                "{} - {}",
+                format_currency(forecast.projected_low),
+                format_currency(forecast.projected_high)
+            )),
+        ];
+        if let Some(budget) = budget {
+            headers.extend(["Budget", "Budget Used"]);
+            let share = projected_share(budget);
+            let used = Cell::new(
+                share.map_or_else(|| "-".to_string(), |share| format!("{:.1}%", share * 100.0)),
+            );
+            row.push(Cell::new(format_currency(budget)));
+            row.push(match share.and_then(|share| budget_color(share * 100.0)) {
+                Some(color) => used.fg(color),
+                None => used,
+            });
+        }
+        let mut table = Table::new();
+        table.load_preset(TABLE_PRESET);
+        table.set_header(headers);
+        table.add_row(row);
+        write_table(&mut output, &table, &args.common)?;
+        if !args.common.quiet {
+            writeln!(
+                output,
+                "Run-rate: average daily cost of the last {} days (including today), counting days without usage as zero.",
+                forecast.window_days
+            )?;
+        }
+        emit_output(&output, &args.common)?;
+    }
+
+    let Some(budget) = over_budget else {
+        return Ok(());
+    };
+    let summary = format!(
+        "{} is projected to cost {}, over the {}/month budget",
+        forecast.month,
+        format_currency(forecast.projected_cost),
+        format_currency(budget)
+    );
+    if args.fail_on_over_budget {
+        return Err(coded_error(ErrorCode::OverBudget, summary));
+    }
+    warn(summary);
+    Ok(())
+}
+
+fn run_diff(args: DiffArgs, config: &Config) -> Result<()> {
+    let timezone = args.common.timezone.as_deref();
+    let today = today_in_timezone(timezone)
+        .ok_or_else(|| invalid_timezone(timezone.unwrap_or_default()))?;
+    let day = |value: &str| {
+        parse_day(value).map_err(|error| coded_error(ErrorCode::InvalidArgument, error.to_string()))
+    };
+    let Some(since) = args.common.since.as_deref().map(day).transpose()? else {
+        return Err(coded_error(
+            ErrorCode::InvalidArgument,
+            "`ccost diff` needs the period to compare: --since (and --until), or a range such as --last 7d",
+        ));
+    };
+    let until = args
+        .common
+        .until
+        .as_deref()
+        .map(day)
+        .transpose()?
+        .unwrap_or(today);
+    if until < since {
+        return Err(coded_error(
+            ErrorCode::InvalidArgument,
+            "--until is before --since",
+        ));
+    }
+    let (vs_since, vs_until) = match (args.vs_since, args.vs_until) {
+        (Some(vs_since), Some(vs_until)) => (vs_since, vs_until),
+        _ => preceding_period(since, until),
+    };
+
+    let mut options = common_options(&args.common, config)?;
+    options.project = args.project.clone().or(options.project);
+    let mut load = |since: NaiveDate, until: NaiveDate| {
+        options.since = Some(since.format("%Y%m%d").to_string());
+        options.until = Some(until.format("%Y%m%d").to_string());
+        load_usage_entries(&options)
+    };
+    let current = load(since, until)?;
+    let baseline = load(vs_since, vs_until)?;
+    if current.is_empty() && baseline.is_empty() {
+        return report_no_data(
+            &args.common,
+            config,
+            "No usage data found in either period.",
+        );
+    }
+    let (models, totals) = compare_periods(&baseline, &current);
+    let period = |since: NaiveDate, until: NaiveDate| DiffPeriodOutput {
+        since: since.format("%Y-%m-%d").to_string(),
+        until: until.format("%Y-%m-%d").to_string(),
+    };
+
+    if args.common.json {
+        let json = serde_json::json!({
+            "period": period(since, until),
+            "baseline": period(vs_since, vs_until),
+            "models": models.iter().map(DiffModelOutput::from).collect::<Vec<_>>(),
+            "totals": DiffModelOutput::from(&totals),
+        });
+        return emit_json(&json, &args.common);
+    }
+
+    let mut output = String::new();
+    let range = |period: DiffPeriodOutput| format!("{} to {}", period.since, period.until);
+    write_title(
+        &mut output,
+        &format!(
+            "{} vs {}",
+            range(period(since, until)),
+            range(period(vs_since, vs_until))
+        ),
+        &args.common,
+    )?;
+    let tokens = token_format(args.common.kmb);
+    let with_ratio = |change: String, ratio: Option<f64>| match ratio {
+        Some(ratio) => format!("{change} ({:+.1}%)", ratio * 100.0),
+        None => change,
+    };
+    let mut table = Table::new();
+    table.load_preset(TABLE_PRESET);
+    table.set_header([
+        "Model",
+        "Tokens Before",
+        "Tokens",
+        "Tokens Change",
+        "Cost Before",
+        "Cost",
+        "Cost Change",
+    ]);
+    for (change, name) in models
+        .iter()
+        .map(|change| (change, format_model_name(&change.model)))
+        .chain([(&totals, totals.model.clone())])
+    {
+        let token_change = change.token_change();
+        let token_change = format!(
+            "{}{}",
+            if token_change < 0 { "-" } else { "+" },
+            format_tokens(token_change.unsigned_abs(), tokens)
+        );
+        let cost_change = Cell::new(with_ratio(
+            format_signed_currency(change.cost_change()),
+            change.cost_change_ratio(),
+        ));
+        table.add_row([
+            Cell::new(name),
+            Cell::new(format_tokens(change.baseline.total_tokens, tokens)),
+            Cell::new(format_tokens(change.current.total_tokens, tokens)),
+            Cell::new(with_ratio(token_change, change.token_change_ratio())),
+            Cell::new(format_currency(change.baseline.cost)),
+            Cell::new(format_currency(change.current.cost)),
+            if change.cost_change() > 0.0 {
+                cost_change.fg(Color::Red)
+            } else if change.cost_change() < 0.0 {
+                cost_change.fg(Color::Green)
+            } else {
+                cost_change
+            },
+        ]);
+    }
+    write_table(&mut output, &table, &args.common)?;
+    emit_output(&output, &args.common)
+}
+
+fn run_summary(args: SummaryArgs, config: &Config) -> Result<()> {
+    let timezone = args.common.timezone.as_deref();
+    let today = today_in_timezone(timezone)
+        .ok_or_else(|| invalid_timezone(timezone.unwrap_or_default()))?;
+    let month_start = today.with_day(1).unwrap_or(today);
+
+    let mut options = common_options(&args.common, config)?;
+    options.since = Some(month_start.format("%Y%m%d").to_string());
+    options.until = Some(today.format("%Y%m%d").to_string());
+    let daily = load_daily_usage_data(options)?;
+
+    let today_key = today.format("%Y-%m-%d").to_string();
+    let (today_tokens, today_cost) = daily
+        .iter()
+        .find(|entry| entry.date == today_key)
+        .map_or((0, 0.0), |entry| (entry.total_tokens, entry.total_cost));
+    let month_totals = calculate_totals_daily(&daily);
+    let top_model = top_model_by_cost(&daily);
+
+    if args.common.json {
+        let output = SummaryOutput {
+            today: SummaryPeriodOutput {
+                period: today_key,
+                total_tokens: today_tokens,
+                total_cost: today_cost,
+            },
+            month: SummaryPeriodOutput {
+                period: month_start.format("%Y-%m").to_string(),
+                total_tokens: month_totals.total_tokens(),
+                total_cost: month_totals.total_cost,
+            },
+            top_model,
+        };
+        emit_json(&output, &args.common)?;
+        return Ok(());
+    }
+    if args.common.format.is_plain_table() {
+        let token_format = token_format(args.common.kmb);
+        let mut table = Table::new();
+        table.load_preset(TABLE_PRESET);
+        table.set_header(vec![
+            "Period",
+            "Total Tokens",
+            cost_header("Cost").as_str(),
+            "Top Model",
+        ]);
+        table.add_row(vec![
+            today_key,
+            format_tokens(today_tokens, token_format),
+            format_currency(today_cost),
+            String::new(),
+        ]);
+        table.add_row(vec![
+            month_start.format("%Y-%m").to_string(),
+            format_tokens(month_totals.total_tokens(), token_format),
+            format_currency(month_totals.total_cost),
+            top_model
+                .as_deref()
+                .map(format_model_name)
+                .unwrap_or_default(),
+        ]);
+        let mut output = String::new();
+        write_table(&mut output, &table, &args.common)?;
+        return emit_output(&output, &args.common);
+    }
+
+    println!(
+        "{}",
+        summary_line(
+            today_tokens,
+            today_cost,
+            month_totals.total_cost,
+            top_model.as_deref()
+        )
+    );
+    Ok(())
+}
+
+fn run_badge(args: BadgeArgs, config: &Config) -> Result<()> {
+    let timezone = args.common.timezone.as_deref();
+    let today = today_in_timezone(timezone)
+        .ok_or_else(|| invalid_timezone(timezone.unwrap_or_default()))?;
+    let month_start = today.with_day(1).unwrap_or(today);
+
+    let mut options = common_options(&args.common, config)?;
+    let (since, until) = match args.metric {
+        BadgeMetric::TodayCost | BadgeMetric::TodayTokens => (today, today),
+        BadgeMetric::MonthCost | BadgeMetric::MonthTokens => (month_start, today),
+    };
+    options.since = Some(since.format("%Y%m%d").to_string());
+    options.until = Some(until.format("%Y%m%d").to_string());
+    let totals = calculate_totals_daily(&load_daily_usage_data(options)?);
+
+    let output = BadgeOutput {
+        schema_version: 1,
+        label: args.label,
+        message: badge_message(args.metric, &totals),
+        color: args.color,
+    };
+    println!("{}", serde_json::to_string(&output)?);
+    Ok(())
+}
+
+fn badge_message(metric: BadgeMetric, totals: &UsageTotals) -> String {
+    match metric {
+        BadgeMetric::MonthCost | BadgeMetric::TodayCost => format_currency(totals.total_cost),
+        BadgeMetric::MonthTokens | BadgeMetric::TodayTokens => {
+            format_tokens(totals.total_tokens(), TokenFormat::HumanReadable)
+        }
+    }
+}
+
+fn run_recap(args: RecapArgs, config: &Config) -> Result<()> {
+    let (first_day, last_day) = match &args.month {
+        Some(month) => month_bounds(month)?,
+        None => {
+            let timezone = args.common.timezone.as_deref();
+            let today = today_in_timezone(timezone)
+                .ok_or_else(|| invalid_timezone(timezone.unwrap_or_default()))?;
+            month_bounds(&today.format("%Y-%m").to_string())?
+        }
+    };
+
+    let mut options = common_options(&args.common, config)?;
+    options.group_by_project = true;
+    options.since = Some(first_day.format("%Y%m%d").to_string());
+    options.until = Some(last_day.format("%Y%m%d").to_string());
+    let daily = load_daily_usage_data(options)?;
+
+    let recap = recap_markdown(&first_day.format("%Y-%m").to_string(), &daily);
+    emit_output(&recap, &args.common)
+}
+
+/// First and last day of a `YYYY-MM` month.
+fn month_bounds(month: &str) -> Result<(NaiveDate, NaiveDate)> {
+    let first_day =
+        NaiveDate::parse_from_str(&format!("{month}-01"), "%Y-%m-%d").map_err(|_| {
+            coded_error(
+                ErrorCode::InvalidArgument,
+                format!("Invalid month: {month} (expected YYYY-MM)"),
+            )
+        })?;
+    let last_day = first_day
+        .checked_add_months(Months::new(1))
+        .and_then(|next| next.pred_opt())
+        .ok_or_else(|| {
+            coded_error(
+                ErrorCode::InvalidArgument,
+                format!("Invalid month: {month}"),
+            )
+        })?;
+    Ok((first_day, last_day))
+}
+
+fn run_import(args: ImportArgs) -> Result<()> {
+    let content = std::fs::read_to_string(&args.file)
+        .map_err(|error| anyhow!("failed to read {}: {error}", args.file.display()))?;
+    let format = match args.format {
+        ImportFormat::Auto
+            if args
+                .file
+                .extension()
+                .is_some_and(|extension| extension.eq_ignore_ascii_case("csv")) =>
+        {
+            ImportFormat::Csv
+        }
+        ImportFormat::Auto => ImportFormat::Ccusage,
+        format => format,
+    };
+    let mut records = match format {
+        ImportFormat::Csv => parse_csv_export(&content, &parse_csv_mapping(&args.map)?),
+        ImportFormat::Openrouter => parse_openrouter_export(&content),
+        _ => parse_ccusage_export(&content),
+    }
+    .map_err(|error| anyhow!("{}: {error}", args.file.display()))?;
+    if records.is_empty() {
+        return Err(anyhow!("{}: no records to import", args.file.display()));
+    }
+    if let Some(project) = &args.project {
+        for record in &mut records {
+            record.project = Some(project.clone());
+        }
+    }
+
+    let name = project_dir_name(Some(args.name.as_deref().unwrap_or_else(|| {
+        args.file
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or(DEFAULT_IMPORT_PROJECT)
+    })));
+    let ids = record_ids(&records);
+    let mut projects: std::collections::BTreeMap<String, Vec<String>> =
+        std::collections::BTreeMap::new();
+    for (record, id) in records.iter().zip(&ids) {
+        projects
+            .entry(project_dir_name(record.project.as_deref()))
+            .or_default()
+            .push(claude_usage_line(record, &name, id));
+    }
+    let first = records.iter().map(|record| record.timestamp).min();
+    let last = records.iter().map(|record| record.timestamp).max();
+    let cost = records.iter().filter_map(|record| record.cost).sum::<f64>();
+    let range = match (first, last) {
+        (Some(first), Some(last)) => format!(
+            "{} to {}",
+            first.format("%Y-%m-%d"),
+            last.format("%Y-%m-%d")
+        ),
+        _ => String::new(),
+    };
+
+    let root = imports_dir()
+        .ok_or_else(|| anyhow!("Could not determine the ccost config directory"))?
+        .join(CLAUDE_PROJECTS_DIR_NAME);
+    // Files of an earlier import with the same name, whichever projects they were in, are
+    // replaced; records that other imports already hold are written too but counted once.
+    let file_name = format!("{name}.jsonl");
+    let mut previous = Vec::new();
+    let mut imported = std::collections::HashSet::new();
+    for project in std::fs::read_dir(&root).into_iter().flatten().flatten() {
+        for file in std::fs::read_dir(project.path())
+            .into_iter()
+            .flatten()
+            .flatten()
+        {
+            if file.file_name().to_str() == Some(file_name.as_str()) {
+                previous.push(file.path());
+            } else {
+                imported.extend(imported_ids(&file.path()));
+            }
+        }
+    }
+    let already = ids.iter().filter(|id| imported.contains(*id)).count();
+    let overlap = if already == 0 {
+        String::new()
+    } else {
+        format!(", {already} of them already imported under another name")
+    };
+
+    if args.dry_run {
+        println!(
+            "Would import {} records ({range}, {}) into {} as \"{name}\"{overlap}",
+            records.len(),
+            format_currency(cost),
+            root.display()
+        );
+        return Ok(());
+    }
+
+    for path in &previous {
+        std::fs::remove_file(path)
+            .map_err(|error| anyhow!("failed to remove {}: {error}", path.display()))?;
+    }
+    for (project, lines) in &projects {
+        let dir = root.join(project);
+        std::fs::create_dir_all(&dir)
+            .map_err(|error| anyhow!("failed to create {}: {error}", dir.display()))?;
+        let path = dir.join(format!("{name}.jsonl"));
+        std::fs::write(&path, format!("{}\n", lines.join("\n")))
+            .map_err(|error| anyhow!("failed to write {}: {error}", path.display()))?;
+    }
+    println!(
+        "Imported {} records ({range}, {}) into {} as \"{name}\"{overlap}",
+        records.len(),
+        format_currency(cost),
+        root.display()
+    );
+    Ok(())
+}
+
+fn run_query_command(args: QueryArgs, config: &Config) -> Result<()> {
+    let Some(sql) = args.sql.as_deref().filter(|_| !args.schema) else {
+        println!("{ENTRIES_SCHEMA};");
+        return Ok(());
+    };
+    let options = common_options(&args.common, config)?;
+    let entries = load_usage_entries(&options)?;
+    let result = run_query(&entries, sql)?;
+
+    if args.common.json {
+        let rows = result
+            .rows
+            .into_iter()
+            .map(|row| {
+                result
+                    .columns
+                    .iter()
+                    .cloned()
+                    .zip(row)
+                    .collect::<serde_json::Map<_, _>>()
+            })
+            .collect::<Vec<_>>();
+        emit_json(&rows, &args.common)?;
+        return Ok(());
+    }
+
+    let mut table = Table::new();
+    table.load_preset(TABLE_PRESET);
+    table.set_header(result.columns);
+    for row in result.rows {
+        table.add_row(row.into_iter().map(|value| match value {
+            serde_json::Value::Null => String::new(),
+            serde_json::Value::String(text) => text,
+            other => other.to_string(),
+        }));
+    }
+    let mut output = String::new();
+    write_table(&mut output, &table, &args.common)?;
+    emit_output(&output, &args.common)
+}
+
+/// Per-project daily totals over all history, in the shape saved by `ccost snapshot`.
+fn current_snapshot(common: &CommonArgs, config: &Config) -> Result<Snapshot> {
+    let timezone = common.timezone.as_deref();
+    let today = today_in_timezone(timezone)
+        .ok_or_else(|| invalid_timezone(timezone.unwrap_or_default()))?;
+    let mut options = common_options(common, config)?;
+    options.group_by_project = true;
+    let daily = load_daily_usage_data(options)?;
+    Ok(Snapshot::from_daily(
+        &daily,
+        Utc::now(),
+        today.format("%Y-%m-%d").to_string(),
+    ))
+}
+
+fn run_snapshot(args: SnapshotArgs, config: &Config) -> Result<()> {
+    let snapshot = current_snapshot(&args.common, config)?;
+    let dir =
+        snapshots_dir().ok_or_else(|| anyhow!("Could not determine the ccost config directory"))?;
+    let path = save_snapshot(&dir, &snapshot)?;
+    let cost = snapshot.days.iter().map(|day| day.total_cost).sum::<f64>();
+    println!(
+        "Saved snapshot of {} project-days ({}) to {}",
+        snapshot.days.len(),
+        format_currency(cost),
+        path.display()
+    );
+    Ok(())
+}
+
+fn run_drift(args: DriftArgs, config: &Config) -> Result<()> {
+    let path = match &args.snapshot {
+        Some(path) => path.clone(),
+        None => snapshots_dir()
+            .and_then(|dir| latest_snapshot_path(&dir))
+            .ok_or_else(|| anyhow!("No snapshot found; run `ccost snapshot` first"))?,
+    };
+    let saved = load_snapshot(&path)?;
+    let live = current_snapshot(&args.common, config)?;
+    let drift = detect_drift(&saved, &live);
+
+    if args.common.json {
+        let output = serde_json::json!({
+            "snapshot": path,
+            "snapshotCreated": saved.created,
+            "drift": drift,
+        });
+        emit_json(&output, &args.common)?;
+    } else if drift.is_empty() {
+        println!(
+            "No drift since the snapshot of {} ({})",
+            saved.created.format("%Y-%m-%d %H:%M UTC"),
+            path.display()
+        );
+    } else {
+        let mut output = String::new();
+        writeln!(
+            output,
+            "Drift since the snapshot of {} ({})",
+            saved.created.format("%Y-%m-%d %H:%M UTC"),
+            path.display()
+        )?;
+        writeln!(
+            output,
+            "{}",
+            drift_table(&drift, token_format(args.common.kmb))
+        )?;
+        emit_output(&output, &args.common)?;
+    }
+
+    if args.fail_on_drift && !drift.is_empty() {
+        return Err(anyhow!("{} day(s) changed since the snapshot", drift.len()));
+    }
+    Ok(())
+}
+
+fn drift_table(drift: &[Drift], token_format: TokenFormat) -> Table {
+    let tokens = |day: Option<&SnapshotDay>| {
+        day.map_or("-".to_string(), |day| {
+            format_tokens(day.total_tokens, token_format)
+        })
+    };
+    let cost = |day: Option<&SnapshotDay>| {
+        day.map_or("-".to_string(), |day| format_currency(day.total_cost))
+    };
+    let mut table = Table::new();
+    table.load_preset(TABLE_PRESET);
+    table.set_header(vec![
+        "Date",
+        "Project",
+        "Change",
+        "Tokens Before",
+        "Tokens After",
+        "Cost Before",
+        "Cost After",
+    ]);
+    for entry in drift {
+        let change = match entry.kind {
+            DriftKind::Removed => "removed",
+            DriftKind::Changed => "changed",
+            DriftKind::Added => "added",
+        };
+        table.add_row(vec![
+            entry.date.clone(),
+            entry.project.clone().unwrap_or_default(),
+            change.to_string(),
+            tokens(entry.before.as_ref()),
+            tokens(entry.after.as_ref()),
+            cost(entry.before.as_ref()),
+            cost(entry.after.as_ref()),
+        ]);
+    }
+    table
+}
+
+fn run_notify(args: NotifyArgs, config: &Config) -> Result<()> {
+    let timezone = args.common.timezone.as_deref();
+    let today = today_in_timezone(timezone)
+        .ok_or_else(|| invalid_timezone(timezone.unwrap_or_default()))?;
+    let month_start = today.with_day(1).unwrap_or(today);
+
+    let mut options = common_options(&args.common, config)?;
+    options.group_by_project = true;
+    options.since = Some(month_start.format("%Y%m%d").to_string());
+    options.until = Some(today.format("%Y%m%d").to_string());
+    let daily = load_daily_usage_data(options)?;
+
+    let today_key = today.format("%Y-%m-%d").to_string();
+    let today_usage = daily
+        .iter()
+        .filter(|entry| entry.date == today_key)
+        .cloned()
+        .collect::<Vec<_>>();
+    let today_totals = calculate_totals_daily(&today_usage);
+    let month_totals = calculate_totals_daily(&daily);
+    let summary = NotifySummary {
+        date: today_key,
+        today_tokens: today_totals.total_tokens(),
+        today_cost: today_totals.total_cost,
+        month: month_start.format("%Y-%m").to_string(),
+        month_tokens: month_totals.total_tokens(),
+        month_cost: month_totals.total_cost,
+        top_projects: top_projects_by_cost(&daily, args.top),
+        daily_budget: config.budgets.daily,
+        monthly_budget: config.budgets.monthly,
+    };
+
+    let url = args.webhook.as_deref().unwrap_or_default();
+    let payload = webhook_payload(url, &summary_text(&summary));
+    if args.dry_run {
+        println!("{}", serde_json::to_string_pretty(&payload)?);
+        return Ok(());
+    }
+    post_webhook(url, &payload)
+}
+
+fn run_email(args: EmailArgs, config: &Config) -> Result<()> {
+    let options = common_options(&args.common, config)?;
+    let monthly = load_monthly_usage_data(options)?;
+    let body = if monthly.is_empty() {
+        "No usage data found.\n".to_string()
+    } else {
+        render_monthly_report(
+            &monthly,
+            TableMode::Full,
+            &args.common,
+            config,
+            config.budgets.monthly,
+        )?
+    };
+    let message = compose_message(
+        &EmailMessage {
+            from: &args.from,
+            to: &args.to,
+            subject: &args.subject,
+            body: &body,
+            html: args.html,
+        },
+        Utc::now(),
+    )?;
+
+    match args.smtp.as_deref() {
+        Some(smtp) if !args.dry_run => send_message(
+            smtp,
+            &args.from,
+            &args.to,
+            args.smtp_user.as_deref(),
+            &message,
+        ),
+        _ => {
+            print!("{message}");
+            Ok(())
+        }
+    }
+}
+
+/// Parses a `[[schedule]]` entry's command. Scheduled reports run unattended, so interactive
+/// commands are refused and output is never sent to a pager.
+fn scheduled_command(report: &ScheduledReport) -> Result<Command> {
+    let args = std::iter::once("ccost").chain(report.command.iter().map(String::as_str));
+    let mut cli = Cli::try_parse_from(args)
+        .map_err(|error| anyhow!("Invalid scheduled command `{}`: {error}", report.describe()))?;
+    match cli.command {
+        Command::Schedule(_) => {
+            return Err(anyhow!("A scheduled command cannot run `schedule` itself"));
+        }
+        Command::Live(_) | Command::Tui(_) | Command::Init(_) => {
+            return Err(anyhow!(
+                "Scheduled command `{}` is interactive and cannot run unattended",
+                report.describe()
+            ));
+        }
+        _ => {}
+    }
+    if let Some(common) = cli.command.common_mut() {
+        common.no_pager = true;
+    }
+    Ok(cli.command)
+}
+
+fn run_init(args: InitArgs) -> Result<()> {
+    let path =
+        config_path().ok_or_else(|| anyhow!("Could not determine the ccost config directory"))?;
+    if path.exists() && !args.force {
+        return Err(anyhow!(
+            "{} already exists; rerun with --force to replace it",
+            path.display()
+        ));
+    }
+    let detected = get_claude_paths().unwrap_or_default();
+    let answers = ask_init_answers(
+        &mut std::io::stdin().lock(),
+        &mut std::io::stdout(),
+        &detected,
+    )?;
+    let toml = init_config_toml(&answers);
+    parse_config(&toml).map_err(|error| {
+        coded_error(
+            ErrorCode::InvalidConfig,
+            format!("ccost init built an invalid config, nothing was written: {error}"),
+        )
+    })?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|error| anyhow!("failed to create {}: {error}", dir.display()))?;
+    }
+    std::fs::write(&path, toml)
+        .map_err(|error| anyhow!("failed to write {}: {error}", path.display()))?;
+    println!("Wrote {}", path.display());
+    Ok(())
+}
+
+fn run_schedule(args: ScheduleArgs, config: &Config) -> Result<()> {
+    let reports = config
+        .schedules
+        .iter()
+        .map(ScheduledReport::from_entry)
+        .collect::<Result<Vec<_>>>()?;
+    if reports.is_empty() {
+        return Err(anyhow!("No [[schedule]] entries in the config file"));
+    }
+    for report in &reports {
+        scheduled_command(report)?;
+    }
+
+    let now = Local::now().naive_local();
+    if args.list {
+        for report in &reports {
+            let next = report.next_run(now).map_or_else(
+                || "never".to_string(),
+                |next| next.format("%Y-%m-%d %H:%M").to_string(),
+            );
+            println!("{} (next: {next})", report.describe());
+        }
+        return Ok(());
+    }
+
+    let mut last_check = now;
+    loop {
+        // Sleep in short steps so suspend/resume or clock changes are noticed promptly.
+        std::thread::sleep(std::time::Duration::from_secs(30));
+        let now = Local::now().naive_local();
+        for report in &reports {
+            if report.next_run(last_check).is_some_and(|next| next <= now) {
+                eprintln!("Running {}", report.describe());
+                let result = scheduled_command(report).and_then(|mut command| {
+                    prepare_command(&mut command, config)?;
+                    dispatch(command, config)
+                });
+                if let Err(error) = result {
+                    eprintln!("Scheduled report failed: {error}");
+                }
+                print_warnings();
+            }
+        }
+        last_check = now;
+    }
+}
+
+fn run_render(args: RenderArgs, config: &Config) -> Result<()> {
+    let source = std::fs::read_to_string(&args.template)
+        .map_err(|error| anyhow!("failed to read {}: {error}", args.template.display()))?;
+    let template = Template::parse(&source)
+        .map_err(|error| anyhow!("{}: {error}", args.template.display()))?;
+
+    let options = common_options(&args.common, config)?;
+    let daily = load_daily_usage_data(options.clone())?;
+    let monthly = load_monthly_usage_data(options)?;
+    // Templates get every optional column so they never depend on --columns.
+    let columns = ExtraColumn::value_variants();
+    let context = serde_json::json!({
+        "title": report_title("Usage", &args.common),
+        "generated": Local::now().format("%Y-%m-%d %H:%M").to_string(),
+        "since": args.common.since,
+        "until": args.common.until,
+        "totals": totals_output(calculate_totals_daily(&daily), columns),
+        "daily": daily
+            .into_iter()
+            .map(|entry| daily_entry_output(entry, true, columns))
+            .collect::<Vec<_>>(),
+        "monthly": monthly
+            .into_iter()
+            .map(|entry| monthly_entry_output(entry, columns))
+            .collect::<Vec<_>>(),
+    });
+    let rendered = template
+        .render(&context)
+        .map_err(|error| anyhow!("{}: {error}", args.template.display()))?;
+
+    match &args.output {
+        Some(path) => std::fs::write(path, rendered)
+            .map_err(|error| anyhow!("failed to write {}: {error}", path.display())),
+        None => emit_output(&rendered, &args.common),
+    }
+}
+
+fn run_conversation(args: ConversationArgs, config: &Config) -> Result<()> {
+    let mut options = common_options(&args.common, config)?;
+    options.session = Some(args.session_id.clone());
+    let entries = load_usage_entries(&options)?;
+    if entries.is_empty() {
+        return Err(anyhow!(
+            "No usage data found for session {}",
+            args.session_id
+        ));
+    }
+
+    let mut session_ids = entries
+        .iter()
+        .filter_map(|entry| entry.session_id.as_deref())
+        .collect::<Vec<_>>();
+    session_ids.sort_unstable();
+    session_ids.dedup();
+    if session_ids.len() > 1 {
+        return Err(anyhow!(
+            "Session prefix {} is ambiguous: {}",
+            args.session_id,
+            session_ids.join(", ")
+        ));
+    }
+    let session_id = session_ids
+        .first()
+        .map_or(args.session_id.clone(), |id| id.to_string());
+    let project = entries.iter().find_map(|entry| entry.project.clone());
+    let session_title = load_session_titles(&entries, options.read_limits).remove(&session_id);
+    let totals = calculate_totals_entries(&entries);
+
+    if args.common.json {
+        let json = serde_json::json!({
+            "sessionId": session_id,
+            "title": session_title,
+            "project": project,
+            "entries": entries
+                .iter()
+                .map(conversation_entry_output)
+                .collect::<Vec<_>>(),
+            "totals": totals_output(totals, &args.common.columns)
+        });
+        return emit_json(&json, &args.common);
+    }
+
+    let mut output = String::new();
+    let mut title = format!("Conversation {session_id}");
+    if let Some(session_title) = &session_title {
+        write!(title, ": {session_title}")?;
+    }
+    if let Some(project) = &project {
+        write!(title, " ({project})")?;
+    }
+    if !args.common.quiet {
+        writeln!(output, "{title}")?;
+    }
+
+    let mode = table_mode(&args.common);
+    let token_format = token_format(args.common.kmb);
+    let max_models = args.common.max_models.or(config.max_models);
+    let mut table = usage_table("Time", mode, &args.common);
+    for entry in &entries {
+        let time = format_timestamp(entry.timestamp, args.common.timezone.as_deref());
+        let data = usage_row_from_entry(entry);
+        let row = build_usage_row(&time, &data, mode, token_format, max_models);
+        table.add_data_row(row, &data);
+    }
+    let totals_data = usage_row_from_totals(&totals);
+    table.add_data_row(
+        build_totals_row(&totals_data, mode, token_format),
+        &totals_data,
+    );
+    write_table(&mut output, &table.table, &args.common)?;
+    emit_output(&output, &args.common)
+}
+
+fn run_session(args: SessionArgs, config: &Config) -> Result<()> {
+    let mut options = common_options(&args.common, config)?;
+    options.project = args.project.clone().or(options.project);
+    let entries = load_usage_entries(&options)?;
+    let mut sessions = rank_sessions(
+        group_entries_by_session(&entries),
+        args.sort,
+        options.order,
+        args.top,
+    );
+    if sessions.is_empty() {
+        return report_no_data(&args.common, config, "No usage data found.");
+    }
+    let mut titles = load_session_titles(&entries, options.read_limits);
+    for session in &mut sessions {
+        session.title = titles.remove(&session.session_id);
+    }
+    let totals = if args.top.is_some() {
+        calculate_totals_sessions(&sessions)
+    } else {
+        calculate_totals_entries(&entries)
+    };
+
+    if args.common.json {
+        let json = serde_json::json!({
+            "sessions": sessions.iter().map(session_entry_output).collect::<Vec<_>>(),
+            "totals": totals_output(totals, &args.common.columns)
+        });
+        return emit_json(&json, &args.common);
+    }
+
+    let mut output = String::new();
+    write_title(&mut output, "Sessions", &args.common)?;
+
+    let token_format = token_format(args.common.kmb);
+    let max_models = args.common.max_models.or(config.max_models);
+    let cost = cost_header("Cost");
+    let mut table = Table::new();
+    table.load_preset(TABLE_PRESET);
+    let show_titles = sessions.iter().any(|session| session.title.is_some());
+    let mut header = vec![
+        "Session",
+        "Project",
+        "Last Activity",
+        "Models",
+        "Total Tokens",
+        cost.as_str(),
+        "Duration",
+        "Cost/h",
+        "Tokens/h",
+    ];
+    if show_titles {
+        header.insert(1, "Title");
+    }
+    table.set_header(header);
+    for session in &sessions {
+        let mut row = vec![
+            short_session_id(&session.session_id).to_string(),
+            session.project.clone().unwrap_or_default(),
+            format_timestamp(session.last_timestamp, args.common.timezone.as_deref()),
+            format_models_display_truncated(&session.models_used, max_models),
+            format_tokens(session.total_tokens, token_format),
+            format_currency(session.total_cost),
+            format_duration(session.duration()),
+            session
+                .cost_per_hour()
+                .map_or_else(|| "-".to_string(), format_currency),
+            session.tokens_per_hour().map_or_else(
+                || "-".to_string(),
+                |rate| format_tokens(rate.round() as u64, token_format),
+            ),
+        ];
+        if show_titles {
+            row.insert(
+                1,
+                session
+                    .title
+                    .as_deref()
+                    .map(truncate_title)
+                    .unwrap_or_default(),
+            );
+        }
+        table.add_row(row);
+    }
+    let mut total_row = vec![
+        "Total".to_string(),
+        String::new(),
+        String::new(),
+        String::new(),
+        format_tokens(totals.total_tokens(), token_format),
+        format_currency(totals.total_cost),
+        String::new(),
+        String::new(),
+        String::new(),
+    ];
+    if show_titles {
+        total_row.insert(1, String::new());
+    }
+    table.add_row(total_row);
+    write_table(&mut output, &table, &args.common)?;
+    emit_output(&output, &args.common)
+}
+
+fn run_export(args: ExportArgs, config: &Config) -> Result<()> {
+    if let Some(ExportTarget::Otlp(otlp)) = args.target {
+        return run_export_otlp(*otlp, config);
+    }
+    let dir = args.dir.unwrap_or_default();
+    let mut common = args.common.clone();
+    if common.format == OutputFormat::Table {
+        common.format = OutputFormat::Csv;
+        common.resolve_format();
+    }
+    let extension = match common.format {
+        OutputFormat::Csv => "csv",
+        OutputFormat::Md => "md",
+        OutputFormat::Json => "json",
+        OutputFormat::Ndjson => "ndjson",
+        OutputFormat::Table | OutputFormat::Pdf => {
+            return Err(anyhow!("export writes csv, json, ndjson, or md files"));
+        }
+    };
+
+    let mut options = common_options(&common, config)?;
+    options.group_by_project = true;
+    let projects = match args.split_by {
+        SplitBy::Project => load_daily_usage_data(options)?
+            .into_iter()
+            .filter_map(|day| day.project)
+            .collect::<std::collections::BTreeSet<_>>(),
+    };
+    if projects.is_empty() {
+        return report_no_data(&common, config, "No project usage data found.");
+    }
+
+    std::fs::create_dir_all(&dir)
+        .map_err(|error| anyhow!("failed to create {}: {error}", dir.display()))?;
+    for (project, stem) in export_file_stems(projects) {
+        let path = dir.join(format!("{stem}.{extension}"));
+        let daily = DailyArgs {
+            common: CommonArgs {
+                output_file: Some(path.clone()),
+                ..common.clone()
+            },
+            instances: false,
+            project: Some(project),
+            trend: false,
+            by_directory: false,
+            budget: None,
+            fail_on_over_budget: false,
+            group_by: None,
+        };
+        run_daily(daily, config)?;
+        println!("Wrote {}", path.display());
+    }
+    Ok(())
+}
+
+#[cfg(feature = "otlp")]
+fn run_export_otlp(args: OtlpArgs, config: &Config) -> Result<()> {
+    use crate::otlp::{DEFAULT_ENDPOINT, Signal, parse_headers, payloads, push};
+
+    let endpoint = args
+        .endpoint
+        .clone()
+        .or_else(|| std::env::var("OTEL_EXPORTER_OTLP_ENDPOINT").ok())
+        .filter(|endpoint| !endpoint.is_empty())
+        .unwrap_or_else(|| DEFAULT_ENDPOINT.to_string());
+    let mut headers =
+        parse_headers(&std::env::var("OTEL_EXPORTER_OTLP_HEADERS").unwrap_or_default())?;
+    headers.extend(parse_headers(&args.headers.join(","))?);
+    let signal = match args.signal {
+        OtlpSignal::Logs => Signal::Logs,
+        OtlpSignal::Metrics => Signal::Metrics,
+    };
+
+    let entries = load_usage_entries(&common_options(&args.common, config)?)?;
+    if entries.is_empty() {
+        return report_no_data(&args.common, config, "No usage data found.");
+    }
+    let payloads = payloads(&entries, signal, &args.service_name);
+    if args.dry_run {
+        let output = payloads
+            .iter()
+            .map(|payload| format!("{payload}\n"))
+            .collect::<String>();
+        return emit_output(&output, &args.common);
+    }
+    for payload in &payloads {
+        push(&endpoint, signal, &headers, payload)?;
+    }
+    eprintln!(
+        "Sent {} usage records in {} requests to {endpoint}",
+        entries.len(),
+        payloads.len()
+    );
+    Ok(())
+}
+
+#[cfg(not(feature = "otlp"))]
+fn run_export_otlp(_args: OtlpArgs, _config: &Config) -> Result<()> {
+    Err(coded_error(
+        ErrorCode::InvalidArgument,
+        "`ccost export otlp` needs a build with the `otlp` feature \
+         (cargo install --git https://github.com/mkusaka/ccost --features otlp)",
+    ))
+}
+
+/// File name stems of `projects`, from [`export_file_stem`]. Projects whose stems would
+/// collide (such as `a/b` and `a_b`, or `App` and `app` on case-insensitive file systems) get
+/// the first 8 hex digits of the SHA-256 of their name appended, so no file is overwritten.
+fn export_file_stems(projects: std::collections::BTreeSet<String>) -> Vec<(String, String)> {
+    let mut counts = std::collections::HashMap::<String, usize>::new();
+    for project in &projects {
+        *counts
+            .entry(export_file_stem(project).to_lowercase())
+            .or_default() += 1;
+    }
+    projects
+        .into_iter()
+        .map(|project| {
+            let mut stem = export_file_stem(&project);
+            if counts[&stem.to_lowercase()] > 1 {
+                let digest = Sha256::digest(project.as_bytes());
+                stem.push('-');
+                stem.extend(digest[..4].iter().map(|byte| format!("{byte:02x}")));
+            }
+            (project, stem)
+        })
+        .collect()
+}
+
+/// `project` with characters that aren't safe in file names replaced by `_`.
+fn export_file_stem(project: &str) -> String {
+    let stem = project
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+    match stem.trim_matches('.') {
+        "" => "_".to_string(),
+        _ => stem,
+    }
+}
+
+fn run_archive(args: ArchiveArgs, config: &Config) -> Result<()> {
+    let options = common_options(&args.common, config)?;
+    let cutoff = std::time::SystemTime::now()
+        - std::time::Duration::from_secs(u64::from(args.older_than) * 24 * 60 * 60);
+    let logs = claude_log_files(&options)
+        .into_iter()
+        .filter(|log| {
+            std::fs::metadata(&log.file)
+                .and_then(|metadata| metadata.modified())
+                .is_ok_and(|modified| modified < cutoff)
+        })
+        .map(|log| ArchivedLog::new(log.file, &log.base_dir))
+        .collect::<Vec<_>>();
+    if logs.is_empty() {
+        return report_no_data(
+            &args.common,
+            config,
+            &format!("No Claude Code logs older than {} days.", args.older_than),
+        );
+    }
+    let bytes = logs
+        .iter()
+        .filter_map(|log| std::fs::metadata(&log.file).ok())
+        .map(|metadata| metadata.len())
+        .sum::<u64>();
+    let summary = format!(
+        "{} log{} ({:.1} MB)",
+        logs.len(),
+        if logs.len() == 1 { "" } else { "s" },
+        bytes as f64 / 1_000_000.0
+    );
+    if args.dry_run {
+        for log in &logs {
+            println!("{}", log.file.display());
+        }
+        println!("Would archive {summary} to {}", args.to.display());
+        return Ok(());
+    }
+
+    write_archive(&logs, &args.to)?;
+    if !args.remove {
+        println!("Archived {summary} to {}", args.to.display());
+        return Ok(());
+    }
+    let index =
+        archive_dir().ok_or_else(|| anyhow!("Could not determine the ccost config directory"))?;
+    record_usage(&logs, &index)?;
+    for log in &logs {
+        std::fs::remove_file(&log.file)
+            .map_err(|error| anyhow!("failed to remove {}: {error}", log.file.display()))?;
+    }
+    println!(
+        "Archived {summary} to {} and removed the originals; their usage stays in reports through {}",
+        args.to.display(),
+        index.display()
+    );
+    Ok(())
+}
+
+fn run_whatif(args: WhatifArgs, config: &Config) -> Result<()> {
+    let scenario = Scenario {
+        no_cache: args.no_cache,
+        model_map: parse_model_map(&args.model_map)?,
+    };
+    if scenario == Scenario::default() {
+        return Err(anyhow!(
+            "Choose a scenario, e.g. --no-cache or --map claude-opus-4=claude-sonnet-4"
+        ));
+    }
+    let pricing = PricingFetcher::new();
+    for (_, to) in &scenario.model_map {
+        if pricing.get_model_pricing(to).is_none() {
+            return Err(anyhow!("No pricing data for model: {to}"));
+        }
+    }
+    let mut options = common_options(&args.common, config)?;
+    options.project = args.project.clone().or(options.project);
+    let models = simulate(&load_usage_entries(&options)?, &scenario, &pricing);
+    if models.is_empty() {
+        return report_no_data(&args.common, config, "No usage data found.");
+    }
+    let totals = WhatIfModel {
+        model: "Total".to_string(),
+        priced_as: None,
+        message_count: models.iter().map(|model| model.message_count).sum(),
+        actual_cost: models.iter().map(|model| model.actual_cost).sum(),
+        simulated_cost: models.iter().map(|model| model.simulated_cost).sum(),
+    };
+
+    if args.common.json {
+        let output = |model: &WhatIfModel| WhatIfModelOutput {
+            model: model.model.clone(),
+            priced_as: model.priced_as.clone(),
+            message_count: model.message_count,
+            actual_cost: model.actual_cost,
+            simulated_cost: model.simulated_cost,
+            difference: model.difference(),
+        };
+        let json = serde_json::json!({
+            "scenario": {
+                "noCache": scenario.no_cache,
+                "modelMap": scenario
+                    .model_map
+                    .iter()
+                    .map(|(from, to)| serde_json::json!({ "from": from, "to": to }))
+                    .collect::<Vec<_>>(),
+            },
+            "models": models.iter().map(output).collect::<Vec<_>>(),
+            "totals": output(&totals),
+        });
+        return emit_json(&json, &args.common);
+    }
+
+    let mut output = String::new();
+    write_title(&mut output, "What If", &args.common)?;
+    let mut table = Table::new();
+    table.load_preset(TABLE_PRESET);
+    let substituted = !scenario.model_map.is_empty();
+    let calculated = cost_header("Calculated");
+    let what_if = cost_header("What If");
+    let mut header = vec!["Model", "Messages", calculated.as_str(), what_if.as_str()];
+    if substituted {
+        header.insert(1, "Priced As");
+    }
+    header.extend(["Difference", "Change"]);
+    table.set_header(header);
+    let cells = |model: &WhatIfModel, name: String| {
+        let mut cells = vec![name];
+        if substituted {
+            cells.push(
+                model
+                    .priced_as
+                    .as_deref()
+                    .map(format_model_name)
+                    .unwrap_or_default(),
+            );
+        }
+        cells.extend([
+            model.message_count.to_string(),
+            format_currency(model.actual_cost),
+            format_currency(model.simulated_cost),
+            format_signed_currency(model.difference()),
+            if model.actual_cost > 0.0 {
+                format!("{:+.1}%", model.difference() / model.actual_cost * 100.0)
+            } else {
+                "-".to_string()
+            },
+        ]);
+        cells
+    };
+    for model in &models {
+        table.add_row(cells(model, format_model_name(&model.model)));
+    }
+    table.add_row(cells(&totals, totals.model.clone()));
+    write_table(&mut output, &table, &args.common)?;
+    if scenario.no_cache && !args.common.quiet {
+        writeln!(
+            output,
+            "What If: cache reads billed as regular input and no cache writes."
+        )?;
+    }
+    for (from, to) in scenario.model_map.iter().filter(|_| !args.common.quiet) {
+        writeln!(output, "What If: {from}* priced as {to}.")?;
+    }
+    emit_output(&output, &args.common)
+}
+
+fn format_signed_currency(amount: f64) -> String {
+    if amount < 0.0 {
+        format!("-{}", format_currency(-amount))
+    } else {
+        format!("+{}", format_currency(amount))
+    }
+}
+
+fn run_matrix(args: MatrixArgs, config: &Config) -> Result<()> {
+    let mut options = common_options(&args.common, config)?;
+    options.project = args.project.clone().or(options.project);
+    let matrix = weekday_hour_costs(&load_usage_entries(&options)?);
+    let output = match args.common.format {
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            let rows = WEEKDAYS
+                .iter()
+                .zip(matrix)
+                .map(|(weekday, hours)| MatrixRowOutput {
+                    weekday,
+                    total_cost: hours.iter().sum(),
+                    hours: hours.to_vec(),
+                })
+                .collect::<Vec<_>>();
+            return emit_json(&rows, &args.common);
+        }
+        OutputFormat::Csv => matrix_csv(&matrix),
+        _ => {
+            let mut header = vec!["Weekday".to_string()];
+            header.extend((0..24).map(|hour| format!("{hour:02}")));
+            header.push("Total".to_string());
+            let mut table = Table::new();
+            table.load_preset(TABLE_PRESET);
+            table.set_header(header);
+            for (weekday, hours) in WEEKDAYS.iter().zip(matrix) {
+                let mut row = vec![weekday[..3].to_string()];
+                row.extend(hours.iter().map(|cost| format_matrix_cost(*cost)));
+                row.push(format_currency(hours.iter().sum()));
+                table.add_row(row);
+            }
+            let mut output = String::new();
+            write_title(&mut output, "Weekday x Hour", &args.common)?;
+            write_table(&mut output, &table, &args.common)?;
+            if !args.common.quiet {
+                writeln!(
+                    output,
+                    "Cost in {} by local weekday and hour",
+                    currency_code()
+                )?;
+            }
+            output
+        }
+    };
+    emit_output(&output, &args.common)
+}
+
+fn matrix_csv(matrix: &[[f64; 24]; 7]) -> String {
+    let mut output = String::from("weekday");
+    for hour in 0..24 {
+        let _ = write!(output, ",{hour:02}");
+    }
+    output.push_str(",total\n");
+    for (weekday, hours) in WEEKDAYS.iter().zip(matrix) {
+        output.push_str(weekday);
+        for cost in hours {
+            let _ = write!(output, ",{cost:.4}");
+        }
+        let _ = writeln!(output, ",{:.4}", hours.iter().sum::<f64>());
+    }
+    output
+}
+
+/// Compact cell for the 24-column table: blank without usage, whole dollars from $10 up.
+fn format_matrix_cost(cost: f64) -> String {
+    if cost <= 0.0 {
+        String::new()
+    } else if cost >= 10.0 {
+        format!("{cost:.0}")
+    } else {
+        format!("{cost:.2}")
+    }
+}
+
+fn run_cache(args: CacheArgs, config: &Config) -> Result<()> {
+    let mut options = common_options(&args.common, config)?;
+    options.project = args.project.clone().or(options.project);
+    let entries = load_usage_entries(&options)?;
+
+    let pricing = PricingFetcher::new();
+    let prices = std::cell::RefCell::new(std::collections::HashMap::new());
+    let write_price = |model: &str| {
+        *prices
+            .borrow_mut()
+            .entry(model.to_string())
+            .or_insert_with(|| {
+                pricing
+                    .get_model_pricing(model)
+                    .and_then(|pricing| pricing.cache_creation_input_token_cost)
+            })
+    };
+    let mut sessions = analyze_cache_efficiency(
+        &entries,
+        chrono::Duration::minutes(args.ttl.max(0)),
+        write_price,
+    );
+    if let Some(top) = args.top {
+        sessions.truncate(top);
+    }
+    if sessions.is_empty() {
+        return report_no_data(&args.common, config, "No cache writes found.");
+    }
+
+    if args.common.json {
+        let output = sessions
+            .iter()
+            .map(|session| CacheEfficiencyOutput {
+                session_id: session.session_id.clone(),
+                project: session.project.clone(),
+                first_activity: session.first_timestamp.to_rfc3339(),
+                writes: session.writes,
+                unread_writes: session.unread_writes,
+                cache_creation_tokens: session.cache_creation_tokens,
+                cache_read_tokens: session.cache_read_tokens,
+                read_ratio: session.read_ratio(),
+                wasted_creation_tokens: session.wasted_creation_tokens,
+                wasted_cost: session.wasted_cost,
+            })
+            .collect::<Vec<_>>();
+        return emit_json(&output, &args.common);
+    }
+
+    let mut output = String::new();
+    write_title(&mut output, "Cache Efficiency", &args.common)?;
+    let token_format = token_format(args.common.kmb);
+    let ratio = |ratio: Option<f64>| ratio.map_or_else(|| "-".to_string(), |r| format!("{r:.1}x"));
+    let mut table = Table::new();
+    table.load_preset(TABLE_PRESET);
+    table.set_header(vec![
+        "Session",
+        "Project",
+        "First Activity",
+        "Writes",
+        "Unread",
+        "Cache Create",
+        "Cache Read",
+        "Read/Write",
+        "Wasted Tokens",
+        cost_header("Wasted").as_str(),
+    ]);
+    for session in &sessions {
+        table.add_row(vec![
+            short_session_id(&session.session_id).to_string(),
+            session.project.clone().unwrap_or_default(),
+            format_timestamp(session.first_timestamp, args.common.timezone.as_deref()),
+            session.writes.to_string(),
+            session.unread_writes.to_string(),
+            format_tokens(session.cache_creation_tokens, token_format),
+            format_tokens(session.cache_read_tokens, token_format),
+            ratio(session.read_ratio()),
+            format_tokens(session.wasted_creation_tokens, token_format),
+            format_currency(session.wasted_cost),
+        ]);
+    }
+    let sum = |value: fn(&CacheEfficiency) -> u64| sessions.iter().map(value).sum::<u64>();
+    let created = sum(|session| session.cache_creation_tokens);
+    let read = sum(|session| session.cache_read_tokens);
+    table.add_row(vec![
+        "Total".to_string(),
+        String::new(),
+        String::new(),
+        sum(|session| session.writes).to_string(),
+        sum(|session| session.unread_writes).to_string(),
+        format_tokens(created, token_format),
+        format_tokens(read, token_format),
+        ratio((created > 0).then(|| read as f64 / created as f64)),
+        format_tokens(sum(|session| session.wasted_creation_tokens), token_format),
+        format_currency(sessions.iter().map(|session| session.wasted_cost).sum()),
+    ]);
+    write_table(&mut output, &table, &args.common)?;
+    if !args.common.quiet {
+        writeln!(
+            output,
+            "Unread: cache writes not read back by the next request within {} minutes.",
+            args.ttl
+        )?;
+    }
+    emit_output(&output, &args.common)
+}
+
+fn run_models(args: ModelsArgs, config: &Config) -> Result<()> {
+    let mut options = common_options(&args.common, config)?;
+    options.project = args.project.clone().or(options.project);
+    let models = rank_models(
+        group_entries_by_model(&load_usage_entries(&options)?),
+        args.sort,
+    );
+    if models.is_empty() {
+        return report_no_data(&args.common, config, "No usage data found.");
+    }
+
+    let total_cost = models.iter().map(|model| model.total_cost).sum::<f64>();
+    let cost_share = |model: &ModelUsage| (total_cost > 0.0).then(|| model.total_cost / total_cost);
+    if args.common.json {
+        let output = models
+            .iter()
+            .enumerate()
+            .map(|(index, model)| ModelUsageOutput {
+                rank: index + 1,
+                model: model.model.clone(),
+                message_count: model.message_count,
+                input_tokens: model.input_tokens,
+                output_tokens: model.output_tokens,
+                cache_creation_tokens: model.cache_creation_tokens,
+                cache_read_tokens: model.cache_read_tokens,
+                total_tokens: model.total_tokens,
+                total_cost: model.total_cost,
+                cost_per_message: model.cost_per_message(),
+                cost_per_1k_output_tokens: model.cost_per_1k_output(),
+                cache_read_share: model.cache_read_share(),
+                cost_share: cost_share(model),
+            })
+            .collect::<Vec<_>>();
+        return emit_json(&output, &args.common);
+    }
+
+    let mut output = String::new();
+    write_title(&mut output, "Models", &args.common)?;
+    let token_format = token_format(args.common.kmb);
+    let rate = |cost: Option<f64>| cost.map_or_else(|| "-".to_string(), format_currency_rate);
+    let mut table = Table::new();
+    table.load_preset(TABLE_PRESET);
+    table.set_header(vec![
+        "Rank",
+        "Model",
+        "Messages",
+        "Output",
+        "Total Tokens",
+        cost_header("Cost").as_str(),
+        "Share",
+        "Cost/Msg",
+        "Cost/1K Out",
+        "Cache Read",
+    ]);
+    for (index, model) in models.iter().enumerate() {
+        table.add_row(vec![
+            (index + 1).to_string(),
+            format_model_name(&model.model),
+            model.message_count.to_string(),
+            format_tokens(model.output_tokens, token_format),
+            format_tokens(model.total_tokens, token_format),
+            format_currency(model.total_cost),
+            cost_share(model)
+                .map_or_else(|| "-".to_string(), |share| format!("{:.1}%", share * 100.0)),
+            rate(model.cost_per_message()),
+            rate(model.cost_per_1k_output()),
+            model
+                .cache_read_share()
+                .map_or_else(|| "-".to_string(), |share| format!("{:.1}%", share * 100.0)),
+        ]);
+    }
+    write_table(&mut output, &table, &args.common)?;
+    if !args.common.quiet {
+        writeln!(
+            output,
+            "Cache Read: share of prompt tokens (input, cache writes, cache reads) read from the cache."
+        )?;
+    }
+    emit_output(&output, &args.common)
+}
+
+fn run_mix(args: MixArgs, config: &Config) -> Result<()> {
+    let mut options = common_options(&args.common, config)?;
+    options.project = args.project.clone().or(options.project);
+    let periods = premium_mix(&load_usage_entries(&options)?, args.monthly);
+    if periods.is_empty() {
+        return report_no_data(
+            &args.common,
+            config,
+            "No Opus, Sonnet, or Haiku usage found.",
+        );
+    }
+    let changes = premium_share_changes(&periods);
+    let totals = MixPeriod {
+        period: "Total".to_string(),
+        premium_tokens: periods.iter().map(|period| period.premium_tokens).sum(),
+        standard_tokens: periods.iter().map(|period| period.standard_tokens).sum(),
+    };
+    let mut rows = periods.iter().zip(changes).collect::<Vec<_>>();
+    if matches!(options.order, SortOrder::Desc) {
+        rows.reverse();
+    }
+
+    if args.common.json {
+        let output = |period: &MixPeriod, change: Option<f64>| MixPeriodOutput {
+            period: period.period.clone(),
+            premium_tokens: period.premium_tokens,
+            standard_tokens: period.standard_tokens,
+            ratio: period.ratio(),
+            premium_share: period.premium_share(),
+            change,
+        };
+        let json = serde_json::json!({
+            "periods": rows
+                .iter()
+                .map(|(period, change)| output(period, *change))
+                .collect::<Vec<_>>(),
+            "totals": output(&totals, None),
+        });
+        return emit_json(&json, &args.common);
+    }
+
+    let mut output = String::new();
+    write_title(&mut output, "Premium Mix", &args.common)?;
+    let token_format = token_format(args.common.kmb);
+    let cells = |period: &MixPeriod, change: Option<f64>| {
+        vec![
+            period.period.clone(),
+            format_tokens(period.premium_tokens, token_format),
+            format_tokens(period.standard_tokens, token_format),
+            period
+                .ratio()
+                .map_or_else(|| "-".to_string(), |ratio| format!("{ratio:.2}")),
+            period
+                .premium_share()
+                .map_or_else(|| "-".to_string(), |share| format!("{:.1}%", share * 100.0)),
+            change.map_or_else(String::new, |change| format!("{change:+.1} pp")),
+        ]
+    };
+    let mut table = Table::new();
+    table.load_preset(TABLE_PRESET);
+    table.set_header(vec![
+        if args.monthly { "Month" } else { "Date" },
+        "Premium Tokens",
+        "Standard Tokens",
+        "Premium:Standard",
+        "Premium Share",
+        "Change",
+    ]);
+    for (period, change) in &rows {
+        table.add_row(cells(period, *change));
+    }
+    table.add_row(cells(&totals, None));
+    write_table(&mut output, &table, &args.common)?;
+    if !args.common.quiet {
+        writeln!(
+            output,
+            "Premium: Opus-class models. Standard: Sonnet- and Haiku-class models. \
+             Change: premium share versus the previous {} with usage.",
+            if args.monthly { "month" } else { "day" }
+        )?;
+    }
+    emit_output(&output, &args.common)
+}
+
+fn run_tiers(args: TiersArgs, config: &Config) -> Result<()> {
+    let mut options = common_options(&args.common, config)?;
+    options.project = args.project.clone().or(options.project);
+    let mut periods = service_tier_usage(&load_usage_entries(&options)?, args.monthly);
+    if periods.is_empty() {
+        return report_no_data(&args.common, config, "No usage data found.");
+    }
+    let totals = tier_totals(&periods);
+    if matches!(options.order, SortOrder::Desc) {
+        periods.reverse();
+    }
+
+    if args.common.json {
+        let rows = |period: &TierPeriod| {
+            period
+                .tiers
+                .iter()
+                .map(|tier| TierUsageOutput {
+                    period: period.period.clone(),
+                    tier: tier.tier.clone(),
+                    request_count: tier.request_count,
+                    request_share: period.request_share(tier),
+                    total_tokens: tier.total_tokens,
+                    total_cost: tier.total_cost,
+                })
+                .collect::<Vec<_>>()
+        };
+        let json = serde_json::json!({
+            "tiers": periods.iter().flat_map(rows).collect::<Vec<_>>(),
+            "totals": rows(&totals),
+        });
+        return emit_json(&json, &args.common);
+    }
+
+    let mut output = String::new();
+    write_title(&mut output, "Service Tiers", &args.common)?;
+    let token_format = token_format(args.common.kmb);
+    let mut table = Table::new();
+    table.load_preset(TABLE_PRESET);
+    table.set_header(vec![
+        if args.monthly { "Month" } else { "Date" },
+        "Tier",
+        "Requests",
+        "Share",
+        "Total Tokens",
+        cost_header("Cost").as_str(),
+    ]);
+    for period in periods.iter().chain(std::iter::once(&totals)) {
+        for (index, tier) in period.tiers.iter().enumerate() {
+            let cells = |tier: &TierUsage| {
+                vec![
+                    if index == 0 {
+                        period.period.clone()
+                    } else {
+                        String::new()
+                    },
+                    tier.tier.clone(),
+                    format_number(tier.request_count as f64),
+                    period
+                        .request_share(tier)
+                        .map_or_else(|| "-".to_string(), |share| format!("{:.1}%", share * 100.0)),
+                    format_tokens(tier.total_tokens, token_format),
+                    format_currency(tier.total_cost),
+                ]
+            };
+            table.add_row(cells(tier));
+        }
+    }
+    write_table(&mut output, &table, &args.common)?;
+    if !args.common.quiet {
+        writeln!(
+            output,
+            "Share: fraction of the {}'s requests; \"unknown\" means the log records no tier.",
+            if args.monthly { "month" } else { "day" }
+        )?;
+    }
+    emit_output(&output, &args.common)
+}
+
+fn run_stop_reasons(args: StopReasonsArgs, config: &Config) -> Result<()> {
+    let mut options = common_options(&args.common, config)?;
+    options.project = args.project.clone().or(options.project);
+    let mut periods = stop_reason_counts(&load_usage_entries(&options)?, args.monthly);
+    if periods.is_empty() {
+        return report_no_data(&args.common, config, "No usage data found.");
+    }
+    let totals = stop_reason_totals(&periods);
+    if matches!(options.order, SortOrder::Desc) {
+        periods.reverse();
+    }
+
+    if args.common.json {
+        let rows = |period: &StopReasonPeriod| {
+            period
+                .reasons
+                .iter()
+                .map(|reason| StopReasonOutput {
+                    period: period.period.clone(),
+                    stop_reason: reason.reason.clone(),
+                    request_count: reason.request_count,
+                    request_share: period.request_share(reason),
+                    output_tokens: reason.output_tokens,
+                    total_cost: reason.total_cost,
+                })
+                .collect::<Vec<_>>()
+        };
+        let json = serde_json::json!({
+            "stopReasons": periods.iter().flat_map(rows).collect::<Vec<_>>(),
+            "totals": rows(&totals),
+        });
+        return emit_json(&json, &args.common);
+    }
+
+    let mut output = String::new();
+    write_title(&mut output, "Stop Reasons", &args.common)?;
+    let token_format = token_format(args.common.kmb);
+    let mut table = Table::new();
+    table.load_preset(TABLE_PRESET);
+    table.set_header(vec![
+        if args.monthly { "Month" } else { "Date" },
+        "Stop Reason",
+        "Requests",
+        "Share",
+        "Output Tokens",
+        cost_header("Cost").as_str(),
+    ]);
+    for period in periods.iter().chain(std::iter::once(&totals)) {
+        for (index, reason) in period.reasons.iter().enumerate() {
+            table.add_row(vec![
+                if index == 0 {
+                    period.period.clone()
+                } else {
+                    String::new()
+                },
+                reason.reason.clone(),
+                format_number(reason.request_count as f64),
+                period
+                    .request_share(reason)
+                    .map_or_else(|| "-".to_string(), |share| format!("{:.1}%", share * 100.0)),
+                format_tokens(reason.output_tokens, token_format),
+                format_currency(reason.total_cost),
+            ]);
+        }
+    }
+    write_table(&mut output, &table, &args.common)?;
+    if !args.common.quiet {
+        writeln!(
+            output,
+            "{} of {} requests stopped at max_tokens (truncated output). Share: fraction of the {}'s requests; \"unknown\" means the log records no stop reason.",
+            format_number(totals.max_tokens_count() as f64),
+            format_number(totals.request_count() as f64),
+            if args.monthly { "month" } else { "day" }
+        )?;
+    }
+    emit_output(&output, &args.common)
+}
+
+fn run_distribution(args: DistributionArgs, config: &Config) -> Result<()> {
+    let mut options = common_options(&args.common, config)?;
+    options.project = args.project.clone().or(options.project);
+    let distribution = request_size_distribution(&load_usage_entries(&options)?);
+    if distribution.input.request_count == 0 {
+        return report_no_data(&args.common, config, "No usage data found.");
+    }
+
+    if args.common.json {
+        let json = serde_json::json!({
+            "input": TokenHistogramOutput::from(&distribution.input),
+            "output": TokenHistogramOutput::from(&distribution.output),
+        });
+        return emit_json(&json, &args.common);
+    }
+
+    let mut output = String::new();
+    write_title(&mut output, "Request Size Distribution", &args.common)?;
+    let bars = args.common.format == OutputFormat::Table;
+    let mut table = Table::new();
+    table.load_preset(TABLE_PRESET);
+    let cost = cost_header("Cost");
+    let mut header = vec!["Tokens", "Range", "Requests", "Share", cost.as_str()];
+    if bars {
+        header.push("Histogram");
+    }
+    table.set_header(header);
+    let histograms = [
+        ("Prompt", &distribution.input),
+        ("Output", &distribution.output),
+    ];
+    for (kind, histogram) in histograms {
+        let largest = histogram
+            .buckets
+            .iter()
+            .map(|bucket| bucket.request_count)
+            .max()
+            .unwrap_or(0);
+        for (index, bucket) in histogram.buckets.iter().enumerate() {
+            let mut row = vec![
+                if index == 0 {
+                    kind.to_string()
+                } else {
+                    String::new()
+                },
+                bucket.label(),
+                format_number(bucket.request_count as f64),
+                format!(
+                    "{:.1}%",
+                    bucket.request_count as f64 / histogram.request_count as f64 * 100.0
+                ),
+                format_currency(bucket.total_cost),
+            ];
+            if bars {
+                let width = (bucket.request_count as f64 / largest as f64
+                    * HISTOGRAM_BAR_WIDTH as f64)
+                    .ceil() as usize;
+                row.push("█".repeat(width));
+            }
+            table.add_row(row);
+        }
+    }
+    write_table(&mut output, &table, &args.common)?;
+    if !args.common.quiet {
+        let token_format = token_format(args.common.kmb);
+        for (kind, histogram) in histograms {
+            writeln!(
+                output,
+                "{kind} tokens per request: median {}, p90 {}, max {}.",
+                format_tokens(histogram.median, token_format),
+                format_tokens(histogram.p90, token_format),
+                format_tokens(histogram.max, token_format)
+            )?;
+        }
+        writeln!(
+            output,
+            "Prompt tokens include cache creation and cache reads. Cost is the full cost of the requests in each range."
+        )?;
+    }
+    emit_output(&output, &args.common)
+}
+
+/// Change in premium share from the previous period, in percentage points.
+fn premium_share_changes(periods: &[MixPeriod]) -> Vec<Option<f64>> {
+    let mut previous = None;
+    periods
+        .iter()
+        .map(|period| {
+            let share = period.premium_share();
+            let change = previous
+                .zip(share)
+                .map(|(previous, share): (f64, f64)| (share - previous) * 100.0);
+            previous = share.or(previous);
+            change
+        })
+        .collect()
+}
+
+fn rank_models(mut models: Vec<ModelUsage>, sort: ModelSort) -> Vec<ModelUsage> {
+    // Models without a figure (no output tokens, no prompt tokens) sink to the bottom.
+    let ascending = |a: Option<f64>, b: Option<f64>| match (a, b) {
+        (Some(a), Some(b)) => a.total_cmp(&b),
+        (a, b) => b.is_some().cmp(&a.is_some()),
+    };
+    match sort {
+        ModelSort::Efficiency => {
+            models.sort_by(|a, b| ascending(a.cost_per_1k_output(), b.cost_per_1k_output()))
+        }
+        ModelSort::Cost => {}
+        ModelSort::PerMessage => {
+            models.sort_by(|a, b| ascending(a.cost_per_message(), b.cost_per_message()))
+        }
+        ModelSort::CacheRead => models.sort_by(|a, b| {
+            ascending(
+                a.cache_read_share().map(|share| -share),
+                b.cache_read_share().map(|share| -share),
+            )
+        }),
+    }
+    models
+}
+
+fn rank_sessions(
+    mut sessions: Vec<SessionUsage>,
+    sort: SessionSort,
+    order: SortOrder,
+    top: Option<usize>,
+) -> Vec<SessionUsage> {
+    let descending = |a: f64, b: f64| b.partial_cmp(&a).unwrap_or(std::cmp::Ordering::Equal);
+    match sort {
+        SessionSort::LastActivity => {
+            if matches!(order, SortOrder::Desc) {
+                sessions.reverse();
+            }
+        }
+        SessionSort::Cost => sessions.sort_by(|a, b| descending(a.total_cost, b.total_cost)),
+        SessionSort::Tokens => {
+            sessions.sort_by_key(|session| std::cmp::Reverse(session.total_tokens))
+        }
+        SessionSort::Duration => {
+            sessions.sort_by_key(|session| std::cmp::Reverse(session.duration()))
+        }
+    }
+    if let Some(top) = top {
+        sessions.truncate(top);
+    }
+    sessions
+}
+
+fn calculate_totals_sessions(sessions: &[SessionUsage]) -> UsageTotals {
+    let mut totals = UsageTotals::default();
+    for session in sessions {
+        totals.input_tokens += session.input_tokens;
+        totals.output_tokens += session.output_tokens;
+        totals.cache_creation_tokens += session.cache_creation_tokens;
+        totals.cache_read_tokens += session.cache_read_tokens;
+        totals.total_tokens += session.total_tokens;
+        totals.total_cost += session.total_cost;
+        totals.message_count += session.entry_count as u64;
+        totals.subagent_cost += session.subagent_cost;
+        totals.tool_calls += session.tool_calls;
+        widen_activity(
+            &mut totals.first_timestamp,
+            &mut totals.last_timestamp,
+            Some(session.first_timestamp),
+            Some(session.last_timestamp),
+        );
+    }
+    totals
+}
+
+fn run_active(args: ActiveArgs, config: &Config) -> Result<()> {
+    let options = common_options(&args.common, config)?;
+    let entries = load_usage_entries(&options)?;
+    let mut sessions = active_sessions(
+        group_entries_by_session(&entries),
+        Utc::now(),
+        chrono::Duration::minutes(args.minutes),
+    );
+    if matches!(options.order, SortOrder::Desc) {
+        sessions.reverse();
+    }
+
+    if args.common.json {
+        let json = sessions
+            .iter()
+            .map(|session| ActiveSessionOutput {
+                session_id: session.session_id.clone(),
+                project: session.project.clone(),
+                last_activity: session.last_timestamp.to_rfc3339(),
+                current_model: session.last_model.clone(),
+                total_tokens: session.total_tokens,
+                total_cost: session.total_cost,
+            })
+            .collect::<Vec<_>>();
+        return emit_json(&json, &args.common);
+    }
+    if sessions.is_empty() {
+        return report_no_data(
+            &args.common,
+            config,
+            &format!("No active sessions in the last {} minutes.", args.minutes),
+        );
+    }
+
+    let token_format = token_format(args.common.kmb);
+    let mut table = Table::new();
+    table.load_preset(TABLE_PRESET);
+    table.set_header(vec![
+        "Session",
+        "Project",
+        "Last Activity",
+        "Current Model",
+        "Total Tokens",
+        cost_header("Cost").as_str(),
+    ]);
+    for session in &sessions {
+        table.add_row(vec![
+            short_session_id(&session.session_id).to_string(),
+            session.project.clone().unwrap_or_default(),
+            format_timestamp(session.last_timestamp, args.common.timezone.as_deref()),
+            session
+                .last_model
+                .as_deref()
+                .map(format_model_name)
+                .unwrap_or_default(),
+            format_tokens(session.total_tokens, token_format),
+            format_currency(session.total_cost),
+        ]);
+    }
+    let mut output = String::new();
+    write_table(&mut output, &table, &args.common)?;
+    emit_output(&output, &args.common)
+}
+
+/// Groups `entries` into 5-hour billing blocks. The blocks are Claude's usage windows, so only
+/// Claude Code requests count unless `--agent` names the agents to include.
+fn billing_blocks(
+    entries: &[UsageEntry],
+    common: &CommonArgs,
+    now: DateTime<Utc>,
+) -> Vec<UsageBlock> {
+    let any_agent = !common.agent.is_empty();
+    group_entries_into_blocks(
+        entries
+            .iter()
+            .filter(|entry| any_agent || entry.source == UsageSource::ClaudeCode),
+        now,
+    )
+}
+
+fn run_blocks(args: BlocksArgs, config: &Config) -> Result<()> {
+    let mut options = common_options(&args.common, config)?;
+    options.project = args.project.clone().or(options.project);
+    let entries = load_usage_entries(&options)?;
+    let now = Utc::now();
+    let mut blocks = billing_blocks(&entries, &args.common, now);
+    if args.active {
+        blocks.retain(|block| block.is_active);
+    }
+    if matches!(options.order, SortOrder::Desc) {
+        blocks.reverse();
+    }
+
+    if args.common.json {
+        let json = blocks
+            .iter()
+            .map(|block| block_output(block, now))
+            .collect::<Vec<_>>();
+        return emit_json(&json, &args.common);
+    }
+    if blocks.is_empty() {
+        let message = if args.active {
+            "No active block."
+        } else {
+            "No usage data found."
+        };
+        return report_no_data(&args.common, config, message);
+    }
+
+    let mut output = String::new();
+    write_title(&mut output, "Blocks", &args.common)?;
+    let token_format = token_format(args.common.kmb);
+    let max_models = args.common.max_models.or(config.max_models);
+    let mut table = Table::new();
+    table.load_preset(TABLE_PRESET);
+    table.set_header(vec![
+        "Block Start",
+        "Models",
+        "Requests",
+        "Total Tokens",
+        cost_header("Cost").as_str(),
+        "Remaining",
+    ]);
+    for block in &blocks {
+        table.add_row(vec![
+            format_timestamp(block.start, args.common.timezone.as_deref()),
+            format_models_display_truncated(&block.models_used, max_models),
+            block.entry_count.to_string(),
+            format_tokens(block.total_tokens, token_format),
+            format_currency(block.total_cost),
+            block
+                .remaining(now)
+                .map_or_else(|| "-".to_string(), format_duration),
+        ]);
+    }
+    table.add_row(vec![
+        "Total".to_string(),
+        String::new(),
+        blocks
+            .iter()
+            .map(|block| block.entry_count)
+            .sum::<usize>()
+            .to_string(),
+        format_tokens(
+            blocks.iter().map(|block| block.total_tokens).sum(),
+            token_format,
+        ),
+        format_currency(blocks.iter().map(|block| block.total_cost).sum()),
+        String::new(),
+    ]);
+    write_table(&mut output, &table, &args.common)?;
+    if !args.common.quiet
+        && let Some(active) = blocks.iter().find(|block| block.is_active)
+    {
+        let ends = format_timestamp(active.end, args.common.timezone.as_deref());
+        match active.projected_cost(now) {
+            Some(projected) => writeln!(
+                output,
+                "Active block: {} so far, {} projected by {ends} at the current rate.",
+                format_currency(active.total_cost),
+                format_currency(projected)
+            )?,
+            None => writeln!(
+                output,
+                "Active block: {} so far, ends {ends}.",
+                format_currency(active.total_cost)
+            )?,
+        }
+    }
+    emit_output(&output, &args.common)
+}
+
+fn run_top(args: TopArgs, config: &Config) -> Result<()> {
+    let mut options = common_options(&args.common, config)?;
+    options.project = args.project.clone().or(options.project);
+    let entries = load_usage_entries(&options)?;
+    if entries.is_empty() {
+        return report_no_data(&args.common, config, "No usage data found.");
+    }
+    let rows = match args.kind {
+        TopKind::Days => {
+            let mut days: std::collections::BTreeMap<&str, TopRow> =
+                std::collections::BTreeMap::new();
+            for entry in &entries {
+                let day = days.entry(&entry.date).or_insert_with(|| TopRow {
+                    name: entry.date.clone(),
+                    project: None,
+                    title: None,
+                    total_tokens: 0,
+                    total_cost: 0.0,
+                });
+                day.total_tokens += entry.total_tokens;
+                day.total_cost += entry.cost;
+            }
+            days.into_values().collect()
+        }
+        TopKind::Sessions => {
+            let mut titles = load_session_titles(&entries, options.read_limits);
+            group_entries_by_session(&entries)
+                .into_iter()
+                .map(|session| TopRow {
+                    title: titles.remove(&session.session_id),
+                    name: session.session_id,
+                    project: session.project,
+                    total_tokens: session.total_tokens,
+                    total_cost: session.total_cost,
+                })
+                .collect()
+        }
+        TopKind::Projects => group_entries_by_project(&entries)
+            .into_iter()
+            .map(|project| TopRow {
+                name: project.project,
+                project: None,
+                title: None,
+                total_tokens: project.total_tokens,
+                total_cost: project.total_cost,
+            })
+            .collect(),
+    };
+    let total_tokens = entries.iter().map(|entry| entry.total_tokens).sum::<u64>();
+    let total_cost = entries.iter().map(|entry| entry.cost).sum::<f64>();
+    let share = |row: &TopRow| {
+        let (value, total) = match args.by {
+            TopMetric::Cost => (row.total_cost, total_cost),
+            TopMetric::Tokens => (row.total_tokens as f64, total_tokens as f64),
+        };
+        if total > 0.0 { value / total } else { 0.0 }
+    };
+    let rows = rank_top(rows, args.by, args.limit);
 
-    if args.instances && daily.iter().any(|d| d.project.is_some()) {
-        let grouped = group_daily_by_project(&daily);
-        let mut first = true;
-        for (project, entries) in grouped {
-            if !first {
-                table.add_row(vec![String::new(); table.column_count()]);
+    if args.common.json {
+        let json = serde_json::json!({
+            "top": rows
+                .iter()
+                .enumerate()
+                .map(|(index, row)| TopRowOutput {
+                    rank: index + 1,
+                    name: row.name.clone(),
+                    project: row.project.clone(),
+                    title: row.title.clone(),
+                    total_tokens: row.total_tokens,
+                    total_cost: row.total_cost,
+                    share: share(row),
+                })
+                .collect::<Vec<_>>(),
+            "totals": { "totalTokens": total_tokens, "totalCost": total_cost },
+        });
+        return emit_json(&json, &args.common);
+    }
+
+    let (title, first_column) = match args.kind {
+        TopKind::Days => ("Top Days", "Date"),
+        TopKind::Sessions => ("Top Sessions", "Session"),
+        TopKind::Projects => ("Top Projects", "Project"),
+    };
+    let mut output = String::new();
+    write_title(&mut output, title, &args.common)?;
+    let token_format = token_format(args.common.kmb);
+    let sessions = args.kind == TopKind::Sessions;
+    let show_titles = rows.iter().any(|row| row.title.is_some());
+    let mut header = vec!["Rank", first_column];
+    if sessions {
+        header.push("Project");
+    }
+    if show_titles {
+        header.push("Title");
+    }
+    let cost = cost_header("Cost");
+    header.extend(["Total Tokens", cost.as_str(), "Share"]);
+    let mut table = Table::new();
+    table.load_preset(TABLE_PRESET);
+    table.set_header(header);
+    for (index, row) in rows.iter().enumerate() {
+        let mut cells = vec![
+            (index + 1).to_string(),
+            if sessions {
+                short_session_id(&row.name).to_string()
+            } else {
+                row.name.clone()
+            },
+        ];
+        if sessions {
+            cells.push(row.project.clone().unwrap_or_default());
+        }
+        if show_titles {
+            cells.push(row.title.as_deref().map(truncate_title).unwrap_or_default());
+        }
+        cells.extend([
+            format_tokens(row.total_tokens, token_format),
+            format_currency(row.total_cost),
+            format!("{:.1}%", share(row) * 100.0),
+        ]);
+        table.add_row(cells);
+    }
+    write_table(&mut output, &table, &args.common)?;
+    if !args.common.quiet {
+        writeln!(
+            output,
+            "Share: of the {} for the whole range ({}).",
+            match args.by {
+                TopMetric::Cost => "cost",
+                TopMetric::Tokens => "tokens",
+            },
+            match args.by {
+                TopMetric::Cost => format_currency(total_cost),
+                TopMetric::Tokens => format_tokens(total_tokens, token_format),
             }
-            let mut header_row = vec![String::new(); table.column_count()];
-            header_row[0] = format!("Project: {project}");
-            table.add_row(header_row);
-            for entry in entries {
-                let first_col = format_date_compact(&entry.date, args.common.timezone.as_deref())
-                    .unwrap_or(entry.date.clone());
-                let row = build_usage_row(
-                    &first_col,
-                    &usage_row_from_daily(&entry),
-                    mode,
-                    token_format,
-                );
-                table.add_row(row);
-                if args.common.breakdown {
-                    let breakdowns = breakdown_rows_from_breakdowns(&entry.model_breakdowns);
-                    for breakdown in build_breakdown_rows(&breakdowns, mode, token_format) {
-                        table.add_row(breakdown);
-                    }
-                }
+        )?;
+    }
+    emit_output(&output, &args.common)
+}
+
+/// The `limit` rows with the highest cost or tokens, ties broken by name.
+fn rank_top(mut rows: Vec<TopRow>, by: TopMetric, limit: usize) -> Vec<TopRow> {
+    rows.sort_by(|a, b| {
+        match by {
+            TopMetric::Cost => b.total_cost.total_cmp(&a.total_cost),
+            TopMetric::Tokens => b.total_tokens.cmp(&a.total_tokens),
+        }
+        .then_with(|| a.name.cmp(&b.name))
+    });
+    rows.truncate(limit);
+    rows
+}
+
+fn run_projects(args: ProjectsArgs, config: &Config) -> Result<()> {
+    let options = common_options(&args.common, config)?;
+    let mut projects = group_entries_by_project(&load_usage_entries(&options)?);
+    if args.sort == ProjectSort::Tokens {
+        projects.sort_by(|a, b| {
+            b.total_tokens
+                .cmp(&a.total_tokens)
+                .then_with(|| a.project.cmp(&b.project))
+        });
+    }
+    if projects.is_empty() {
+        return report_no_data(&args.common, config, "No usage data found.");
+    }
+
+    if args.common.json {
+        let json = projects
+            .iter()
+            .map(ProjectUsageOutput::from)
+            .collect::<Vec<_>>();
+        return emit_json(&json, &args.common);
+    }
+
+    let mut output = String::new();
+    write_title(&mut output, "Projects", &args.common)?;
+    let token_format = token_format(args.common.kmb);
+    let max_models = args.common.max_models.or(config.max_models);
+    let mut table = Table::new();
+    table.load_preset(TABLE_PRESET);
+    table.set_header(vec![
+        "Project",
+        "First Activity",
+        "Last Activity",
+        "Models",
+        "Sessions",
+        "Requests",
+        "Total Tokens",
+        cost_header("Cost").as_str(),
+    ]);
+    for project in &projects {
+        table.add_row(vec![
+            project.project.clone(),
+            project.first_date.clone(),
+            project.last_date.clone(),
+            format_models_display_truncated(&project.models_used, max_models),
+            project.session_count.to_string(),
+            project.request_count.to_string(),
+            format_tokens(project.total_tokens, token_format),
+            format_currency(project.total_cost),
+        ]);
+    }
+    table.add_row(vec![
+        "Total".to_string(),
+        projects
+            .iter()
+            .map(|project| project.first_date.as_str())
+            .min()
+            .unwrap_or_default()
+            .to_string(),
+        projects
+            .iter()
+            .map(|project| project.last_date.as_str())
+            .max()
+            .unwrap_or_default()
+            .to_string(),
+        String::new(),
+        projects
+            .iter()
+            .map(|project| project.session_count)
+            .sum::<usize>()
+            .to_string(),
+        projects
+            .iter()
+            .map(|project| project.request_count)
+            .sum::<u64>()
+            .to_string(),
+        format_tokens(
+            projects.iter().map(|project| project.total_tokens).sum(),
+            token_format,
+        ),
+        format_currency(projects.iter().map(|project| project.total_cost).sum()),
+    ]);
+    write_table(&mut output, &table, &args.common)?;
+    emit_output(&output, &args.common)
+}
+
+#[cfg(feature = "tui")]
+fn run_tui(args: TuiArgs, config: &Config) -> Result<()> {
+    if !std::io::stdout().is_terminal() {
+        return Err(coded_error(
+            ErrorCode::InvalidArgument,
+            "`ccost tui` needs a terminal",
+        ));
+    }
+    let options = common_options(&args.common, config)?;
+    let entries = load_usage_entries(&options)?;
+    let mut sessions = group_entries_by_session(&entries);
+    sessions.sort_by_key(|session| std::cmp::Reverse(session.last_timestamp));
+    let mut titles = load_session_titles(&entries, options.read_limits);
+    for session in &mut sessions {
+        session.title = titles.remove(&session.session_id);
+    }
+    let projects = group_entries_by_project(&entries);
+    let daily = load_daily_usage_data(options.clone())?;
+    let monthly = load_monthly_usage_data(options)?;
+    crate::tui::run(crate::tui::Dashboard::new(
+        report_title("Dashboard", &args.common),
+        &daily,
+        &monthly,
+        &projects,
+        &sessions,
+    ))
+}
+
+#[cfg(not(feature = "tui"))]
+fn run_tui(_args: TuiArgs, _config: &Config) -> Result<()> {
+    Err(coded_error(
+        ErrorCode::InvalidArgument,
+        "`ccost tui` needs a build with the `tui` feature \
+         (cargo install --git https://github.com/mkusaka/ccost --features tui)",
+    ))
+}
+
+fn run_live(args: LiveArgs, config: &Config) -> Result<()> {
+    if args.common.format != OutputFormat::Table {
+        return Err(coded_error(
+            ErrorCode::InvalidArgument,
+            "`ccost live` only draws tables",
+        ));
+    }
+    let timezone = args.common.timezone.as_deref();
+    let mut options = common_options(&args.common, config)?;
+    options.project = args.project.clone().or(options.project);
+    options.parse_cache = Some(Arc::new(ParseCache::default()));
+    let interval = std::time::Duration::from_secs(args.interval.max(1));
+    let clear = std::io::stdout().is_terminal();
+    // Logs are loaded again only when the watcher saw one change; without a watcher (no log
+    // directories yet, or the system's watch limit reached) every refresh polls them.
+    let watcher = LogWatcher::new(&usage_log_roots(&options))
+        .inspect_err(|error| {
+            if options.verbose {
+                eprintln!("Watching logs failed, polling instead: {error}");
             }
-            first = false;
+        })
+        .ok();
+    let mut loaded: Option<(Option<String>, Vec<UsageEntry>)> = None;
+    loop {
+        let now = Utc::now();
+        let today =
+            today_in_timezone(timezone).ok_or_else(|| invalid_timezone(timezone.unwrap_or("")))?;
+        // Reach back a day so an active block that opened before midnight is complete.
+        if args.common.since.is_none() {
+            let yesterday = today.pred_opt().unwrap_or(today);
+            options.since = Some(yesterday.format("%Y%m%d").to_string());
         }
-    } else {
-        for entry in &daily {
-            let first_col = format_date_compact(&entry.date, args.common.timezone.as_deref())
-                .unwrap_or(entry.date.clone());
-            let row = build_usage_row(&first_col, &usage_row_from_daily(entry), mode, token_format);
-            table.add_row(row);
-            if args.common.breakdown {
-                let breakdowns = breakdown_rows_from_breakdowns(&entry.model_breakdowns);
-                for breakdown in build_breakdown_rows(&breakdowns, mode, token_format) {
-                    table.add_row(breakdown);
-                }
+        let entries = match loaded.take() {
+            Some((since, entries)) if since == options.since => entries,
+            _ => load_usage_entries(&options)?,
+        };
+        let screen = live_screen(&entries, today, now, &args, config)?;
+        let mut stdout = std::io::stdout().lock();
+        if clear {
+            write!(stdout, "\x1b[2J\x1b[H")?;
+        }
+        write!(stdout, "{screen}")?;
+        stdout.flush()?;
+        drop(stdout);
+        print_warnings();
+        match &watcher {
+            Some(watcher) if !watcher.changed_within(interval) => {
+                loaded = Some((options.since.clone(), entries));
             }
+            Some(_) => {}
+            None => std::thread::sleep(interval),
         }
     }
+}
 
-    table.add_row(build_totals_row(
-        &usage_row_from_totals(&totals),
-        mode,
-        token_format,
-    ));
-    println!("{table}");
+/// One refresh of `ccost live`: today's totals, the active block, and today's sessions with
+/// the most recent first.
+fn live_screen(
+    entries: &[UsageEntry],
+    today: NaiveDate,
+    now: DateTime<Utc>,
+    args: &LiveArgs,
+    config: &Config,
+) -> Result<String> {
+    let timezone = args.common.timezone.as_deref();
+    let today_key = today.format("%Y-%m-%d").to_string();
+    let today_entries = entries
+        .iter()
+        .filter(|entry| entry.date == today_key)
+        .cloned()
+        .collect::<Vec<_>>();
+    let totals = calculate_totals_entries(&today_entries);
+    let token_format = token_format(args.common.kmb);
 
-    if matches!(mode, TableMode::Compact) {
-        println!("\nRunning in Compact Mode");
-        println!("Expand terminal width to see cache metrics and total tokens");
+    let mut output = String::new();
+    if !args.common.quiet {
+        writeln!(
+            output,
+            "{} · refreshed {} every {}s (Ctrl-C to stop)",
+            report_title("Live", &args.common),
+            format_timestamp(now, timezone),
+            args.interval.max(1)
+        )?;
+    }
+    writeln!(
+        output,
+        "Today: {} · {} tokens · {} requests",
+        format_currency(totals.total_cost),
+        format_tokens(totals.total_tokens(), token_format),
+        today_entries.len()
+    )?;
+    match billing_blocks(entries, &args.common, now)
+        .into_iter()
+        .find(|block| block.is_active)
+    {
+        Some(block) => {
+            write!(
+                output,
+                "Active block: {} so far, {} left",
+                format_currency(block.total_cost),
+                block
+                    .remaining(now)
+                    .map_or_else(|| "-".to_string(), format_duration)
+            )?;
+            match block.projected_cost(now) {
+                Some(projected) => writeln!(output, ", {} projected", format_currency(projected))?,
+                None => writeln!(output)?,
+            }
+        }
+        None => writeln!(output, "Active block: none")?,
     }
 
-    Ok(())
+    let mut sessions = group_entries_by_session(&today_entries);
+    sessions.reverse();
+    if sessions.is_empty() {
+        writeln!(output, "No usage today yet.")?;
+        return Ok(output);
+    }
+    let max_models = args.common.max_models.or(config.max_models);
+    let mut table = Table::new();
+    table.load_preset(TABLE_PRESET);
+    table.set_header(vec![
+        "Session",
+        "Project",
+        "Last Activity",
+        "Models",
+        "Total Tokens",
+        cost_header("Cost").as_str(),
+    ]);
+    for session in &sessions {
+        table.add_row(vec![
+            short_session_id(&session.session_id).to_string(),
+            session.project.clone().unwrap_or_default(),
+            format_timestamp(session.last_timestamp, timezone),
+            format_models_display_truncated(&session.models_used, max_models),
+            format_tokens(session.total_tokens, token_format),
+            format_currency(session.total_cost),
+        ]);
+    }
+    write_table(&mut output, &table, &args.common)?;
+    Ok(output)
 }
 
-fn run_monthly(args: MonthlyArgs) -> Result<()> {
-    let options = common_options(&args.common)?;
-    let monthly = load_monthly_usage_data(options)?;
-    if monthly.is_empty() {
-        if args.common.json {
-            let empty = serde_json::json!({
-                "monthly": [],
-                "totals": totals_output(UsageTotals::default())
-            });
-            println!("{}", serde_json::to_string_pretty(&empty)?);
-        } else {
-            eprintln!("No usage data found.");
-        }
-        return Ok(());
+fn block_output(block: &UsageBlock, now: DateTime<Utc>) -> BlockOutput {
+    BlockOutput {
+        start: block.start.to_rfc3339(),
+        end: block.end.to_rfc3339(),
+        first_activity: block.first_timestamp.to_rfc3339(),
+        last_activity: block.last_timestamp.to_rfc3339(),
+        is_active: block.is_active,
+        request_count: block.entry_count,
+        input_tokens: block.input_tokens,
+        output_tokens: block.output_tokens,
+        cache_creation_tokens: block.cache_creation_tokens,
+        cache_read_tokens: block.cache_read_tokens,
+        total_tokens: block.total_tokens,
+        total_cost: block.total_cost,
+        models_used: block.models_used.clone(),
+        remaining_minutes: block
+            .remaining(now)
+            .map(|remaining| remaining.num_minutes()),
+        projected_cost: block.projected_cost(now),
     }
+}
 
-    let totals = calculate_totals_monthly(&monthly);
+fn run_model_switches(args: ModelSwitchesArgs, config: &Config) -> Result<()> {
+    let mut options = common_options(&args.common, config)?;
+    options.project = args.project.clone().or(options.project);
+    let entries = load_usage_entries(&options)?;
+    let summaries = summarize_model_switches(&entries);
 
     if args.common.json {
         let json = serde_json::json!({
-            "monthly": monthly.into_iter().map(monthly_entry_output).collect::<Vec<_>>(),
-            "totals": totals_output(totals)
+            "switches": summaries.iter().map(model_switch_output).collect::<Vec<_>>(),
+            "totalSwitches": summaries.iter().map(|summary| summary.switches).sum::<usize>()
         });
-        println!("{}", serde_json::to_string_pretty(&json)?);
-        return Ok(());
+        return emit_json(&json, &args.common);
+    }
+    if summaries.is_empty() {
+        return report_no_data(&args.common, config, "No model switches found.");
     }
 
-    println!("{}", report_title("Monthly", &args.common));
+    let per_message = |cost: Option<f64>| cost.map_or_else(|| "-".to_string(), format_currency);
+    let mut table = Table::new();
+    table.load_preset(TABLE_PRESET);
+    table.set_header(vec![
+        "From",
+        "To",
+        "Switches",
+        "Sessions",
+        "Cost Before",
+        "Cost After",
+        "Cost/Msg Before",
+        "Cost/Msg After",
+    ]);
+    for summary in &summaries {
+        table.add_row(vec![
+            format_model_name(&summary.from_model),
+            format_model_name(&summary.to_model),
+            summary.switches.to_string(),
+            summary.sessions.to_string(),
+            format_currency(summary.cost_before),
+            format_currency(summary.cost_after),
+            per_message(summary.cost_per_message_before()),
+            per_message(summary.cost_per_message_after()),
+        ]);
+    }
 
-    let mode = table_mode(args.common.compact);
-    let token_format = token_format(args.common.kmb);
-    let mut table = usage_table("Month", mode);
+    let mut output = String::new();
+    write_title(&mut output, "Model Switches", &args.common)?;
+    write_table(&mut output, &table, &args.common)?;
+    emit_output(&output, &args.common)
+}
 
-    for entry in &monthly {
-        let row = build_usage_row(
-            &entry.month,
-            &usage_row_from_monthly(entry),
-            mode,
-            token_format,
-        );
-        table.add_row(row);
-        if args.common.breakdown {
-            let breakdowns = breakdown_rows_from_breakdowns(&entry.model_breakdowns);
-            for breakdown in build_breakdown_rows(&breakdowns, mode, token_format) {
-                table.add_row(breakdown);
+fn short_session_id(session_id: &str) -> &str {
+    session_id.get(..8).unwrap_or(session_id)
+}
+
+/// Session title cut to fit a table column.
+fn truncate_title(title: &str) -> String {
+    const MAX_CHARS: usize = 40;
+    if title.chars().count() <= MAX_CHARS {
+        return title.to_string();
+    }
+    let mut truncated = title.chars().take(MAX_CHARS - 1).collect::<String>();
+    truncated.push('…');
+    truncated
+}
+
+fn top_model_by_cost(daily: &[DailyUsage]) -> Option<String> {
+    let mut costs: std::collections::HashMap<&str, f64> = std::collections::HashMap::new();
+    for breakdown in daily.iter().flat_map(|entry| &entry.model_breakdowns) {
+        *costs.entry(breakdown.model_name.as_str()).or_default() += breakdown.cost;
+    }
+    costs
+        .into_iter()
+        .max_by(|a, b| {
+            a.1.partial_cmp(&b.1)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| b.0.cmp(a.0))
+        })
+        .map(|(model, _)| model.to_string())
+}
+
+fn summary_line(
+    today_tokens: u64,
+    today_cost: f64,
+    month_cost: f64,
+    top_model: Option<&str>,
+) -> String {
+    let mut line = format!(
+        "Today: {} ({} tok) · Month: {}",
+        format_currency(today_cost),
+        format_tokens(today_tokens, TokenFormat::HumanReadable),
+        format_currency(month_cost)
+    );
+    if let Some(model) = top_model {
+        line.push_str(&format!(" · Top: {}", format_model_name(model)));
+    }
+    line
+}
+
+fn emit_output(output: &str, args: &CommonArgs) -> Result<()> {
+    if let Some(path) = &args.output_file {
+        return std::fs::write(path, output)
+            .map_err(|error| anyhow!("failed to write {}: {error}", path.display()));
+    }
+    write_paged(output, !args.no_pager)
+}
+
+/// Writes a JSON report, or its records one per line for `--format ndjson`.
+fn emit_json(value: &impl Serialize, args: &CommonArgs) -> Result<()> {
+    if args.selects_json() {
+        let value = select_json(serde_json::to_value(value)?, &args.fields, args.totals_only)?;
+        let output = match args.format {
+            OutputFormat::Ndjson => ndjson_lines(&value),
+            OutputFormat::Csv => json_csv(&value, &args.fields),
+            _ => format!("{}\n", serde_json::to_string_pretty(&value)?),
+        };
+        return emit_output(&output, args);
+    }
+    let output = match args.format {
+        OutputFormat::Ndjson => ndjson_lines(&serde_json::to_value(value)?),
+        OutputFormat::Csv => json_csv(&serde_json::to_value(value)?, &[]),
+        _ => format!("{}\n", serde_json::to_string_pretty(value)?),
+    };
+    emit_output(&output, args)
+}
+
+/// Writes a report whose `key` field holds `records`. For `--format ndjson` each record is
+/// serialized straight to the output as it is produced, then the rest of `report` (totals and
+/// other report-level fields) as the last line, so a large report is never held as one JSON
+/// tree; this bypasses the pager, which only matters on a terminal.
+fn emit_json_records<T: Serialize>(
+    key: &str,
+    records: impl IntoIterator<Item = T>,
+    mut report: serde_json::Value,
+    args: &CommonArgs,
+) -> Result<()> {
+    if args.format != OutputFormat::Ndjson || args.selects_json() {
+        report[key] = serde_json::to_value(records.into_iter().collect::<Vec<_>>())?;
+        return emit_json(&report, args);
+    }
+    let writer: Box<dyn std::io::Write> = match &args.output_file {
+        Some(path) => Box::new(
+            std::fs::File::create(path)
+                .map_err(|error| anyhow!("failed to write {}: {error}", path.display()))?,
+        ),
+        None => Box::new(std::io::stdout().lock()),
+    };
+    let mut writer = std::io::BufWriter::new(writer);
+    for record in records {
+        serde_json::to_writer(&mut writer, &record)?;
+        writer.write_all(b"\n")?;
+    }
+    serde_json::to_writer(&mut writer, &report)?;
+    writer.write_all(b"\n")?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Applies `--totals-only` (the report's `totals` object in place of the report) and then
+/// `--fields` to a JSON report. Fields are kept in every record: the elements of the report's
+/// arrays, its object fields such as `totals`, or the report itself when it is one flat
+/// record. Naming a field no record has is an error.
+fn select_json(
+    mut value: serde_json::Value,
+    fields: &[String],
+    totals_only: bool,
+) -> Result<serde_json::Value> {
+    if totals_only {
+        value = match value.get_mut("totals").map(serde_json::Value::take) {
+            Some(totals) if totals.is_object() => totals,
+            _ => {
+                return Err(coded_error(
+                    ErrorCode::InvalidArgument,
+                    "--totals-only: this report has no totals",
+                ));
+            }
+        };
+    }
+    if fields.is_empty() {
+        return Ok(value);
+    }
+    let mut available = std::collections::BTreeSet::new();
+    for record in json_records_mut(&mut value) {
+        available.extend(record.keys().cloned());
+        record.retain(|key, _| fields.contains(key));
+    }
+    if let Some(unknown) = fields.iter().find(|field| !available.contains(*field))
+        && !available.is_empty()
+    {
+        return Err(coded_error(
+            ErrorCode::InvalidArgument,
+            format!(
+                "Unknown field {unknown}; this report has {}",
+                available.into_iter().collect::<Vec<_>>().join(", ")
+            ),
+        ));
+    }
+    Ok(value)
+}
+
+/// The records of a JSON report, as [`select_json`] describes them.
+fn json_records_mut(
+    value: &mut serde_json::Value,
+) -> Vec<&mut serde_json::Map<String, serde_json::Value>> {
+    match value {
+        serde_json::Value::Array(items) => items
+            .iter_mut()
+            .filter_map(serde_json::Value::as_object_mut)
+            .collect(),
+        serde_json::Value::Object(report) => {
+            if is_flat_record(report) {
+                return vec![report];
             }
+            report
+                .values_mut()
+                .flat_map(|field| match field {
+                    serde_json::Value::Array(items) => items
+                        .iter_mut()
+                        .filter_map(serde_json::Value::as_object_mut)
+                        .collect(),
+                    serde_json::Value::Object(record) => vec![record],
+                    _ => Vec::new(),
+                })
+                .collect()
         }
+        _ => Vec::new(),
     }
+}
 
-    table.add_row(build_totals_row(
-        &usage_row_from_totals(&totals),
-        mode,
-        token_format,
-    ));
-    println!("{table}");
+/// An object with no array or object fields, i.e. a single record rather than a report.
+fn is_flat_record(fields: &serde_json::Map<String, serde_json::Value>) -> bool {
+    !fields
+        .values()
+        .any(|field| field.is_array() || field.is_object())
+}
+
+/// Table headers of JSON fields, used as the CSV header names. Costs stay in USD like the JSON
+/// they come from.
+const CSV_HEADERS: [(&str, &str); 18] = [
+    ("date", "Date"),
+    ("month", "Month"),
+    ("project", "Project"),
+    ("sessionId", "Session"),
+    ("modelName", "Model"),
+    ("modelsUsed", "Models"),
+    ("inputTokens", "Input"),
+    ("outputTokens", "Output"),
+    ("cacheCreationTokens", "Cache Create"),
+    ("cacheReadTokens", "Cache Read"),
+    ("totalTokens", "Total Tokens"),
+    ("totalCost", "Cost (USD)"),
+    ("requestCount", "Requests"),
+    ("sessionCount", "Sessions"),
+    ("firstActivity", "First Activity"),
+    ("lastActivity", "Last Activity"),
+    ("costPerHour", "Cost/h"),
+    ("tokensPerHour", "Tokens/h"),
+];
+
+/// Table header of a JSON field: the table's name from [`CSV_HEADERS`], `Date` or `Month` for
+/// a `period` of days or months, else the field's words capitalized (`subagentCost` becomes
+/// `Subagent Cost`).
+fn csv_header(field: &str, rows: &[serde_json::Map<String, serde_json::Value>]) -> String {
+    if let Some((_, header)) = CSV_HEADERS.iter().find(|(key, _)| *key == field) {
+        return header.to_string();
+    }
+    if field == "period" {
+        let periods = rows
+            .iter()
+            .filter_map(|row| row.get(field)?.as_str())
+            .collect::<Vec<_>>();
+        let all = |format: &str| {
+            !periods.is_empty()
+                && periods.iter().all(|period| {
+                    NaiveDate::parse_from_str(&format!("{period}{format}"), "%Y-%m-%d").is_ok()
+                })
+        };
+        if all("") {
+            return "Date".to_string();
+        }
+        if all("-01") {
+            return "Month".to_string();
+        }
+    }
+    let mut header = String::new();
+    for (index, c) in field.chars().enumerate() {
+        if index == 0 {
+            header.extend(c.to_uppercase());
+        } else {
+            if c.is_ascii_uppercase() {
+                header.push(' ');
+            }
+            header.push(c);
+        }
+    }
+    header
+}
+
+/// The CSV rows of a JSON report: the elements of its arrays, with the group name as a column
+/// for grouped records (such as `daily --instances` by project) and for sections holding their
+/// own records (such as the `input` and `output` token distributions). A report without
+/// records, such as `summary`, gives one row per record field; a flat record is one row.
+fn csv_records(value: &serde_json::Value) -> Vec<serde_json::Map<String, serde_json::Value>> {
+    let records = |items: &Vec<serde_json::Value>| {
+        items
+            .iter()
+            .filter_map(|item| item.as_object())
+            .cloned()
+            .collect::<Vec<_>>()
+    };
+    let report = match value {
+        serde_json::Value::Array(items) => return records(items),
+        serde_json::Value::Object(record) if is_flat_record(record) => return vec![record.clone()],
+        serde_json::Value::Object(report) => report,
+        _ => return Vec::new(),
+    };
+    let mut rows = Vec::new();
+    for (key, field) in report {
+        match field {
+            serde_json::Value::Array(items) => rows.extend(records(items)),
+            serde_json::Value::Object(groups) if groups.values().all(|group| group.is_array()) => {
+                let column = key.strip_suffix('s').unwrap_or(key);
+                for (name, group) in groups {
+                    rows.extend(tagged(csv_records(group), column, name));
+                }
+            }
+            serde_json::Value::Object(section) if !is_flat_record(section) => {
+                rows.extend(tagged(csv_records(field), "group", key));
+            }
+            _ => {}
+        }
+    }
+    if rows.is_empty() {
+        for (key, field) in report {
+            if let Some(record) = field.as_object().filter(|record| is_flat_record(record)) {
+                rows.extend(tagged(vec![record.clone()], "group", key));
+            }
+        }
+    }
+    rows
+}
+
+/// `rows` with a `column` of `name` added where they lack one.
+fn tagged(
+    rows: Vec<serde_json::Map<String, serde_json::Value>>,
+    column: &str,
+    name: &str,
+) -> Vec<serde_json::Map<String, serde_json::Value>> {
+    rows.into_iter()
+        .map(|mut row| {
+            row.entry(column)
+                .or_insert_with(|| serde_json::Value::from(name));
+            row
+        })
+        .collect()
+}
+
+/// CSV of a JSON report: a header of `fields` (or of every field that holds a value or a list
+/// of names, in the order first seen) named as in the tables, then one row per record from
+/// [`csv_records`]. Numbers are written as they are in the JSON, and lists of names are
+/// joined with `;`.
+fn json_csv(value: &serde_json::Value, fields: &[String]) -> String {
+    let rows = csv_records(value);
+    let mut columns = fields.to_vec();
+    if columns.is_empty() {
+        for row in &rows {
+            for (key, value) in row.iter() {
+                let nested = match value {
+                    serde_json::Value::Object(_) => true,
+                    serde_json::Value::Array(items) => !items.iter().all(|item| item.is_string()),
+                    _ => false,
+                };
+                if !nested && !columns.contains(key) {
+                    columns.push(key.clone());
+                }
+            }
+        }
+    }
+    let cell = |value: Option<&serde_json::Value>| match value {
+        None | Some(serde_json::Value::Null) => String::new(),
+        Some(serde_json::Value::String(text)) => text.clone(),
+        Some(serde_json::Value::Array(items)) if items.iter().all(|item| item.is_string()) => items
+            .iter()
+            .filter_map(|item| item.as_str())
+            .collect::<Vec<_>>()
+            .join(";"),
+        Some(other) => other.to_string(),
+    };
 
-    if matches!(mode, TableMode::Compact) {
-        println!("\nRunning in Compact Mode");
-        println!("Expand terminal width to see cache metrics and total tokens");
+    let mut output = String::new();
+    let header = columns
+        .iter()
+        .map(|column| csv_field(&csv_header(column, &rows)))
+        .collect::<Vec<_>>();
+    let _ = writeln!(output, "{}", header.join(","));
+    for row in rows {
+        let cells = columns
+            .iter()
+            .map(|column| csv_field(&cell(row.get(column))))
+            .collect::<Vec<_>>();
+        let _ = writeln!(output, "{}", cells.join(","));
+    }
+    output
+}
+
+/// An array becomes one line per element. An object becomes one line per element of its array
+/// fields, then one line with its remaining fields (such as `totals`), if any.
+fn ndjson_lines(value: &serde_json::Value) -> String {
+    let mut output = String::new();
+    match value {
+        serde_json::Value::Array(items) => {
+            for item in items {
+                let _ = writeln!(output, "{item}");
+            }
+        }
+        serde_json::Value::Object(fields) => {
+            let mut rest = serde_json::Map::new();
+            for (key, field) in fields {
+                match field {
+                    serde_json::Value::Array(items) => {
+                        for item in items {
+                            let _ = writeln!(output, "{item}");
+                        }
+                    }
+                    other => {
+                        rest.insert(key.clone(), other.clone());
+                    }
+                }
+            }
+            if !rest.is_empty() {
+                let _ = writeln!(output, "{}", serde_json::Value::Object(rest));
+            }
+        }
+        other => {
+            let _ = writeln!(output, "{other}");
+        }
     }
+    output
+}
 
+/// Writes `table` as a box-drawn table, or as its cell text for `--format csv` and `md`.
+/// Markdown indents model breakdown rows with non-breaking spaces, since GitHub drops
+/// leading spaces in cells.
+fn write_table(output: &mut String, table: &Table, args: &CommonArgs) -> std::fmt::Result {
+    let rows = || {
+        table
+            .header()
+            .into_iter()
+            .chain(table.row_iter())
+            .map(|row| {
+                row.cell_iter()
+                    .map(|cell| cell.content())
+                    .collect::<Vec<_>>()
+            })
+    };
+    match args.format {
+        OutputFormat::Csv => {
+            for row in rows() {
+                let cells = row.iter().map(|cell| csv_field(cell)).collect::<Vec<_>>();
+                writeln!(output, "{}", cells.join(","))?;
+            }
+        }
+        OutputFormat::Md => {
+            for (index, row) in rows().enumerate() {
+                let cells = row
+                    .iter()
+                    .map(|cell| match cell.strip_prefix(BREAKDOWN_PREFIX) {
+                        Some(model) => format!("&nbsp;&nbsp;↳ {}", markdown_cell(model)),
+                        None => markdown_cell(cell),
+                    })
+                    .collect::<Vec<_>>();
+                writeln!(output, "| {} |", cells.join(" | "))?;
+                if index == 0 {
+                    writeln!(output, "|{}", " --- |".repeat(row.len()))?;
+                }
+            }
+        }
+        _ => writeln!(output, "{table}")?,
+    }
     Ok(())
 }
 
-fn table_mode(force_compact: bool) -> TableMode {
-    if force_compact {
+/// Date column of the daily table: stacked year over month-day on screen, one line in CSV
+/// and Markdown.
+fn date_cell(date: &str, args: &CommonArgs) -> String {
+    if args.format.is_plain_table() {
+        return date.to_string();
+    }
+    format_date_compact(date, args.timezone.as_deref()).unwrap_or(date.to_string())
+}
+
+fn markdown_cell(value: &str) -> String {
+    value.replace('|', "\\|").replace('\n', "<br>")
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn table_mode(args: &CommonArgs) -> TableMode {
+    if args.compact {
         return TableMode::Compact;
     }
+    if args.format.is_plain_table() {
+        return TableMode::Full;
+    }
     let width = terminal_size().map(|(w, _)| w.0 as usize).unwrap_or(120);
     if width < 100 {
         TableMode::Compact
@@ -426,6 +6128,29 @@ fn token_format(kmb: bool) -> TokenFormat {
     }
 }
 
+/// Writes the report title line, unless `--quiet`.
+fn write_title(output: &mut String, period: &str, args: &CommonArgs) -> std::fmt::Result {
+    if args.quiet {
+        return Ok(());
+    }
+    writeln!(output, "{}", report_title(period, args))
+}
+
+/// Tells the user a report has no rows: `[]` for JSON, a stderr note otherwise (unless
+/// `--quiet`). With `--fail-empty` (or `fail_empty` in the config) it is an error instead.
+fn report_no_data(args: &CommonArgs, config: &Config, message: &str) -> Result<()> {
+    let fail = args.fail_empty || config.fail_empty;
+    if args.format == OutputFormat::Json {
+        println!("[]");
+    } else if !args.quiet && !fail {
+        eprintln!("{message}");
+    }
+    if fail {
+        return Err(coded_error(ErrorCode::NoData, message));
+    }
+    Ok(())
+}
+
 fn report_title(period: &str, args: &CommonArgs) -> String {
     let agents = args.agent_flags();
     let mut sources = Vec::new();
@@ -446,8 +6171,19 @@ fn report_title(period: &str, args: &CommonArgs) -> String {
     format!("{source} Token Usage Report - {period}")
 }
 
-fn usage_table(first_column: &str, mode: TableMode) -> UsageTable {
-    let headers = match mode {
+fn usage_table(first_column: &str, mode: TableMode, common: &CommonArgs) -> UsageTable {
+    usage_table_with(first_column, mode, common, &[])
+}
+
+/// Like [`usage_table`], with `trailing` headers after the extra columns.
+fn usage_table_with(
+    first_column: &str,
+    mode: TableMode,
+    common: &CommonArgs,
+    trailing: &[&str],
+) -> UsageTable {
+    let cost = cost_header("Cost");
+    let mut headers = match mode {
         TableMode::Full => vec![
             first_column,
             "Models",
@@ -456,15 +6192,158 @@ fn usage_table(first_column: &str, mode: TableMode) -> UsageTable {
             "Cache Create",
             "Cache Read",
             "Total Tokens",
-            "Cost (USD)",
+            cost.as_str(),
         ],
-        TableMode::Compact => vec![first_column, "Models", "Input", "Output", "Cost (USD)"],
+        TableMode::Compact => vec![first_column, "Models", "Input", "Output", cost.as_str()],
     };
+    headers.extend(extra_column_headers(&common.columns));
+    headers.extend(trailing);
+    let column_count = headers.len();
 
     let mut table = Table::new();
-    table.load_preset("││──╞═╪╡│─┼├┤┬┴┌┐└┘");
+    table.load_preset(TABLE_PRESET);
     table.set_header(headers);
-    UsageTable { table, mode }
+    UsageTable {
+        table,
+        column_count,
+        extra_columns: common.columns.clone(),
+        timezone: common.timezone.clone(),
+    }
+}
+
+/// Month-to-date cost of one project's days as a percentage of its monthly `budget`, in the
+/// order of `entries`.
+fn budget_used_percent(entries: &[DailyUsage], budget: f64) -> Vec<f64> {
+    let mut costs = entries
+        .iter()
+        .map(|entry| (entry.date.as_str(), entry.total_cost))
+        .collect::<Vec<_>>();
+    costs.sort_by(|a, b| a.0.cmp(b.0));
+    let mut month_to_date = std::collections::HashMap::new();
+    let mut month = "";
+    let mut running = 0.0;
+    for (date, cost) in costs {
+        let this_month = date.get(..7).unwrap_or(date);
+        if this_month != month {
+            month = this_month;
+            running = 0.0;
+        }
+        running += cost;
+        month_to_date.insert(date, running);
+    }
+    entries
+        .iter()
+        .map(|entry| {
+            let spent = month_to_date
+                .get(entry.date.as_str())
+                .copied()
+                .unwrap_or(0.0);
+            if budget > 0.0 {
+                spent / budget * 100.0
+            } else {
+                0.0
+            }
+        })
+        .collect()
+}
+
+/// Periods (dates or months) whose cost, summed across projects, exceeds `budget`, oldest
+/// first.
+fn over_budget_periods<'a>(
+    costs: impl IntoIterator<Item = (&'a str, f64)>,
+    budget: f64,
+) -> Vec<(String, f64)> {
+    let mut totals = std::collections::BTreeMap::<&str, f64>::new();
+    for (period, cost) in costs {
+        *totals.entry(period).or_default() += cost;
+    }
+    totals
+        .into_iter()
+        .filter(|(_, cost)| *cost > budget)
+        .map(|(period, cost)| (period.to_string(), cost))
+        .collect()
+}
+
+fn budget_output(budget: f64, over_budget: &[(String, f64)]) -> serde_json::Value {
+    serde_json::json!(BudgetOutput {
+        amount: budget,
+        over_budget: over_budget
+            .iter()
+            .map(|(period, cost)| OverBudgetOutput {
+                period: period.clone(),
+                cost: *cost,
+            })
+            .collect(),
+    })
+}
+
+/// Warns about the periods over `budget`, or fails with them when `fail` is set.
+fn check_budget(
+    over_budget: &[(String, f64)],
+    budget: Option<f64>,
+    unit: &str,
+    fail: bool,
+) -> Result<()> {
+    let Some(budget) = budget.filter(|_| !over_budget.is_empty()) else {
+        return Ok(());
+    };
+    let summary = format!(
+        "{} {unit}{} over the {}/{unit} budget: {}",
+        over_budget.len(),
+        if over_budget.len() == 1 { "" } else { "s" },
+        format_currency(budget),
+        over_budget
+            .iter()
+            .map(|(period, cost)| format!("{period} ({})", format_currency(*cost)))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    if fail {
+        return Err(coded_error(
+            ErrorCode::OverBudget,
+            format!("Over budget: {summary}"),
+        ));
+    }
+    warn(summary);
+    Ok(())
+}
+
+fn parse_day(value: &str) -> Result<NaiveDate> {
+    NaiveDate::parse_from_str(value.trim(), "%Y%m%d")
+        .map_err(|_| anyhow!("expected a date as YYYYMMDD, got {value}"))
+}
+
+fn parse_budget(value: &str) -> Result<f64> {
+    match value.trim().parse::<f64>() {
+        Ok(budget) if budget.is_finite() && budget >= 0.0 => Ok(budget),
+        _ => Err(anyhow!(
+            "expected a non-negative amount in USD, got {value}"
+        )),
+    }
+}
+
+/// Cost z-scores of `daily`, when the z-score column is requested.
+fn cost_z_scores(daily: &[DailyUsage], columns: &[ExtraColumn]) -> Vec<Option<f64>> {
+    if !columns.contains(&ExtraColumn::ZScore) {
+        return vec![None; daily.len()];
+    }
+    z_scores(
+        &daily
+            .iter()
+            .map(|entry| entry.total_cost)
+            .collect::<Vec<_>>(),
+    )
+}
+
+/// Row color for budget use: yellow from 80%, red from 100%.
+fn budget_color(used_percent: f64) -> Option<Color> {
+    if used_percent >= 100.0 {
+        Some(Color::Red)
+    } else if used_percent >= 80.0 {
+        Some(Color::Yellow)
+    } else {
+        None
+    }
 }
 
 fn usage_row_from_daily(entry: &DailyUsage) -> UsageDataRow {
@@ -476,6 +6355,15 @@ fn usage_row_from_daily(entry: &DailyUsage) -> UsageDataRow {
         total_tokens: entry.total_tokens,
         total_cost: entry.total_cost,
         models_used: entry.models_used.clone(),
+        message_count: entry.message_count,
+        subagent_cost: entry.subagent_cost,
+        tool_calls: entry.tool_calls,
+        api_errors: entry.api_errors,
+        sessions: entry.session_ids.len() as u64,
+        first_timestamp: entry.first_timestamp,
+        last_timestamp: entry.last_timestamp,
+        active_hours: entry.active_hours(),
+        z_score: None,
     }
 }
 
@@ -488,6 +6376,36 @@ fn usage_row_from_monthly(entry: &MonthlyUsage) -> UsageDataRow {
         total_tokens: entry.total_tokens,
         total_cost: entry.total_cost,
         models_used: entry.models_used.clone(),
+        message_count: entry.message_count,
+        subagent_cost: entry.subagent_cost,
+        tool_calls: entry.tool_calls,
+        api_errors: entry.api_errors,
+        sessions: entry.session_ids.len() as u64,
+        first_timestamp: entry.first_timestamp,
+        last_timestamp: entry.last_timestamp,
+        active_hours: entry.active_hours,
+        z_score: None,
+    }
+}
+
+fn usage_row_from_entry(entry: &UsageEntry) -> UsageDataRow {
+    UsageDataRow {
+        input_tokens: entry.input_tokens,
+        output_tokens: entry.output_tokens,
+        cache_creation_tokens: entry.cache_creation_tokens,
+        cache_read_tokens: entry.cache_read_tokens,
+        total_tokens: entry.total_tokens,
+        total_cost: entry.cost,
+        models_used: entry.model.iter().cloned().collect(),
+        message_count: u64::from(!entry.is_api_error),
+        subagent_cost: if entry.is_sidechain { entry.cost } else { 0.0 },
+        tool_calls: entry.tool_calls,
+        api_errors: u64::from(entry.is_api_error),
+        sessions: u64::from(entry.session_id.is_some()),
+        first_timestamp: Some(entry.timestamp),
+        last_timestamp: Some(entry.timestamp),
+        active_hours: 1,
+        z_score: None,
     }
 }
 
@@ -500,6 +6418,15 @@ fn usage_row_from_totals(totals: &UsageTotals) -> UsageDataRow {
         total_tokens: totals.total_tokens(),
         total_cost: totals.total_cost,
         models_used: Vec::new(),
+        message_count: totals.message_count,
+        subagent_cost: totals.subagent_cost,
+        tool_calls: totals.tool_calls,
+        api_errors: totals.api_errors,
+        sessions: totals.sessions,
+        first_timestamp: totals.first_timestamp,
+        last_timestamp: totals.last_timestamp,
+        active_hours: totals.active_hours,
+        z_score: None,
     }
 }
 
@@ -518,182 +6445,1264 @@ fn breakdown_rows_from_breakdowns(breakdowns: &[ModelBreakdown]) -> Vec<ModelBre
         .collect()
 }
 
-fn totals_output(totals: UsageTotals) -> TotalsOutput {
-    TotalsOutput {
-        input_tokens: totals.input_tokens,
-        output_tokens: totals.output_tokens,
-        cache_creation_tokens: totals.cache_creation_tokens,
-        cache_read_tokens: totals.cache_read_tokens,
-        total_tokens: totals.total_tokens(),
-        total_cost: totals.total_cost,
+fn totals_output(totals: UsageTotals, columns: &[ExtraColumn]) -> TotalsOutput {
+    let subagents = columns.contains(&ExtraColumn::Subagents);
+    let activity = columns.contains(&ExtraColumn::Activity);
+    TotalsOutput {
+        input_tokens: totals.input_tokens,
+        output_tokens: totals.output_tokens,
+        cache_creation_tokens: totals.cache_creation_tokens,
+        cache_read_tokens: totals.cache_read_tokens,
+        total_tokens: totals.total_tokens(),
+        total_cost: totals.total_cost,
+        message_count: columns
+            .contains(&ExtraColumn::Messages)
+            .then_some(totals.message_count),
+        main_cost: subagents.then_some(totals.total_cost - totals.subagent_cost),
+        subagent_cost: subagents.then_some(totals.subagent_cost),
+        tool_calls: columns
+            .contains(&ExtraColumn::Tools)
+            .then_some(totals.tool_calls),
+        api_errors: columns
+            .contains(&ExtraColumn::Errors)
+            .then_some(totals.api_errors),
+        sessions: columns
+            .contains(&ExtraColumn::Sessions)
+            .then_some(totals.sessions),
+        first_activity: totals.first_timestamp.filter(|_| activity),
+        last_activity: totals.last_timestamp.filter(|_| activity),
+        active_hours: columns
+            .contains(&ExtraColumn::Hours)
+            .then_some(totals.active_hours),
+        cost_coverage: None,
+    }
+}
+
+/// Share of entries with a logged cost; `None` in calculate and max modes, where the cost
+/// shown isn't simply the logged one.
+fn cost_coverage(totals: &UsageTotals, mode: CostMode) -> Option<f64> {
+    if !matches!(mode, CostMode::Auto | CostMode::Display) {
+        return None;
+    }
+    totals.logged_cost_share()
+}
+
+fn write_cost_coverage(
+    output: &mut String,
+    totals: &UsageTotals,
+    common: &CommonArgs,
+) -> std::fmt::Result {
+    let Some(coverage) = cost_coverage(totals, common.mode).filter(|_| !common.quiet) else {
+        return Ok(());
+    };
+    let rest = if common.mode == CostMode::Display {
+        "count as $0"
+    } else {
+        "were calculated from token prices"
+    };
+    writeln!(
+        output,
+        "Cost coverage: {:.1}% of entries logged their cost (costUSD); the rest {rest}.",
+        coverage * 100.0
+    )
+}
+
+fn daily_entry_output(
+    entry: DailyUsage,
+    include_project: bool,
+    columns: &[ExtraColumn],
+) -> DailyEntryOutput {
+    let subagents = columns.contains(&ExtraColumn::Subagents);
+    let activity = columns.contains(&ExtraColumn::Activity);
+    let active_hours = columns
+        .contains(&ExtraColumn::Hours)
+        .then(|| entry.active_hours());
+    DailyEntryOutput {
+        agent: "all".to_string(),
+        cache_creation_tokens: entry.cache_creation_tokens,
+        cache_read_tokens: entry.cache_read_tokens,
+        input_tokens: entry.input_tokens,
+        metadata: DailyMetadataOutput { agents: vec![] },
+        model_breakdowns: entry
+            .model_breakdowns
+            .into_iter()
+            .map(model_breakdown_output)
+            .collect(),
+        models_used: entry.models_used,
+        output_tokens: entry.output_tokens,
+        period: entry.date,
+        total_cost: entry.total_cost,
+        total_tokens: entry.total_tokens,
+        project: if include_project { entry.project } else { None },
+        message_count: columns
+            .contains(&ExtraColumn::Messages)
+            .then_some(entry.message_count),
+        main_cost: subagents.then_some(entry.total_cost - entry.subagent_cost),
+        subagent_cost: subagents.then_some(entry.subagent_cost),
+        tool_calls: columns
+            .contains(&ExtraColumn::Tools)
+            .then_some(entry.tool_calls),
+        api_errors: columns
+            .contains(&ExtraColumn::Errors)
+            .then_some(entry.api_errors),
+        sessions: columns
+            .contains(&ExtraColumn::Sessions)
+            .then_some(entry.session_ids.len() as u64),
+        first_activity: entry.first_timestamp.filter(|_| activity),
+        last_activity: entry.last_timestamp.filter(|_| activity),
+        active_hours,
+        budget_used_percent: None,
+        z_score: None,
+    }
+}
+
+fn monthly_entry_output(entry: MonthlyUsage, columns: &[ExtraColumn]) -> MonthlyEntryOutput {
+    let subagents = columns.contains(&ExtraColumn::Subagents);
+    let activity = columns.contains(&ExtraColumn::Activity);
+    MonthlyEntryOutput {
+        month: entry.month,
+        input_tokens: entry.input_tokens,
+        output_tokens: entry.output_tokens,
+        cache_creation_tokens: entry.cache_creation_tokens,
+        cache_read_tokens: entry.cache_read_tokens,
+        total_tokens: entry.total_tokens,
+        total_cost: entry.total_cost,
+        models_used: entry.models_used,
+        model_breakdowns: entry
+            .model_breakdowns
+            .into_iter()
+            .map(model_breakdown_output)
+            .collect(),
+        message_count: columns
+            .contains(&ExtraColumn::Messages)
+            .then_some(entry.message_count),
+        main_cost: subagents.then_some(entry.total_cost - entry.subagent_cost),
+        subagent_cost: subagents.then_some(entry.subagent_cost),
+        tool_calls: columns
+            .contains(&ExtraColumn::Tools)
+            .then_some(entry.tool_calls),
+        api_errors: columns
+            .contains(&ExtraColumn::Errors)
+            .then_some(entry.api_errors),
+        sessions: columns
+            .contains(&ExtraColumn::Sessions)
+            .then_some(entry.session_ids.len() as u64),
+        first_activity: entry.first_timestamp.filter(|_| activity),
+        last_activity: entry.last_timestamp.filter(|_| activity),
+        active_hours: columns
+            .contains(&ExtraColumn::Hours)
+            .then_some(entry.active_hours),
+        z_score: None,
+    }
+}
+
+fn model_switch_output(summary: &ModelSwitchSummary) -> ModelSwitchOutput {
+    ModelSwitchOutput {
+        from_model: summary.from_model.clone(),
+        to_model: summary.to_model.clone(),
+        switches: summary.switches,
+        sessions: summary.sessions,
+        cost_before: summary.cost_before,
+        messages_before: summary.messages_before,
+        cost_after: summary.cost_after,
+        messages_after: summary.messages_after,
+    }
+}
+
+fn session_entry_output(session: &SessionUsage) -> SessionEntryOutput {
+    SessionEntryOutput {
+        session_id: session.session_id.clone(),
+        title: session.title.clone(),
+        project: session.project.clone(),
+        first_activity: session.first_timestamp.to_rfc3339(),
+        last_activity: session.last_timestamp.to_rfc3339(),
+        duration_seconds: session.duration().num_seconds(),
+        input_tokens: session.input_tokens,
+        output_tokens: session.output_tokens,
+        cache_creation_tokens: session.cache_creation_tokens,
+        cache_read_tokens: session.cache_read_tokens,
+        total_tokens: session.total_tokens,
+        total_cost: session.total_cost,
+        subagent_cost: session.subagent_cost,
+        tool_calls: session.tool_calls,
+        cost_per_hour: session.cost_per_hour(),
+        tokens_per_hour: session.tokens_per_hour(),
+        models_used: session.models_used.clone(),
+    }
+}
+
+fn conversation_entry_output(entry: &UsageEntry) -> ConversationEntryOutput {
+    ConversationEntryOutput {
+        timestamp: entry.timestamp.to_rfc3339(),
+        model: entry.model.clone(),
+        input_tokens: entry.input_tokens,
+        output_tokens: entry.output_tokens,
+        cache_creation_tokens: entry.cache_creation_tokens,
+        cache_read_tokens: entry.cache_read_tokens,
+        total_tokens: entry.total_tokens,
+        cost: entry.cost,
+    }
+}
+
+fn model_breakdown_output(entry: ModelBreakdown) -> ModelBreakdownOutput {
+    ModelBreakdownOutput {
+        model_name: entry.model_name,
+        input_tokens: entry.input_tokens,
+        output_tokens: entry.output_tokens,
+        cache_creation_tokens: entry.cache_creation_tokens,
+        cache_read_tokens: entry.cache_read_tokens,
+        cost: entry.cost,
+    }
+}
+
+struct UsageTable {
+    table: Table,
+    column_count: usize,
+    extra_columns: Vec<ExtraColumn>,
+    timezone: Option<String>,
+}
+
+impl UsageTable {
+    /// Adds a row, padding it with blank cells so it spans any extra columns.
+    fn add_row(&mut self, mut row: Vec<String>) {
+        row.resize(self.column_count, String::new());
+        self.table.add_row(row);
+    }
+
+    /// Adds a usage or totals row followed by the extra column cells for `data`.
+    fn add_data_row(&mut self, row: Vec<String>, data: &UsageDataRow) {
+        self.add_data_row_with(row, data, Vec::new(), None);
+    }
+
+    /// Like [`Self::add_data_row`], then `trailing` cells, with the text colored when `color`
+    /// is set.
+    fn add_data_row_with(
+        &mut self,
+        mut row: Vec<String>,
+        data: &UsageDataRow,
+        trailing: Vec<String>,
+        color: Option<Color>,
+    ) {
+        row.extend(build_extra_cells(
+            data,
+            &self.extra_columns,
+            self.timezone.as_deref(),
+        ));
+        row.extend(trailing);
+        match color {
+            Some(color) => {
+                row.resize(self.column_count, String::new());
+                self.table
+                    .add_row(row.into_iter().map(|cell| Cell::new(cell).fg(color)));
+            }
+            None => self.add_row(row),
+        }
+    }
+
+    fn column_count(&self) -> usize {
+        self.column_count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::load_config_from;
+    use crate::error::error_code;
+
+    fn parse_daily_common(args: &[&str]) -> CommonArgs {
+        let parsed =
+            Cli::try_parse_from(["ccost", "daily"].into_iter().chain(args.iter().copied()))
+                .unwrap();
+        match parsed.command {
+            Command::Daily(args) => args.common,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn billing_blocks_count_other_agents_only_when_asked() {
+        let at = |timestamp: &str| {
+            DateTime::parse_from_rfc3339(timestamp)
+                .unwrap()
+                .with_timezone(&Utc)
+        };
+        let entry = |source, timestamp: &str, cost| UsageEntry {
+            source,
+            timestamp: at(timestamp),
+            cost,
+            ..Default::default()
+        };
+        let entries = [
+            entry(UsageSource::Codex, "2026-01-10T08:30:00Z", 4.0),
+            entry(UsageSource::ClaudeCode, "2026-01-10T10:15:00Z", 1.0),
+            entry(UsageSource::OpenCode, "2026-01-10T12:00:00Z", 2.0),
+            entry(UsageSource::Codex, "2026-01-10T14:45:00Z", 8.0),
+        ];
+        let now = at("2026-01-10T14:50:00Z");
+
+        let blocks = billing_blocks(&entries, &parse_daily_common(&[]), now);
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].start, at("2026-01-10T10:00:00Z"));
+        assert_eq!(blocks[0].total_cost, 1.0);
+        assert_eq!(blocks[0].entry_count, 1);
+        assert!(blocks[0].is_active);
+
+        let blocks = billing_blocks(&entries, &parse_daily_common(&["--agent", "all"]), now);
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].start, at("2026-01-10T08:00:00Z"));
+        assert_eq!(blocks[0].total_cost, 7.0);
+        assert_eq!(blocks[1].total_cost, 8.0);
+    }
+
+    #[test]
+    fn agent_defaults_to_all_sources() {
+        let common = parse_daily_common(&[]);
+
+        assert_eq!(common.agent_flags(), AgentFlags::all());
+        assert_eq!(
+            report_title("Daily", &common),
+            "Claude Code + Codex + OpenCode Token Usage Report - Daily"
+        );
+    }
+
+    #[test]
+    fn agent_accepts_single_source() {
+        let common = parse_daily_common(&["--agent=codex"]);
+
+        assert_eq!(
+            common.agent_flags(),
+            AgentFlags {
+                codex: true,
+                claudecode: false,
+                opencode: false,
+            }
+        );
+        assert_eq!(
+            report_title("Daily", &common),
+            "Codex Token Usage Report - Daily"
+        );
+    }
+
+    #[test]
+    fn agent_accepts_comma_separated_sources() {
+        let common = parse_daily_common(&["--agent=codex,opencode"]);
+
+        assert_eq!(
+            common.agent_flags(),
+            AgentFlags {
+                codex: true,
+                claudecode: false,
+                opencode: true,
+            }
+        );
+        assert_eq!(
+            report_title("Daily", &common),
+            "Codex + OpenCode Token Usage Report - Daily"
+        );
+    }
+
+    #[test]
+    fn source_is_an_alias_of_agent() {
+        let common = parse_daily_common(&["--source", "claude,codex"]);
+
+        assert_eq!(
+            common.agent_flags(),
+            AgentFlags {
+                codex: true,
+                claudecode: true,
+                opencode: false,
+            }
+        );
+    }
+
+    #[test]
+    fn cli_arguments_are_consistent() {
+        Cli::command().debug_assert();
+        assert!(Cli::try_parse_from(["ccost", "whatif", "--no-cache", "--no-parse-cache"]).is_ok());
+    }
+
+    #[test]
+    fn range_resolution_errors_are_reported_in_json() {
+        let mut cli = Cli::try_parse_from([
+            "ccost",
+            "daily",
+            "--json",
+            "--this-month",
+            "--timezone",
+            "Bad/Zone",
+        ])
+        .unwrap();
+        let error = prepare_run(&mut cli.command, &Config::default()).unwrap_err();
+        assert!(JSON_ERRORS.load(Ordering::Relaxed));
+        assert_eq!(
+            json_error(&error),
+            serde_json::json!({
+                "error": {"code": "invalid_timezone", "message": "Invalid timezone: Bad/Zone"}
+            })
+        );
+    }
+
+    #[test]
+    fn config_errors_are_reported_in_json() {
+        let args = |values: &[&str]| {
+            values
+                .iter()
+                .map(std::ffi::OsString::from)
+                .collect::<Vec<_>>()
+        };
+        assert!(asks_for_json(&args(&["ccost", "daily", "-j"])));
+        assert!(asks_for_json(&args(&[
+            "ccost", "daily", "--format", "ndjson"
+        ])));
+        assert!(asks_for_json(&args(&["ccost", "--format=json", "monthly"])));
+        assert!(!asks_for_json(&args(&[
+            "ccost", "daily", "--format", "csv"
+        ])));
+        assert!(!asks_for_json(&args(&["ccost", "daily", "--", "--json"])));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(&path, "timezone = \"UTC\"\n[budgets\n").unwrap();
+        let error = configure(args(&["ccost", "daily", "--format", "json"]), || {
+            load_config_from(&path)
+        })
+        .unwrap_err();
+        assert!(JSON_ERRORS.load(Ordering::Relaxed));
+        let json = json_error(&error);
+        assert_eq!(json["error"]["code"], "invalid_config");
+        assert!(
+            json["error"]["message"]
+                .as_str()
+                .unwrap()
+                .starts_with(&format!("invalid config {}", path.display()))
+        );
+    }
+
+    #[test]
+    fn group_by_model_applies_to_daily_and_monthly_periods() {
+        let parsed = Cli::try_parse_from(["ccost", "monthly", "--group-by", "model"]).unwrap();
+        assert!(
+            matches!(parsed.command, Command::Monthly(args) if args.group_by == Some(GroupBy::Model))
+        );
+        assert!(Cli::try_parse_from(["ccost", "daily", "--group-by", "model", "-i"]).is_err());
+        assert!(Cli::try_parse_from(["ccost", "daily", "--group-by", "project"]).is_err());
+    }
+
+    #[test]
+    fn hourly_takes_one_day_or_a_date_range() {
+        let parsed = Cli::try_parse_from(["ccost", "hourly", "--date", "20260105"]).unwrap();
+        assert!(matches!(
+            parsed.command,
+            Command::Hourly(args) if args.date == NaiveDate::from_ymd_opt(2026, 1, 5)
+        ));
+        assert!(Cli::try_parse_from(["ccost", "hourly", "--date", "2026-01-05"]).is_err());
+        assert!(
+            Cli::try_parse_from([
+                "ccost", "hourly", "--date", "20260105", "--since", "20260101"
+            ])
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn forecast_averages_the_last_week_by_default() {
+        let parsed = Cli::try_parse_from(["ccost", "forecast"]).unwrap();
+        assert!(matches!(parsed.command, Command::Forecast(args) if args.days == 7));
+        let parsed = Cli::try_parse_from(["ccost", "forecast", "--days", "30"]).unwrap();
+        assert!(matches!(parsed.command, Command::Forecast(args) if args.days == 30));
+        assert!(Cli::try_parse_from(["ccost", "forecast", "--days", "0"]).is_err());
+    }
+
+    #[test]
+    fn export_takes_a_directory_or_the_otlp_target() {
+        let parsed = Cli::try_parse_from(["ccost", "export", "--dir", "out"]).unwrap();
+        assert!(matches!(parsed.command, Command::Export(args) if args.target.is_none()));
+        let mut parsed = Cli::try_parse_from([
+            "ccost", "export", "otlp", "--signal", "metrics", "--since", "20260101",
+        ])
+        .unwrap();
+        let since = parsed.command.common_mut().unwrap().since.clone();
+        assert_eq!(since.as_deref(), Some("20260101"));
+        assert!(matches!(
+            parsed.command,
+            Command::Export(args)
+                if matches!(&args.target, Some(ExportTarget::Otlp(otlp)) if otlp.signal == OtlpSignal::Metrics)
+        ));
+        assert!(Cli::try_parse_from(["ccost", "export"]).is_err());
+        assert!(Cli::try_parse_from(["ccost", "export", "--dir", "out", "otlp"]).is_err());
+    }
+
+    #[test]
+    fn relative_ranges_replace_since_and_until() {
+        let mut common = parse_daily_common(&["--last", "2w", "--timezone", "UTC"]);
+        assert_eq!(common.relative_range(), Some(RelativeRange::LastDays(14)));
+        common.resolve_relative_range().unwrap();
+        let today = today_in_timezone(Some("UTC")).unwrap();
+        assert_eq!(common.until, Some(today.format("%Y%m%d").to_string()));
+        assert!(common.since.is_some());
+
+        let common = parse_daily_common(&["--this-month"]);
+        assert_eq!(common.relative_range(), Some(RelativeRange::ThisMonth));
+        for conflicting in [
+            &["daily", "--last", "7d", "--since", "20260101"][..],
+            &["daily", "--yesterday", "--this-week"],
+            &["hourly", "--yesterday", "--date", "20260101"],
+        ] {
+            assert!(
+                Cli::try_parse_from(std::iter::once("ccost").chain(conflicting.iter().copied()))
+                    .is_err()
+            );
+        }
+    }
+
+    #[test]
+    fn dedup_accepts_keep_last_for_keep_latest() {
+        let common = parse_daily_common(&["--dedup", "keep-last", "--report-duplicates"]);
+        assert_eq!(common.dedup, DedupPolicy::KeepLatest);
+        assert!(common.report_duplicates);
+    }
+
+    #[test]
+    fn diff_baseline_needs_both_ends() {
+        let parsed = Cli::try_parse_from([
+            "ccost",
+            "diff",
+            "--since",
+            "20260301",
+            "--vs-since",
+            "20260201",
+            "--vs-until",
+            "20260228",
+        ])
+        .unwrap();
+        assert!(matches!(
+            parsed.command,
+            Command::Diff(args) if args.vs_until == NaiveDate::from_ymd_opt(2026, 2, 28)
+        ));
+        assert!(Cli::try_parse_from(["ccost", "diff", "--last", "7d"]).is_ok());
+        assert!(
+            Cli::try_parse_from(["ccost", "diff", "--last", "7d", "--vs-since", "20260201"])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn top_ranks_by_cost_or_tokens_and_keeps_the_first_n() {
+        let row = |name: &str, total_tokens, total_cost| TopRow {
+            name: name.to_string(),
+            project: None,
+            title: None,
+            total_tokens,
+            total_cost,
+        };
+        let rows = vec![row("a", 10, 3.0), row("b", 30, 1.0), row("c", 20, 3.0)];
+        let names = |rows: Vec<TopRow>| rows.into_iter().map(|row| row.name).collect::<Vec<_>>();
+        assert_eq!(
+            names(rank_top(rows.clone(), TopMetric::Cost, 2)),
+            ["a", "c"]
+        );
+        assert_eq!(
+            names(rank_top(rows, TopMetric::Tokens, 10)),
+            ["b", "c", "a"]
+        );
+
+        let parsed = Cli::try_parse_from(["ccost", "top", "days", "--n", "5"]).unwrap();
+        assert!(matches!(
+            parsed.command,
+            Command::Top(args) if args.kind == TopKind::Days && args.limit == 5
+        ));
+    }
+
+    #[test]
+    fn claude_path_is_an_alias_of_path() {
+        let common = parse_daily_common(&["--path", "/data/a", "--claude-path", "/data/b"]);
+        assert_eq!(
+            common.paths,
+            vec![
+                std::path::PathBuf::from("/data/a"),
+                std::path::PathBuf::from("/data/b")
+            ]
+        );
+        let parsed = Cli::try_parse_from(["ccost", "blocks", "--claude-path", "/data/a"]).unwrap();
+        assert!(matches!(parsed.command, Command::Blocks(args) if args.common.paths.len() == 1));
+    }
+
+    #[test]
+    fn removed_source_boolean_flags_are_rejected() {
+        let result = Cli::try_parse_from(["ccost", "daily", "--codex=false"]);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn kmb_is_opt_in() {
+        assert!(!parse_daily_common(&[]).kmb);
+        assert!(parse_daily_common(&["--kmb"]).kmb);
+
+        let parsed = Cli::try_parse_from(["ccost", "monthly", "--json", "--kmb"]).unwrap();
+        let Command::Monthly(args) = parsed.command else {
+            unreachable!();
+        };
+        assert!(args.common.json);
+        assert!(args.common.kmb);
     }
-}
 
-fn daily_entry_output(entry: DailyUsage, include_project: bool) -> DailyEntryOutput {
-    DailyEntryOutput {
-        agent: "all".to_string(),
-        cache_creation_tokens: entry.cache_creation_tokens,
-        cache_read_tokens: entry.cache_read_tokens,
-        input_tokens: entry.input_tokens,
-        metadata: DailyMetadataOutput { agents: vec![] },
-        model_breakdowns: entry
-            .model_breakdowns
-            .into_iter()
-            .map(model_breakdown_output)
-            .collect(),
-        models_used: entry.models_used,
-        output_tokens: entry.output_tokens,
-        period: entry.date,
-        total_cost: entry.total_cost,
-        total_tokens: entry.total_tokens,
-        project: if include_project { entry.project } else { None },
+    #[test]
+    fn monthly_accepts_a_project_filter() {
+        let parsed = Cli::try_parse_from(["ccost", "monthly", "-p", "my-project"]).unwrap();
+        let Command::Monthly(args) = parsed.command else {
+            unreachable!();
+        };
+        assert_eq!(args.project.as_deref(), Some("my-project"));
     }
-}
 
-fn monthly_entry_output(entry: MonthlyUsage) -> MonthlyEntryOutput {
-    MonthlyEntryOutput {
-        month: entry.month,
-        input_tokens: entry.input_tokens,
-        output_tokens: entry.output_tokens,
-        cache_creation_tokens: entry.cache_creation_tokens,
-        cache_read_tokens: entry.cache_read_tokens,
-        total_tokens: entry.total_tokens,
-        total_cost: entry.total_cost,
-        models_used: entry.models_used,
-        model_breakdowns: entry
-            .model_breakdowns
-            .into_iter()
-            .map(model_breakdown_output)
-            .collect(),
+    #[test]
+    fn summary_line_formats_today_month_and_top_model() {
+        assert_eq!(
+            summary_line(1_234_567, 4.123, 87.4, Some("claude-sonnet-4-20250514")),
+            "Today: $4.12 (1.23M tok) · Month: $87.40 · Top: sonnet-4"
+        );
+        assert_eq!(
+            summary_line(0, 0.0, 0.0, None),
+            "Today: $0.00 (0 tok) · Month: $0.00"
+        );
     }
-}
 
-fn model_breakdown_output(entry: ModelBreakdown) -> ModelBreakdownOutput {
-    ModelBreakdownOutput {
-        model_name: entry.model_name,
-        input_tokens: entry.input_tokens,
-        output_tokens: entry.output_tokens,
-        cache_creation_tokens: entry.cache_creation_tokens,
-        cache_read_tokens: entry.cache_read_tokens,
-        cost: entry.cost,
+    #[test]
+    fn badge_message_formats_cost_or_tokens() {
+        let totals = UsageTotals {
+            total_tokens: 1_500_000,
+            total_cost: 123.456,
+            ..Default::default()
+        };
+        assert_eq!(badge_message(BadgeMetric::MonthCost, &totals), "$123.46");
+        assert_eq!(badge_message(BadgeMetric::TodayTokens, &totals), "1.5M");
+
+        let parsed = Cli::try_parse_from(["ccost", "badge"]).unwrap();
+        let Command::Badge(args) = parsed.command else {
+            unreachable!();
+        };
+        assert_eq!(args.metric, BadgeMetric::MonthCost);
+        assert_eq!(args.label, "claude spend");
     }
-}
 
-struct UsageTable {
-    table: Table,
-    mode: TableMode,
-}
+    #[test]
+    fn month_bounds_covers_the_whole_month() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        assert_eq!(
+            month_bounds("2024-02").unwrap(),
+            (date(2024, 2, 1), date(2024, 2, 29))
+        );
+        assert_eq!(
+            month_bounds("2024-12").unwrap(),
+            (date(2024, 12, 1), date(2024, 12, 31))
+        );
+        assert!(month_bounds("2024-13").is_err());
+        assert!(month_bounds("May 2024").is_err());
+    }
 
-impl UsageTable {
-    fn add_row(&mut self, row: Vec<String>) {
-        self.table.add_row(row);
+    #[test]
+    fn rank_models_orders_by_efficiency_and_cache_share() {
+        let model = |name: &str, output: u64, cache_read: u64, cost: f64| ModelUsage {
+            model: name.to_string(),
+            message_count: 1,
+            input_tokens: 100,
+            output_tokens: output,
+            cache_read_tokens: cache_read,
+            total_cost: cost,
+            ..Default::default()
+        };
+        let models = vec![
+            model("opus", 1_000, 0, 5.0),
+            model("sonnet", 1_000, 900, 1.0),
+            model("empty", 0, 100, 0.5),
+        ];
+        let names = |models: Vec<ModelUsage>| {
+            models
+                .into_iter()
+                .map(|model| model.model)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            names(rank_models(models.clone(), ModelSort::Efficiency)),
+            vec!["sonnet", "opus", "empty"]
+        );
+        assert_eq!(
+            names(rank_models(models.clone(), ModelSort::CacheRead)),
+            vec!["sonnet", "empty", "opus"]
+        );
+        assert_eq!(
+            names(rank_models(models, ModelSort::PerMessage)),
+            vec!["empty", "sonnet", "opus"]
+        );
     }
 
-    fn column_count(&self) -> usize {
-        match self.mode {
-            TableMode::Full => 8,
-            TableMode::Compact => 5,
-        }
+    #[test]
+    fn premium_share_changes_compare_with_previous_period() {
+        let period = |premium: u64, standard: u64| MixPeriod {
+            period: String::new(),
+            premium_tokens: premium,
+            standard_tokens: standard,
+        };
+        let changes =
+            premium_share_changes(&[period(1, 3), period(1, 1), period(0, 0), period(0, 1)]);
+        assert_eq!(changes, vec![None, Some(25.0), None, Some(-50.0)]);
     }
-}
 
-impl std::fmt::Display for UsageTable {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.table)
+    #[test]
+    fn budget_used_percent_accumulates_within_each_month() {
+        let day = |date: &str, cost: f64| DailyUsage {
+            date: date.to_string(),
+            total_cost: cost,
+            ..Default::default()
+        };
+        let entries = vec![
+            day("2026-02-01", 5.0),
+            day("2026-01-31", 40.0),
+            day("2026-01-30", 50.0),
+        ];
+        assert_eq!(budget_used_percent(&entries, 100.0), vec![5.0, 90.0, 50.0]);
+        assert_eq!(budget_color(50.0), None);
+        assert_eq!(budget_color(90.0), Some(Color::Yellow));
+        assert_eq!(budget_color(100.0), Some(Color::Red));
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn over_budget_periods_sum_projects_before_comparing() {
+        let costs = [
+            ("2026-01-02", 3.0),
+            ("2026-01-01", 4.0),
+            ("2026-01-02", 3.0),
+            ("2026-01-03", 5.0),
+        ];
+        assert_eq!(
+            over_budget_periods(costs, 5.0),
+            vec![("2026-01-02".to_string(), 6.0)]
+        );
 
-    fn parse_daily_common(args: &[&str]) -> CommonArgs {
-        let parsed =
-            Cli::try_parse_from(["ccost", "daily"].into_iter().chain(args.iter().copied()))
-                .unwrap();
-        match parsed.command {
-            Command::Daily(args) => args.common,
-            Command::Monthly(_) => unreachable!(),
-        }
+        let error =
+            check_budget(&over_budget_periods(costs, 3.5), Some(3.5), "day", true).unwrap_err();
+        assert_eq!(error_code(&error), ErrorCode::OverBudget);
+        assert_eq!(
+            error.to_string(),
+            "Over budget: 3 days over the $3.50/day budget: \
+             2026-01-01 ($4.00), 2026-01-02 ($6.00), 2026-01-03 ($5.00)"
+        );
+        assert!(check_budget(&[], Some(3.5), "day", true).is_ok());
+        assert!(parse_budget("-1").is_err());
+        assert_eq!(parse_budget("12.5").unwrap(), 12.5);
     }
 
     #[test]
-    fn agent_defaults_to_all_sources() {
-        let common = parse_daily_common(&[]);
+    fn format_resolves_json_and_renders_tables_as_csv_or_markdown() {
+        let mut json = parse_daily_common(&["--json"]);
+        json.resolve_format();
+        assert_eq!(json.format, OutputFormat::Json);
+        let mut ndjson = parse_daily_common(&["--format", "ndjson"]);
+        ndjson.resolve_format();
+        assert!(ndjson.json);
+        let mut fields = parse_daily_common(&["--format", "csv", "--fields", "period"]);
+        fields.resolve_format();
+        fields.resolve_format();
+        assert_eq!((fields.format, fields.json), (OutputFormat::Csv, true));
+        let mut csv = parse_daily_common(&["--format", "csv"]);
+        csv.resolve_format();
+        assert!(csv.json && csv.quiet);
+        assert!(Cli::try_parse_from(["ccost", "daily", "--json", "--format", "csv"]).is_err());
 
-        assert_eq!(common.agent_flags(), AgentFlags::all());
+        let mut table = Table::new();
+        table.set_header(vec!["Model", "Cost"]);
+        table.add_row(vec!["a, b|c", "$1.00"]);
+        table.add_row(vec!["  |- opus-4", "$0.75"]);
+        let render = |format: &str| {
+            let mut args = parse_daily_common(&["--format", format]);
+            args.resolve_format();
+            assert!(args.quiet);
+            let mut output = String::new();
+            write_table(&mut output, &table, &args).unwrap();
+            output
+        };
         assert_eq!(
-            report_title("Daily", &common),
-            "Claude Code + Codex + OpenCode Token Usage Report - Daily"
+            render("csv"),
+            "Model,Cost\n\"a, b|c\",$1.00\n  |- opus-4,$0.75\n"
+        );
+        assert_eq!(
+            render("markdown"),
+            "| Model | Cost |\n| --- | --- |\n| a, b\\|c | $1.00 |\n| &nbsp;&nbsp;↳ opus-4 | $0.75 |\n"
         );
     }
 
     #[test]
-    fn agent_accepts_single_source() {
-        let common = parse_daily_common(&["--agent=codex"]);
+    fn ndjson_lines_splits_records_and_keeps_totals_last() {
+        let report = serde_json::json!({
+            "daily": [{"date": "2026-01-01"}, {"date": "2026-01-02"}],
+            "totals": {"totalCost": 1.5},
+        });
+        assert_eq!(
+            ndjson_lines(&report),
+            "{\"date\":\"2026-01-01\"}\n{\"date\":\"2026-01-02\"}\n{\"totals\":{\"totalCost\":1.5}}\n"
+        );
+        assert_eq!(ndjson_lines(&serde_json::json!([1, 2])), "1\n2\n");
+    }
 
+    #[test]
+    fn select_json_keeps_the_chosen_fields_or_the_totals() {
+        let report = || {
+            serde_json::json!({
+                "daily": [
+                    {"period": "2026-01-01", "totalCost": 1.5, "modelsUsed": ["opus", "haiku"]},
+                    {"period": "2026-01-02", "totalCost": 0.5, "modelsUsed": []},
+                ],
+                "totals": {"totalCost": 2.0, "inputTokens": 30},
+            })
+        };
+        let fields = ["period".to_string(), "modelsUsed".to_string()];
+        let selected = select_json(report(), &fields, false).unwrap();
         assert_eq!(
-            common.agent_flags(),
-            AgentFlags {
-                codex: true,
-                claudecode: false,
-                opencode: false,
-            }
+            selected,
+            serde_json::json!({
+                "daily": [
+                    {"period": "2026-01-01", "modelsUsed": ["opus", "haiku"]},
+                    {"period": "2026-01-02", "modelsUsed": []},
+                ],
+                "totals": {},
+            })
         );
         assert_eq!(
-            report_title("Daily", &common),
-            "Codex Token Usage Report - Daily"
+            json_csv(&selected, &fields),
+            "Date,Models\n2026-01-01,opus;haiku\n2026-01-02,\n"
+        );
+
+        let totals = select_json(report(), &[], true).unwrap();
+        assert_eq!(
+            totals,
+            serde_json::json!({"totalCost": 2.0, "inputTokens": 30})
+        );
+        assert_eq!(json_csv(&totals, &[]), "Input,Cost (USD)\n30,2.0\n");
+        let monthly = serde_json::json!([{"period": "2026-01", "subagentCost": 0.5}]);
+        assert_eq!(
+            json_csv(&monthly, &[]),
+            "Month,Subagent Cost\n2026-01,0.5\n"
         );
+
+        let unknown = select_json(report(), &["date".to_string()], false).unwrap_err();
+        assert!(unknown.to_string().starts_with("Unknown field date"));
+        assert!(select_json(serde_json::json!({"sessions": []}), &[], true).is_err());
     }
 
     #[test]
-    fn agent_accepts_comma_separated_sources() {
-        let common = parse_daily_common(&["--agent=codex,opencode"]);
+    fn emit_json_records_streams_records_then_the_report_fields() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("daily.ndjson");
+        let records = || {
+            ["2026-01-01", "2026-01-02"]
+                .into_iter()
+                .map(|date| serde_json::json!({ "date": date }))
+        };
+        let report = || serde_json::json!({ "totals": { "totalCost": 1.5 } });
 
+        let mut args = parse_daily_common(&["--format", "ndjson"]);
+        args.resolve_format();
+        args.output_file = Some(path.clone());
+        emit_json_records("daily", records(), report(), &args).unwrap();
         assert_eq!(
-            common.agent_flags(),
-            AgentFlags {
-                codex: true,
-                claudecode: false,
-                opencode: true,
-            }
+            std::fs::read_to_string(&path).unwrap(),
+            "{\"date\":\"2026-01-01\"}\n{\"date\":\"2026-01-02\"}\n{\"totals\":{\"totalCost\":1.5}}\n"
+        );
+
+        let mut args = parse_daily_common(&["--json"]);
+        args.resolve_format();
+        args.output_file = Some(path.clone());
+        emit_json_records("daily", records(), report(), &args).unwrap();
+        let json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(json["daily"][1]["date"], "2026-01-02");
+        assert_eq!(json["totals"]["totalCost"], 1.5);
+    }
+
+    #[test]
+    fn csv_writes_raw_numbers_from_the_json_records() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("daily.csv");
+        let record = |date: &str, cost: f64| {
+            serde_json::json!({
+                "period": date,
+                "inputTokens": 659042,
+                "totalCost": cost,
+                "modelsUsed": ["claude-opus-4", "claude-sonnet-4"],
+                "modelBreakdowns": [{"modelName": "claude-opus-4", "cost": cost}],
+                "metadata": {"agents": []},
+            })
+        };
+        let mut args = parse_daily_common(&["--format", "csv"]);
+        args.resolve_format();
+        args.output_file = Some(path.clone());
+        emit_json_records(
+            "daily",
+            [record("2026-01-01", 1.99), record("2026-01-02", 0.5)],
+            serde_json::json!({ "totals": { "totalCost": 2.49 } }),
+            &args,
+        )
+        .unwrap();
+        let csv = std::fs::read_to_string(&path).unwrap();
+        let lines = csv.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], "Input,Models,Date,Cost (USD)");
+        assert_eq!(lines.len(), 3);
+        let cells = lines[1].split(',').collect::<Vec<_>>();
+        assert_eq!(cells[0].parse::<u64>().unwrap(), 659042);
+        assert_eq!(cells[1], "claude-opus-4;claude-sonnet-4");
+        assert_eq!(cells[3].parse::<f64>().unwrap(), 1.99);
+
+        let grouped = serde_json::json!({
+            "projects": {"app": [{"period": "2026-01-01", "totalCost": 1.0}]},
+            "totals": {"totalCost": 1.0},
+        });
+        assert_eq!(
+            json_csv(&grouped, &[]),
+            "Date,Project,Cost (USD)\n2026-01-01,app,1.0\n"
         );
+        let sections = serde_json::json!({
+            "input": {"buckets": [{"range": "<1K", "requestCount": 3}], "max": 900},
+            "output": {"buckets": [{"range": "<1K", "requestCount": 1}], "max": 400},
+        });
         assert_eq!(
-            report_title("Daily", &common),
-            "Codex + OpenCode Token Usage Report - Daily"
+            json_csv(&sections, &[]),
+            "Group,Range,Requests\ninput,<1K,3\noutput,<1K,1\n"
         );
     }
 
     #[test]
-    fn removed_source_boolean_flags_are_rejected() {
-        let result = Cli::try_parse_from(["ccost", "daily", "--codex=false"]);
+    fn matrix_csv_has_header_and_seven_rows() {
+        let mut matrix = [[0.0; 24]; 7];
+        matrix[0][9] = 1.5;
+        matrix[6][23] = 0.25;
+        let csv = matrix_csv(&matrix);
+        let lines = csv.lines().collect::<Vec<_>>();
 
-        assert!(result.is_err());
+        assert_eq!(lines.len(), 8);
+        assert!(lines[0].starts_with("weekday,00,01,"));
+        assert!(lines[0].ends_with(",23,total"));
+        assert!(lines[1].starts_with("Monday,0.0000,"));
+        assert!(lines[1].ends_with(",1.5000"));
+        assert!(lines[7].ends_with(",0.2500,0.2500"));
+        assert_eq!(format_matrix_cost(0.0), "");
+        assert_eq!(format_matrix_cost(12.6), "13");
+        assert_eq!(format_matrix_cost(0.456), "0.46");
     }
 
     #[test]
-    fn kmb_is_opt_in() {
-        assert!(!parse_daily_common(&[]).kmb);
-        assert!(parse_daily_common(&["--kmb"]).kmb);
+    fn top_model_by_cost_sums_breakdowns_across_days() {
+        let breakdown = |model: &str, cost: f64| ModelBreakdown {
+            model_name: model.to_string(),
+            input_tokens: 0,
+            output_tokens: 0,
+            cache_creation_tokens: 0,
+            cache_read_tokens: 0,
+            total_tokens: 0,
+            cost,
+        };
+        let day = |breakdowns: Vec<ModelBreakdown>| DailyUsage {
+            date: "2026-01-01".to_string(),
+            input_tokens: 0,
+            output_tokens: 0,
+            cache_creation_tokens: 0,
+            cache_read_tokens: 0,
+            total_tokens: 0,
+            total_cost: 0.0,
+            models_used: Vec::new(),
+            model_breakdowns: breakdowns,
+            project: None,
+            message_count: 0,
+            subagent_cost: 0.0,
+            tool_calls: 0,
+            api_errors: 0,
+            logged_costs: 0,
+            session_ids: Vec::new(),
+            first_timestamp: None,
+            last_timestamp: None,
+            hour_mask: 0,
+        };
+        let daily = vec![
+            day(vec![breakdown("opus", 3.0), breakdown("sonnet", 2.0)]),
+            day(vec![breakdown("sonnet", 2.0)]),
+        ];
 
-        let parsed = Cli::try_parse_from(["ccost", "monthly", "--json", "--kmb"]).unwrap();
-        let Command::Monthly(args) = parsed.command else {
+        assert_eq!(top_model_by_cost(&daily).as_deref(), Some("sonnet"));
+        assert_eq!(top_model_by_cost(&[]), None);
+    }
+
+    #[test]
+    fn sessions_accept_top_and_sort() {
+        let parsed =
+            Cli::try_parse_from(["ccost", "sessions", "--top", "10", "--sort", "cost"]).unwrap();
+        let Command::Session(args) = parsed.command else {
             unreachable!();
         };
-        assert!(args.common.json);
-        assert!(args.common.kmb);
+        assert_eq!(args.top, Some(10));
+        assert_eq!(args.sort, SessionSort::Cost);
+        assert!(Cli::try_parse_from(["ccost", "sessions", "--sort", "price"]).is_err());
+    }
+
+    #[test]
+    fn projects_sort_by_cost_unless_tokens_are_asked_for() {
+        let Command::Projects(args) = Cli::try_parse_from(["ccost", "projects"]).unwrap().command
+        else {
+            unreachable!();
+        };
+        assert_eq!(args.sort, ProjectSort::Cost);
+        let Command::Projects(args) =
+            Cli::try_parse_from(["ccost", "projects", "--sort", "tokens"])
+                .unwrap()
+                .command
+        else {
+            unreachable!();
+        };
+        assert_eq!(args.sort, ProjectSort::Tokens);
     }
 
     #[test]
     fn json_totals_keep_raw_numeric_tokens() {
-        let output = totals_output(UsageTotals {
-            input_tokens: 69_960_297_352,
-            total_tokens: 69_960_297_352,
-            ..UsageTotals::default()
-        });
+        let output = totals_output(
+            UsageTotals {
+                input_tokens: 69_960_297_352,
+                total_tokens: 69_960_297_352,
+                ..UsageTotals::default()
+            },
+            &[],
+        );
         let json = serde_json::to_value(output).unwrap();
 
         assert_eq!(json["inputTokens"].as_u64(), Some(69_960_297_352));
         assert_eq!(json["totalTokens"].as_u64(), Some(69_960_297_352));
+        assert!(json.get("messageCount").is_none());
+    }
+
+    #[test]
+    fn default_subcommand_is_inserted_only_when_missing() {
+        let args = |values: &[&str]| {
+            values
+                .iter()
+                .map(std::ffi::OsString::from)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            with_default_subcommand(args(&["ccost"]), "monthly"),
+            args(&["ccost", "monthly"])
+        );
+        assert_eq!(
+            with_default_subcommand(args(&["ccost", "--json"]), "daily"),
+            args(&["ccost", "daily", "--json"])
+        );
+        assert_eq!(
+            with_default_subcommand(args(&["ccost", "session"]), "monthly"),
+            args(&["ccost", "session"])
+        );
+        assert_eq!(
+            with_default_subcommand(args(&["ccost", "--help"]), "monthly"),
+            args(&["ccost", "--help"])
+        );
+    }
+
+    #[test]
+    fn help_version_and_init_work_without_the_config() {
+        let works = |values: &[&str]| {
+            works_without_config(
+                &values
+                    .iter()
+                    .map(std::ffi::OsString::from)
+                    .collect::<Vec<_>>(),
+            )
+        };
+        assert!(works(&["ccost", "--help"]));
+        assert!(works(&["ccost", "daily", "-h"]));
+        assert!(works(&["ccost", "-V"]));
+        assert!(works(&["ccost", "help", "daily"]));
+        assert!(works(&["ccost", "init", "--force"]));
+        assert!(!works(&["ccost", "daily", "--since", "20260101"]));
+        assert!(!works(&["ccost", "daily", "--project", "init"]));
+        assert!(!works(&["ccost"]));
+    }
+
+    #[test]
+    fn default_args_follow_the_subcommand_and_yield_to_later_flags() {
+        let mut config = Config::default();
+        config.default_args.insert(
+            "daily".to_string(),
+            vec![
+                "--breakdown".to_string(),
+                "--since".to_string(),
+                "20250101".to_string(),
+            ],
+        );
+        let args = with_default_args(
+            ["ccost", "d", "--since", "20250301"]
+                .map(std::ffi::OsString::from)
+                .to_vec(),
+            &config,
+        )
+        .unwrap();
+        let Command::Daily(parsed) = Cli::try_parse_from(args).unwrap().command else {
+            unreachable!();
+        };
+        assert!(parsed.common.breakdown);
+        assert_eq!(parsed.common.since.as_deref(), Some("20250301"));
+
+        config.default_args.insert("dialy".to_string(), Vec::new());
+        assert!(with_default_args(Vec::new(), &config).is_err());
+    }
+
+    #[test]
+    fn arg_files_expand_in_place() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("report-args.txt");
+        std::fs::write(
+            &path,
+            "# weekly report\n--breakdown --instances\n--project \"my project\" --columns=''\n",
+        )
+        .unwrap();
+        let expanded = expand_arg_files(
+            ["ccost", "daily", &format!("@{}", path.display()), "--json"]
+                .into_iter()
+                .map(std::ffi::OsString::from)
+                .collect(),
+        )
+        .unwrap();
+        assert_eq!(
+            expanded,
+            [
+                "ccost",
+                "daily",
+                "--breakdown",
+                "--instances",
+                "--project",
+                "my project",
+                "--columns=",
+                "--json"
+            ]
+            .map(std::ffi::OsString::from)
+        );
+        assert!(split_arg_file("--project 'open").is_err());
+        assert!(expand_arg_files(vec!["ccost".into(), "@/nonexistent/args".into()]).is_err());
+    }
+
+    #[test]
+    fn mode_and_order_are_validated_by_clap() {
+        let common = parse_daily_common(&["--mode", "calculate", "--order", "desc"]);
+        assert_eq!(common.mode, CostMode::Calculate);
+        assert_eq!(common.order, SortOrder::Desc);
+
+        let error = Cli::try_parse_from(["ccost", "daily", "--mode", "calculated"])
+            .err()
+            .unwrap();
+        assert_eq!(error.kind(), clap::error::ErrorKind::InvalidValue);
+        assert!(Cli::try_parse_from(["ccost", "daily", "--order", "up"]).is_err());
+    }
+
+    #[test]
+    fn follow_symlinks_defaults_on_and_accepts_false() {
+        assert!(parse_daily_common(&[]).follow_symlinks);
+        assert!(parse_daily_common(&["--follow-symlinks"]).follow_symlinks);
+        assert!(!parse_daily_common(&["--follow-symlinks=false"]).follow_symlinks);
+    }
+
+    #[test]
+    fn export_file_stem_replaces_unsafe_characters() {
+        assert_eq!(export_file_stem("-home-me-app"), "-home-me-app");
+        assert_eq!(export_file_stem("team/api: v2"), "team_api__v2");
+        assert_eq!(export_file_stem(".."), "_");
+
+        let projects = ["a/b", "a_b", "App", "app", "solo"].map(String::from);
+        let stems = export_file_stems(projects.clone().into_iter().collect());
+        let stem = |project: &str| {
+            stems
+                .iter()
+                .find(|(name, _)| name == project)
+                .map(|(_, stem)| stem.clone())
+                .unwrap()
+        };
+        assert_eq!(stem("solo"), "solo");
+        assert!(stem("a/b").starts_with("a_b-"));
+        assert!(stem("a_b").starts_with("a_b-"));
+        assert_ne!(stem("a/b"), stem("a_b"));
+        assert_eq!(stem("a/b").len(), "a_b-".len() + 8);
+        assert_ne!(stem("App").to_lowercase(), stem("app").to_lowercase());
+        assert_eq!(stems, export_file_stems(projects.into_iter().collect()));
+    }
+
+    #[test]
+    fn truncate_title_shortens_long_titles_on_char_boundaries() {
+        assert_eq!(truncate_title("Fix login bug"), "Fix login bug");
+        let long = "é".repeat(45);
+        let truncated = truncate_title(&long);
+        assert_eq!(truncated.chars().count(), 40);
+        assert!(truncated.ends_with('…'));
+    }
+
+    #[test]
+    fn scheduled_command_parses_subcommands_but_not_schedule() {
+        let report = |command: &[&str]| ScheduledReport {
+            at: chrono::NaiveTime::from_hms_opt(18, 0, 0).unwrap(),
+            day: None,
+            command: command.iter().map(|value| value.to_string()).collect(),
+        };
+        assert!(matches!(
+            scheduled_command(&report(&["summary", "--json"])),
+            Ok(Command::Summary(_))
+        ));
+        let Ok(Command::Daily(daily)) = scheduled_command(&report(&["daily"])) else {
+            panic!("expected a daily command");
+        };
+        assert!(daily.common.no_pager);
+        assert!(scheduled_command(&report(&["tui"])).is_err());
+        assert!(scheduled_command(&report(&["live"])).is_err());
+        assert!(scheduled_command(&report(&["summary", "--bogus"])).is_err());
+        assert!(scheduled_command(&report(&["schedule"])).is_err());
+    }
+
+    #[test]
+    fn profile_supplies_paths_and_project() {
+        let mut config = Config::default();
+        config.profiles.insert(
+            "work".to_string(),
+            crate::config::Profile {
+                claude_path: Some(std::path::PathBuf::from("/data/work/.claude")),
+                project: Some("acme".to_string()),
+                ..Default::default()
+            },
+        );
+
+        let options = common_options(&parse_daily_common(&["--profile", "work"]), &config).unwrap();
+        assert_eq!(
+            options.claude_paths,
+            vec![std::path::PathBuf::from("/data/work/.claude")]
+        );
+        assert_eq!(options.project.as_deref(), Some("acme"));
+
+        let options = common_options(
+            &parse_daily_common(&["--profile", "work", "--path", "/a", "--path", "/b"]),
+            &config,
+        )
+        .unwrap();
+        assert_eq!(
+            options.claude_paths,
+            vec![
+                std::path::PathBuf::from("/a"),
+                std::path::PathBuf::from("/b")
+            ]
+        );
+        assert!(common_options(&parse_daily_common(&["--profile", "home"]), &config).is_err());
+    }
+
+    #[test]
+    fn columns_flag_accepts_comma_separated_values() {
+        let common = parse_daily_common(&["--columns", "messages,subagents"]);
+        assert_eq!(
+            common.columns,
+            vec![ExtraColumn::Messages, ExtraColumn::Subagents]
+        );
+        assert!(parse_daily_common(&[]).columns.is_empty());
     }
 }
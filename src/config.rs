@@ -0,0 +1,506 @@
+use crate::error::{ErrorCode, coded_error};
+use crate::table::DisplayCurrency;
+use anyhow::{Result, anyhow};
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+const CCOST_CONFIG_ENV: &str = "CCOST_CONFIG";
+const CONFIG_DIR_NAME: &str = "ccost";
+const CONFIG_FILENAME: &str = "config.toml";
+const IMPORTS_DIR_NAME: &str = "imports";
+const SNAPSHOTS_DIR_NAME: &str = "snapshots";
+const ARCHIVE_DIR_NAME: &str = "archive";
+const PRICING_FILENAME: &str = "pricing.toml";
+const PARSE_CACHE_FILENAME: &str = "parse-cache.sqlite";
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub max_models: Option<usize>,
+    /// Subcommand to run when `ccost` is invoked without one (defaults to `daily`).
+    pub default_command: Option<String>,
+    /// Gitignore-style patterns excluded from scanning, combined with `--ignore`.
+    pub ignore: Vec<String>,
+    /// Largest JSONL line to parse, e.g. `"64M"`; overridden by `--max-line-size`.
+    pub max_line_size: Option<String>,
+    /// Largest log file to read, e.g. `"4G"`; overridden by `--max-file-size`.
+    pub max_file_size: Option<String>,
+    /// Treat reports without usage data as errors, like `--fail-empty`.
+    pub fail_empty: bool,
+    /// IANA timezone used when `--timezone` is not given.
+    pub timezone: Option<String>,
+    /// Claude log field holding the request cost when it isn't one of `costUSD`, `costUsd`, or
+    /// `cost_usd`; dots separate nested keys.
+    pub cost_field: Option<String>,
+    /// Ordered Claude data roots to probe instead of the built-in list; `~`, `$VAR`, and `${VAR}`
+    /// are expanded, and entries naming an unset variable are skipped.
+    pub claude_search_paths: Vec<String>,
+    /// Reports run by `ccost schedule`, written as `[[schedule]]` tables.
+    #[serde(rename = "schedule")]
+    pub schedules: Vec<ScheduleEntry>,
+    pub budgets: Budgets,
+    /// Currency costs are shown in instead of US dollars, written as a `[currency]` table.
+    pub currency: Option<CurrencyConfig>,
+    pub profiles: BTreeMap<String, Profile>,
+    /// Model-name display rules, written as `[[model_name]]` tables and tried in order.
+    #[serde(rename = "model_name")]
+    pub model_names: Vec<ModelNameRule>,
+    /// Flags inserted after a subcommand's name, keyed by subcommand, e.g.
+    /// `daily = ["--breakdown", "--instances"]`; flags given on the command line win.
+    pub default_args: BTreeMap<String, Vec<String>>,
+}
+
+/// Spending limits in USD, written as a `[budgets]` table.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Budgets {
+    /// Limit per day across all projects, used by `ccost daily` when `--budget` isn't given.
+    pub daily: Option<f64>,
+    /// Limit per month across all projects, used by `ccost monthly` when `--budget` isn't given.
+    pub monthly: Option<f64>,
+    /// Monthly limit per project name, as shown by `--instances`.
+    pub projects: BTreeMap<String, f64>,
+}
+
+/// Shows costs in another currency, converted from US dollars at a fixed `rate`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CurrencyConfig {
+    /// ISO 4217 code, e.g. `EUR`.
+    pub code: String,
+    /// Units of the currency one US dollar buys, e.g. `0.92` for euros.
+    pub rate: f64,
+    /// Shown before amounts; defaults to the usual symbol of common currencies, else the code.
+    pub symbol: Option<String>,
+}
+
+/// Symbols and decimal places of common currencies.
+const CURRENCIES: [(&str, &str, usize); 12] = [
+    ("USD", "$", 2),
+    ("EUR", "€", 2),
+    ("GBP", "£", 2),
+    ("JPY", "¥", 0),
+    ("CNY", "¥", 2),
+    ("KRW", "₩", 0),
+    ("INR", "₹", 2),
+    ("CAD", "CA$", 2),
+    ("AUD", "A$", 2),
+    ("CHF", "CHF ", 2),
+    ("BRL", "R$", 2),
+    ("SEK", "SEK ", 2),
+];
+
+impl CurrencyConfig {
+    pub fn display_currency(&self) -> DisplayCurrency {
+        let known = CURRENCIES.iter().find(|(code, ..)| *code == self.code);
+        DisplayCurrency {
+            code: self.code.clone(),
+            symbol: self
+                .symbol
+                .clone()
+                .or_else(|| known.map(|(_, symbol, _)| symbol.to_string()))
+                .unwrap_or_else(|| format!("{} ", self.code)),
+            rate: self.rate,
+            decimals: known.map_or(2, |(.., decimals)| *decimals),
+        }
+    }
+}
+
+/// Checks an ISO 4217 currency code and a USD exchange rate, as set by `[currency]`.
+pub fn validate_currency(code: &str, rate: f64) -> Result<()> {
+    if code.len() != 3 || !code.chars().all(|c| c.is_ascii_uppercase()) {
+        return Err(anyhow!(
+            "currency.code must be a three-letter ISO code such as EUR, got {code:?}"
+        ));
+    }
+    if !rate.is_finite() || rate <= 0.0 {
+        return Err(anyhow!(
+            "currency.rate must be a positive number of {code} per US dollar"
+        ));
+    }
+    Ok(())
+}
+
+/// A report to run at `at` (`HH:MM`, local time), daily or on day `day` of each month.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ScheduleEntry {
+    pub at: String,
+    pub day: Option<u32>,
+    /// Subcommand and flags, e.g. `["notify", "--webhook", "https://..."]`.
+    pub command: Vec<String>,
+}
+
+/// Shows model names matching the regex `pattern` as `replacement`, which may refer to capture
+/// groups as `$1` or `${name}`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ModelNameRule {
+    pub pattern: String,
+    pub replacement: String,
+}
+
+/// A named set of data locations and filters, selected with `--profile`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Profile {
+    pub claude_path: Option<PathBuf>,
+    pub codex_path: Option<PathBuf>,
+    pub opencode_path: Option<PathBuf>,
+    pub project: Option<String>,
+}
+
+impl Config {
+    pub fn claude_search_paths(&self) -> Vec<PathBuf> {
+        self.claude_search_paths
+            .iter()
+            .filter_map(|raw| {
+                expand_variables(raw, |name| std::env::var(name).ok())
+                    .map(|expanded| expand_home(Path::new(&expanded)))
+            })
+            .collect()
+    }
+
+    pub fn model_name_rules(&self) -> Result<Vec<(Regex, String)>> {
+        self.model_names
+            .iter()
+            .map(|rule| {
+                Regex::new(&rule.pattern)
+                    .map(|pattern| (pattern, rule.replacement.clone()))
+                    .map_err(|error| {
+                        anyhow!("invalid model_name pattern {:?}: {error}", rule.pattern)
+                    })
+            })
+            .collect()
+    }
+
+    pub fn profile(&self, name: &str) -> Result<&Profile> {
+        self.profiles.get(name).ok_or_else(|| {
+            let available = self.profiles.keys().cloned().collect::<Vec<_>>();
+            if available.is_empty() {
+                anyhow!("Unknown profile: {name} (no profiles are configured)")
+            } else {
+                anyhow!(
+                    "Unknown profile: {name} (available: {})",
+                    available.join(", ")
+                )
+            }
+        })
+    }
+}
+
+pub fn config_path() -> Option<PathBuf> {
+    if let Ok(value) = std::env::var(CCOST_CONFIG_ENV) {
+        let trimmed = value.trim();
+        if !trimmed.is_empty() {
+            return Some(PathBuf::from(trimmed));
+        }
+    }
+    dirs::config_dir().map(|dir| dir.join(CONFIG_DIR_NAME).join(CONFIG_FILENAME))
+}
+
+/// Directory next to the config file where `ccost import` stores converted records.
+pub fn imports_dir() -> Option<PathBuf> {
+    config_sibling(IMPORTS_DIR_NAME)
+}
+
+/// Directory next to the config file where `ccost archive --remove` keeps the usage lines of
+/// the logs it removes.
+pub fn archive_dir() -> Option<PathBuf> {
+    config_sibling(ARCHIVE_DIR_NAME)
+}
+
+/// Directory next to the config file where `ccost snapshot` saves aggregates.
+pub fn snapshots_dir() -> Option<PathBuf> {
+    config_sibling(SNAPSHOTS_DIR_NAME)
+}
+
+/// User pricing file next to the config file, read when `--pricing-file` is not given.
+pub fn pricing_path() -> Option<PathBuf> {
+    config_sibling(PRICING_FILENAME)
+}
+
+/// SQLite file in the user cache directory where parsed Claude logs are kept between runs.
+pub fn parse_cache_path() -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join(CONFIG_DIR_NAME).join(PARSE_CACHE_FILENAME))
+}
+
+fn config_sibling(name: &str) -> Option<PathBuf> {
+    config_path()
+        .and_then(|path| path.parent().map(Path::to_path_buf))
+        .map(|dir| dir.join(name))
+}
+
+pub fn load_config() -> Result<Config> {
+    match config_path() {
+        Some(path) if path.is_file() => load_config_from(&path),
+        _ => Ok(Config::default()),
+    }
+}
+
+pub fn load_config_from(path: &Path) -> Result<Config> {
+    let content = std::fs::read_to_string(path).map_err(|error| {
+        coded_error(
+            ErrorCode::InvalidConfig,
+            format!("failed to read config {}: {error}", path.display()),
+        )
+    })?;
+    parse_config(&content).map_err(|error| {
+        coded_error(
+            ErrorCode::InvalidConfig,
+            format!("invalid config {}: {error}", path.display()),
+        )
+    })
+}
+
+pub(crate) fn parse_config(content: &str) -> Result<Config> {
+    let mut config: Config = toml::from_str(content)?;
+    for profile in config.profiles.values_mut() {
+        for path in [
+            &mut profile.claude_path,
+            &mut profile.codex_path,
+            &mut profile.opencode_path,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            *path = expand_home(path);
+        }
+    }
+    config.model_name_rules()?;
+    if let Some(currency) = &config.currency {
+        validate_currency(&currency.code, currency.rate)?;
+    }
+    for (key, budget) in [
+        ("daily", config.budgets.daily),
+        ("monthly", config.budgets.monthly),
+    ] {
+        if budget.is_some_and(|budget| !budget.is_finite() || budget < 0.0) {
+            return Err(anyhow!(
+                "budgets.{key} must be a non-negative amount in USD"
+            ));
+        }
+    }
+    Ok(config)
+}
+
+fn expand_variables<F>(raw: &str, var: F) -> Option<String>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut output = String::new();
+    let mut rest = raw;
+    while let Some(start) = rest.find('$') {
+        output.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let (name, remainder) = match after.strip_prefix('{') {
+            Some(braced) => {
+                let end = braced.find('}')?;
+                (&braced[..end], &braced[end + 1..])
+            }
+            None => {
+                let end = after
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .unwrap_or(after.len());
+                (&after[..end], &after[end..])
+            }
+        };
+        if name.is_empty() {
+            return None;
+        }
+        output.push_str(&var(name).filter(|value| !value.is_empty())?);
+        rest = remainder;
+    }
+    output.push_str(rest);
+    Some(output)
+}
+
+fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), dirs::home_dir()) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_config_reads_max_models() {
+        let config = parse_config("max_models = 3\n").unwrap();
+        assert_eq!(config.max_models, Some(3));
+    }
+
+    #[test]
+    fn parse_config_reads_cost_field() {
+        let config = parse_config("cost_field = \"metadata.cost\"\n").unwrap();
+        assert_eq!(config.cost_field.as_deref(), Some("metadata.cost"));
+    }
+
+    #[test]
+    fn parse_config_reads_fail_empty() {
+        assert!(parse_config("fail_empty = true\n").unwrap().fail_empty);
+        assert!(!parse_config("").unwrap().fail_empty);
+    }
+
+    #[test]
+    fn parse_config_reads_default_args() {
+        let config =
+            parse_config("[default_args]\ndaily = [\"--breakdown\", \"--instances\"]\n").unwrap();
+        assert_eq!(
+            config.default_args["daily"],
+            vec!["--breakdown", "--instances"]
+        );
+        assert!(parse_config("[default_args]\ndaily = \"--breakdown\"\n").is_err());
+    }
+
+    #[test]
+    fn parse_config_reads_default_command() {
+        let config = parse_config("default_command = \"monthly\"\n").unwrap();
+        assert_eq!(config.default_command.as_deref(), Some("monthly"));
+    }
+
+    #[test]
+    fn expand_variables_substitutes_or_skips_unset() {
+        let var = |name: &str| match name {
+            "XDG_DATA_HOME" => Some("/home/me/.local/share".to_string()),
+            _ => None,
+        };
+        assert_eq!(
+            expand_variables("$XDG_DATA_HOME/claude", var).as_deref(),
+            Some("/home/me/.local/share/claude")
+        );
+        assert_eq!(
+            expand_variables("${XDG_DATA_HOME}/claude", var).as_deref(),
+            Some("/home/me/.local/share/claude")
+        );
+        assert_eq!(
+            expand_variables("~/.claude", var).as_deref(),
+            Some("~/.claude")
+        );
+        assert_eq!(expand_variables("$UNSET/claude", var), None);
+        assert_eq!(expand_variables("${XDG_DATA_HOME/claude", var), None);
+    }
+
+    #[test]
+    fn parse_config_defaults_when_empty() {
+        let config = parse_config("").unwrap();
+        assert_eq!(config.max_models, None);
+        assert!(config.profiles.is_empty());
+    }
+
+    #[test]
+    fn parse_config_rejects_unknown_keys() {
+        assert!(parse_config("max_model = 3\n").is_err());
+        assert!(parse_config("[profiles.work]\nclaude_dir = \"/tmp\"\n").is_err());
+    }
+
+    #[test]
+    fn parse_config_reads_schedules() {
+        let config = parse_config(
+            r#"
+[[schedule]]
+at = "18:00"
+command = ["notify", "--webhook", "https://hooks.slack.com/services/x"]
+
+[[schedule]]
+at = "09:00"
+day = 1
+command = ["email", "--to", "team@example.com"]
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.schedules.len(), 2);
+        assert_eq!(config.schedules[0].at, "18:00");
+        assert_eq!(config.schedules[0].day, None);
+        assert_eq!(config.schedules[1].day, Some(1));
+        assert_eq!(config.schedules[1].command[0], "email");
+    }
+
+    #[test]
+    fn parse_config_reads_project_budgets() {
+        let config = parse_config("[budgets.projects]\nacme = 50\n\"my app\" = 12.5\n").unwrap();
+        assert_eq!(config.budgets.projects.get("acme"), Some(&50.0));
+        assert_eq!(config.budgets.projects.get("my app"), Some(&12.5));
+        assert!(parse_config("[budgets]\nacme = 50\n").is_err());
+    }
+
+    #[test]
+    fn parse_config_reads_daily_and_monthly_budgets() {
+        let config = parse_config("[budgets]\ndaily = 5\nmonthly = 120.5\n").unwrap();
+        assert_eq!(config.budgets.daily, Some(5.0));
+        assert_eq!(config.budgets.monthly, Some(120.5));
+        assert!(parse_config("[budgets]\ndaily = -1\n").is_err());
+    }
+
+    #[test]
+    fn parse_config_reads_the_display_currency() {
+        let config = parse_config("[currency]\ncode = \"EUR\"\nrate = 0.92\n").unwrap();
+        let currency = config.currency.unwrap().display_currency();
+        assert_eq!(
+            (
+                currency.code.as_str(),
+                currency.symbol.as_str(),
+                currency.rate
+            ),
+            ("EUR", "€", 0.92)
+        );
+        let config = parse_config("[currency]\ncode = \"NOK\"\nrate = 10\n").unwrap();
+        assert_eq!(config.currency.unwrap().display_currency().symbol, "NOK ");
+        assert!(parse_config("[currency]\ncode = \"eur\"\nrate = 1\n").is_err());
+        assert!(parse_config("[currency]\ncode = \"EUR\"\nrate = 0\n").is_err());
+    }
+
+    #[test]
+    fn parse_config_reads_model_name_rules() {
+        let config = parse_config(
+            r#"
+[[model_name]]
+pattern = '^gateway/(.+)$'
+replacement = "$1"
+"#,
+        )
+        .unwrap();
+        let rules = config.model_name_rules().unwrap();
+        assert_eq!(rules.len(), 1);
+        assert_eq!(rules[0].0.replace("gateway/gpt-5", &rules[0].1), "gpt-5");
+
+        let error = parse_config("[[model_name]]\npattern = \"(\"\nreplacement = \"\"\n")
+            .unwrap_err()
+            .to_string();
+        assert!(error.starts_with("invalid model_name pattern \"(\""));
+    }
+
+    #[test]
+    fn parse_config_reads_named_profiles() {
+        let config = parse_config(
+            r#"
+[profiles.work]
+claude_path = "/data/work/.claude"
+project = "acme"
+
+[profiles.personal]
+codex_path = "~/personal/.codex"
+"#,
+        )
+        .unwrap();
+
+        let work = config.profile("work").unwrap();
+        assert_eq!(work.claude_path, Some(PathBuf::from("/data/work/.claude")));
+        assert_eq!(work.project.as_deref(), Some("acme"));
+        let personal = config.profile("personal").unwrap();
+        assert!(
+            personal
+                .codex_path
+                .as_ref()
+                .is_some_and(|path| !path.starts_with("~"))
+        );
+
+        let error = config.profile("home").unwrap_err().to_string();
+        assert_eq!(error, "Unknown profile: home (available: personal, work)");
+    }
+}
@@ -1,25 +1,42 @@
-use crate::pricing::{CacheCreationTokens, CostMode, PricingFetcher, UsageTokens};
+use crate::duplicates::record_duplicate;
+use crate::error::{ErrorCode, coded_error};
+use crate::ignore::IgnorePatterns;
+use crate::model_filter::ModelFilter;
+use crate::pricing::{
+    CacheCreationTokens, CostMode, PricingFetcher, UsageTokens, pricing_overrides_key,
+};
 use crate::time_utils::{
-    SortOrder, filter_by_date_range, format_date_with_tz, format_month, sort_by_date,
+    SortOrder, filter_by_date_range, format_date_with_tz, format_month, local_hour, sort_by_date,
 };
+use crate::timings::{Phase, phase_start, record_phase};
+use crate::warnings;
 use anyhow::{Result, anyhow};
-use chrono::{DateTime, NaiveDate, Utc};
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
 use chrono_tz::Tz;
 use jwalk::WalkDir;
 use memchr::{memchr, memmem};
 use rayon::prelude::*;
-use rusqlite::{Connection, OpenFlags};
+use rusqlite::{Connection, OpenFlags, params};
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use sonic_rs::JsonValueTrait;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs::File;
+use std::hash::{DefaultHasher, Hash, Hasher};
 use std::io::{BufRead, BufReader, Read};
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 const CLAUDE_CONFIG_DIR_ENV: &str = "CLAUDE_CONFIG_DIR";
-const CLAUDE_PROJECTS_DIR_NAME: &str = "projects";
+pub const CLAUDE_PROJECTS_DIR_NAME: &str = "projects";
 const DEFAULT_CLAUDE_CODE_PATH: &str = ".claude";
+const WINDOWS_USER_PROFILE_ENV: &str = "USERPROFILE";
+const WINDOWS_APP_DATA_ENV: &str = "APPDATA";
+const WINDOWS_APP_DATA_CLAUDE_DIR_NAME: &str = "claude";
+const CLAUDE_DESKTOP_DIR_NAME: &str = "Claude";
+/// Levels below Claude Desktop's app data directory searched for Claude Code-format roots.
+const CLAUDE_DESKTOP_SEARCH_DEPTH: usize = 3;
 const CODEX_HOME_ENV: &str = "CODEX_HOME";
 const CODEX_SESSIONS_DIR_NAME: &str = "sessions";
 const CODEX_ARCHIVED_SESSIONS_DIR_NAME: &str = "archived_sessions";
@@ -39,6 +56,15 @@ const CODEX_THREAD_SPAWN_MARKER: &[u8] = b"thread_spawn";
 const CODEX_FORKED_FROM_ID_MARKER: &[u8] = b"forked_from_id";
 const CODEX_AUTO_REVIEW_MODEL: &str = "codex-auto-review";
 const ADVISOR_MESSAGE_MARKER: &[u8] = b"\"advisor_message\"";
+const TOOL_USE_MARKER: &[u8] = b"\"tool_use\"";
+const SUMMARY_MARKER: &[u8] = b"\"type\":\"summary\"";
+const UUID_MARKER: &[u8] = b"\"uuid\":\"";
+const USER_MARKER: &[u8] = b"\"type\":\"user\"";
+/// Longest prompt kept as a session title; longer prompts end in `…`.
+const PROMPT_TITLE_MAX_CHARS: usize = 100;
+const SYNTHETIC_MODEL: &str = "<synthetic>";
+const DEFAULT_MAX_LINE_BYTES: u64 = 64 << 20;
+const DEFAULT_MAX_FILE_BYTES: u64 = 4 << 30;
 const CODEX_AUTO_REVIEW_FALLBACKS: &[(&str, &str)] = &[
     ("2026-04-23", "gpt-5.5"),
     ("2026-03-05", "gpt-5.4"),
@@ -59,6 +85,89 @@ fn default_claude_config_path() -> PathBuf {
     PathBuf::from(".config/claude")
 }
 
+/// `%USERPROFILE%\.claude` and `%APPDATA%\claude`, which `dirs` can miss when the
+/// environment points somewhere other than the known-folder locations.
+fn windows_claude_paths<F>(var: F) -> Vec<PathBuf>
+where
+    F: Fn(&str) -> Option<std::ffi::OsString>,
+{
+    [
+        (WINDOWS_USER_PROFILE_ENV, DEFAULT_CLAUDE_CODE_PATH),
+        (WINDOWS_APP_DATA_ENV, WINDOWS_APP_DATA_CLAUDE_DIR_NAME),
+    ]
+    .into_iter()
+    .filter_map(|(name, child)| {
+        var(name)
+            .filter(|value| !value.is_empty())
+            .map(|value| PathBuf::from(value).join(child))
+    })
+    .collect()
+}
+
+fn default_claude_data_path() -> PathBuf {
+    if let Some(dir) = dirs::data_dir() {
+        return dir.join("claude");
+    }
+    if let Ok(home) = std::env::var("HOME") {
+        return PathBuf::from(home).join(".local/share/claude");
+    }
+    PathBuf::from(".local/share/claude")
+}
+
+/// Candidate Claude data roots in probe order when neither `--path` nor `CLAUDE_CONFIG_DIR`
+/// is set.
+pub fn default_claude_search_paths() -> Vec<PathBuf> {
+    let mut paths = vec![
+        default_claude_config_path(),
+        dirs::home_dir()
+            .unwrap_or_else(|| PathBuf::from("~"))
+            .join(DEFAULT_CLAUDE_CODE_PATH),
+        default_claude_data_path(),
+    ];
+    if cfg!(windows) {
+        paths.extend(windows_claude_paths(|name| std::env::var_os(name)));
+    }
+    paths
+}
+
+/// Claude Desktop's app data directory: `~/Library/Application Support/Claude`,
+/// `%APPDATA%\Claude`, or `~/.config/Claude`.
+fn default_claude_desktop_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join(CLAUDE_DESKTOP_DIR_NAME))
+}
+
+/// Claude Code-format data roots (directories with a `projects/` subdirectory) at or below
+/// `base`, searched [`CLAUDE_DESKTOP_SEARCH_DEPTH`] levels deep without following symlinks.
+/// Desktop keeps the logs of Claude Code sessions it runs in this layout; its chat history
+/// has no token usage.
+pub fn find_claude_desktop_roots(base: &Path) -> Vec<PathBuf> {
+    fn visit(dir: &Path, depth: usize, roots: &mut Vec<PathBuf>) {
+        if is_claude_data_root(dir) {
+            roots.push(dir.to_path_buf());
+            return;
+        }
+        if depth == 0 {
+            return;
+        }
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+        let mut children = entries
+            .flatten()
+            .filter(|entry| entry.file_type().is_ok_and(|file_type| file_type.is_dir()))
+            .map(|entry| entry.path())
+            .collect::<Vec<_>>();
+        children.sort();
+        for child in children {
+            visit(&child, depth - 1, roots);
+        }
+    }
+
+    let mut roots = Vec::new();
+    visit(base, CLAUDE_DESKTOP_SEARCH_DEPTH, &mut roots);
+    roots
+}
+
 fn default_codex_home_path() -> PathBuf {
     if let Ok(home) = std::env::var("HOME") {
         return PathBuf::from(home).join(DEFAULT_CODEX_PATH);
@@ -80,6 +189,7 @@ struct UsageMessageUsage {
     cache_creation_input_tokens: Option<u64>,
     cache_read_input_tokens: Option<u64>,
     cache_creation: Option<CacheCreationUsage>,
+    service_tier: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -118,11 +228,30 @@ struct UsageIteration {
     cache_creation: Option<CacheCreationUsage>,
 }
 
+#[derive(Debug, Clone, Deserialize)]
+struct ToolUseEnvelope {
+    message: ToolUseMessage,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ToolUseMessage {
+    #[serde(default)]
+    content: Vec<ContentBlock>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ContentBlock {
+    #[serde(rename = "type")]
+    kind: String,
+    id: Option<String>,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 struct UsageMessage {
     usage: Option<UsageMessageUsage>,
     model: Option<String>,
     id: Option<String>,
+    stop_reason: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -139,11 +268,29 @@ struct UsageData {
     session_id: Option<String>,
     #[serde(rename = "costUSD")]
     cost_usd: Option<f64>,
+    /// Spellings of `costUSD` written by other log producers. Kept as separate fields rather
+    /// than serde aliases so a line carrying two of them still parses.
+    #[serde(rename = "costUsd")]
+    cost_usd_camel_case: Option<f64>,
+    #[serde(rename = "cost_usd")]
+    cost_usd_snake_case: Option<f64>,
     #[serde(rename = "requestId")]
     request_id: Option<String>,
     request: Option<UsageRequest>,
     #[serde(rename = "isSidechain")]
     is_sidechain: Option<bool>,
+    /// Set on the placeholder message Claude Code logs when an API request fails.
+    #[serde(rename = "isApiErrorMessage")]
+    is_api_error_message: Option<bool>,
+}
+
+impl UsageData {
+    /// Logged cost under any of the known field names, `costUSD` first.
+    fn logged_cost_usd(&self) -> Option<f64> {
+        self.cost_usd
+            .or(self.cost_usd_camel_case)
+            .or(self.cost_usd_snake_case)
+    }
 }
 
 impl AgentProgressEntry {
@@ -154,9 +301,12 @@ impl AgentProgressEntry {
             version: None,
             session_id: None,
             cost_usd: self.data.message.cost_usd,
+            cost_usd_camel_case: None,
+            cost_usd_snake_case: None,
             request_id: self.data.message.request_id,
             request: None,
             is_sidechain: self.data.message.is_sidechain,
+            is_api_error_message: None,
         }
     }
 }
@@ -182,9 +332,41 @@ struct AgentProgressMessage {
     is_sidechain: Option<bool>,
 }
 
+/// Title line Claude Code writes into a session log for the conversation ending at `leaf_uuid`.
 #[derive(Debug, Clone, Deserialize)]
-struct TimestampOnly {
-    timestamp: Option<String>,
+#[serde(rename_all = "camelCase")]
+struct SessionSummaryLine {
+    summary: Option<String>,
+    leaf_uuid: Option<String>,
+}
+
+/// User line whose prompt titles a session that has no summary.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SessionPromptLine {
+    #[serde(default)]
+    is_meta: bool,
+    message: Option<SessionPromptMessage>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct SessionPromptMessage {
+    content: Option<SessionPromptContent>,
+}
+
+/// A typed prompt is a plain string; pasted images and tool results come as content blocks.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum SessionPromptContent {
+    Text(String),
+    Blocks(Vec<SessionPromptBlock>),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct SessionPromptBlock {
+    #[serde(rename = "type")]
+    kind: Option<String>,
+    text: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -241,6 +423,8 @@ struct CodexRawUsage {
 #[derive(Debug, Clone, Deserialize)]
 struct OpenCodeMessage {
     id: Option<String>,
+    #[serde(rename = "sessionID")]
+    session_id: Option<String>,
     role: Option<String>,
     #[serde(rename = "providerID")]
     provider_id: Option<String>,
@@ -280,7 +464,7 @@ pub struct ModelBreakdown {
     pub cost: f64,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct DailyUsage {
     pub date: String,
     pub input_tokens: u64,
@@ -292,9 +476,29 @@ pub struct DailyUsage {
     pub models_used: Vec<String>,
     pub model_breakdowns: Vec<ModelBreakdown>,
     pub project: Option<String>,
+    pub message_count: u64,
+    pub subagent_cost: f64,
+    pub tool_calls: u64,
+    /// Failed API requests (Claude Code only).
+    pub api_errors: u64,
+    /// Entries whose cost was read from the log (`costUSD`) rather than calculated.
+    pub logged_costs: u64,
+    /// Distinct sessions with usage in the row, sorted.
+    pub session_ids: Vec<String>,
+    pub first_timestamp: Option<DateTime<Utc>>,
+    pub last_timestamp: Option<DateTime<Utc>>,
+    /// Bit `n` is set when local hour `n` of the day had any usage.
+    pub hour_mask: u32,
 }
 
-#[derive(Debug, Clone)]
+impl DailyUsage {
+    /// Number of distinct hours of the day with any usage.
+    pub fn active_hours(&self) -> u32 {
+        self.hour_mask.count_ones()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
 pub struct MonthlyUsage {
     pub month: String,
     pub input_tokens: u64,
@@ -306,6 +510,15 @@ pub struct MonthlyUsage {
     pub models_used: Vec<String>,
     pub model_breakdowns: Vec<ModelBreakdown>,
     pub project: Option<String>,
+    pub message_count: u64,
+    pub subagent_cost: f64,
+    pub tool_calls: u64,
+    pub api_errors: u64,
+    pub logged_costs: u64,
+    pub session_ids: Vec<String>,
+    pub first_timestamp: Option<DateTime<Utc>>,
+    pub last_timestamp: Option<DateTime<Utc>>,
+    pub active_hours: u32,
 }
 
 #[derive(Debug, Clone)]
@@ -342,6 +555,16 @@ struct Aggregate {
     models_used: Vec<String>,
     models_used_seen: HashSet<String>,
     model_breakdowns: HashMap<String, TokenStats>,
+    message_count: u64,
+    subagent_cost: f64,
+    tool_calls: u64,
+    api_errors: u64,
+    logged_costs: u64,
+    session_ids: HashSet<String>,
+    first_timestamp: Option<DateTime<Utc>>,
+    last_timestamp: Option<DateTime<Utc>>,
+    hour_mask: u32,
+    active_hours: u32,
 }
 
 impl Default for Aggregate {
@@ -356,54 +579,435 @@ impl Default for Aggregate {
             models_used: Vec::new(),
             models_used_seen: HashSet::new(),
             model_breakdowns: HashMap::new(),
+            message_count: 0,
+            subagent_cost: 0.0,
+            tool_calls: 0,
+            api_errors: 0,
+            logged_costs: 0,
+            session_ids: HashSet::new(),
+            first_timestamp: None,
+            last_timestamp: None,
+            hour_mask: 0,
+            active_hours: 0,
         }
     }
 }
 
 impl Aggregate {
     fn push_model(&mut self, model: &str) {
-        let owned = model.to_string();
-        if self.models_used_seen.insert(owned.clone()) {
-            self.models_used.push(owned);
+        if !self.models_used_seen.contains(model) {
+            self.models_used_seen.insert(model.to_string());
+            self.models_used.push(model.to_string());
         }
     }
+
+    /// Adds `other`, the aggregate of records that come after this one's.
+    fn merge(&mut self, other: Aggregate) {
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.cache_creation_tokens += other.cache_creation_tokens;
+        self.cache_read_tokens += other.cache_read_tokens;
+        self.total_tokens += other.total_tokens;
+        self.total_cost += other.total_cost;
+        for model in &other.models_used {
+            self.push_model(model);
+        }
+        for (model, stats) in other.model_breakdowns {
+            let entry = self.model_breakdowns.entry(model).or_default();
+            entry.input_tokens += stats.input_tokens;
+            entry.output_tokens += stats.output_tokens;
+            entry.cache_creation_tokens += stats.cache_creation_tokens;
+            entry.cache_read_tokens += stats.cache_read_tokens;
+            entry.total_tokens += stats.total_tokens;
+            entry.cost += stats.cost;
+        }
+        self.message_count += other.message_count;
+        self.subagent_cost += other.subagent_cost;
+        self.tool_calls += other.tool_calls;
+        self.api_errors += other.api_errors;
+        self.logged_costs += other.logged_costs;
+        self.session_ids.extend(other.session_ids);
+        widen_activity(
+            &mut self.first_timestamp,
+            &mut self.last_timestamp,
+            other.first_timestamp,
+            other.last_timestamp,
+        );
+        self.hour_mask |= other.hour_mask;
+        self.active_hours += other.active_hours;
+    }
+}
+
+/// How records that share a dedupe key (Claude message/request id, Codex/OpenCode hash) are
+/// resolved.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum DedupPolicy {
+    /// Keep the most complete copy: main thread over sidechain, then the most tokens
+    #[default]
+    Auto,
+    /// Count every record, including duplicates
+    Off,
+    /// Keep the copy read first (files are read oldest first)
+    KeepFirst,
+    /// Keep the copy read last
+    #[value(alias = "keep-last")]
+    KeepLatest,
 }
 
 #[derive(Debug, Clone)]
 pub struct LoadOptions {
-    pub claude_path: Option<PathBuf>,
+    /// Claude data roots to scan instead of the default locations.
+    pub claude_paths: Vec<PathBuf>,
+    /// Candidate roots probed in order when no explicit roots are given; empty uses
+    /// [`default_claude_search_paths`].
+    pub claude_search_paths: Vec<PathBuf>,
+    /// Report which data roots were checked and which matched on stderr.
+    pub verbose: bool,
     pub codex_path: Option<PathBuf>,
     pub opencode_path: Option<PathBuf>,
     pub mode: CostMode,
     pub order: SortOrder,
-    pub offline: bool,
     pub codex: bool,
     pub claudecode: bool,
     pub opencode: bool,
     pub group_by_project: bool,
     pub project: Option<String>,
+    pub session: Option<String>,
     pub since: Option<String>,
     pub until: Option<String>,
     pub timezone: Option<String>,
+    pub dedup: DedupPolicy,
+    /// Drop Claude Code's placeholder entries for failed API requests.
+    pub skip_errors: bool,
+    /// Extra Claude log field holding the request cost, read when none of the known cost
+    /// fields is present.
+    pub cost_field: Option<String>,
+    /// Also treat Claude records as duplicates when they lack a request id but match on
+    /// message id, timestamp, and token counts.
+    pub fallback_dedup: bool,
+    /// Keep Claude Code's `<synthetic>` placeholder model in models and breakdowns.
+    pub include_synthetic: bool,
+    /// Traverse symlinked directories and files while discovering usage logs.
+    pub follow_symlinks: bool,
+    /// Files and directories skipped while scanning, relative to each scan root.
+    pub ignore: IgnorePatterns,
+    /// Models whose usage is kept; applied to each record after dedup, so every report built
+    /// from the records agrees.
+    pub models: ModelFilter,
+    pub read_limits: ReadLimits,
+    /// Also scan Claude Desktop's app data directory for Claude Code-format logs when the
+    /// Claude roots come from the default search rather than `--path`.
+    pub claude_desktop: bool,
+    /// Root written by `ccost import`, scanned alongside the Claude data roots when present.
+    pub import_path: Option<PathBuf>,
+    /// Root where `ccost archive --remove` keeps the usage lines of removed logs, scanned like
+    /// `import_path`.
+    pub archive_path: Option<PathBuf>,
+    /// Parsed Claude log files kept between loads with these options.
+    pub parse_cache: Option<Arc<ParseCache>>,
+    /// SQLite file where parsed Claude log files are kept between runs; `None` parses every
+    /// file on every load.
+    pub cache_path: Option<PathBuf>,
+    /// Parse every Claude and Codex log even when `since`/`until` rule it out by its
+    /// modification time or first timestamp.
+    pub scan_all_files: bool,
+}
+
+/// Parsed Claude log files kept between loads, so repeated loads with the same options (as in
+/// `ccost live`) re-parse only the files whose size or modification time changed.
+#[derive(Default)]
+pub struct ParseCache {
+    files: Mutex<HashMap<PathBuf, CachedFile>>,
+}
+
+impl std::fmt::Debug for ParseCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ParseCache").finish_non_exhaustive()
+    }
+}
+
+struct CachedFile {
+    stamp: FileStamp,
+    with_projects: bool,
+    parsed: ParsedFileRecords,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FileStamp {
+    len: u64,
+    modified: Option<SystemTime>,
+}
+
+impl FileStamp {
+    fn of(file: &Path) -> Option<Self> {
+        let metadata = std::fs::metadata(file).ok()?;
+        Some(Self {
+            len: metadata.len(),
+            modified: metadata.modified().ok(),
+        })
+    }
+
+    fn modified_nanos(&self) -> Option<i64> {
+        let since_epoch = self.modified?.duration_since(UNIX_EPOCH).ok()?;
+        i64::try_from(since_epoch.as_nanos()).ok()
+    }
+}
+
+impl ParseCache {
+    fn get(&self, file: &Path, stamp: FileStamp, with_projects: bool) -> Option<ParsedFileRecords> {
+        let files = self.files.lock().ok()?;
+        files
+            .get(file)
+            .filter(|cached| cached.stamp == stamp && cached.with_projects == with_projects)
+            .map(|cached| cached.parsed.clone())
+    }
+
+    fn insert(&self, stamp: FileStamp, with_projects: bool, parsed: &ParsedFileRecords) {
+        if let Ok(mut files) = self.files.lock() {
+            files.insert(
+                parsed.file.clone(),
+                CachedFile {
+                    stamp,
+                    with_projects,
+                    parsed: parsed.clone(),
+                },
+            );
+        }
+    }
+
+    /// Forgets files that are no longer part of the scan, e.g. deleted logs.
+    fn retain(&self, keep: &HashSet<&Path>) {
+        if let Ok(mut files) = self.files.lock() {
+            files.retain(|file, _| keep.contains(file.as_path()));
+        }
+    }
+
+    /// Number of files currently cached.
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.files.lock().map_or(0, |files| files.len())
+    }
+}
+
+/// Parsed Claude log files kept on disk between runs (see [`LoadOptions::cache_path`]), one
+/// row per log file. A row is reused only while its file has the same size and modification
+/// time and was parsed by this ccost version with the same [`parse_fingerprint`]; anything else
+/// is parsed again and its row replaced. Rows are looked up one file at a time, so logs a run
+/// doesn't scan are never read from the cache.
+struct DiskCache {
+    connection: Mutex<Connection>,
+    fingerprint: String,
+}
+
+impl DiskCache {
+    fn open(path: &Path, fingerprint: String) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let connection = Connection::open(path)?;
+        connection.busy_timeout(Duration::from_secs(2))?;
+        connection.execute_batch(
+            "CREATE TABLE IF NOT EXISTS files (
+                path TEXT PRIMARY KEY,
+                version TEXT NOT NULL,
+                fingerprint TEXT NOT NULL,
+                len INTEGER NOT NULL,
+                modified_nanos INTEGER,
+                earliest_timestamp TEXT,
+                records TEXT NOT NULL
+            )",
+        )?;
+        let version = env!("CARGO_PKG_VERSION");
+        connection.execute("DELETE FROM files WHERE version != ?1", params![version])?;
+        Ok(Self {
+            connection: Mutex::new(connection),
+            fingerprint,
+        })
+    }
+
+    /// The earliest timestamp and serialized records stored for `file`, if it was parsed with
+    /// the same options and hasn't changed since.
+    fn stored(&self, file: &Path, stamp: FileStamp) -> Option<(Option<String>, String)> {
+        let path = file.to_str()?;
+        let connection = self.connection.lock().ok()?;
+        let mut select = connection
+            .prepare_cached(
+                "SELECT earliest_timestamp, records FROM files
+                 WHERE path = ?1 AND fingerprint = ?2 AND len = ?3 AND modified_nanos IS ?4",
+            )
+            .ok()?;
+        select
+            .query_row(
+                params![
+                    path,
+                    self.fingerprint,
+                    stamp.len as i64,
+                    stamp.modified_nanos()
+                ],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .ok()
+    }
+
+    /// The stored records of `file`, with the fields shared by the whole file filled in.
+    fn get(
+        &self,
+        file: &Path,
+        stamp: FileStamp,
+        project: Option<&Arc<str>>,
+        data_dir: Option<&Arc<Path>>,
+    ) -> Option<ParsedFileRecords> {
+        let (earliest_timestamp, records) = self.stored(file, stamp)?;
+        let mut records = serde_json::from_str::<Vec<ParsedRecord>>(&records).ok()?;
+        let source_file: Arc<Path> = Arc::from(file);
+        for record in &mut records {
+            record.project = project.cloned();
+            record.source_file = Some(source_file.clone());
+            record.data_dir = data_dir.cloned();
+        }
+        Some(ParsedFileRecords {
+            file: file.to_path_buf(),
+            earliest_timestamp: earliest_timestamp
+                .and_then(|timestamp| DateTime::parse_from_rfc3339(&timestamp).ok())
+                .map(|timestamp| timestamp.with_timezone(&Utc)),
+            records,
+        })
+    }
+
+    /// Saves freshly parsed files and drops the rows of log files that no longer exist, so the
+    /// cache never outgrows the logs on disk.
+    fn store(&self, parsed: &[(FileStamp, &ParsedFileRecords)]) -> Result<()> {
+        let mut connection = self
+            .connection
+            .lock()
+            .map_err(|_| anyhow!("parse cache lock poisoned"))?;
+        let transaction = connection.transaction()?;
+        {
+            let mut insert = transaction.prepare(
+                "INSERT OR REPLACE INTO files
+                 (path, version, fingerprint, len, modified_nanos, earliest_timestamp, records)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            )?;
+            for (stamp, file) in parsed {
+                let Some(path) = file.file.to_str() else {
+                    continue;
+                };
+                insert.execute(params![
+                    path,
+                    env!("CARGO_PKG_VERSION"),
+                    self.fingerprint,
+                    stamp.len as i64,
+                    stamp.modified_nanos(),
+                    file.earliest_timestamp
+                        .map(|timestamp| timestamp.to_rfc3339()),
+                    serde_json::to_string(&file.records)?,
+                ])?;
+            }
+            let paths = transaction
+                .prepare("SELECT path FROM files")?
+                .query_map([], |row| row.get::<_, String>(0))?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            let mut delete = transaction.prepare("DELETE FROM files WHERE path = ?1")?;
+            for path in paths.iter().filter(|path| !Path::new(path).exists()) {
+                delete.execute(params![path])?;
+            }
+        }
+        transaction.commit()?;
+        Ok(())
+    }
+}
+
+/// Options that change what parsing a Claude log produces; rows stored under other options
+/// are not reused.
+fn parse_fingerprint(options: &LoadOptions, timezone: Option<Tz>) -> String {
+    let timezone = match timezone {
+        Some(timezone) => timezone.name().to_string(),
+        None => format!("local{}", chrono::Local::now().offset()),
+    };
+    format!(
+        "{timezone}|{:?}|{}|{:?}|{}|{}|{}|{}",
+        options.mode,
+        options.skip_errors,
+        options.cost_field,
+        options.include_synthetic,
+        options.read_limits.max_line_bytes,
+        options.read_limits.max_file_bytes,
+        pricing_overrides_key(),
+    )
+}
+
+/// Upper bounds for a single JSONL line and file; anything larger is skipped with a warning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReadLimits {
+    pub max_line_bytes: u64,
+    pub max_file_bytes: u64,
+}
+
+impl Default for ReadLimits {
+    fn default() -> Self {
+        Self {
+            max_line_bytes: DEFAULT_MAX_LINE_BYTES,
+            max_file_bytes: DEFAULT_MAX_FILE_BYTES,
+        }
+    }
+}
+
+/// Parses a byte size such as `512`, `64K`, `16MB`, or `2GiB` (binary units).
+pub fn parse_byte_size(value: &str) -> Result<u64> {
+    let trimmed = value.trim();
+    let split = trimmed
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(trimmed.len());
+    let (digits, unit) = trimmed.split_at(split);
+    let number = digits
+        .parse::<u64>()
+        .map_err(|_| anyhow!("Invalid size: {value}"))?;
+    let multiplier: u64 = match unit.trim().to_ascii_uppercase().as_str() {
+        "" | "B" => 1,
+        "K" | "KB" | "KIB" => 1 << 10,
+        "M" | "MB" | "MIB" => 1 << 20,
+        "G" | "GB" | "GIB" => 1 << 30,
+        _ => return Err(anyhow!("Invalid size: {value}")),
+    };
+    number
+        .checked_mul(multiplier)
+        .ok_or_else(|| anyhow!("Invalid size: {value}"))
 }
 
 impl Default for LoadOptions {
     fn default() -> Self {
         Self {
-            claude_path: None,
+            claude_paths: Vec::new(),
+            claude_search_paths: Vec::new(),
+            verbose: false,
             codex_path: None,
             opencode_path: None,
             mode: CostMode::Auto,
             order: SortOrder::Desc,
-            offline: true,
             codex: false,
             claudecode: true,
             opencode: false,
             group_by_project: false,
             project: None,
+            session: None,
             since: None,
             until: None,
             timezone: None,
+            dedup: DedupPolicy::Auto,
+            skip_errors: false,
+            cost_field: None,
+            fallback_dedup: false,
+            include_synthetic: false,
+            follow_symlinks: true,
+            ignore: IgnorePatterns::default(),
+            models: ModelFilter::default(),
+            read_limits: ReadLimits::default(),
+            claude_desktop: true,
+            import_path: None,
+            archive_path: None,
+            parse_cache: None,
+            cache_path: None,
+            scan_all_files: false,
         }
     }
 }
@@ -416,20 +1020,49 @@ pub struct GlobResult {
 type GroupKey = (String, Option<Arc<str>>);
 type MonthKey = (String, Option<String>);
 
-#[derive(Clone)]
+/// One usage record parsed from a log. The fields shared by every record of a file are left
+/// out of the on-disk cache and filled in from the file when it is read back.
+#[derive(Clone, Serialize, Deserialize)]
 struct ParsedRecord {
     unique_hash: Option<String>,
     message_id: Option<String>,
     request_id: Option<String>,
     is_sidechain: Option<bool>,
+    timestamp: String,
+    session_id: Option<String>,
     date: String,
+    #[serde(skip)]
     project: Option<Arc<str>>,
-    model: Option<String>,
+    /// Shared between the records of a file that name the same model.
+    model: Option<Arc<str>>,
     tokens: UsageTokens,
     total_tokens: u64,
     cost: f64,
+    /// Ids of `tool_use` content blocks; Claude streams one block per line, so these are
+    /// merged across the lines of a deduplicated message.
+    tool_use_ids: Vec<String>,
+    /// Placeholder for a failed API request rather than a response.
+    is_api_error: bool,
+    /// Whether `cost` was read from the log instead of calculated from tokens.
+    cost_logged: bool,
+    /// API service tier, e.g. `standard`, `priority`, or `batch`.
+    service_tier: Option<String>,
+    /// Why generation ended, e.g. `end_turn` or `max_tokens`. Only the last streamed line of
+    /// a message has it, so it is carried over when a duplicate replaces the record.
+    stop_reason: Option<String>,
+    /// File the record was parsed from.
+    #[serde(skip)]
+    source_file: Option<Arc<Path>>,
+    /// 1-based line in `source_file`; `None` for OpenCode, which stores one message per file
+    /// or database row.
+    source_line: Option<usize>,
+    /// Data directory the file was found under: the Claude data root, Codex home, or OpenCode
+    /// directory.
+    #[serde(skip)]
+    data_dir: Option<Arc<Path>>,
 }
 
+#[derive(Clone)]
 struct ParsedFileRecords {
     file: PathBuf,
     earliest_timestamp: Option<DateTime<Utc>>,
@@ -481,10 +1114,25 @@ fn update_earliest_timestamp_from_line(line: &[u8], earliest: &mut Option<DateTi
 }
 
 pub fn get_claude_paths() -> Result<Vec<PathBuf>> {
+    find_claude_paths(&default_claude_search_paths(), false)
+}
+
+/// Resolves Claude data roots from `CLAUDE_CONFIG_DIR`, or else by probing `candidates` in
+/// order. With `verbose`, every checked directory is reported on stderr.
+pub fn find_claude_paths(candidates: &[PathBuf], verbose: bool) -> Result<Vec<PathBuf>> {
+    let env_paths = std::env::var(CLAUDE_CONFIG_DIR_ENV).ok();
+    resolve_claude_paths(env_paths.as_deref(), candidates, verbose)
+}
+
+fn resolve_claude_paths(
+    env_paths: Option<&str>,
+    candidates: &[PathBuf],
+    verbose: bool,
+) -> Result<Vec<PathBuf>> {
     let mut paths = Vec::new();
     let mut seen = HashSet::new();
 
-    if let Ok(env_paths) = std::env::var(CLAUDE_CONFIG_DIR_ENV) {
+    if let Some(env_paths) = env_paths {
         let env_paths = env_paths.trim();
         if !env_paths.is_empty() {
             for raw in env_paths.split(',') {
@@ -493,7 +1141,11 @@ pub fn get_claude_paths() -> Result<Vec<PathBuf>> {
                     continue;
                 }
                 let base = PathBuf::from(trimmed);
-                if base.is_dir() && base.join(CLAUDE_PROJECTS_DIR_NAME).is_dir() {
+                let found = is_claude_data_root(&base);
+                if verbose {
+                    report_claude_root(&base, found, CLAUDE_CONFIG_DIR_ENV);
+                }
+                if found {
                     let normalized = base.canonicalize().unwrap_or(base.clone());
                     if seen.insert(normalized.clone()) {
                         paths.push(normalized);
@@ -509,15 +1161,12 @@ pub fn get_claude_paths() -> Result<Vec<PathBuf>> {
         }
     }
 
-    let defaults = vec![
-        default_claude_config_path(),
-        dirs::home_dir()
-            .unwrap_or_else(|| PathBuf::from("~"))
-            .join(DEFAULT_CLAUDE_CODE_PATH),
-    ];
-
-    for base in defaults {
-        if base.is_dir() && base.join(CLAUDE_PROJECTS_DIR_NAME).is_dir() {
+    for base in candidates {
+        let found = is_claude_data_root(base);
+        if verbose {
+            report_claude_root(base, found, "search path");
+        }
+        if found {
             let normalized = base.canonicalize().unwrap_or(base.clone());
             if seen.insert(normalized.clone()) {
                 paths.push(normalized);
@@ -526,22 +1175,52 @@ pub fn get_claude_paths() -> Result<Vec<PathBuf>> {
     }
 
     if paths.is_empty() {
-        return Err(anyhow!("No valid Claude data directories found"));
+        return Err(coded_error(
+            ErrorCode::NoDataDirectory,
+            "No valid Claude data directories found",
+        ));
     }
 
     Ok(paths)
 }
 
+fn is_claude_data_root(base: &Path) -> bool {
+    base.is_dir() && base.join(CLAUDE_PROJECTS_DIR_NAME).is_dir()
+}
+
+fn report_claude_root(base: &Path, found: bool, source: &str) {
+    let status = if found {
+        "found"
+    } else {
+        "no projects/ directory"
+    };
+    eprintln!("Checked {} ({source}): {status}", base.display());
+}
+
+/// Canonicalizes `roots` and drops repeats, so overlapping mounts or links are scanned once.
+fn dedupe_roots(roots: &[PathBuf]) -> Vec<PathBuf> {
+    let mut seen = HashSet::new();
+    roots
+        .iter()
+        .map(|root| root.canonicalize().unwrap_or_else(|_| root.clone()))
+        .filter(|root| seen.insert(root.clone()))
+        .collect()
+}
+
 fn parse_file_records(
     file: &Path,
     project: Option<Arc<str>>,
+    data_dir: Option<Arc<Path>>,
     timezone: Option<chrono_tz::Tz>,
     options: &LoadOptions,
     pricing: Option<&PricingFetcher>,
 ) -> Result<ParsedFileRecords> {
     let mut records = Vec::new();
     let mut earliest_timestamp: Option<DateTime<Utc>> = None;
-    process_jsonl_file_by_line_bytes(file, |line, _| {
+    let file_session_id = session_id_from_path(file);
+    let source_file: Arc<Path> = Arc::from(file);
+    let mut models = HashSet::new();
+    process_jsonl_file_by_line_bytes(file, options.read_limits, |line, line_number| {
         update_earliest_timestamp_from_line(line, &mut earliest_timestamp);
 
         if !line_contains_any_marker(line, &[USAGE_FIELD_MARKER]) {
@@ -562,6 +1241,10 @@ fn parse_file_records(
         if !is_valid_usage_data(&parsed) {
             return Ok(());
         }
+        let is_api_error = parsed.is_api_error_message == Some(true);
+        if is_api_error && options.skip_errors {
+            return Ok(());
+        }
         let timestamp = match parsed.timestamp.as_deref() {
             Some(ts) => ts,
             None => return Ok(()),
@@ -575,35 +1258,69 @@ fn parse_file_records(
             Some(message) => message,
             None => return Ok(()),
         };
+        let session_id = parsed
+            .session_id
+            .clone()
+            .or_else(|| file_session_id.clone());
+        let service_tier = message
+            .usage
+            .as_ref()
+            .and_then(|usage| usage.service_tier.clone());
+        let tool_use_ids = if line_contains_any_marker(line, &[TOOL_USE_MARKER]) {
+            extract_tool_use_ids(line)
+        } else {
+            Vec::new()
+        };
         let advisor_usages = if line_contains_any_marker(line, &[ADVISOR_MESSAGE_MARKER]) {
             extract_advisor_usages(line)
         } else {
             Vec::new()
         };
         if let Some((tokens, cache_creation)) = extract_usage_tokens_with_cache_creation(message) {
+            let logged_cost = parsed.logged_cost_usd().or_else(|| {
+                options
+                    .cost_field
+                    .as_deref()
+                    .and_then(|field| extract_cost_field(line, field))
+            });
             let cost = calculate_cost_for_usage(
                 message.model.as_deref(),
                 &tokens,
                 cache_creation.as_ref(),
-                parsed.cost_usd,
+                logged_cost,
                 options.mode,
                 pricing,
             );
             let total_tokens = total_tokens_from_usage(&tokens);
-            let model = message.model.clone();
+            let model = message
+                .model
+                .as_deref()
+                .map(|model| intern(&mut models, model));
 
-            if total_tokens > 0 || !advisor_usages.is_empty() {
+            let keep_synthetic =
+                options.include_synthetic && model.as_deref() == Some(SYNTHETIC_MODEL);
+            if total_tokens > 0 || !advisor_usages.is_empty() || keep_synthetic || is_api_error {
                 records.push(ParsedRecord {
                     unique_hash,
                     message_id: message_id.clone(),
                     request_id: request_id.clone(),
                     is_sidechain: parsed.is_sidechain,
+                    timestamp: timestamp.to_string(),
+                    session_id: session_id.clone(),
                     date: date.clone(),
                     project: project.clone(),
                     model,
                     tokens,
                     total_tokens,
                     cost,
+                    tool_use_ids,
+                    is_api_error,
+                    cost_logged: logged_cost.is_some() && options.mode != CostMode::Calculate,
+                    service_tier: service_tier.clone(),
+                    stop_reason: message.stop_reason.clone(),
+                    source_file: Some(source_file.clone()),
+                    source_line: Some(line_number),
+                    data_dir: data_dir.clone(),
                 });
             }
         }
@@ -625,12 +1342,22 @@ fn parse_file_records(
                     .map(|message_id| format!("{message_id}:advisor:{index}")),
                 request_id: request_id.clone(),
                 is_sidechain: parsed.is_sidechain,
+                timestamp: timestamp.to_string(),
+                session_id: session_id.clone(),
                 date: date.clone(),
                 project: project.clone(),
-                model: Some(model),
+                model: Some(intern(&mut models, &model)),
                 tokens,
                 total_tokens,
                 cost,
+                tool_use_ids: Vec::new(),
+                is_api_error: false,
+                cost_logged: false,
+                service_tier: service_tier.clone(),
+                stop_reason: None,
+                source_file: Some(source_file.clone()),
+                source_line: Some(line_number),
+                data_dir: data_dir.clone(),
             });
         }
 
@@ -643,6 +1370,16 @@ fn parse_file_records(
     })
 }
 
+/// `name` from `names`, added on first use, so records share one allocation per name.
+fn intern(names: &mut HashSet<Arc<str>>, name: &str) -> Arc<str> {
+    if let Some(name) = names.get(name) {
+        return name.clone();
+    }
+    let name = Arc::<str>::from(name);
+    names.insert(name.clone());
+    name
+}
+
 fn compare_parsed_file_records(a: &ParsedFileRecords, b: &ParsedFileRecords) -> std::cmp::Ordering {
     match (&a.earliest_timestamp, &b.earliest_timestamp) {
         (Some(a_ts), Some(b_ts)) => a_ts.cmp(b_ts).then_with(|| a.file.cmp(&b.file)),
@@ -652,46 +1389,225 @@ fn compare_parsed_file_records(a: &ParsedFileRecords, b: &ParsedFileRecords) ->
     }
 }
 
-pub fn extract_project_from_path(path: &Path) -> String {
-    let mut found_projects = false;
-    for component in path.components() {
-        let value = component.as_os_str().to_string_lossy();
-        if found_projects {
-            return if value.trim().is_empty() {
-                "unknown".to_string()
-            } else {
-                value.to_string()
-            };
+/// Lines read for a file's first timestamp before giving up; Claude logs can open with a few
+/// summary lines that have none.
+const FIRST_TIMESTAMP_MAX_LINES: usize = 32;
+
+/// Instants a log must have activity in to hold entries dated within `since..=until` in any
+/// timezone, widened for clock skew and lines logged slightly out of order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FileTimeWindow {
+    start: Option<DateTime<Utc>>,
+    end: Option<DateTime<Utc>>,
+}
+
+impl FileTimeWindow {
+    /// `None` without a date range or when every file is to be scanned.
+    fn from_options(options: &LoadOptions) -> Option<Self> {
+        if options.scan_all_files {
+            return None;
+        }
+        let midnight = |date: &str| {
+            NaiveDate::parse_from_str(date, "%Y%m%d")
+                .ok()
+                .map(|date| date.and_time(chrono::NaiveTime::MIN).and_utc())
+        };
+        // Local dates run from UTC-12 to UTC+14, plus a day of slack either side.
+        let start = options
+            .since
+            .as_deref()
+            .and_then(midnight)
+            .map(|since| since - chrono::TimeDelta::days(2));
+        let end = options
+            .until
+            .as_deref()
+            .and_then(midnight)
+            .map(|until| until + chrono::TimeDelta::days(3));
+        (start.is_some() || end.is_some()).then_some(Self { start, end })
+    }
+
+    /// Whether `file` can't hold entries in the window: it was last written before the window
+    /// opens, or its first timestamp comes after the window closes. Logs are appended in
+    /// order, and resumed sessions start with the messages they copy, so both bound every
+    /// entry in the file.
+    fn excludes(&self, file: &Path, limits: ReadLimits) -> bool {
+        if let Some(start) = self.start
+            && let Some(modified) = FileStamp::of(file).and_then(|stamp| stamp.modified)
+            && DateTime::<Utc>::from(modified) < start
+        {
+            return true;
+        }
+        self.end.is_some_and(|end| {
+            first_timestamp(file, limits.max_line_bytes).is_some_and(|first| first > end)
+        })
+    }
+}
+
+/// Timestamp of the first of the opening lines of `file` that has one.
+fn first_timestamp(file: &Path, max_line_bytes: u64) -> Option<DateTime<Utc>> {
+    let mut reader = BufReader::new(File::open(file).ok()?);
+    let mut line = Vec::new();
+    for _ in 0..FIRST_TIMESTAMP_MAX_LINES {
+        match read_line_bounded(&mut reader, &mut line, max_line_bytes).ok()? {
+            LineRead::Eof => return None,
+            LineRead::TooLong => continue,
+            LineRead::Line | LineRead::Unterminated => {}
         }
-        if value == CLAUDE_PROJECTS_DIR_NAME {
-            found_projects = true;
+        let mut timestamp = None;
+        update_earliest_timestamp_from_line(&line, &mut timestamp);
+        if timestamp.is_some() {
+            return timestamp;
         }
     }
-    "unknown".to_string()
+    None
+}
+
+/// `files` minus those [`FileTimeWindow`] rules out for the date range in `options`.
+fn skip_files_outside_range<T: Send>(
+    files: Vec<T>,
+    path: impl Fn(&T) -> &Path + Sync,
+    options: &LoadOptions,
+) -> Vec<T> {
+    let Some(window) = FileTimeWindow::from_options(options) else {
+        return files;
+    };
+    let total = files.len();
+    let files = files
+        .into_par_iter()
+        .filter(|file| !window.excludes(path(file), options.read_limits))
+        .collect::<Vec<_>>();
+    if options.verbose && files.len() < total {
+        eprintln!(
+            "Skipped {} of {total} log files outside the date range",
+            total - files.len()
+        );
+    }
+    files
+}
+
+/// Claude stores a session as `<session>.jsonl` plus an optional `<session>/` directory of
+/// subagent logs, and Codex embeds the session id in its rollout file names.
+fn path_mentions_session(path: &Path, session: &str) -> bool {
+    path.components()
+        .any(|component| component.as_os_str().to_string_lossy().contains(session))
+}
+
+fn session_id_from_path(path: &Path) -> Option<String> {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .filter(|stem| !stem.is_empty())
+}
+
+pub fn extract_project_from_path(path: &Path) -> String {
+    // Split on both separators so Windows-style paths resolve on every platform.
+    let path = path.to_string_lossy();
+    let mut segments = path
+        .split(['/', '\\'])
+        .filter(|segment| !segment.is_empty());
+    if !segments.any(|segment| segment == CLAUDE_PROJECTS_DIR_NAME) {
+        return "unknown".to_string();
+    }
+    match segments.next() {
+        Some(value) if !value.trim().is_empty() => value.to_string(),
+        _ => "unknown".to_string(),
+    }
 }
 
-pub fn process_jsonl_file_by_line<F>(file_path: &Path, mut process_line: F) -> Result<()>
+#[cfg(test)]
+fn process_jsonl_file_by_line<F>(
+    file_path: &Path,
+    limits: ReadLimits,
+    mut process_line: F,
+) -> Result<()>
 where
     F: FnMut(&str, usize) -> Result<()> + Send,
 {
-    let file = File::open(file_path)?;
-    let mut reader = BufReader::with_capacity(64 * 1024, file);
-    let mut line = String::new();
-    let mut line_number = 0;
+    process_jsonl_file_by_line_bytes(file_path, limits, |line, line_number| {
+        let line = String::from_utf8_lossy(line);
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            return Ok(());
+        }
+        process_line(trimmed, line_number)
+    })
+}
+
+enum LineRead {
+    Eof,
+    Line,
+    /// The file ended without a newline after this line, e.g. while it is still being written.
+    Unterminated,
+    TooLong,
+}
+
+/// Reads the next line into `line` without buffering more than `max_len` bytes of it; longer
+/// lines are consumed and reported as `TooLong` with `line` left empty.
+fn read_line_bounded<R: BufRead>(
+    reader: &mut R,
+    line: &mut Vec<u8>,
+    max_len: u64,
+) -> std::io::Result<LineRead> {
+    line.clear();
+    let mut read_any = false;
+    let mut too_long = false;
+    let mut terminated = false;
     loop {
-        line.clear();
-        let bytes = reader.read_line(&mut line)?;
-        if bytes == 0 {
+        let available = match reader.fill_buf() {
+            Ok(available) => available,
+            Err(error) if error.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(error) => return Err(error),
+        };
+        if available.is_empty() {
             break;
         }
-        line_number += 1;
-        let trimmed = line.trim();
-        if trimmed.is_empty() {
-            continue;
+        read_any = true;
+        let (chunk, complete) = match memchr(b'\n', available) {
+            Some(index) => (&available[..index], true),
+            None => (available, false),
+        };
+        if !too_long {
+            if (line.len() + chunk.len()) as u64 > max_len {
+                too_long = true;
+                line.clear();
+            } else {
+                line.extend_from_slice(chunk);
+            }
+        }
+        let consumed = chunk.len() + usize::from(complete);
+        reader.consume(consumed);
+        if complete {
+            terminated = true;
+            break;
         }
-        process_line(trimmed, line_number)?;
     }
-    Ok(())
+    Ok(match (read_any, too_long, terminated) {
+        (false, _, _) => LineRead::Eof,
+        (true, true, _) => LineRead::TooLong,
+        (true, false, true) => LineRead::Line,
+        (true, false, false) => LineRead::Unterminated,
+    })
+}
+
+/// Whether a final line without a newline holds a whole JSON value rather than a record that is
+/// still being appended; truncated lines are skipped silently.
+fn is_complete_json(line: &[u8]) -> bool {
+    let line = String::from_utf8_lossy(trim_ascii_whitespace(line));
+    sonic_rs::from_str::<serde::de::IgnoredAny>(&line).is_ok()
+}
+
+/// Opens `file_path` for line reading unless it exceeds `limits.max_file_bytes`.
+fn open_jsonl_reader(file_path: &Path, limits: ReadLimits) -> Result<Option<BufReader<File>>> {
+    let file = File::open(file_path)?;
+    let size = file.metadata()?.len();
+    if size > limits.max_file_bytes {
+        warnings::warn(format!(
+            "skipped {}: {size} bytes exceeds the {}-byte file size limit",
+            file_path.display(),
+            limits.max_file_bytes
+        ));
+        return Ok(None);
+    }
+    Ok(Some(BufReader::with_capacity(64 * 1024, file)))
 }
 
 fn trim_ascii_whitespace(bytes: &[u8]) -> &[u8] {
@@ -706,104 +1622,136 @@ fn trim_ascii_whitespace(bytes: &[u8]) -> &[u8] {
     &bytes[start..end]
 }
 
-pub fn process_jsonl_file_by_line_bytes<F>(file_path: &Path, mut process_line: F) -> Result<()>
+pub fn process_jsonl_file_by_line_bytes<F>(
+    file_path: &Path,
+    limits: ReadLimits,
+    mut process_line: F,
+) -> Result<()>
 where
     F: FnMut(&[u8], usize) -> Result<()> + Send,
 {
-    let file = File::open(file_path)?;
-    let mut reader = BufReader::with_capacity(64 * 1024, file);
+    let Some(mut reader) = open_jsonl_reader(file_path, limits)? else {
+        return Ok(());
+    };
     let mut line = Vec::new();
     let mut line_number = 0;
+    let mut invalid_utf8_lines = 0;
     loop {
-        line.clear();
-        let bytes = reader.read_until(b'\n', &mut line)?;
-        if bytes == 0 {
-            break;
-        }
+        let read = read_line_bounded(&mut reader, &mut line, limits.max_line_bytes)?;
         line_number += 1;
+        match read {
+            LineRead::Eof => break,
+            LineRead::TooLong => {
+                warnings::warn(format!(
+                    "skipped line {line_number} of {}: longer than the {}-byte line length limit",
+                    file_path.display(),
+                    limits.max_line_bytes
+                ));
+                continue;
+            }
+            LineRead::Line => {}
+            LineRead::Unterminated => {
+                if !is_complete_json(&line) {
+                    break;
+                }
+            }
+        }
         let trimmed = trim_ascii_whitespace(&line);
         if trimmed.is_empty() {
             continue;
         }
+        if std::str::from_utf8(trimmed).is_err() {
+            invalid_utf8_lines += 1;
+            let lossy = String::from_utf8_lossy(trimmed).into_owned();
+            process_line(lossy.as_bytes(), line_number)?;
+            continue;
+        }
         process_line(trimmed, line_number)?;
     }
+    if invalid_utf8_lines > 0 {
+        warnings::warn(format!(
+            "{}: {invalid_utf8_lines} line(s) contained invalid UTF-8 and were decoded lossily",
+            file_path.display()
+        ));
+    }
     Ok(())
 }
 
-pub fn get_earliest_timestamp(file_path: &Path) -> Option<DateTime<Utc>> {
-    let file = File::open(file_path).ok()?;
-    let mut reader = BufReader::with_capacity(64 * 1024, file);
-    let mut earliest: Option<DateTime<Utc>> = None;
-    let mut line = Vec::new();
-    loop {
-        line.clear();
-        let bytes = match reader.read_until(b'\n', &mut line) {
-            Ok(bytes) => bytes,
-            Err(_) => continue,
-        };
-        if bytes == 0 {
-            break;
-        }
-        let trimmed = trim_ascii_whitespace(&line);
-        if trimmed.is_empty() {
-            continue;
-        }
-        let parsed: Result<TimestampOnly, _> = sonic_rs::from_slice(trimmed);
-        if let Ok(parsed) = parsed
-            && let Some(ts) = parsed.timestamp.as_deref()
-            && let Ok(dt) = DateTime::parse_from_rfc3339(ts)
-        {
-            let utc = dt.with_timezone(&Utc);
-            earliest = match earliest {
-                Some(existing) if existing <= utc => Some(existing),
-                _ => Some(utc),
-            };
+/// Walks `root` for files with `extension`, pruning entries matched by `options.ignore`.
+///
+/// When following symlinks, jwalk reports links back to an ancestor as loop errors, which are
+/// skipped, and files reachable through several links are kept once by canonical path. Without
+/// following, symlinked files and directories are ignored.
+/// What makes two discovered paths the same file: the device and inode on Unix, so bind
+/// mounts and overlapping roots match, and the canonical path elsewhere.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum FileIdentity {
+    #[cfg(unix)]
+    Inode(u64, u64),
+    Path(PathBuf),
+}
+
+fn file_identity(file: &Path) -> FileIdentity {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        if let Ok(metadata) = std::fs::metadata(file) {
+            return FileIdentity::Inode(metadata.dev(), metadata.ino());
         }
     }
-    earliest
+    FileIdentity::Path(file.canonicalize().unwrap_or_else(|_| file.to_path_buf()))
 }
 
-pub fn sort_files_by_timestamp(files: Vec<PathBuf>) -> Vec<PathBuf> {
-    let mut files_with_ts: Vec<(PathBuf, Option<DateTime<Utc>>)> = files
-        .into_par_iter()
-        .map(|file| {
-            let ts = get_earliest_timestamp(&file);
-            (file, ts)
+/// Files under `root` with `extension`, skipping any whose identity is already in `seen`.
+fn walk_files(
+    root: &Path,
+    extension: &str,
+    options: &LoadOptions,
+    seen: &mut HashSet<FileIdentity>,
+) -> Vec<PathBuf> {
+    let follow_symlinks = options.follow_symlinks;
+    let mut walker = WalkDir::new(root)
+        .parallelism(jwalk::Parallelism::RayonNewPool(0))
+        .follow_links(follow_symlinks);
+    if !options.ignore.is_empty() {
+        let ignore = options.ignore.clone();
+        let root = root.to_path_buf();
+        walker = walker.process_read_dir(move |_, dir, _, children| {
+            let relative_dir = dir.strip_prefix(&root).unwrap_or(dir).to_path_buf();
+            children.retain(|child| match child {
+                Ok(entry) => !ignore.is_ignored(
+                    &relative_dir.join(&entry.file_name),
+                    entry.file_type.is_dir(),
+                ),
+                Err(_) => true,
+            });
+        });
+    }
+    walker
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file())
+        .filter(|entry| {
+            entry
+                .path()
+                .extension()
+                .map(|ext| ext == extension)
+                .unwrap_or(false)
         })
-        .collect();
-
-    files_with_ts.sort_by(|a, b| match (&a.1, &b.1) {
-        (Some(a_ts), Some(b_ts)) => a_ts.cmp(b_ts),
-        (Some(_), None) => std::cmp::Ordering::Less,
-        (None, Some(_)) => std::cmp::Ordering::Greater,
-        (None, None) => std::cmp::Ordering::Equal,
-    });
-
-    files_with_ts.into_iter().map(|(file, _)| file).collect()
+        .map(|entry| entry.path().to_path_buf())
+        .filter(|file| seen.insert(file_identity(file)))
+        .collect()
 }
 
-pub fn glob_usage_files(claude_paths: &[PathBuf]) -> Vec<GlobResult> {
+pub fn glob_usage_files(claude_paths: &[PathBuf], options: &LoadOptions) -> Vec<GlobResult> {
     let mut results = Vec::new();
+    let mut seen = HashSet::new();
     for base in claude_paths {
         let projects_dir = base.join(CLAUDE_PROJECTS_DIR_NAME);
         if !projects_dir.is_dir() {
             continue;
         }
-        let entries = WalkDir::new(&projects_dir)
-            .parallelism(jwalk::Parallelism::RayonNewPool(0))
-            .follow_links(true)
-            .into_iter()
-            .filter_map(|entry| entry.ok())
-            .filter(|entry| entry.file_type().is_file())
-            .filter(|entry| {
-                entry
-                    .path()
-                    .extension()
-                    .map(|ext| ext == "jsonl")
-                    .unwrap_or(false)
-            })
-            .map(|entry| entry.path().to_path_buf())
-            .collect::<Vec<_>>();
+        let entries = walk_files(&projects_dir, "jsonl", options, &mut seen);
         for file in entries {
             results.push(GlobResult {
                 file,
@@ -926,6 +1874,19 @@ fn extract_usage_tokens_with_cache_creation(
     Some((tokens, cache_creation))
 }
 
+fn extract_tool_use_ids(line: &[u8]) -> Vec<String> {
+    let Ok(envelope) = sonic_rs::from_slice::<ToolUseEnvelope>(line) else {
+        return Vec::new();
+    };
+    envelope
+        .message
+        .content
+        .into_iter()
+        .filter(|block| block.kind == "tool_use")
+        .filter_map(|block| block.id)
+        .collect()
+}
+
 fn extract_advisor_usages(line: &[u8]) -> Vec<(String, UsageTokens, Option<CacheCreationTokens>)> {
     let Ok(envelope) = sonic_rs::from_slice::<UsageIterationsEnvelope>(line) else {
         return Vec::new();
@@ -979,11 +1940,42 @@ fn total_tokens_from_usage(tokens: &UsageTokens) -> u64 {
         .saturating_add(tokens.cache_read_input_tokens)
 }
 
-fn claude_dedupe_key(message_id: &str, request_id: Option<&str>) -> String {
-    match request_id {
-        Some(request_id) => format!("{message_id}\0{request_id}"),
-        None => format!("{message_id}\0"),
-    }
+fn hash_key(key: impl Hash) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Key for the copies of one message/request; with `request_id` `None`, the key shared by
+/// every request of the message.
+fn claude_dedupe_key(message_id: &str, request_id: Option<&str>) -> u64 {
+    hash_key((0u8, message_id, request_id))
+}
+
+/// Fields identifying copies of one entry that can't be matched by request id: message id
+/// (when present), timestamp, and token counts.
+fn claude_fallback_fields(record: &ParsedRecord) -> (&str, &str, [u64; 4]) {
+    let tokens = &record.tokens;
+    (
+        record.message_id.as_deref().unwrap_or_default(),
+        &record.timestamp,
+        [
+            tokens.input_tokens,
+            tokens.output_tokens,
+            tokens.cache_creation_input_tokens,
+            tokens.cache_read_input_tokens,
+        ],
+    )
+}
+
+fn claude_fallback_dedupe_key(record: &ParsedRecord) -> u64 {
+    hash_key((1u8, claude_fallback_fields(record)))
+}
+
+/// Records can only be matched by records of the same message, so this partitions them into
+/// groups that deduplicate independently.
+fn claude_message_key(record: &ParsedRecord) -> u64 {
+    hash_key(record.message_id.as_deref().unwrap_or_default())
 }
 
 fn is_sidechain_record(record: &ParsedRecord) -> bool {
@@ -1003,67 +1995,202 @@ fn should_replace_claude_record(candidate: &ParsedRecord, existing: &ParsedRecor
     candidate.cost > existing.cost
 }
 
-fn push_deduped_claude_index(
-    deduped_indexes: &mut HashMap<String, Vec<usize>>,
-    key: String,
-    index: usize,
-) {
-    let indexes = deduped_indexes.entry(key).or_default();
-    if !indexes.contains(&index) {
-        indexes.push(index);
-    }
+/// Claude records deduplicated in the order they are pushed. Each record is kept with the
+/// log position of its first copy, so the records of several dedupers can be merged back into
+/// log order.
+struct ClaudeDeduper {
+    policy: DedupPolicy,
+    fallback_dedup: bool,
+    /// Hashed keys to the records indexed under them; a hit is confirmed against the record.
+    indexes: HashMap<u64, Vec<usize>>,
+    records: Vec<(usize, ParsedRecord)>,
 }
 
-fn push_deduped_claude_record(
-    record: ParsedRecord,
-    deduped_indexes: &mut HashMap<String, Vec<usize>>,
-    deduped: &mut Vec<ParsedRecord>,
-) {
-    let dedupe_lookup = record.message_id.as_deref().map(|message_id| {
-        let request_id = record.request_id.as_deref();
-        let exact_key = claude_dedupe_key(message_id, request_id);
-        let existing_index = deduped_indexes
-            .get(&exact_key)
-            .and_then(|indexes| {
-                indexes.iter().copied().find(|&index| {
-                    deduped[index].message_id.as_deref() == Some(message_id)
-                        && deduped[index].request_id.as_deref() == request_id
-                })
+impl ClaudeDeduper {
+    fn new(policy: DedupPolicy, fallback_dedup: bool) -> Self {
+        Self {
+            policy,
+            fallback_dedup,
+            indexes: HashMap::new(),
+            records: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, position: usize, record: ParsedRecord) {
+        let Some(index) = self.find(&record) else {
+            let index = self.records.len();
+            self.records.push((position, record));
+            self.index(index);
+            return;
+        };
+
+        let existing = &mut self.records[index].1;
+        let replace = match self.policy {
+            DedupPolicy::Auto => should_replace_claude_record(&record, existing),
+            DedupPolicy::KeepFirst => false,
+            DedupPolicy::KeepLatest | DedupPolicy::Off => true,
+        };
+        report_duplicate(if replace { existing } else { &record });
+        if replace {
+            // Streamed lines of a message share its keys, so only a copy matched another
+            // way needs indexing.
+            let same_keys = record.message_id == existing.message_id
+                && record.request_id == existing.request_id
+                && (!self.fallback_dedup
+                    || claude_fallback_fields(&record) == claude_fallback_fields(existing));
+            let previous_tool_use_ids = std::mem::take(&mut existing.tool_use_ids);
+            let previous_stop_reason = existing.stop_reason.take();
+            *existing = record;
+            merge_tool_use_ids(&mut existing.tool_use_ids, previous_tool_use_ids);
+            if existing.stop_reason.is_none() {
+                existing.stop_reason = previous_stop_reason;
+            }
+            if !same_keys {
+                self.index(index);
+            }
+        } else {
+            merge_tool_use_ids(&mut existing.tool_use_ids, record.tool_use_ids);
+            if existing.stop_reason.is_none() {
+                existing.stop_reason = record.stop_reason;
+            }
+        }
+    }
+
+    /// Index of the kept copy `record` duplicates, if any.
+    fn find(&self, record: &ParsedRecord) -> Option<usize> {
+        let candidates = |key: u64| self.indexes.get(&key).into_iter().flatten().copied();
+        let kept = |index: usize| &self.records[index].1;
+        record
+            .message_id
+            .as_deref()
+            .and_then(|message_id| {
+                let request_id = record.request_id.as_deref();
+                candidates(claude_dedupe_key(message_id, request_id))
+                    .find(|&index| {
+                        kept(index).message_id.as_deref() == Some(message_id)
+                            && kept(index).request_id.as_deref() == request_id
+                    })
+                    .or_else(|| {
+                        let candidate_is_sidechain = is_sidechain_record(record);
+                        candidates(claude_dedupe_key(message_id, None)).find(|&index| {
+                            kept(index).message_id.as_deref() == Some(message_id)
+                                && (candidate_is_sidechain || is_sidechain_record(kept(index)))
+                        })
+                    })
             })
             .or_else(|| {
-                let message_key = claude_dedupe_key(message_id, None);
-                let candidate_is_sidechain = is_sidechain_record(&record);
-                deduped_indexes.get(&message_key).and_then(|indexes| {
-                    indexes.iter().copied().find(|&index| {
-                        deduped[index].message_id.as_deref() == Some(message_id)
-                            && (candidate_is_sidechain || is_sidechain_record(&deduped[index]))
-                    })
+                if !self.fallback_dedup {
+                    return None;
+                }
+                let fields = claude_fallback_fields(record);
+                candidates(claude_fallback_dedupe_key(record)).find(|&index| {
+                    claude_fallback_fields(kept(index)) == fields
+                        && (record.request_id.is_none() || kept(index).request_id.is_none())
                 })
-            });
-        (exact_key, existing_index)
-    });
+            })
+    }
 
-    if let Some((key, Some(index))) = dedupe_lookup {
-        if should_replace_claude_record(&record, &deduped[index]) {
-            deduped[index] = record;
-            push_deduped_claude_index(deduped_indexes, key, index);
-            if let Some(message_id) = deduped[index].message_id.as_deref() {
-                push_deduped_claude_index(
-                    deduped_indexes,
-                    claude_dedupe_key(message_id, None),
-                    index,
-                );
+    /// Indexes the record at `index` under its message/request keys and, with fallback
+    /// dedup, its fallback key. A record that replaces another stays indexed under the old
+    /// keys too.
+    fn index(&mut self, index: usize) {
+        let record = &self.records[index].1;
+        let message_keys = record.message_id.as_deref().map(|message_id| {
+            [
+                claude_dedupe_key(message_id, record.request_id.as_deref()),
+                claude_dedupe_key(message_id, None),
+            ]
+        });
+        let fallback_key = self
+            .fallback_dedup
+            .then(|| claude_fallback_dedupe_key(record));
+        for key in message_keys.into_iter().flatten().chain(fallback_key) {
+            let indexes = self.indexes.entry(key).or_default();
+            if !indexes.contains(&index) {
+                indexes.push(index);
             }
         }
-        return;
     }
+}
+
+/// Deduplicates the records of `parsed_files`, which are in log order. Messages logged in a
+/// single file are deduplicated per file in parallel; only messages repeated across files
+/// (resumed or forked sessions) go through one ordered pass. Copies of different messages
+/// never match, so this keeps the same records as one pass over every record.
+fn dedupe_claude_files(
+    parsed_files: Vec<ParsedFileRecords>,
+    policy: DedupPolicy,
+    fallback_dedup: bool,
+) -> Vec<ParsedRecord> {
+    let message_keys = parsed_files
+        .par_iter()
+        .map(|parsed_file| {
+            parsed_file
+                .records
+                .iter()
+                .map(claude_message_key)
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    // The only file each message is logged in, or `None` once it turns up in a second one.
+    let mut message_files: HashMap<u64, Option<usize>> = HashMap::new();
+    for (file, keys) in message_keys.iter().enumerate() {
+        for &key in keys {
+            let only_file = message_files.entry(key).or_insert(Some(file));
+            if *only_file != Some(file) {
+                *only_file = None;
+            }
+        }
+    }
+
+    let mut offset = 0;
+    let files = parsed_files
+        .into_iter()
+        .zip(message_keys)
+        .map(|(parsed_file, keys)| {
+            let first_position = offset;
+            offset += parsed_file.records.len();
+            (first_position, parsed_file.records, keys)
+        })
+        .collect::<Vec<_>>();
+    let (local, shared): (Vec<_>, Vec<_>) = files
+        .into_par_iter()
+        .map(|(first_position, records, keys)| {
+            let mut deduper = ClaudeDeduper::new(policy, fallback_dedup);
+            let mut shared = Vec::new();
+            for ((position, record), key) in (first_position..).zip(records).zip(keys) {
+                if message_files[&key].is_none() {
+                    shared.push((position, record));
+                } else {
+                    deduper.push(position, record);
+                }
+            }
+            (deduper.records, shared)
+        })
+        .unzip();
+
+    let mut deduper = ClaudeDeduper::new(policy, fallback_dedup);
+    for (position, record) in shared.into_iter().flatten() {
+        deduper.push(position, record);
+    }
+    // Both halves are in log order; interleave them back into one.
+    let capacity = local.iter().map(Vec::len).sum::<usize>() + deduper.records.len();
+    let mut records = Vec::with_capacity(capacity);
+    let mut shared = deduper.records.into_iter().peekable();
+    for (position, record) in local.into_iter().flatten() {
+        while let Some((_, shared_record)) = shared.next_if(|(shared, _)| *shared < position) {
+            records.push(shared_record);
+        }
+        records.push(record);
+    }
+    records.extend(shared.map(|(_, record)| record));
+    records
+}
 
-    let index = deduped.len();
-    deduped.push(record);
-    if let Some((key, None)) = dedupe_lookup {
-        push_deduped_claude_index(deduped_indexes, key, index);
-        if let Some(message_id) = deduped[index].message_id.as_deref() {
-            push_deduped_claude_index(deduped_indexes, claude_dedupe_key(message_id, None), index);
+fn merge_tool_use_ids(target: &mut Vec<String>, ids: Vec<String>) {
+    for id in ids {
+        if !target.contains(&id) {
+            target.push(id);
         }
     }
 }
@@ -1110,12 +2237,22 @@ fn calculate_cost_for_entry(
         message.model.as_deref(),
         &tokens,
         cache_creation.as_ref(),
-        data.cost_usd,
+        data.logged_cost_usd(),
         mode,
         pricing,
     )
 }
 
+/// Number (or numeric string) at `field` in `line`; dots separate nested keys, e.g.
+/// `metadata.costUSD`.
+fn extract_cost_field(line: &[u8], field: &str) -> Option<f64> {
+    let value = sonic_rs::get_from_slice(line, field.split('.')).ok()?;
+    value
+        .as_f64()
+        .or_else(|| value.as_str()?.trim().parse().ok())
+        .filter(|cost: &f64| cost.is_finite())
+}
+
 fn calculate_cost_for_usage(
     model: Option<&str>,
     tokens: &UsageTokens,
@@ -1149,6 +2286,18 @@ fn calculate_cost_for_usage(
                 })
                 .unwrap_or(0.0)
         }
+        CostMode::Max => {
+            let calculated = pricing
+                .map(|fetcher| {
+                    fetcher.calculate_cost_from_tokens_with_cache_creation(
+                        tokens,
+                        cache_creation,
+                        model,
+                    )
+                })
+                .unwrap_or(0.0);
+            cost_usd.map_or(calculated, |cost| cost.max(calculated))
+        }
     }
 }
 
@@ -1180,24 +2329,12 @@ fn codex_usage_dirs(home: &Path) -> Vec<PathBuf> {
         .collect()
 }
 
-fn glob_codex_usage_files(source_dirs: &[PathBuf]) -> Vec<PathBuf> {
+fn glob_codex_usage_files(source_dirs: &[PathBuf], options: &LoadOptions) -> Vec<PathBuf> {
     let mut seen_relative_paths = HashSet::new();
+    let mut seen_canonical_paths = HashSet::new();
     let mut files = Vec::new();
     for source_dir in source_dirs {
-        let source_files = WalkDir::new(source_dir)
-            .parallelism(jwalk::Parallelism::RayonNewPool(0))
-            .follow_links(true)
-            .into_iter()
-            .filter_map(|entry| entry.ok())
-            .filter(|entry| entry.file_type().is_file())
-            .filter(|entry| {
-                entry
-                    .path()
-                    .extension()
-                    .map(|ext| ext == "jsonl")
-                    .unwrap_or(false)
-            })
-            .map(|entry| entry.path().to_path_buf());
+        let source_files = walk_files(source_dir, "jsonl", options, &mut seen_canonical_paths);
         for file in source_files {
             let relative = file
                 .strip_prefix(source_dir)
@@ -1283,22 +2420,8 @@ fn resolve_opencode_messages_dir(base: PathBuf) -> Option<PathBuf> {
     None
 }
 
-fn glob_opencode_message_files(messages_dir: &Path) -> Vec<PathBuf> {
-    WalkDir::new(messages_dir)
-        .parallelism(jwalk::Parallelism::RayonNewPool(0))
-        .follow_links(true)
-        .into_iter()
-        .filter_map(|entry| entry.ok())
-        .filter(|entry| entry.file_type().is_file())
-        .filter(|entry| {
-            entry
-                .path()
-                .extension()
-                .map(|ext| ext == "json")
-                .unwrap_or(false)
-        })
-        .map(|entry| entry.path().to_path_buf())
-        .collect()
+fn glob_opencode_message_files(messages_dir: &Path, options: &LoadOptions) -> Vec<PathBuf> {
+    walk_files(messages_dir, "json", options, &mut HashSet::new())
 }
 
 fn normalized_non_empty(value: Option<&str>) -> Option<String> {
@@ -1391,6 +2514,9 @@ fn codex_usage_to_tokens(delta: &CodexRawUsage) -> UsageTokens {
     }
 }
 
+/// Service tier reported for Codex usage when the Codex config enables fast mode.
+const CODEX_FAST_SERVICE_TIER: &str = "fast";
+
 fn is_codex_fast_service_tier(config: &str) -> bool {
     config.lines().any(|line| {
         let uncommented = line.split('#').next().unwrap_or_default();
@@ -1465,17 +2591,18 @@ fn codex_timestamp_second(timestamp: &str) -> Option<[u8; 19]> {
         .and_then(|value| value.try_into().ok())
 }
 
-fn detect_codex_subagent_replay_second(file: &Path) -> Option<[u8; 19]> {
+fn detect_codex_subagent_replay_second(file: &Path, limits: ReadLimits) -> Option<[u8; 19]> {
     let file = File::open(file).ok()?;
     let mut reader = BufReader::with_capacity(64 * 1024, file);
     let mut line = Vec::new();
     let mut first_second: Option<[u8; 19]> = None;
 
     loop {
-        line.clear();
-        let bytes_read = reader.read_until(b'\n', &mut line).ok()?;
-        if bytes_read == 0 {
-            break;
+        match read_line_bounded(&mut reader, &mut line, limits.max_line_bytes).ok()? {
+            LineRead::Eof => break,
+            LineRead::TooLong => continue,
+            LineRead::Unterminated if !is_complete_json(&line) => break,
+            LineRead::Line | LineRead::Unterminated => {}
         }
         let trimmed = trim_ascii_whitespace(&line);
         if trimmed.is_empty()
@@ -1515,17 +2642,21 @@ fn detect_codex_subagent_replay_second(file: &Path) -> Option<[u8; 19]> {
 fn parse_codex_file_records(
     file: &Path,
     timezone: Option<chrono_tz::Tz>,
+    limits: ReadLimits,
 ) -> Result<ParsedFileRecords> {
     let mut records = Vec::new();
     let mut earliest_timestamp: Option<DateTime<Utc>> = None;
     let mut previous_totals: Option<CodexRawUsage> = None;
     let mut current_model: Option<String> = None;
+    let file_session_id = session_id_from_path(file);
     let replay_second = is_codex_subagent_session(file)
-        .then(|| detect_codex_subagent_replay_second(file))
+        .then(|| detect_codex_subagent_replay_second(file, limits))
         .flatten();
     let mut skip_replay = replay_second.is_some();
 
-    process_jsonl_file_by_line_bytes(file, |line, _| {
+    let source_file: Arc<Path> = Arc::from(file);
+    let mut models = HashSet::new();
+    process_jsonl_file_by_line_bytes(file, limits, |line, line_number| {
         update_earliest_timestamp_from_line(line, &mut earliest_timestamp);
 
         if !line_contains_any_marker(line, &[CODEX_TURN_CONTEXT_MARKER, CODEX_TOKEN_COUNT_MARKER]) {
@@ -1621,12 +2752,22 @@ fn parse_codex_file_records(
             message_id: None,
             request_id: None,
             is_sidechain: None,
+            timestamp: timestamp.to_string(),
+            session_id: file_session_id.clone(),
             date,
             project: None,
-            model: Some(model),
+            model: Some(intern(&mut models, &model)),
             tokens,
             total_tokens: raw_usage.total_tokens,
             cost,
+            tool_use_ids: Vec::new(),
+            is_api_error: false,
+            cost_logged: false,
+            service_tier: None,
+            stop_reason: None,
+            source_file: Some(source_file.clone()),
+            source_line: Some(line_number),
+            data_dir: None,
         });
 
         Ok(())
@@ -1692,7 +2833,8 @@ fn parse_opencode_message(
         .and_then(|time| time.created)
         .or(created_override)?;
     let created_dt = DateTime::<Utc>::from_timestamp_millis(created)?;
-    let date = format_date_with_tz(&created_dt.to_rfc3339(), timezone)?;
+    let timestamp = created_dt.to_rfc3339();
+    let date = format_date_with_tz(&timestamp, timezone)?;
     let tokens = extract_opencode_usage_tokens(&message)?;
     let total_tokens = total_tokens_from_usage(&tokens);
     let cost = calculate_cost_for_opencode_entry(&message, &tokens, options.mode, pricing);
@@ -1702,12 +2844,23 @@ fn parse_opencode_message(
         message_id: None,
         request_id: None,
         is_sidechain: None,
+        timestamp,
+        session_id: normalized_non_empty(message.session_id.as_deref()),
         date,
         project: None,
-        model: Some(model),
+        model: Some(model.into()),
         tokens,
         total_tokens,
         cost,
+        tool_use_ids: Vec::new(),
+        is_api_error: false,
+        cost_logged: message.cost.is_some_and(|cost| cost > 0.0)
+            && options.mode != CostMode::Calculate,
+        service_tier: None,
+        stop_reason: None,
+        source_file: None,
+        source_line: None,
+        data_dir: None,
     })
 }
 
@@ -1734,6 +2887,12 @@ fn calculate_cost_for_opencode_entry(
         CostMode::Calculate => pricing
             .map(|fetcher| fetcher.calculate_cost_from_tokens(tokens, model.as_deref()))
             .unwrap_or(0.0),
+        CostMode::Max => {
+            let calculated = pricing
+                .map(|fetcher| fetcher.calculate_cost_from_tokens(tokens, model.as_deref()))
+                .unwrap_or(0.0);
+            message.cost.map_or(calculated, |cost| cost.max(calculated))
+        }
     }
 }
 
@@ -1745,7 +2904,11 @@ fn parse_opencode_message_record(
 ) -> Option<ParsedRecord> {
     let content = std::fs::read(file).ok()?;
     let message: OpenCodeMessage = sonic_rs::from_slice(&content).ok()?;
-    parse_opencode_message(message, None, None, false, timezone, options, pricing)
+    let record = parse_opencode_message(message, None, None, false, timezone, options, pricing)?;
+    Some(ParsedRecord {
+        source_file: Some(Arc::from(file)),
+        ..record
+    })
 }
 
 fn parse_opencode_sqlite_row(
@@ -1797,7 +2960,10 @@ fn load_opencode_sqlite_records(
             && let Some(record) =
                 parse_opencode_sqlite_row(row_id, time_created, &data, timezone, options, pricing)
         {
-            parsed_records.push(record);
+            parsed_records.push(ParsedRecord {
+                source_file: Some(Arc::from(db_path)),
+                ..record
+            });
         }
     }
 
@@ -1810,27 +2976,81 @@ fn load_opencode_json_records(
     options: &LoadOptions,
     pricing: Option<&PricingFetcher>,
 ) -> Vec<ParsedRecord> {
-    let files = glob_opencode_message_files(messages_dir);
+    let files = glob_opencode_message_files(messages_dir, options);
     files
         .par_iter()
         .filter_map(|file| parse_opencode_message_record(file, timezone, options, pricing))
         .collect::<Vec<_>>()
 }
 
+/// Records aggregated together before the partial aggregates are merged.
+const AGGREGATION_CHUNK_RECORDS: usize = 4096;
+
+/// Aggregates records by date (and project) in parallel: every chunk of records gets partial
+/// aggregates of its own, which are then merged pairwise. Chunks and merges follow the record
+/// order rather than the thread schedule, so costs sum the same way on every run.
+fn aggregate_records(
+    records: Vec<ParsedRecord>,
+    needs_project_grouping: bool,
+    timezone: Option<Tz>,
+) -> HashMap<GroupKey, Aggregate> {
+    let mut partials = records
+        .into_par_iter()
+        .chunks(AGGREGATION_CHUNK_RECORDS)
+        .map(|chunk| {
+            let mut aggregates = HashMap::new();
+            for record in chunk {
+                aggregate_usage_record(&mut aggregates, record, needs_project_grouping, timezone);
+            }
+            aggregates
+        })
+        .collect::<Vec<_>>();
+    while partials.len() > 1 {
+        partials = partials
+            .into_par_iter()
+            .chunks(2)
+            .map(|pair| {
+                pair.into_iter()
+                    .reduce(merge_aggregates)
+                    .unwrap_or_default()
+            })
+            .collect();
+    }
+    partials.pop().unwrap_or_default()
+}
+
+fn merge_aggregates(
+    mut aggregates: HashMap<GroupKey, Aggregate>,
+    later: HashMap<GroupKey, Aggregate>,
+) -> HashMap<GroupKey, Aggregate> {
+    for (key, aggregate) in later {
+        match aggregates.entry(key) {
+            std::collections::hash_map::Entry::Occupied(mut entry) => {
+                entry.get_mut().merge(aggregate)
+            }
+            std::collections::hash_map::Entry::Vacant(entry) => {
+                entry.insert(aggregate);
+            }
+        }
+    }
+    aggregates
+}
+
 fn aggregate_usage_record(
     aggregates: &mut HashMap<GroupKey, Aggregate>,
-    group: GroupKey,
+    record: ParsedRecord,
     needs_project_grouping: bool,
-    model: Option<&str>,
-    tokens: &UsageTokens,
-    total_tokens: u64,
-    cost: f64,
+    timezone: Option<Tz>,
 ) {
+    let is_sidechain = is_sidechain_record(&record);
     let key = if needs_project_grouping {
-        group
+        (record.date, record.project)
     } else {
-        (group.0, None)
+        (record.date, None)
     };
+    let tokens = &record.tokens;
+    let total_tokens = record.total_tokens;
+    let cost = record.cost;
 
     let entry = aggregates.entry(key).or_default();
     entry.input_tokens += tokens.input_tokens;
@@ -1839,18 +3059,40 @@ fn aggregate_usage_record(
     entry.cache_read_tokens += tokens.cache_read_input_tokens;
     entry.total_tokens += total_tokens;
     entry.total_cost += cost;
+    entry.message_count += u64::from(!record.is_api_error);
+    entry.tool_calls += record.tool_use_ids.len() as u64;
+    entry.api_errors += u64::from(record.is_api_error);
+    entry.logged_costs += u64::from(record.cost_logged);
+    if let Some(session_id) = &record.session_id
+        && !entry.session_ids.contains(session_id)
+    {
+        entry.session_ids.insert(session_id.clone());
+    }
+    let timestamp = DateTime::parse_from_rfc3339(&record.timestamp)
+        .ok()
+        .map(|timestamp| timestamp.with_timezone(&Utc));
+    widen_activity(
+        &mut entry.first_timestamp,
+        &mut entry.last_timestamp,
+        timestamp,
+        timestamp,
+    );
+    if let Some(timestamp) = timestamp {
+        entry.hour_mask |= 1 << local_hour(timestamp, timezone);
+    }
+    if is_sidechain {
+        entry.subagent_cost += cost;
+    }
 
-    if let Some(model) = model {
-        if model != "<synthetic>" {
-            entry.push_model(model);
-            update_model_breakdowns(
-                &mut entry.model_breakdowns,
-                model,
-                tokens,
-                total_tokens,
-                cost,
-            );
-        }
+    if let Some(model) = record.model.as_deref() {
+        entry.push_model(model);
+        update_model_breakdowns(
+            &mut entry.model_breakdowns,
+            model,
+            tokens,
+            total_tokens,
+            cost,
+        );
     } else {
         update_model_breakdowns(
             &mut entry.model_breakdowns,
@@ -1862,6 +3104,23 @@ fn aggregate_usage_record(
     }
 }
 
+/// Extends a `[first, last]` activity window to cover another one.
+pub fn widen_activity(
+    first: &mut Option<DateTime<Utc>>,
+    last: &mut Option<DateTime<Utc>>,
+    other_first: Option<DateTime<Utc>>,
+    other_last: Option<DateTime<Utc>>,
+) {
+    *first = match (*first, other_first) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    };
+    *last = match (*last, other_last) {
+        (Some(a), Some(b)) => Some(a.max(b)),
+        (a, b) => a.or(b),
+    };
+}
+
 fn recalculate_codex_aggregate_costs(
     aggregates: &mut HashMap<GroupKey, Aggregate>,
     pricing: Option<&PricingFetcher>,
@@ -1895,7 +3154,6 @@ fn aggregates_to_daily_usage(aggregates: HashMap<GroupKey, Aggregate>) -> Vec<Da
         let mut model_breakdowns = aggregate
             .model_breakdowns
             .into_iter()
-            .filter(|(name, _)| name != "<synthetic>")
             .map(|(model_name, stats)| ModelBreakdown {
                 model_name,
                 input_tokens: stats.input_tokens,
@@ -1923,105 +3181,299 @@ fn aggregates_to_daily_usage(aggregates: HashMap<GroupKey, Aggregate>) -> Vec<Da
             models_used: aggregate.models_used,
             model_breakdowns,
             project,
+            message_count: aggregate.message_count,
+            subagent_cost: aggregate.subagent_cost,
+            tool_calls: aggregate.tool_calls,
+            api_errors: aggregate.api_errors,
+            logged_costs: aggregate.logged_costs,
+            session_ids: sorted_session_ids(aggregate.session_ids),
+            first_timestamp: aggregate.first_timestamp,
+            last_timestamp: aggregate.last_timestamp,
+            hour_mask: aggregate.hour_mask,
         });
     }
     results
 }
 
-fn load_claude_daily_usage_data(options: &LoadOptions) -> Result<Vec<DailyUsage>> {
-    let parsed_timezone = match options.timezone.as_deref() {
-        Some(tz_str) => Tz::from_str(tz_str).ok(),
-        None => None,
-    };
-    if options.timezone.is_some() && parsed_timezone.is_none() {
-        return Ok(Vec::new());
+/// Returns `None` when a timezone was requested but is not a valid IANA name.
+fn resolve_timezone(options: &LoadOptions) -> Option<Option<Tz>> {
+    match options.timezone.as_deref() {
+        Some(tz_str) => Tz::from_str(tz_str).ok().map(Some),
+        None => Some(None),
+    }
+}
+
+fn pricing_for_mode(mode: CostMode) -> Option<PricingFetcher> {
+    if matches!(mode, CostMode::Display) {
+        None
+    } else {
+        Some(PricingFetcher::new())
     }
+}
 
-    let claude_paths = if let Some(path) = &options.claude_path {
-        vec![path.clone()]
+/// Claude data roots scanned with `options`: `--path` roots or the ones found by searching,
+/// Claude Desktop's when enabled, and the `ccost import` root.
+fn claude_roots(options: &LoadOptions) -> Vec<PathBuf> {
+    let mut claude_paths = if !options.claude_paths.is_empty() {
+        let roots = dedupe_roots(&options.claude_paths);
+        if options.verbose {
+            for root in &roots {
+                report_claude_root(root, is_claude_data_root(root), "--path");
+            }
+        }
+        roots
     } else {
-        match get_claude_paths() {
-            Ok(paths) => paths,
-            Err(_) => return Ok(Vec::new()),
+        let candidates = if options.claude_search_paths.is_empty() {
+            default_claude_search_paths()
+        } else {
+            options.claude_search_paths.clone()
+        };
+        let mut roots = find_claude_paths(&candidates, options.verbose).unwrap_or_default();
+        if options.claude_desktop
+            && let Some(desktop) = default_claude_desktop_path()
+        {
+            let desktop_roots = find_claude_desktop_roots(&desktop);
+            if options.verbose && desktop.is_dir() && desktop_roots.is_empty() {
+                report_claude_root(&desktop, false, "Claude Desktop");
+            }
+            for root in desktop_roots {
+                let root = root.canonicalize().unwrap_or(root);
+                if options.verbose {
+                    report_claude_root(&root, true, "Claude Desktop");
+                }
+                if !roots.contains(&root) {
+                    roots.push(root);
+                }
+            }
+        }
+        roots
+    };
+    for root in options.import_path.iter().chain(&options.archive_path) {
+        if is_claude_data_root(root) && !claude_paths.contains(root) {
+            claude_paths.push(root.clone());
         }
+    }
+    claude_paths
+}
+
+/// Claude log files under the data roots of `options` (of `options.project` when set), leaving
+/// out what `ccost import` and `ccost archive` wrote.
+pub fn claude_log_files(options: &LoadOptions) -> Vec<GlobResult> {
+    let options = LoadOptions {
+        import_path: None,
+        archive_path: None,
+        ..options.clone()
+    };
+    let mut files = glob_usage_files(&claude_roots(&options), &options);
+    if let Some(project) = &options.project {
+        files.retain(|file| extract_project_from_path(&file.file) == *project);
+    }
+    files
+}
+
+/// Directories holding the usage logs that loads with `options` read, for watching them for
+/// changes: the Claude data roots, Codex home, and OpenCode data directory of the enabled
+/// sources.
+pub fn usage_log_roots(options: &LoadOptions) -> Vec<PathBuf> {
+    let quiet = LoadOptions {
+        verbose: false,
+        ..options.clone()
     };
+    let mut roots = Vec::new();
+    if options.claudecode {
+        roots.extend(claude_roots(&quiet));
+    }
+    if options.codex && options.project.is_none() {
+        roots.extend(resolve_codex_home(&quiet));
+    }
+    if options.opencode && options.project.is_none() {
+        roots.extend(
+            options
+                .opencode_path
+                .clone()
+                .filter(|path| path.exists())
+                .or_else(opencode_base_dir),
+        );
+    }
+    roots
+}
 
-    let all_files = glob_usage_files(&claude_paths);
+fn load_claude_records(
+    options: &LoadOptions,
+    timezone: Option<Tz>,
+    with_projects: bool,
+) -> Result<Vec<ParsedRecord>> {
+    let discovery = phase_start();
+    let claude_paths = claude_roots(options);
+    let all_files = glob_usage_files(&claude_paths, options);
     if all_files.is_empty() {
         return Ok(Vec::new());
     }
 
-    let mut file_list = all_files.into_iter().map(|f| f.file).collect::<Vec<_>>();
+    let mut file_list = all_files
+        .into_iter()
+        .map(|f| (f.file, f.base_dir))
+        .collect::<Vec<_>>();
 
     if let Some(project) = &options.project {
-        file_list.retain(|file| extract_project_from_path(file) == *project);
+        file_list.retain(|(file, _)| extract_project_from_path(file) == *project);
     }
+    if let Some(session) = &options.session {
+        file_list.retain(|(file, _)| path_mentions_session(file, session));
+    }
+    let file_list = skip_files_outside_range(file_list, |(file, _)| file, options);
+    record_phase(Phase::Discovery, discovery, Some(file_list.len()));
 
     if file_list.is_empty() {
         return Ok(Vec::new());
     }
 
-    let pricing = if matches!(options.mode, CostMode::Display) {
-        None
-    } else {
-        Some(PricingFetcher::new())
-    };
-
-    let mut aggregates: HashMap<GroupKey, Aggregate> = HashMap::new();
-    let needs_project_grouping = options.group_by_project || options.project.is_some();
-
+    let pricing = pricing_for_mode(options.mode);
     let pricing_ref = pricing.as_ref();
+    // Sessions of a project share its name, so every record of the project points at one string.
+    let mut projects = HashSet::new();
     let file_entries = file_list
         .into_iter()
-        .map(|file| {
-            let project = if needs_project_grouping {
-                Some(Arc::<str>::from(extract_project_from_path(&file)))
-            } else {
-                None
-            };
-            (file, project)
+        .map(|(file, projects_dir)| {
+            let project =
+                with_projects.then(|| intern(&mut projects, &extract_project_from_path(&file)));
+            let data_dir = Arc::<Path>::from(projects_dir.parent().unwrap_or(&projects_dir));
+            (file, project, data_dir)
         })
         .collect::<Vec<_>>();
 
-    let mut parsed_files = file_entries
-        .par_iter()
-        .map(|(file, project)| {
-            parse_file_records(file, project.clone(), parsed_timezone, options, pricing_ref)
-        })
-        .collect::<Result<Vec<_>>>()?;
-    parsed_files.sort_by(compare_parsed_file_records);
-
-    let mut deduped_indexes: HashMap<String, Vec<usize>> = HashMap::new();
-    let mut deduped_records = Vec::new();
-    for record in parsed_files
-        .into_iter()
-        .flat_map(|parsed_file| parsed_file.records)
-    {
-        push_deduped_claude_record(record, &mut deduped_indexes, &mut deduped_records);
+    let parsing = phase_start();
+    let cache = options.parse_cache.as_deref();
+    let disk_cache = options.cache_path.as_deref().and_then(|path| {
+        DiskCache::open(path, parse_fingerprint(options, timezone))
+            .inspect_err(|error| {
+                if options.verbose {
+                    eprintln!("Parse cache {} unavailable: {error}", path.display());
+                }
+            })
+            .ok()
+    });
+    if let Some(cache) = cache {
+        cache.retain(
+            &file_entries
+                .iter()
+                .map(|(file, ..)| file.as_path())
+                .collect(),
+        );
+    }
+    let parsed_files = file_entries
+        .par_iter()
+        .map(|(file, project, data_dir)| {
+            let stamp = (cache.is_some() || disk_cache.is_some())
+                .then(|| FileStamp::of(file))
+                .flatten();
+            if let (Some(cache), Some(stamp)) = (cache, stamp)
+                && let Some(parsed) = cache.get(file, stamp, with_projects)
+            {
+                return Ok((parsed, None));
+            }
+            let stored = disk_cache
+                .as_ref()
+                .zip(stamp)
+                .and_then(|(disk_cache, stamp)| {
+                    disk_cache.get(file, stamp, project.as_ref(), Some(data_dir))
+                });
+            let fresh = stored.is_none();
+            let parsed = match stored {
+                Some(parsed) => parsed,
+                None => parse_file_records(
+                    file,
+                    project.clone(),
+                    Some(data_dir.clone()),
+                    timezone,
+                    options,
+                    pricing_ref,
+                )?,
+            };
+            if let (Some(cache), Some(stamp)) = (cache, stamp) {
+                cache.insert(stamp, with_projects, &parsed);
+            }
+            Ok((parsed, stamp.filter(|_| fresh)))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let (mut parsed_files, fresh): (Vec<_>, Vec<_>) = parsed_files.into_iter().unzip();
+    if let Some(disk_cache) = &disk_cache {
+        let fresh = fresh
+            .into_iter()
+            .zip(&parsed_files)
+            .filter_map(|(stamp, parsed)| Some((stamp?, parsed)))
+            .collect::<Vec<_>>();
+        if options.verbose {
+            eprintln!(
+                "Parse cache: {} of {} files unchanged",
+                parsed_files.len() - fresh.len(),
+                parsed_files.len()
+            );
+        }
+        if let Err(error) = disk_cache.store(&fresh)
+            && options.verbose
+        {
+            eprintln!("Parse cache not saved: {error}");
+        }
     }
+    record_phase(
+        Phase::Parsing,
+        parsing,
+        Some(parsed_record_count(&parsed_files)),
+    );
+    let sorting = phase_start();
+    parsed_files.sort_by(compare_parsed_file_records);
+    record_phase(Phase::Sorting, sorting, Some(parsed_files.len()));
 
-    for record in deduped_records {
-        let ParsedRecord {
-            date,
-            project,
-            model,
-            tokens,
-            total_tokens,
-            cost,
-            ..
-        } = record;
-
-        aggregate_usage_record(
-            &mut aggregates,
-            (date, project),
-            needs_project_grouping,
-            model.as_deref(),
-            &tokens,
-            total_tokens,
-            cost,
-        );
+    if options.dedup == DedupPolicy::Off {
+        let records = parsed_files
+            .into_iter()
+            .flat_map(|parsed_file| parsed_file.records)
+            .collect();
+        return Ok(retain_models(records, &options.models));
+    }
+
+    let dedup = phase_start();
+    let deduped_records = dedupe_claude_files(parsed_files, options.dedup, options.fallback_dedup);
+    record_phase(Phase::Dedup, dedup, Some(deduped_records.len()));
+    Ok(retain_models(deduped_records, &options.models))
+}
+
+/// `records` whose model passes `filter`, checking each distinct model once.
+fn retain_models(mut records: Vec<ParsedRecord>, filter: &ModelFilter) -> Vec<ParsedRecord> {
+    if filter.is_empty() {
+        return records;
+    }
+    let mut verdicts: HashMap<Option<Arc<str>>, bool> = HashMap::new();
+    records.retain(|record| {
+        *verdicts
+            .entry(record.model.clone())
+            .or_insert_with_key(|model| filter.matches(model.as_deref()))
+    });
+    records
+}
+
+fn parsed_record_count(parsed_files: &[ParsedFileRecords]) -> usize {
+    parsed_files
+        .iter()
+        .map(|parsed_file| parsed_file.records.len())
+        .sum()
+}
+
+fn load_claude_daily_usage_data(options: &LoadOptions) -> Result<Vec<DailyUsage>> {
+    let Some(parsed_timezone) = resolve_timezone(options) else {
+        return Ok(Vec::new());
+    };
+
+    let needs_project_grouping = options.group_by_project || options.project.is_some();
+    let records = load_claude_records(options, parsed_timezone, needs_project_grouping)?;
+    if records.is_empty() {
+        return Ok(Vec::new());
     }
 
+    let aggregation = phase_start();
+    let aggregates = aggregate_records(records, needs_project_grouping, parsed_timezone);
+
     let filtered = filter_by_date_range(
         aggregates_to_daily_usage(aggregates),
         |item| item.date.as_str(),
@@ -2039,142 +3491,138 @@ fn load_claude_daily_usage_data(options: &LoadOptions) -> Result<Vec<DailyUsage>
     };
 
     final_results = sort_by_date(final_results, |item| item.date.as_str(), options.order);
+    record_phase(Phase::Aggregation, aggregation, None);
     Ok(final_results)
 }
 
-fn load_codex_daily_usage_data(options: &LoadOptions) -> Result<Vec<DailyUsage>> {
-    if options.project.is_some() {
-        return Ok(Vec::new());
-    }
-
-    let parsed_timezone = match options.timezone.as_deref() {
-        Some(tz_str) => Tz::from_str(tz_str).ok(),
-        None => None,
-    };
-    if options.timezone.is_some() && parsed_timezone.is_none() {
-        return Ok(Vec::new());
-    }
-
-    let codex_home = if let Some(path) = &options.codex_path {
-        if path.is_dir() {
-            if path
-                .file_name()
-                .is_some_and(|name| name == CODEX_SESSIONS_DIR_NAME)
-            {
-                path.parent().unwrap_or(path).to_path_buf()
-            } else {
-                path.clone()
-            }
-        } else {
-            return Ok(Vec::new());
+fn resolve_codex_home(options: &LoadOptions) -> Option<PathBuf> {
+    if let Some(path) = &options.codex_path {
+        if !path.is_dir() {
+            return None;
         }
-    } else {
-        match codex_home_dir() {
-            Some(path) => path,
-            None => return Ok(Vec::new()),
+        if path
+            .file_name()
+            .is_some_and(|name| name == CODEX_SESSIONS_DIR_NAME)
+        {
+            return Some(path.parent().unwrap_or(path).to_path_buf());
         }
+        return Some(path.clone());
+    }
+    codex_home_dir()
+}
+
+/// Loads deduplicated Codex records along with whether the fast service tier is configured.
+fn load_codex_records(
+    options: &LoadOptions,
+    timezone: Option<Tz>,
+) -> Result<(Vec<ParsedRecord>, bool)> {
+    let discovery = phase_start();
+    let Some(codex_home) = resolve_codex_home(options) else {
+        return Ok((Vec::new(), false));
     };
 
     let source_dirs = codex_usage_dirs(&codex_home);
-    let files = if source_dirs.is_empty() && options.codex_path.is_some() {
-        glob_codex_usage_files(std::slice::from_ref(&codex_home))
+    let mut files = if source_dirs.is_empty() && options.codex_path.is_some() {
+        glob_codex_usage_files(std::slice::from_ref(&codex_home), options)
     } else {
-        glob_codex_usage_files(&source_dirs)
+        glob_codex_usage_files(&source_dirs, options)
     };
+    if let Some(session) = &options.session {
+        files.retain(|file| path_mentions_session(file, session));
+    }
+    let files = skip_files_outside_range(files, PathBuf::as_path, options);
+    record_phase(Phase::Discovery, discovery, Some(files.len()));
     if files.is_empty() {
-        return Ok(Vec::new());
+        return Ok((Vec::new(), false));
     }
 
-    let pricing = if matches!(options.mode, CostMode::Display) {
-        None
-    } else {
-        Some(PricingFetcher::new())
-    };
-    let mut aggregates: HashMap<GroupKey, Aggregate> = HashMap::new();
-    let needs_project_grouping = options.group_by_project;
-    let pricing_ref = pricing.as_ref();
     let codex_fast_speed = resolve_codex_fast_speed(&codex_home);
-    let mut processed_hashes = HashSet::new();
 
+    let parsing = phase_start();
     let mut parsed_files = files
         .par_iter()
-        .map(|file| parse_codex_file_records(file, parsed_timezone))
+        .map(|file| parse_codex_file_records(file, timezone, options.read_limits))
         .collect::<Result<Vec<_>>>()?;
+    record_phase(
+        Phase::Parsing,
+        parsing,
+        Some(parsed_record_count(&parsed_files)),
+    );
+    let sorting = phase_start();
     parsed_files.sort_by(compare_parsed_file_records);
+    record_phase(Phase::Sorting, sorting, Some(parsed_files.len()));
 
-    for parsed_file in parsed_files {
-        for record in parsed_file.records {
-            if let Some(hash) = record.unique_hash.as_ref()
-                && !processed_hashes.insert(hash.clone())
-            {
-                continue;
-            }
-            aggregate_usage_record(
-                &mut aggregates,
-                (record.date, record.project),
-                needs_project_grouping,
-                record.model.as_deref(),
-                &record.tokens,
-                record.total_tokens,
-                record.cost,
-            );
-        }
-    }
-
-    recalculate_codex_aggregate_costs(&mut aggregates, pricing_ref, codex_fast_speed);
-
-    let filtered = filter_by_date_range(
-        aggregates_to_daily_usage(aggregates),
-        |item| item.date.as_str(),
-        options.since.as_deref(),
-        options.until.as_deref(),
+    let dedup = phase_start();
+    let data_dir = Arc::<Path>::from(codex_home);
+    let records = dedupe_records_by_hash(
+        parsed_files
+            .into_iter()
+            .flat_map(|parsed_file| parsed_file.records)
+            .map(|record| ParsedRecord {
+                data_dir: Some(data_dir.clone()),
+                ..record
+            }),
+        options.dedup,
     );
-    Ok(sort_by_date(
-        filtered,
-        |item| item.date.as_str(),
-        options.order,
-    ))
+    record_phase(Phase::Dedup, dedup, Some(records.len()));
+    Ok((retain_models(records, &options.models), codex_fast_speed))
 }
 
-fn load_opencode_daily_usage_data(options: &LoadOptions) -> Result<Vec<DailyUsage>> {
+fn load_codex_daily_usage_data(options: &LoadOptions) -> Result<Vec<DailyUsage>> {
     if options.project.is_some() {
         return Ok(Vec::new());
     }
 
-    let parsed_timezone = match options.timezone.as_deref() {
-        Some(tz_str) => Tz::from_str(tz_str).ok(),
-        None => None,
+    let Some(parsed_timezone) = resolve_timezone(options) else {
+        return Ok(Vec::new());
     };
-    if options.timezone.is_some() && parsed_timezone.is_none() {
+
+    let (records, codex_fast_speed) = load_codex_records(options, parsed_timezone)?;
+    if records.is_empty() {
         return Ok(Vec::new());
     }
 
+    let aggregation = phase_start();
+    let pricing = pricing_for_mode(options.mode);
+    let mut aggregates = aggregate_records(records, options.group_by_project, parsed_timezone);
+
+    recalculate_codex_aggregate_costs(&mut aggregates, pricing.as_ref(), codex_fast_speed);
+
+    let filtered = filter_by_date_range(
+        aggregates_to_daily_usage(aggregates),
+        |item| item.date.as_str(),
+        options.since.as_deref(),
+        options.until.as_deref(),
+    );
+    let sorted = sort_by_date(filtered, |item| item.date.as_str(), options.order);
+    record_phase(Phase::Aggregation, aggregation, None);
+    Ok(sorted)
+}
+
+fn load_opencode_records(options: &LoadOptions, timezone: Option<Tz>) -> Vec<ParsedRecord> {
     let base_path = if let Some(path) = &options.opencode_path {
         if path.exists() {
             path.clone()
         } else {
-            return Ok(Vec::new());
+            return Vec::new();
         }
     } else {
         match opencode_base_dir() {
             Some(path) => path,
-            None => return Ok(Vec::new()),
+            None => return Vec::new(),
         }
     };
 
-    let pricing = if matches!(options.mode, CostMode::Display) {
-        None
-    } else {
-        Some(PricingFetcher::new())
-    };
-
+    let pricing = pricing_for_mode(options.mode);
     let pricing_ref = pricing.as_ref();
+    let parsing = phase_start();
+    let data_dir = Arc::<Path>::from(base_path.as_path());
     let parsed_records = if let Some(db_path) = resolve_opencode_db_path(base_path.clone()) {
-        match load_opencode_sqlite_records(&db_path, parsed_timezone, options, pricing_ref) {
+        match load_opencode_sqlite_records(&db_path, timezone, options, pricing_ref) {
             Ok(records) => records,
             Err(_) => match resolve_opencode_messages_dir(base_path.clone()) {
                 Some(messages_dir) => {
-                    load_opencode_json_records(&messages_dir, parsed_timezone, options, pricing_ref)
+                    load_opencode_json_records(&messages_dir, timezone, options, pricing_ref)
                 }
                 None => Vec::new(),
             },
@@ -2182,61 +3630,91 @@ fn load_opencode_daily_usage_data(options: &LoadOptions) -> Result<Vec<DailyUsag
     } else {
         match resolve_opencode_messages_dir(base_path) {
             Some(messages_dir) => {
-                load_opencode_json_records(&messages_dir, parsed_timezone, options, pricing_ref)
+                load_opencode_json_records(&messages_dir, timezone, options, pricing_ref)
             }
             None => Vec::new(),
         }
     };
 
-    if parsed_records.is_empty() {
-        return Ok(Vec::new());
-    }
+    record_phase(Phase::Parsing, parsing, Some(parsed_records.len()));
 
-    let needs_project_grouping = options.group_by_project;
-    let mut processed_hashes = HashSet::new();
-    let mut aggregates: HashMap<GroupKey, Aggregate> = HashMap::new();
+    let dedup = phase_start();
+    let records = dedupe_records_by_hash(
+        parsed_records.into_iter().map(|record| ParsedRecord {
+            data_dir: Some(data_dir.clone()),
+            ..record
+        }),
+        options.dedup,
+    );
+    record_phase(Phase::Dedup, dedup, Some(records.len()));
+    retain_models(records, &options.models)
+}
 
-    for record in parsed_records {
-        let ParsedRecord {
-            unique_hash,
-            date,
-            project,
-            model,
-            tokens,
-            total_tokens,
-            cost,
-            ..
-        } = record;
+/// Notes a record dropped by deduplication for `--report-duplicates`.
+fn report_duplicate(record: &ParsedRecord) {
+    record_duplicate(
+        record.source_file.as_deref(),
+        record.source_line,
+        &record.timestamp,
+    );
+}
 
-        if let Some(hash) = unique_hash
-            && !processed_hashes.insert(hash)
-        {
-            continue;
+fn dedupe_records_by_hash(
+    records: impl IntoIterator<Item = ParsedRecord>,
+    policy: DedupPolicy,
+) -> Vec<ParsedRecord> {
+    let mut indexes: HashMap<String, usize> = HashMap::new();
+    let mut deduped: Vec<ParsedRecord> = Vec::new();
+    for record in records {
+        let existing = match (policy, record.unique_hash.as_ref()) {
+            (DedupPolicy::Off, _) | (_, None) => None,
+            (_, Some(hash)) => indexes.get(hash).copied(),
+        };
+        match existing {
+            Some(index) if policy == DedupPolicy::KeepLatest => {
+                report_duplicate(&deduped[index]);
+                deduped[index] = record;
+            }
+            Some(_) => report_duplicate(&record),
+            None => {
+                if policy != DedupPolicy::Off
+                    && let Some(hash) = record.unique_hash.clone()
+                {
+                    indexes.insert(hash, deduped.len());
+                }
+                deduped.push(record);
+            }
         }
+    }
+    deduped
+}
 
-        aggregate_usage_record(
-            &mut aggregates,
-            (date, project),
-            needs_project_grouping,
-            model.as_deref(),
-            &tokens,
-            total_tokens,
-            cost,
-        );
+fn load_opencode_daily_usage_data(options: &LoadOptions) -> Result<Vec<DailyUsage>> {
+    if options.project.is_some() {
+        return Ok(Vec::new());
+    }
+
+    let Some(parsed_timezone) = resolve_timezone(options) else {
+        return Ok(Vec::new());
+    };
+
+    let records = load_opencode_records(options, parsed_timezone);
+    if records.is_empty() {
+        return Ok(Vec::new());
     }
 
+    let aggregation = phase_start();
+    let aggregates = aggregate_records(records, options.group_by_project, parsed_timezone);
+
     let filtered = filter_by_date_range(
         aggregates_to_daily_usage(aggregates),
         |item| item.date.as_str(),
         options.since.as_deref(),
         options.until.as_deref(),
     );
-
-    Ok(sort_by_date(
-        filtered,
-        |item| item.date.as_str(),
-        options.order,
-    ))
+    let sorted = sort_by_date(filtered, |item| item.date.as_str(), options.order);
+    record_phase(Phase::Aggregation, aggregation, None);
+    Ok(sorted)
 }
 
 fn merge_daily_usage(entries: Vec<DailyUsage>, order: SortOrder) -> Vec<DailyUsage> {
@@ -2251,6 +3729,19 @@ fn merge_daily_usage(entries: Vec<DailyUsage>, order: SortOrder) -> Vec<DailyUsa
         aggregate.cache_read_tokens += entry.cache_read_tokens;
         aggregate.total_tokens += entry.total_tokens;
         aggregate.total_cost += entry.total_cost;
+        aggregate.message_count += entry.message_count;
+        aggregate.subagent_cost += entry.subagent_cost;
+        aggregate.tool_calls += entry.tool_calls;
+        aggregate.api_errors += entry.api_errors;
+        aggregate.logged_costs += entry.logged_costs;
+        aggregate.session_ids.extend(entry.session_ids);
+        widen_activity(
+            &mut aggregate.first_timestamp,
+            &mut aggregate.last_timestamp,
+            entry.first_timestamp,
+            entry.last_timestamp,
+        );
+        aggregate.hour_mask |= entry.hour_mask;
         for model in entry.models_used {
             aggregate.push_model(&model);
         }
@@ -2275,7 +3766,6 @@ fn merge_daily_usage(entries: Vec<DailyUsage>, order: SortOrder) -> Vec<DailyUsa
         let mut model_breakdowns = aggregate
             .model_breakdowns
             .into_iter()
-            .filter(|(name, _)| name != "<synthetic>")
             .map(|(model_name, stats)| ModelBreakdown {
                 model_name,
                 input_tokens: stats.input_tokens,
@@ -2302,6 +3792,15 @@ fn merge_daily_usage(entries: Vec<DailyUsage>, order: SortOrder) -> Vec<DailyUsa
             models_used: aggregate.models_used,
             model_breakdowns,
             project,
+            message_count: aggregate.message_count,
+            subagent_cost: aggregate.subagent_cost,
+            tool_calls: aggregate.tool_calls,
+            api_errors: aggregate.api_errors,
+            logged_costs: aggregate.logged_costs,
+            session_ids: sorted_session_ids(aggregate.session_ids),
+            first_timestamp: aggregate.first_timestamp,
+            last_timestamp: aggregate.last_timestamp,
+            hour_mask: aggregate.hour_mask,
         });
     }
 
@@ -2324,7 +3823,18 @@ pub fn load_daily_usage_data(options: LoadOptions) -> Result<Vec<DailyUsage>> {
     if all_entries.is_empty() {
         return Ok(Vec::new());
     }
-    Ok(merge_daily_usage(all_entries, options.order))
+    let aggregation = phase_start();
+    let mut merged = merge_daily_usage(all_entries, options.order);
+    if !options.include_synthetic {
+        for entry in &mut merged {
+            entry.models_used.retain(|model| model != SYNTHETIC_MODEL);
+            entry
+                .model_breakdowns
+                .retain(|breakdown| breakdown.model_name != SYNTHETIC_MODEL);
+        }
+    }
+    record_phase(Phase::Aggregation, aggregation, None);
+    Ok(merged)
 }
 
 pub fn load_monthly_usage_data(options: LoadOptions) -> Result<Vec<MonthlyUsage>> {
@@ -2333,6 +3843,7 @@ pub fn load_monthly_usage_data(options: LoadOptions) -> Result<Vec<MonthlyUsage>
         return Ok(Vec::new());
     }
 
+    let aggregation = phase_start();
     let mut aggregates: HashMap<MonthKey, Aggregate> = HashMap::new();
     let needs_project_grouping = options.group_by_project || options.project.is_some();
 
@@ -2362,6 +3873,19 @@ pub fn load_monthly_usage_data(options: LoadOptions) -> Result<Vec<MonthlyUsage>
         aggregate.cache_read_tokens += entry.cache_read_tokens;
         aggregate.total_tokens += entry.total_tokens;
         aggregate.total_cost += entry.total_cost;
+        aggregate.message_count += entry.message_count;
+        aggregate.subagent_cost += entry.subagent_cost;
+        aggregate.tool_calls += entry.tool_calls;
+        aggregate.api_errors += entry.api_errors;
+        aggregate.logged_costs += entry.logged_costs;
+        widen_activity(
+            &mut aggregate.first_timestamp,
+            &mut aggregate.last_timestamp,
+            entry.first_timestamp,
+            entry.last_timestamp,
+        );
+        aggregate.active_hours += entry.active_hours();
+        aggregate.session_ids.extend(entry.session_ids);
         for model in entry.models_used {
             aggregate.push_model(&model);
         }
@@ -2386,7 +3910,6 @@ pub fn load_monthly_usage_data(options: LoadOptions) -> Result<Vec<MonthlyUsage>
         let mut model_breakdowns = aggregate
             .model_breakdowns
             .into_iter()
-            .filter(|(name, _)| name != "<synthetic>")
             .map(|(model_name, stats)| ModelBreakdown {
                 model_name,
                 input_tokens: stats.input_tokens,
@@ -2416,16 +3939,281 @@ pub fn load_monthly_usage_data(options: LoadOptions) -> Result<Vec<MonthlyUsage>
             models_used,
             model_breakdowns,
             project,
+            message_count: aggregate.message_count,
+            subagent_cost: aggregate.subagent_cost,
+            tool_calls: aggregate.tool_calls,
+            api_errors: aggregate.api_errors,
+            logged_costs: aggregate.logged_costs,
+            session_ids: sorted_session_ids(aggregate.session_ids),
+            first_timestamp: aggregate.first_timestamp,
+            last_timestamp: aggregate.last_timestamp,
+            active_hours: aggregate.active_hours,
         });
     }
 
     let results = sort_by_date(results, |item| item.month.as_str(), options.order);
-
+    record_phase(Phase::Aggregation, aggregation, None);
     Ok(results)
 }
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum UsageSource {
+    #[default]
+    ClaudeCode,
+    Codex,
+    OpenCode,
+}
+
+impl UsageSource {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::ClaudeCode => "claudecode",
+            Self::Codex => "codex",
+            Self::OpenCode => "opencode",
+        }
+    }
+}
+
+/// A single deduplicated usage record, before any daily or monthly aggregation.
+#[derive(Debug, Clone, Default)]
+pub struct UsageEntry {
+    pub source: UsageSource,
+    pub timestamp: DateTime<Utc>,
+    pub date: String,
+    pub session_id: Option<String>,
+    pub project: Option<String>,
+    pub model: Option<String>,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub total_tokens: u64,
+    pub cost: f64,
+    /// Whether the request came from a subagent (Task tool) sidechain.
+    pub is_sidechain: bool,
+    /// Number of `tool_use` blocks in the message (Claude Code only).
+    pub tool_calls: u64,
+    /// Whether this is Claude Code's placeholder for a failed API request.
+    pub is_api_error: bool,
+    /// Whether `cost` is the log's own figure (`costUSD`) rather than calculated from tokens.
+    pub cost_logged: bool,
+    /// Hour of day (0-23) in the grouping timezone, matching `date`.
+    pub local_hour: u32,
+    /// API service tier the request ran on, when the log records it.
+    pub service_tier: Option<String>,
+    /// Why generation ended (`end_turn`, `max_tokens`, `refusal`, ...), when the log records it.
+    pub stop_reason: Option<String>,
+    /// Log file (or OpenCode database) the entry was read from.
+    pub source_file: Option<PathBuf>,
+    /// 1-based line of the entry in `source_file`, for JSONL logs.
+    pub source_line: Option<usize>,
+    /// Data directory the entry was found under: the Claude data root, Codex home, or OpenCode
+    /// directory.
+    pub data_dir: Option<PathBuf>,
+}
+
+fn usage_entry_from_record(
+    record: ParsedRecord,
+    source: UsageSource,
+    timezone: Option<Tz>,
+) -> Option<UsageEntry> {
+    let timestamp = DateTime::parse_from_rfc3339(&record.timestamp)
+        .ok()?
+        .with_timezone(&Utc);
+    let is_sidechain = is_sidechain_record(&record);
+    Some(UsageEntry {
+        source,
+        timestamp,
+        date: record.date,
+        session_id: record.session_id,
+        project: record.project.map(|project| project.to_string()),
+        model: record.model.map(|model| model.to_string()),
+        input_tokens: record.tokens.input_tokens,
+        output_tokens: record.tokens.output_tokens,
+        cache_creation_tokens: record.tokens.cache_creation_input_tokens,
+        cache_read_tokens: record.tokens.cache_read_input_tokens,
+        total_tokens: record.total_tokens,
+        cost: record.cost,
+        is_sidechain,
+        tool_calls: record.tool_use_ids.len() as u64,
+        is_api_error: record.is_api_error,
+        cost_logged: record.cost_logged,
+        local_hour: local_hour(timestamp, timezone),
+        service_tier: record.service_tier,
+        stop_reason: record.stop_reason,
+        source_file: record.source_file.map(|file| file.to_path_buf()),
+        source_line: record.source_line,
+        data_dir: record.data_dir.map(|dir| dir.to_path_buf()),
+    })
+}
+
+/// Loads deduplicated per-request usage entries from every enabled source, filtered by
+/// `since`/`until` and sorted by timestamp in `options.order`.
+pub fn load_usage_entries(options: &LoadOptions) -> Result<Vec<UsageEntry>> {
+    let Some(timezone) = resolve_timezone(options) else {
+        return Ok(Vec::new());
+    };
+
+    let mut entries = Vec::new();
+    if options.claudecode {
+        entries.extend(
+            load_claude_records(options, timezone, true)?
+                .into_iter()
+                .filter_map(|record| {
+                    usage_entry_from_record(record, UsageSource::ClaudeCode, timezone)
+                }),
+        );
+    }
+    if options.codex && options.project.is_none() {
+        let (records, codex_fast_speed) = load_codex_records(options, timezone)?;
+        let pricing = pricing_for_mode(options.mode);
+        entries.extend(records.into_iter().filter_map(|mut record| {
+            if let Some(fetcher) = pricing.as_ref() {
+                record.cost = fetcher.calculate_codex_cost_from_tokens(
+                    &record.tokens,
+                    record.model.as_deref(),
+                    codex_fast_speed,
+                );
+            }
+            if codex_fast_speed {
+                record.service_tier = Some(CODEX_FAST_SERVICE_TIER.to_string());
+            }
+            usage_entry_from_record(record, UsageSource::Codex, timezone)
+        }));
+    }
+    if options.opencode && options.project.is_none() {
+        entries.extend(
+            load_opencode_records(options, timezone)
+                .into_iter()
+                .filter_map(|record| {
+                    usage_entry_from_record(record, UsageSource::OpenCode, timezone)
+                }),
+        );
+    }
+
+    let aggregation = phase_start();
+    if let Some(session) = &options.session {
+        entries.retain(|entry| {
+            entry
+                .session_id
+                .as_deref()
+                .is_some_and(|id| id.starts_with(session.as_str()))
+        });
+    }
+
+    let mut entries = filter_by_date_range(
+        entries,
+        |entry| entry.date.as_str(),
+        options.since.as_deref(),
+        options.until.as_deref(),
+    );
+    entries.sort_by(|a, b| match options.order {
+        SortOrder::Asc => a.timestamp.cmp(&b.timestamp),
+        SortOrder::Desc => b.timestamp.cmp(&a.timestamp),
+    });
+    record_phase(Phase::Aggregation, aggregation, None);
+    Ok(entries)
+}
+
+/// Titles of the Claude Code sessions in `entries`, keyed by session id, read from the summary
+/// lines in each session's log or, for sessions without one, the first prompt typed. Sessions
+/// with neither are left out.
+pub fn load_session_titles(entries: &[UsageEntry], limits: ReadLimits) -> HashMap<String, String> {
+    let mut logs = HashMap::new();
+    for entry in entries {
+        if entry.source != UsageSource::ClaudeCode {
+            continue;
+        }
+        if let (Some(session_id), Some(file)) = (&entry.session_id, &entry.source_file)
+            && session_id_from_path(file).as_deref() == Some(session_id.as_str())
+        {
+            logs.entry(session_id.clone())
+                .or_insert_with(|| file.clone());
+        }
+    }
+    logs.into_par_iter()
+        .filter_map(|(session_id, file)| {
+            session_title_from_file(&file, limits).map(|title| (session_id, title))
+        })
+        .collect()
+}
+
+/// A resumed session's log also carries the summaries of earlier conversations, so the summary
+/// whose leaf message is in this log wins; otherwise the last summary is used, and without
+/// summaries the first prompt.
+fn session_title_from_file(file: &Path, limits: ReadLimits) -> Option<String> {
+    let mut summaries = Vec::new();
+    let mut uuids = HashSet::new();
+    let mut first_prompt = None;
+    let _ = process_jsonl_file_by_line_bytes(file, limits, |line, _| {
+        if first_prompt.is_none() && line_contains_any_marker(line, &[USER_MARKER]) {
+            first_prompt = sonic_rs::from_slice::<SessionPromptLine>(line)
+                .ok()
+                .and_then(prompt_title);
+        }
+        if line_contains_any_marker(line, &[SUMMARY_MARKER]) {
+            if let Ok(SessionSummaryLine {
+                summary: Some(summary),
+                leaf_uuid,
+            }) = sonic_rs::from_slice::<SessionSummaryLine>(line)
+                && !summary.trim().is_empty()
+            {
+                summaries.push((summary.trim().to_string(), leaf_uuid));
+            }
+        } else if let Some(uuid) = extract_json_string_marker(line, UUID_MARKER) {
+            uuids.insert(uuid.to_string());
+        }
+        Ok(())
+    });
+    let own = summaries
+        .iter()
+        .rposition(|(_, leaf)| leaf.as_ref().is_some_and(|leaf| uuids.contains(leaf)));
+    match own.or_else(|| summaries.len().checked_sub(1)) {
+        Some(index) => Some(summaries.swap_remove(index).0),
+        None => first_prompt,
+    }
+}
+
+/// First line of a typed prompt. Tool results, slash-command and hook output (wrapped in
+/// `<...>` tags), and meta lines are not prompts.
+fn prompt_title(line: SessionPromptLine) -> Option<String> {
+    if line.is_meta {
+        return None;
+    }
+    let text = match line.message?.content? {
+        SessionPromptContent::Text(text) => text,
+        SessionPromptContent::Blocks(blocks) => {
+            blocks
+                .into_iter()
+                .find(|block| block.kind.as_deref() == Some("text"))?
+                .text?
+        }
+    };
+    let text = text.trim();
+    if text.starts_with('<') || text.starts_with("Caveat:") {
+        return None;
+    }
+    let first_line = text.lines().map(str::trim).find(|line| !line.is_empty())?;
+    let mut title = first_line
+        .chars()
+        .take(PROMPT_TITLE_MAX_CHARS)
+        .collect::<String>();
+    if first_line.chars().count() > PROMPT_TITLE_MAX_CHARS {
+        title.pop();
+        title.push('…');
+    }
+    Some(title)
+}
+
+fn sorted_session_ids(session_ids: HashSet<String>) -> Vec<String> {
+    let mut session_ids = session_ids.into_iter().collect::<Vec<_>>();
+    session_ids.sort_unstable();
+    session_ids
+}
+
 pub fn calculate_totals_daily(data: &[DailyUsage]) -> UsageTotals {
     let mut totals = UsageTotals::default();
+    let mut sessions = HashSet::new();
     for item in data {
         totals.input_tokens += item.input_tokens;
         totals.output_tokens += item.output_tokens;
@@ -2433,12 +4221,27 @@ pub fn calculate_totals_daily(data: &[DailyUsage]) -> UsageTotals {
         totals.cache_read_tokens += item.cache_read_tokens;
         totals.total_tokens += item.total_tokens;
         totals.total_cost += item.total_cost;
+        totals.message_count += item.message_count;
+        totals.subagent_cost += item.subagent_cost;
+        totals.tool_calls += item.tool_calls;
+        totals.api_errors += item.api_errors;
+        totals.logged_costs += item.logged_costs;
+        sessions.extend(&item.session_ids);
+        widen_activity(
+            &mut totals.first_timestamp,
+            &mut totals.last_timestamp,
+            item.first_timestamp,
+            item.last_timestamp,
+        );
+        totals.active_hours += item.active_hours();
     }
+    totals.sessions = sessions.len() as u64;
     totals
 }
 
 pub fn calculate_totals_monthly(data: &[MonthlyUsage]) -> UsageTotals {
     let mut totals = UsageTotals::default();
+    let mut sessions = HashSet::new();
     for item in data {
         totals.input_tokens += item.input_tokens;
         totals.output_tokens += item.output_tokens;
@@ -2446,28 +4249,218 @@ pub fn calculate_totals_monthly(data: &[MonthlyUsage]) -> UsageTotals {
         totals.cache_read_tokens += item.cache_read_tokens;
         totals.total_tokens += item.total_tokens;
         totals.total_cost += item.total_cost;
+        totals.message_count += item.message_count;
+        totals.subagent_cost += item.subagent_cost;
+        totals.tool_calls += item.tool_calls;
+        totals.api_errors += item.api_errors;
+        totals.logged_costs += item.logged_costs;
+        sessions.extend(&item.session_ids);
+        widen_activity(
+            &mut totals.first_timestamp,
+            &mut totals.last_timestamp,
+            item.first_timestamp,
+            item.last_timestamp,
+        );
+        totals.active_hours += item.active_hours;
     }
+    totals.sessions = sessions.len() as u64;
     totals
 }
 
-#[derive(Debug, Default, Clone, Serialize)]
-pub struct UsageTotals {
-    pub input_tokens: u64,
-    pub output_tokens: u64,
-    pub cache_creation_tokens: u64,
-    pub cache_read_tokens: u64,
-    pub total_tokens: u64,
-    pub total_cost: f64,
-}
-
-impl UsageTotals {
-    pub fn total_tokens(&self) -> u64 {
-        self.total_tokens
-    }
-}
-
-pub fn group_daily_by_project(data: &[DailyUsage]) -> HashMap<String, Vec<DailyUsage>> {
-    let mut projects: HashMap<String, Vec<DailyUsage>> = HashMap::new();
+pub fn calculate_totals_entries(data: &[UsageEntry]) -> UsageTotals {
+    let mut totals = UsageTotals::default();
+    let mut hours = HashSet::new();
+    let mut sessions = HashSet::new();
+    for item in data {
+        totals.input_tokens += item.input_tokens;
+        totals.output_tokens += item.output_tokens;
+        totals.cache_creation_tokens += item.cache_creation_tokens;
+        totals.cache_read_tokens += item.cache_read_tokens;
+        totals.total_tokens += item.total_tokens;
+        totals.total_cost += item.cost;
+        totals.message_count += u64::from(!item.is_api_error);
+        if item.is_sidechain {
+            totals.subagent_cost += item.cost;
+        }
+        totals.tool_calls += item.tool_calls;
+        totals.api_errors += u64::from(item.is_api_error);
+        totals.logged_costs += u64::from(item.cost_logged);
+        widen_activity(
+            &mut totals.first_timestamp,
+            &mut totals.last_timestamp,
+            Some(item.timestamp),
+            Some(item.timestamp),
+        );
+        hours.insert((item.date.as_str(), item.local_hour));
+        if let Some(session_id) = &item.session_id {
+            sessions.insert(session_id.as_str());
+        }
+    }
+    totals.active_hours = hours.len() as u32;
+    totals.sessions = sessions.len() as u64;
+    totals
+}
+
+/// Cost by local weekday (Monday first) and hour of day, in the grouping timezone.
+pub fn weekday_hour_costs(data: &[UsageEntry]) -> [[f64; 24]; 7] {
+    let mut matrix = [[0.0; 24]; 7];
+    for item in data {
+        let Ok(date) = NaiveDate::parse_from_str(&item.date, "%Y-%m-%d") else {
+            continue;
+        };
+        let weekday = date.weekday().num_days_from_monday() as usize;
+        if let Some(cell) = matrix[weekday].get_mut(item.local_hour as usize) {
+            *cell += item.cost;
+        }
+    }
+    matrix
+}
+
+/// Usage in one local hour of one day, as returned by `UsageReport::hourly`.
+#[derive(Debug, Clone, Default)]
+pub struct HourlyUsage {
+    /// Day of the hour, as `YYYY-MM-DD` in the grouping timezone.
+    pub date: String,
+    /// Hour of day (0-23) in the grouping timezone.
+    pub hour: u32,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub total_tokens: u64,
+    pub total_cost: f64,
+    pub models_used: Vec<String>,
+    /// Per-model usage, most expensive first.
+    pub model_breakdowns: Vec<ModelBreakdown>,
+    pub message_count: u64,
+    pub subagent_cost: f64,
+    pub tool_calls: u64,
+    pub api_errors: u64,
+    /// Distinct sessions with usage in the hour.
+    pub session_count: usize,
+    pub first_timestamp: Option<DateTime<Utc>>,
+    pub last_timestamp: Option<DateTime<Utc>>,
+}
+
+/// Buckets entries by local day and hour of day, oldest hour first. Hours without usage are
+/// left out.
+pub fn group_entries_by_hour(entries: &[UsageEntry]) -> Vec<HourlyUsage> {
+    let mut hours: BTreeMap<(&str, u32), Aggregate> = BTreeMap::new();
+    for entry in entries {
+        let aggregate = hours
+            .entry((entry.date.as_str(), entry.local_hour))
+            .or_default();
+        aggregate.input_tokens += entry.input_tokens;
+        aggregate.output_tokens += entry.output_tokens;
+        aggregate.cache_creation_tokens += entry.cache_creation_tokens;
+        aggregate.cache_read_tokens += entry.cache_read_tokens;
+        aggregate.total_tokens += entry.total_tokens;
+        aggregate.total_cost += entry.cost;
+        aggregate.message_count += u64::from(!entry.is_api_error);
+        aggregate.tool_calls += entry.tool_calls;
+        aggregate.api_errors += u64::from(entry.is_api_error);
+        if entry.is_sidechain {
+            aggregate.subagent_cost += entry.cost;
+        }
+        if let Some(session_id) = &entry.session_id {
+            aggregate.session_ids.insert(session_id.clone());
+        }
+        widen_activity(
+            &mut aggregate.first_timestamp,
+            &mut aggregate.last_timestamp,
+            Some(entry.timestamp),
+            Some(entry.timestamp),
+        );
+        if let Some(model) = entry.model.as_deref() {
+            aggregate.push_model(model);
+        }
+        update_model_breakdowns(
+            &mut aggregate.model_breakdowns,
+            entry.model.as_deref().unwrap_or("unknown"),
+            &UsageTokens {
+                input_tokens: entry.input_tokens,
+                output_tokens: entry.output_tokens,
+                cache_creation_input_tokens: entry.cache_creation_tokens,
+                cache_read_input_tokens: entry.cache_read_tokens,
+            },
+            entry.total_tokens,
+            entry.cost,
+        );
+    }
+    hours
+        .into_iter()
+        .map(|((date, hour), aggregate)| {
+            let mut model_breakdowns = aggregate
+                .model_breakdowns
+                .into_iter()
+                .map(|(model_name, stats)| ModelBreakdown {
+                    model_name,
+                    input_tokens: stats.input_tokens,
+                    output_tokens: stats.output_tokens,
+                    cache_creation_tokens: stats.cache_creation_tokens,
+                    cache_read_tokens: stats.cache_read_tokens,
+                    total_tokens: stats.total_tokens,
+                    cost: stats.cost,
+                })
+                .collect::<Vec<_>>();
+            model_breakdowns.sort_by(|a, b| b.cost.total_cmp(&a.cost));
+            HourlyUsage {
+                date: date.to_string(),
+                hour,
+                input_tokens: aggregate.input_tokens,
+                output_tokens: aggregate.output_tokens,
+                cache_creation_tokens: aggregate.cache_creation_tokens,
+                cache_read_tokens: aggregate.cache_read_tokens,
+                total_tokens: aggregate.total_tokens,
+                total_cost: aggregate.total_cost,
+                models_used: aggregate.models_used,
+                model_breakdowns,
+                message_count: aggregate.message_count,
+                subagent_cost: aggregate.subagent_cost,
+                tool_calls: aggregate.tool_calls,
+                api_errors: aggregate.api_errors,
+                session_count: aggregate.session_ids.len(),
+                first_timestamp: aggregate.first_timestamp,
+                last_timestamp: aggregate.last_timestamp,
+            }
+        })
+        .collect()
+}
+
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct UsageTotals {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub total_tokens: u64,
+    pub total_cost: f64,
+    pub message_count: u64,
+    pub subagent_cost: f64,
+    pub tool_calls: u64,
+    pub api_errors: u64,
+    pub logged_costs: u64,
+    /// Distinct sessions across all rows.
+    pub sessions: u64,
+    pub first_timestamp: Option<DateTime<Utc>>,
+    pub last_timestamp: Option<DateTime<Utc>>,
+    pub active_hours: u32,
+}
+
+impl UsageTotals {
+    pub fn total_tokens(&self) -> u64 {
+        self.total_tokens
+    }
+
+    /// Fraction of entries whose cost was read from the log, or `None` without entries.
+    pub fn logged_cost_share(&self) -> Option<f64> {
+        let entries = self.message_count + self.api_errors;
+        (entries > 0).then(|| self.logged_costs as f64 / entries as f64)
+    }
+}
+
+pub fn group_daily_by_project(data: &[DailyUsage]) -> HashMap<String, Vec<DailyUsage>> {
+    let mut projects: HashMap<String, Vec<DailyUsage>> = HashMap::new();
     for item in data {
         let project = item
             .project
@@ -2483,6 +4476,7 @@ mod tests {
     use super::*;
     use rusqlite::{Connection, params};
     use serde_json::json;
+    use std::collections::BTreeSet;
     use tempfile::TempDir;
 
     fn write_file(base: &Path, rel: &str, content: &str) {
@@ -2526,7 +4520,7 @@ mod tests {
         let fixture = create_fixture();
         write_file(fixture.path(), "projects", "");
         let result = load_daily_usage_data(LoadOptions {
-            claude_path: Some(fixture.path().to_path_buf()),
+            claude_paths: vec![fixture.path().to_path_buf()],
             timezone: Some("UTC".to_string()),
             ..LoadOptions::default()
         })
@@ -2570,7 +4564,7 @@ mod tests {
         );
 
         let result = load_daily_usage_data(LoadOptions {
-            claude_path: Some(fixture.path().to_path_buf()),
+            claude_paths: vec![fixture.path().to_path_buf()],
             timezone: Some("UTC".to_string()),
             ..LoadOptions::default()
         })
@@ -2583,6 +4577,47 @@ mod tests {
         assert_eq!(result[0].total_cost, 0.06);
     }
 
+    #[test]
+    fn load_daily_usage_includes_imported_records() {
+        let fixture = create_fixture();
+        let imports = create_fixture();
+        write_file(
+            fixture.path(),
+            "projects/app/session.jsonl",
+            &json!({
+                "timestamp": "2024-01-01T10:00:00Z",
+                "message": { "usage": { "input_tokens": 100, "output_tokens": 50 } },
+                "costUSD": 0.01
+            })
+            .to_string(),
+        );
+        write_file(
+            imports.path(),
+            "projects/imported/ccusage.jsonl",
+            &json!({
+                "timestamp": "2023-12-01T12:00:00.000Z",
+                "requestId": "import-ccusage-0",
+                "message": { "id": "import-ccusage-0", "usage": { "input_tokens": 7, "output_tokens": 3 } },
+                "costUSD": 1.5
+            })
+            .to_string(),
+        );
+
+        let result = load_daily_usage_data(LoadOptions {
+            claude_paths: vec![fixture.path().to_path_buf()],
+            import_path: Some(imports.path().to_path_buf()),
+            timezone: Some("UTC".to_string()),
+            order: SortOrder::Asc,
+            ..LoadOptions::default()
+        })
+        .unwrap();
+
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].date, "2023-12-01");
+        assert_eq!(result[0].total_cost, 1.5);
+        assert_eq!(result[1].date, "2024-01-01");
+    }
+
     #[test]
     fn load_daily_usage_handles_cache_tokens() {
         let fixture = create_fixture();
@@ -2593,19 +4628,747 @@ mod tests {
         });
         write_file(
             fixture.path(),
-            "projects/project1/session1/file.jsonl",
-            &data.to_string(),
+            "projects/project1/session1/file.jsonl",
+            &data.to_string(),
+        );
+
+        let result = load_daily_usage_data(LoadOptions {
+            claude_paths: vec![fixture.path().to_path_buf()],
+            timezone: Some("UTC".to_string()),
+            ..LoadOptions::default()
+        })
+        .unwrap();
+
+        assert_eq!(result[0].cache_creation_tokens, 25);
+        assert_eq!(result[0].cache_read_tokens, 10);
+    }
+
+    #[test]
+    fn load_daily_and_monthly_usage_track_messages_activity_and_hours() {
+        let fixture = create_fixture();
+        let data = [
+            json!({
+                "timestamp": "2024-01-01T10:00:00Z",
+                "message": { "usage": { "input_tokens": 100, "output_tokens": 50 } },
+                "costUSD": 0.01
+            }),
+            json!({
+                "timestamp": "2024-01-01T11:00:00Z",
+                "message": { "usage": { "input_tokens": 100, "output_tokens": 50 } },
+                "costUSD": 0.01
+            }),
+            json!({
+                "timestamp": "2024-01-02T10:00:00Z",
+                "message": { "usage": { "input_tokens": 100, "output_tokens": 50 } },
+                "costUSD": 0.01
+            }),
+        ];
+        write_file(
+            fixture.path(),
+            "projects/project1/session1/file.jsonl",
+            &data
+                .iter()
+                .map(|v| v.to_string())
+                .collect::<Vec<_>>()
+                .join("\n"),
+        );
+        let options = LoadOptions {
+            claude_paths: vec![fixture.path().to_path_buf()],
+            timezone: Some("UTC".to_string()),
+            order: SortOrder::Asc,
+            ..LoadOptions::default()
+        };
+
+        let daily = load_daily_usage_data(options.clone()).unwrap();
+        let monthly = load_monthly_usage_data(options).unwrap();
+
+        assert_eq!(daily[0].message_count, 2);
+        assert_eq!(daily[1].message_count, 1);
+        assert_eq!(calculate_totals_daily(&daily).message_count, 3);
+        assert_eq!(monthly[0].message_count, 3);
+        assert_eq!(
+            daily[0]
+                .first_timestamp
+                .map(|ts| ts.to_rfc3339())
+                .as_deref(),
+            Some("2024-01-01T10:00:00+00:00")
+        );
+        assert_eq!(
+            daily[0].last_timestamp.map(|ts| ts.to_rfc3339()).as_deref(),
+            Some("2024-01-01T11:00:00+00:00")
+        );
+        assert_eq!(monthly[0].last_timestamp, daily[1].last_timestamp);
+        assert_eq!(daily[0].active_hours(), 2);
+        assert_eq!(monthly[0].active_hours, 3);
+    }
+
+    #[test]
+    fn load_daily_usage_attributes_sidechain_cost_to_subagents() {
+        let fixture = create_fixture();
+        let data = [
+            json!({
+                "timestamp": "2024-01-01T10:00:00Z",
+                "message": { "usage": { "input_tokens": 100, "output_tokens": 50 } },
+                "costUSD": 0.03
+            }),
+            json!({
+                "timestamp": "2024-01-01T10:01:00Z",
+                "isSidechain": true,
+                "message": { "usage": { "input_tokens": 100, "output_tokens": 50 } },
+                "costUSD": 0.01
+            }),
+        ];
+        write_file(
+            fixture.path(),
+            "projects/project1/session1/file.jsonl",
+            &data
+                .iter()
+                .map(|v| v.to_string())
+                .collect::<Vec<_>>()
+                .join("\n"),
+        );
+
+        let result = load_daily_usage_data(LoadOptions {
+            claude_paths: vec![fixture.path().to_path_buf()],
+            timezone: Some("UTC".to_string()),
+            ..LoadOptions::default()
+        })
+        .unwrap();
+
+        assert!((result[0].total_cost - 0.04).abs() < 1e-9);
+        assert!((result[0].subagent_cost - 0.01).abs() < 1e-9);
+    }
+
+    #[test]
+    fn load_daily_usage_counts_tool_use_blocks_across_streamed_lines() {
+        let fixture = create_fixture();
+        let line = |content: serde_json::Value| {
+            json!({
+                "timestamp": "2024-01-01T10:00:00Z",
+                "requestId": "req_1",
+                "message": {
+                    "id": "msg_1",
+                    "content": content,
+                    "usage": { "input_tokens": 100, "output_tokens": 50 }
+                },
+                "costUSD": 0.01
+            })
+            .to_string()
+        };
+        let lines = [
+            line(json!([{ "type": "text", "text": "Checking" }])),
+            line(json!([{ "type": "tool_use", "id": "toolu_1", "name": "Read", "input": {} }])),
+            line(json!([{ "type": "tool_use", "id": "toolu_2", "name": "Bash", "input": {} }])),
+            line(json!([{ "type": "tool_use", "id": "toolu_2", "name": "Bash", "input": {} }])),
+        ];
+        write_file(
+            fixture.path(),
+            "projects/project1/session1.jsonl",
+            &lines.join("\n"),
+        );
+
+        let result = load_daily_usage_data(LoadOptions {
+            claude_paths: vec![fixture.path().to_path_buf()],
+            timezone: Some("UTC".to_string()),
+            ..LoadOptions::default()
+        })
+        .unwrap();
+
+        assert_eq!(result[0].message_count, 1);
+        assert_eq!(result[0].tool_calls, 2);
+    }
+
+    #[test]
+    fn load_usage_entries_keeps_the_stop_reason_of_any_streamed_line() {
+        let fixture = create_fixture();
+        let line = |message_id: &str, stop_reason: Option<&str>, output_tokens: u64| {
+            json!({
+                "timestamp": "2024-01-01T10:00:00Z",
+                "requestId": format!("req_{message_id}"),
+                "message": {
+                    "id": message_id,
+                    "stop_reason": stop_reason,
+                    "usage": { "input_tokens": 100, "output_tokens": output_tokens }
+                },
+                "costUSD": 0.01
+            })
+            .to_string()
+        };
+        let lines = [
+            line("msg_1", None, 1),
+            line("msg_1", Some("max_tokens"), 50),
+            line("msg_2", Some("end_turn"), 50),
+            line("msg_2", None, 50),
+        ];
+        write_file(
+            fixture.path(),
+            "projects/project1/session1.jsonl",
+            &lines.join("\n"),
+        );
+
+        let mut stop_reasons = load_usage_entries(&LoadOptions {
+            claude_paths: vec![fixture.path().to_path_buf()],
+            timezone: Some("UTC".to_string()),
+            ..LoadOptions::default()
+        })
+        .unwrap()
+        .into_iter()
+        .map(|entry| entry.stop_reason)
+        .collect::<Vec<_>>();
+        stop_reasons.sort();
+
+        assert_eq!(
+            stop_reasons,
+            vec![Some("end_turn".to_string()), Some("max_tokens".to_string())]
+        );
+    }
+
+    #[test]
+    fn load_usage_entries_record_the_source_file_and_line() {
+        let fixture = create_fixture();
+        let line = |message_id: &str| {
+            json!({
+                "timestamp": "2024-01-01T10:00:00Z",
+                "requestId": format!("req_{message_id}"),
+                "message": { "id": message_id, "usage": { "input_tokens": 100, "output_tokens": 5 } },
+                "costUSD": 0.01
+            })
+            .to_string()
+        };
+        write_file(
+            fixture.path(),
+            "projects/project1/session1.jsonl",
+            &format!(
+                "{}\n{{\"type\":\"user\"}}\n\n{}",
+                line("msg_1"),
+                line("msg_2")
+            ),
+        );
+
+        let mut sources = load_usage_entries(&LoadOptions {
+            claude_paths: vec![fixture.path().to_path_buf()],
+            timezone: Some("UTC".to_string()),
+            ..LoadOptions::default()
+        })
+        .unwrap()
+        .into_iter()
+        .map(|entry| (entry.source_file, entry.source_line))
+        .collect::<Vec<_>>();
+        sources.sort();
+
+        let file = fixture
+            .path()
+            .join("projects/project1/session1.jsonl")
+            .canonicalize()
+            .unwrap();
+        assert_eq!(
+            sources,
+            vec![(Some(file.clone()), Some(1)), (Some(file), Some(4))]
+        );
+    }
+
+    #[test]
+    fn load_session_titles_prefer_the_summary_of_the_sessions_own_conversation() {
+        let fixture = create_fixture();
+        let line = |uuid: &str, message_id: &str| {
+            json!({
+                "timestamp": "2024-01-01T10:00:00Z",
+                "uuid": uuid,
+                "requestId": format!("req_{message_id}"),
+                "message": { "id": message_id, "usage": { "input_tokens": 100, "output_tokens": 5 } }
+            })
+            .to_string()
+        };
+        let summary = |title: &str, leaf: &str| {
+            json!({ "type": "summary", "summary": title, "leafUuid": leaf }).to_string()
+        };
+        write_file(
+            fixture.path(),
+            "projects/project1/resumed.jsonl",
+            &[
+                summary("Fix login bug", "u-old"),
+                summary("Add OAuth callback", "u-2"),
+                summary("Unrelated", "u-other"),
+                line("u-1", "msg_1"),
+                line("u-2", "msg_2"),
+            ]
+            .join("\n"),
+        );
+        write_file(
+            fixture.path(),
+            "projects/project1/carried.jsonl",
+            &[summary("Earlier work", "u-gone"), line("u-3", "msg_3")].join("\n"),
+        );
+        write_file(
+            fixture.path(),
+            "projects/project1/untitled.jsonl",
+            &line("u-4", "msg_4"),
+        );
+        let prompt = |content: serde_json::Value| {
+            json!({ "type": "user", "uuid": "u-p", "message": { "role": "user", "content": content } })
+                .to_string()
+        };
+        write_file(
+            fixture.path(),
+            "projects/project1/prompted.jsonl",
+            &[
+                prompt(json!("<command-name>/clear</command-name>")),
+                prompt(json!([{ "type": "tool_result", "content": "ok" }])),
+                prompt(json!([{ "type": "text", "text": "\n  Add a --dry-run flag\nto export" }])),
+                prompt(json!("Later prompt")),
+                line("u-5", "msg_5"),
+            ]
+            .join("\n"),
+        );
+
+        let entries = load_usage_entries(&LoadOptions {
+            claude_paths: vec![fixture.path().to_path_buf()],
+            timezone: Some("UTC".to_string()),
+            ..LoadOptions::default()
+        })
+        .unwrap();
+        let titles = load_session_titles(&entries, ReadLimits::default());
+
+        assert_eq!(titles.len(), 3);
+        assert_eq!(titles["resumed"], "Add OAuth callback");
+        assert_eq!(titles["carried"], "Earlier work");
+        assert_eq!(titles["prompted"], "Add a --dry-run flag");
+    }
+
+    #[test]
+    fn parse_cache_reparses_changed_files_and_forgets_deleted_ones() {
+        let fixture = create_fixture();
+        let line = |message_id: &str| {
+            json!({
+                "timestamp": "2024-01-01T10:00:00Z",
+                "requestId": format!("req_{message_id}"),
+                "message": { "id": message_id, "usage": { "input_tokens": 100, "output_tokens": 5 } }
+            })
+            .to_string()
+        };
+        write_file(fixture.path(), "projects/p/a.jsonl", &line("msg_1"));
+        write_file(fixture.path(), "projects/p/b.jsonl", &line("msg_2"));
+        let cache = Arc::new(ParseCache::default());
+        let options = LoadOptions {
+            claude_paths: vec![fixture.path().to_path_buf()],
+            timezone: Some("UTC".to_string()),
+            parse_cache: Some(cache.clone()),
+            ..LoadOptions::default()
+        };
+
+        assert_eq!(load_usage_entries(&options).unwrap().len(), 2);
+        assert_eq!(cache.len(), 2);
+
+        write_file(
+            fixture.path(),
+            "projects/p/a.jsonl",
+            &format!("{}\n{}", line("msg_1"), line("msg_3")),
+        );
+        assert_eq!(load_usage_entries(&options).unwrap().len(), 3);
+
+        std::fs::remove_file(fixture.path().join("projects/p/b.jsonl")).unwrap();
+        assert_eq!(load_usage_entries(&options).unwrap().len(), 2);
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn disk_cache_reuses_unchanged_files_across_loads() {
+        let fixture = create_fixture();
+        let cache_dir = tempfile::TempDir::new().unwrap();
+        let cache_path = cache_dir.path().join("ccost/parse-cache.sqlite");
+        let line = |message_id: &str| {
+            json!({
+                "timestamp": "2024-01-01T10:00:00Z",
+                "requestId": format!("req_{message_id}"),
+                "message": { "id": message_id, "usage": { "input_tokens": 100, "output_tokens": 5 } }
+            })
+            .to_string()
+        };
+        write_file(fixture.path(), "projects/p/a.jsonl", &line("msg_1"));
+        let options = LoadOptions {
+            claude_paths: vec![fixture.path().to_path_buf()],
+            timezone: Some("UTC".to_string()),
+            group_by_project: true,
+            cache_path: Some(cache_path.clone()),
+            ..LoadOptions::default()
+        };
+        let entries = load_usage_entries(&options).unwrap();
+        assert_eq!(entries.len(), 1);
+
+        // Emptying the stored records shows the next load reads the row instead of the file.
+        let connection = Connection::open(&cache_path).unwrap();
+        connection
+            .execute("UPDATE files SET records = '[]'", [])
+            .unwrap();
+        assert!(load_usage_entries(&options).unwrap().is_empty());
+
+        let tokyo = LoadOptions {
+            timezone: Some("Asia/Tokyo".to_string()),
+            ..options.clone()
+        };
+        assert_eq!(load_usage_entries(&tokyo).unwrap().len(), 1);
+        write_file(
+            fixture.path(),
+            "projects/p/a.jsonl",
+            &format!("{}\n{}", line("msg_1"), line("msg_2")),
+        );
+        let reparsed = load_usage_entries(&options).unwrap();
+        assert_eq!(reparsed.len(), 2);
+        assert_eq!(reparsed[0].project, entries[0].project);
+        assert_eq!(reparsed[0].source_file, entries[0].source_file);
+
+        // Rows of deleted logs are dropped, whatever options they were parsed with.
+        write_file(fixture.path(), "projects/p/b.jsonl", &line("msg_3"));
+        assert_eq!(load_usage_entries(&tokyo).unwrap().len(), 3);
+        let rows = || {
+            connection
+                .query_row("SELECT COUNT(*) FROM files", [], |row| row.get::<_, i64>(0))
+                .unwrap()
+        };
+        assert_eq!(rows(), 2);
+        std::fs::remove_file(fixture.path().join("projects/p/b.jsonl")).unwrap();
+        assert_eq!(load_usage_entries(&options).unwrap().len(), 2);
+        assert_eq!(rows(), 1);
+    }
+
+    #[test]
+    fn load_daily_usage_applies_dedup_policy() {
+        let fixture = create_fixture();
+        let line = |timestamp: &str, output_tokens: u64| {
+            json!({
+                "timestamp": timestamp,
+                "requestId": "req_1",
+                "message": {
+                    "id": "msg_1",
+                    "usage": { "input_tokens": 100, "output_tokens": output_tokens }
+                },
+                "costUSD": 0.01
+            })
+            .to_string()
+        };
+        write_file(
+            fixture.path(),
+            "projects/project1/session1.jsonl",
+            &line("2024-01-01T10:00:00Z", 50),
+        );
+        write_file(
+            fixture.path(),
+            "projects/project1/session2.jsonl",
+            &[
+                line("2024-01-01T11:00:00Z", 200),
+                line("2024-01-01T11:00:00Z", 10),
+            ]
+            .join("\n"),
+        );
+        let total_tokens = |dedup: DedupPolicy| {
+            load_daily_usage_data(LoadOptions {
+                claude_paths: vec![fixture.path().to_path_buf()],
+                timezone: Some("UTC".to_string()),
+                dedup,
+                ..LoadOptions::default()
+            })
+            .unwrap()[0]
+                .total_tokens
+        };
+
+        assert_eq!(total_tokens(DedupPolicy::Auto), 300);
+        assert_eq!(total_tokens(DedupPolicy::KeepFirst), 150);
+        assert_eq!(total_tokens(DedupPolicy::KeepLatest), 110);
+        assert_eq!(total_tokens(DedupPolicy::Off), 560);
+    }
+
+    #[test]
+    fn load_daily_usage_fallback_dedup_matches_entries_without_request_ids() {
+        let fixture = create_fixture();
+        let line = |request_id: Option<&str>, timestamp: &str| {
+            json!({
+                "timestamp": timestamp,
+                "requestId": request_id,
+                "message": {
+                    "id": "msg_1",
+                    "usage": { "input_tokens": 100, "output_tokens": 50 }
+                },
+                "costUSD": 0.01
+            })
+            .to_string()
+        };
+        write_file(
+            fixture.path(),
+            "projects/project1/session1.jsonl",
+            &line(Some("req_1"), "2024-01-01T10:00:00Z"),
+        );
+        write_file(
+            fixture.path(),
+            "projects/project1/session2.jsonl",
+            &[
+                line(None, "2024-01-01T10:00:00Z"),
+                line(None, "2024-01-01T12:00:00Z"),
+            ]
+            .join("\n"),
+        );
+        write_file(
+            fixture.path(),
+            "projects/project1/session3.jsonl",
+            &line(Some("req_2"), "2024-01-01T12:00:00Z"),
+        );
+        let message_count = |fallback_dedup: bool| {
+            load_daily_usage_data(LoadOptions {
+                claude_paths: vec![fixture.path().to_path_buf()],
+                timezone: Some("UTC".to_string()),
+                fallback_dedup,
+                ..LoadOptions::default()
+            })
+            .unwrap()[0]
+                .message_count
+        };
+
+        // Without the fallback the copies lacking a requestId only merge with each other.
+        assert_eq!(message_count(false), 3);
+        assert_eq!(message_count(true), 2);
+    }
+
+    #[test]
+    fn load_usage_entries_reads_cost_field_aliases_and_the_configured_field() {
+        let fixture = create_fixture();
+        let line = |id: &str, cost: serde_json::Value| {
+            let mut line = json!({
+                "timestamp": "2024-01-01T10:00:00Z",
+                "requestId": format!("req_{id}"),
+                "message": {
+                    "id": id,
+                    "usage": { "input_tokens": 100, "output_tokens": 50 }
+                }
+            });
+            line.as_object_mut()
+                .unwrap()
+                .extend(cost.as_object().unwrap().clone());
+            line.to_string()
+        };
+        let lines = [
+            line("msg_1", json!({ "costUSD": 0.5, "costUsd": 9.0 })),
+            line("msg_2", json!({ "costUsd": 0.25 })),
+            line("msg_3", json!({ "cost_usd": 0.125 })),
+            line("msg_4", json!({ "metadata": { "cost": "1.5" } })),
+        ];
+        write_file(
+            fixture.path(),
+            "projects/project1/session1.jsonl",
+            &lines.join("\n"),
+        );
+        let costs = |cost_field: Option<&str>| {
+            let mut costs = load_usage_entries(&LoadOptions {
+                claude_paths: vec![fixture.path().to_path_buf()],
+                timezone: Some("UTC".to_string()),
+                mode: CostMode::Display,
+                cost_field: cost_field.map(str::to_string),
+                ..LoadOptions::default()
+            })
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.cost)
+            .collect::<Vec<_>>();
+            costs.sort_by(f64::total_cmp);
+            costs
+        };
+
+        assert_eq!(costs(None), vec![0.0, 0.125, 0.25, 0.5]);
+        assert_eq!(costs(Some("metadata.cost")), vec![0.125, 0.25, 0.5, 1.5]);
+    }
+
+    #[test]
+    fn load_daily_usage_counts_api_errors_unless_skipped() {
+        let fixture = create_fixture();
+        let lines = [
+            json!({
+                "timestamp": "2024-01-01T10:00:00Z",
+                "requestId": "req_1",
+                "message": {
+                    "id": "msg_1",
+                    "model": "claude-sonnet-4-20250514",
+                    "usage": { "input_tokens": 100, "output_tokens": 50 }
+                },
+                "costUSD": 0.01
+            }),
+            json!({
+                "timestamp": "2024-01-01T10:01:00Z",
+                "isApiErrorMessage": true,
+                "message": {
+                    "id": "msg_2",
+                    "model": "<synthetic>",
+                    "usage": { "input_tokens": 0, "output_tokens": 0 }
+                }
+            }),
+        ]
+        .map(|line| line.to_string());
+        write_file(
+            fixture.path(),
+            "projects/project1/session1.jsonl",
+            &lines.join("\n"),
+        );
+        let load = |skip_errors: bool| {
+            load_daily_usage_data(LoadOptions {
+                claude_paths: vec![fixture.path().to_path_buf()],
+                timezone: Some("UTC".to_string()),
+                skip_errors,
+                ..LoadOptions::default()
+            })
+            .unwrap()
+        };
+
+        let counted = load(false);
+        assert_eq!(counted[0].message_count, 1);
+        assert_eq!(counted[0].api_errors, 1);
+        assert_eq!(counted[0].models_used, vec!["claude-sonnet-4-20250514"]);
+
+        let skipped = load(true);
+        assert_eq!(skipped[0].message_count, 1);
+        assert_eq!(skipped[0].api_errors, 0);
+    }
+
+    #[test]
+    fn load_daily_usage_counts_entries_with_logged_costs() {
+        let fixture = create_fixture();
+        let line = |message_id: &str, cost: Option<f64>| {
+            let mut line = json!({
+                "timestamp": "2024-01-01T10:00:00Z",
+                "requestId": format!("req_{message_id}"),
+                "message": {
+                    "id": message_id,
+                    "model": "claude-sonnet-4-20250514",
+                    "usage": { "input_tokens": 100, "output_tokens": 50 }
+                }
+            });
+            if let Some(cost) = cost {
+                line["costUSD"] = json!(cost);
+            }
+            line.to_string()
+        };
+        let lines = [line("msg_1", Some(0.01)), line("msg_2", None)];
+        write_file(
+            fixture.path(),
+            "projects/project1/session1.jsonl",
+            &lines.join("\n"),
+        );
+        let load = |mode: CostMode| {
+            calculate_totals_daily(
+                &load_daily_usage_data(LoadOptions {
+                    claude_paths: vec![fixture.path().to_path_buf()],
+                    timezone: Some("UTC".to_string()),
+                    mode,
+                    ..LoadOptions::default()
+                })
+                .unwrap(),
+            )
+        };
+
+        let display = load(CostMode::Display);
+        assert_eq!(display.logged_costs, 1);
+        assert_eq!(display.logged_cost_share(), Some(0.5));
+        assert_eq!(load(CostMode::Calculate).logged_costs, 0);
+    }
+
+    #[test]
+    fn load_daily_usage_counts_distinct_sessions_per_day() {
+        let fixture = create_fixture();
+        let line = |timestamp: &str, message_id: &str| {
+            json!({
+                "timestamp": timestamp,
+                "requestId": format!("req_{message_id}"),
+                "message": { "id": message_id, "usage": { "input_tokens": 100, "output_tokens": 50 } },
+                "costUSD": 0.01
+            })
+            .to_string()
+        };
+        write_file(
+            fixture.path(),
+            "projects/project1/session-a.jsonl",
+            &[
+                line("2024-01-01T10:00:00Z", "msg_1"),
+                line("2024-01-01T11:00:00Z", "msg_2"),
+                line("2024-01-02T09:00:00Z", "msg_3"),
+            ]
+            .join("\n"),
+        );
+        write_file(
+            fixture.path(),
+            "projects/project1/session-b.jsonl",
+            &line("2024-01-01T12:00:00Z", "msg_4"),
+        );
+
+        let daily = load_daily_usage_data(LoadOptions {
+            claude_paths: vec![fixture.path().to_path_buf()],
+            timezone: Some("UTC".to_string()),
+            mode: CostMode::Display,
+            ..LoadOptions::default()
+        })
+        .unwrap();
+
+        let sessions_on = |date: &str| {
+            daily
+                .iter()
+                .find(|day| day.date == date)
+                .map(|day| day.session_ids.clone())
+                .unwrap()
+        };
+        assert_eq!(sessions_on("2024-01-01"), vec!["session-a", "session-b"]);
+        assert_eq!(sessions_on("2024-01-02"), vec!["session-a"]);
+        assert_eq!(calculate_totals_daily(&daily).sessions, 2);
+    }
+
+    #[test]
+    fn load_daily_usage_hides_synthetic_entries_unless_requested() {
+        let fixture = create_fixture();
+        let data = [
+            json!({
+                "timestamp": "2024-01-01T10:00:00Z",
+                "message": {
+                    "model": "claude-sonnet-4-20250514",
+                    "usage": { "input_tokens": 100, "output_tokens": 50 }
+                },
+                "costUSD": 0.01
+            }),
+            json!({
+                "timestamp": "2024-01-01T10:01:00Z",
+                "message": {
+                    "model": "<synthetic>",
+                    "usage": { "input_tokens": 0, "output_tokens": 0 }
+                }
+            }),
+        ];
+        write_file(
+            fixture.path(),
+            "projects/project1/session1.jsonl",
+            &data
+                .iter()
+                .map(|v| v.to_string())
+                .collect::<Vec<_>>()
+                .join("\n"),
         );
+        let load = |include_synthetic: bool| {
+            load_daily_usage_data(LoadOptions {
+                claude_paths: vec![fixture.path().to_path_buf()],
+                timezone: Some("UTC".to_string()),
+                include_synthetic,
+                ..LoadOptions::default()
+            })
+            .unwrap()
+        };
 
-        let result = load_daily_usage_data(LoadOptions {
-            claude_path: Some(fixture.path().to_path_buf()),
-            timezone: Some("UTC".to_string()),
-            ..LoadOptions::default()
-        })
-        .unwrap();
+        let hidden = load(false);
+        assert_eq!(hidden[0].models_used, vec!["claude-sonnet-4-20250514"]);
+        assert_eq!(hidden[0].model_breakdowns.len(), 1);
+        assert_eq!(hidden[0].message_count, 1);
 
-        assert_eq!(result[0].cache_creation_tokens, 25);
-        assert_eq!(result[0].cache_read_tokens, 10);
+        let included = load(true);
+        assert!(included[0].models_used.iter().any(|m| m == "<synthetic>"));
+        assert!(
+            included[0]
+                .model_breakdowns
+                .iter()
+                .any(|b| b.model_name == "<synthetic>" && b.total_tokens == 0)
+        );
+        assert_eq!(included[0].message_count, 2);
+        assert_eq!(included[0].total_tokens, hidden[0].total_tokens);
     }
 
     #[test]
@@ -2639,7 +5402,7 @@ mod tests {
         );
 
         let result = load_daily_usage_data(LoadOptions {
-            claude_path: Some(fixture.path().to_path_buf()),
+            claude_paths: vec![fixture.path().to_path_buf()],
             since: Some("20240110".to_string()),
             until: Some("20240125".to_string()),
             ..LoadOptions::default()
@@ -2682,7 +5445,7 @@ mod tests {
         );
 
         let result = load_daily_usage_data(LoadOptions {
-            claude_path: Some(fixture.path().to_path_buf()),
+            claude_paths: vec![fixture.path().to_path_buf()],
             timezone: Some("UTC".to_string()),
             ..LoadOptions::default()
         })
@@ -2724,7 +5487,7 @@ mod tests {
         );
 
         let result = load_daily_usage_data(LoadOptions {
-            claude_path: Some(fixture.path().to_path_buf()),
+            claude_paths: vec![fixture.path().to_path_buf()],
             order: SortOrder::Asc,
             ..LoadOptions::default()
         })
@@ -2754,7 +5517,7 @@ mod tests {
         );
 
         let result = load_daily_usage_data(LoadOptions {
-            claude_path: Some(fixture.path().to_path_buf()),
+            claude_paths: vec![fixture.path().to_path_buf()],
             timezone: Some("UTC".to_string()),
             ..LoadOptions::default()
         })
@@ -2786,7 +5549,7 @@ mod tests {
         );
 
         let result = load_daily_usage_data(LoadOptions {
-            claude_path: Some(fixture.path().to_path_buf()),
+            claude_paths: vec![fixture.path().to_path_buf()],
             timezone: Some("UTC".to_string()),
             ..LoadOptions::default()
         })
@@ -2829,7 +5592,7 @@ mod tests {
         );
 
         let result = load_monthly_usage_data(LoadOptions {
-            claude_path: Some(fixture.path().to_path_buf()),
+            claude_paths: vec![fixture.path().to_path_buf()],
             ..LoadOptions::default()
         })
         .unwrap();
@@ -2846,7 +5609,7 @@ mod tests {
         let fixture = create_fixture();
         write_file(fixture.path(), "projects", "");
         let result = load_monthly_usage_data(LoadOptions {
-            claude_path: Some(fixture.path().to_path_buf()),
+            claude_paths: vec![fixture.path().to_path_buf()],
             ..LoadOptions::default()
         })
         .unwrap();
@@ -2890,7 +5653,7 @@ mod tests {
         );
 
         let desc = load_monthly_usage_data(LoadOptions {
-            claude_path: Some(fixture.path().to_path_buf()),
+            claude_paths: vec![fixture.path().to_path_buf()],
             ..LoadOptions::default()
         })
         .unwrap();
@@ -2901,7 +5664,7 @@ mod tests {
         );
 
         let asc = load_monthly_usage_data(LoadOptions {
-            claude_path: Some(fixture.path().to_path_buf()),
+            claude_paths: vec![fixture.path().to_path_buf()],
             order: SortOrder::Asc,
             ..LoadOptions::default()
         })
@@ -2941,7 +5704,7 @@ mod tests {
         );
 
         let result = load_monthly_usage_data(LoadOptions {
-            claude_path: Some(fixture.path().to_path_buf()),
+            claude_paths: vec![fixture.path().to_path_buf()],
             since: Some("20240110".to_string()),
             until: Some("20240225".to_string()),
             ..LoadOptions::default()
@@ -2979,7 +5742,7 @@ mod tests {
         );
 
         let result = load_monthly_usage_data(LoadOptions {
-            claude_path: Some(fixture.path().to_path_buf()),
+            claude_paths: vec![fixture.path().to_path_buf()],
             ..LoadOptions::default()
         })
         .unwrap();
@@ -3008,7 +5771,7 @@ mod tests {
         );
 
         let auto_result = load_daily_usage_data(LoadOptions {
-            claude_path: Some(fixture.path().to_path_buf()),
+            claude_paths: vec![fixture.path().to_path_buf()],
             mode: CostMode::Auto,
             ..LoadOptions::default()
         })
@@ -3016,7 +5779,7 @@ mod tests {
         assert!(auto_result[0].total_cost > 0.05);
 
         let calculate_result = load_daily_usage_data(LoadOptions {
-            claude_path: Some(fixture.path().to_path_buf()),
+            claude_paths: vec![fixture.path().to_path_buf()],
             mode: CostMode::Calculate,
             ..LoadOptions::default()
         })
@@ -3024,7 +5787,7 @@ mod tests {
         assert!(calculate_result[0].total_cost < 1.0);
 
         let display_result = load_daily_usage_data(LoadOptions {
-            claude_path: Some(fixture.path().to_path_buf()),
+            claude_paths: vec![fixture.path().to_path_buf()],
             mode: CostMode::Display,
             ..LoadOptions::default()
         })
@@ -3043,16 +5806,21 @@ mod tests {
                     cache_creation_input_tokens: Some(200),
                     cache_read_input_tokens: Some(100),
                     cache_creation: None,
+                    service_tier: None,
                 }),
                 model: Some("claude-sonnet-4-20250514".to_string()),
                 id: None,
+                stop_reason: None,
             }),
             cost_usd: Some(0.05),
+            cost_usd_camel_case: None,
+            cost_usd_snake_case: None,
             request_id: None,
             request: None,
             version: None,
             session_id: None,
             is_sidechain: None,
+            is_api_error_message: None,
         };
         let fetcher = PricingFetcher::new();
         let result = calculate_cost_for_entry(&data, CostMode::Display, Some(&fetcher));
@@ -3070,16 +5838,21 @@ mod tests {
                     cache_creation_input_tokens: None,
                     cache_read_input_tokens: None,
                     cache_creation: None,
+                    service_tier: None,
                 }),
                 model: Some("claude-4-sonnet-20250514".to_string()),
                 id: None,
+                stop_reason: None,
             }),
             cost_usd: Some(99.99),
+            cost_usd_camel_case: None,
+            cost_usd_snake_case: None,
             request_id: None,
             request: None,
             version: None,
             session_id: None,
             is_sidechain: None,
+            is_api_error_message: None,
         };
         let fetcher = PricingFetcher::new();
         let result = calculate_cost_for_entry(&data, CostMode::Calculate, Some(&fetcher));
@@ -3098,20 +5871,37 @@ mod tests {
                     cache_creation_input_tokens: None,
                     cache_read_input_tokens: None,
                     cache_creation: None,
+                    service_tier: None,
                 }),
                 model: Some("claude-4-sonnet-20250514".to_string()),
                 id: None,
+                stop_reason: None,
             }),
             cost_usd: Some(0.05),
+            cost_usd_camel_case: None,
+            cost_usd_snake_case: None,
             request_id: None,
             request: None,
             version: None,
             session_id: None,
             is_sidechain: None,
+            is_api_error_message: None,
         };
         let fetcher = PricingFetcher::new();
         let result = calculate_cost_for_entry(&data, CostMode::Auto, Some(&fetcher));
         assert_eq!(result, 0.05);
+
+        let calculated = calculate_cost_for_entry(&data, CostMode::Calculate, Some(&fetcher));
+        let max = calculate_cost_for_entry(&data, CostMode::Max, Some(&fetcher));
+        assert_eq!(max, calculated.max(0.05));
+        let under_reported = UsageData {
+            cost_usd: Some(0.0001),
+            ..data
+        };
+        assert_eq!(
+            calculate_cost_for_entry(&under_reported, CostMode::Max, Some(&fetcher)),
+            calculated
+        );
     }
 
     #[test]
@@ -3126,9 +5916,11 @@ mod tests {
                     ephemeral_5m_input_tokens: 10,
                     ephemeral_1h_input_tokens: 20,
                 }),
+                service_tier: None,
             }),
             model: None,
             id: None,
+            stop_reason: None,
         };
 
         let tokens = extract_usage_tokens(&message).unwrap();
@@ -3163,7 +5955,7 @@ mod tests {
         );
 
         let result = load_daily_usage_data(LoadOptions {
-            claude_path: Some(fixture.path().to_path_buf()),
+            claude_paths: vec![fixture.path().to_path_buf()],
             timezone: Some("UTC".to_string()),
             mode: CostMode::Calculate,
             ..LoadOptions::default()
@@ -3213,7 +6005,7 @@ mod tests {
         );
 
         let result = load_daily_usage_data(LoadOptions {
-            claude_path: Some(fixture.path().to_path_buf()),
+            claude_paths: vec![fixture.path().to_path_buf()],
             timezone: Some("UTC".to_string()),
             mode: CostMode::Calculate,
             ..LoadOptions::default()
@@ -3226,112 +6018,6 @@ mod tests {
         assert_eq!(result[0].total_tokens, 15);
     }
 
-    #[test]
-    fn get_earliest_timestamp_extracts_minimum() {
-        let fixture = create_fixture();
-        let content = [
-            json!({ "timestamp": "2025-01-15T12:00:00Z", "message": { "usage": {} } }),
-            json!({ "timestamp": "2025-01-10T10:00:00Z", "message": { "usage": {} } }),
-            json!({ "timestamp": "2025-01-12T11:00:00Z", "message": { "usage": {} } }),
-        ]
-        .iter()
-        .map(|v| v.to_string())
-        .collect::<Vec<_>>()
-        .join("\n");
-        write_file(fixture.path(), "test.jsonl", &content);
-        let ts = get_earliest_timestamp(&fixture.path().join("test.jsonl")).unwrap();
-        assert_eq!(
-            ts,
-            DateTime::parse_from_rfc3339("2025-01-10T10:00:00Z")
-                .unwrap()
-                .with_timezone(&Utc)
-        );
-    }
-
-    #[test]
-    fn get_earliest_timestamp_handles_missing() {
-        let fixture = create_fixture();
-        let content = [
-            json!({ "message": { "usage": {} } }),
-            json!({ "data": "no timestamp" }),
-        ]
-        .iter()
-        .map(|v| v.to_string())
-        .collect::<Vec<_>>()
-        .join("\n");
-        write_file(fixture.path(), "test.jsonl", &content);
-        let ts = get_earliest_timestamp(&fixture.path().join("test.jsonl"));
-        assert!(ts.is_none());
-    }
-
-    #[test]
-    fn sort_files_by_timestamp_orders_files() {
-        let fixture = create_fixture();
-        write_file(
-            fixture.path(),
-            "file1.jsonl",
-            &json!({ "timestamp": "2025-01-15T10:00:00Z" }).to_string(),
-        );
-        write_file(
-            fixture.path(),
-            "file2.jsonl",
-            &json!({ "timestamp": "2025-01-10T10:00:00Z" }).to_string(),
-        );
-        write_file(
-            fixture.path(),
-            "file3.jsonl",
-            &json!({ "timestamp": "2025-01-12T10:00:00Z" }).to_string(),
-        );
-        let files = vec![
-            fixture.path().join("file1.jsonl"),
-            fixture.path().join("file2.jsonl"),
-            fixture.path().join("file3.jsonl"),
-        ];
-        let sorted = sort_files_by_timestamp(files);
-        assert_eq!(
-            sorted,
-            vec![
-                fixture.path().join("file2.jsonl"),
-                fixture.path().join("file3.jsonl"),
-                fixture.path().join("file1.jsonl"),
-            ]
-        );
-    }
-
-    #[test]
-    fn sort_files_by_timestamp_places_missing_at_end() {
-        let fixture = create_fixture();
-        write_file(
-            fixture.path(),
-            "file1.jsonl",
-            &json!({ "timestamp": "2025-01-15T10:00:00Z" }).to_string(),
-        );
-        write_file(
-            fixture.path(),
-            "file2.jsonl",
-            &json!({ "no_timestamp": true }).to_string(),
-        );
-        write_file(
-            fixture.path(),
-            "file3.jsonl",
-            &json!({ "timestamp": "2025-01-10T10:00:00Z" }).to_string(),
-        );
-        let files = vec![
-            fixture.path().join("file1.jsonl"),
-            fixture.path().join("file2.jsonl"),
-            fixture.path().join("file3.jsonl"),
-        ];
-        let sorted = sort_files_by_timestamp(files);
-        assert_eq!(
-            sorted,
-            vec![
-                fixture.path().join("file3.jsonl"),
-                fixture.path().join("file1.jsonl"),
-                fixture.path().join("file2.jsonl"),
-            ]
-        );
-    }
-
     #[test]
     fn load_daily_usage_deduplicates_by_message_and_request() {
         let fixture = create_fixture();
@@ -3353,7 +6039,7 @@ mod tests {
         );
 
         let result = load_daily_usage_data(LoadOptions {
-            claude_path: Some(fixture.path().to_path_buf()),
+            claude_paths: vec![fixture.path().to_path_buf()],
             mode: CostMode::Display,
             ..LoadOptions::default()
         })
@@ -3389,109 +6075,435 @@ mod tests {
         });
         write_file(fixture.path(), "projects/agent.jsonl", &entry.to_string());
 
-        let result = load_daily_usage_data(LoadOptions {
-            claude_path: Some(fixture.path().to_path_buf()),
-            mode: CostMode::Display,
-            ..LoadOptions::default()
+        let result = load_daily_usage_data(LoadOptions {
+            claude_paths: vec![fixture.path().to_path_buf()],
+            mode: CostMode::Display,
+            ..LoadOptions::default()
+        })
+        .unwrap();
+
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].input_tokens, 100);
+        assert_eq!(result[0].output_tokens, 50);
+        assert_eq!(result[0].cache_creation_tokens, 20);
+        assert_eq!(result[0].cache_read_tokens, 30);
+        assert_eq!(result[0].total_cost, 0.05);
+    }
+
+    #[test]
+    fn load_daily_usage_prefers_parent_over_sidechain_replay() {
+        let fixture = create_fixture();
+        let parent = json!({
+            "timestamp": "2025-01-10T10:00:00Z",
+            "message": { "id": "msg_123", "usage": { "input_tokens": 100, "output_tokens": 50 } },
+            "requestId": "req_parent",
+            "isSidechain": false,
+            "costUSD": 0.001
+        });
+        let sidechain = json!({
+            "timestamp": "2025-01-15T10:00:00Z",
+            "message": { "id": "msg_123", "usage": { "input_tokens": 500, "output_tokens": 250 } },
+            "requestId": "req_sidechain",
+            "isSidechain": true,
+            "costUSD": 0.005
+        });
+        write_file(fixture.path(), "projects/parent.jsonl", &parent.to_string());
+        write_file(
+            fixture.path(),
+            "projects/sidechain.jsonl",
+            &sidechain.to_string(),
+        );
+
+        let result = load_daily_usage_data(LoadOptions {
+            claude_paths: vec![fixture.path().to_path_buf()],
+            mode: CostMode::Display,
+            ..LoadOptions::default()
+        })
+        .unwrap();
+
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].date, "2025-01-10");
+        assert_eq!(result[0].input_tokens, 100);
+        assert_eq!(result[0].output_tokens, 50);
+    }
+
+    #[test]
+    fn load_daily_usage_skips_files_outside_the_date_range() {
+        let fixture = create_fixture();
+        let line = |date: &str, message: &str| {
+            json!({
+                "timestamp": format!("{date}T10:00:00Z"),
+                "message": { "id": message, "usage": { "input_tokens": 100, "output_tokens": 0 } },
+                "requestId": format!("req_{message}"),
+                "costUSD": 0.001
+            })
+            .to_string()
+        };
+        write_file(
+            fixture.path(),
+            "projects/app/kept.jsonl",
+            &line("2025-01-10", "a"),
+        );
+        // Both files hold an entry in range that their bounds contradict, which shows they
+        // were never parsed.
+        write_file(
+            fixture.path(),
+            "projects/app/stale.jsonl",
+            &line("2025-01-10", "b"),
+        );
+        File::options()
+            .write(true)
+            .open(fixture.path().join("projects/app/stale.jsonl"))
+            .unwrap()
+            .set_modified(UNIX_EPOCH + Duration::from_secs(1_735_689_600))
+            .unwrap();
+        let late = [line("2025-02-01", "c"), line("2025-01-10", "d")];
+        write_file(fixture.path(), "projects/app/late.jsonl", &late.join("\n"));
+
+        let input_tokens = |scan_all_files| {
+            load_daily_usage_data(LoadOptions {
+                claude_paths: vec![fixture.path().to_path_buf()],
+                mode: CostMode::Display,
+                since: Some("20250105".to_string()),
+                until: Some("20250115".to_string()),
+                timezone: Some("UTC".to_string()),
+                scan_all_files,
+                ..LoadOptions::default()
+            })
+            .unwrap()
+            .iter()
+            .map(|day| day.input_tokens)
+            .sum::<u64>()
+        };
+        assert_eq!(input_tokens(false), 100);
+        assert_eq!(input_tokens(true), 300);
+    }
+
+    #[test]
+    fn load_daily_usage_keeps_larger_entry_for_dedup() {
+        let fixture = create_fixture();
+        let newer = json!({
+            "timestamp": "2025-01-15T10:00:00Z",
+            "message": { "id": "msg_123", "usage": { "input_tokens": 200, "output_tokens": 100 } },
+            "requestId": "req_456",
+            "costUSD": 0.002
+        });
+        let older = json!({
+            "timestamp": "2025-01-10T10:00:00Z",
+            "message": { "id": "msg_123", "usage": { "input_tokens": 100, "output_tokens": 50 } },
+            "requestId": "req_456",
+            "costUSD": 0.001
+        });
+        write_file(fixture.path(), "projects/newer.jsonl", &newer.to_string());
+        write_file(fixture.path(), "projects/older.jsonl", &older.to_string());
+
+        let result = load_daily_usage_data(LoadOptions {
+            claude_paths: vec![fixture.path().to_path_buf()],
+            mode: CostMode::Display,
+            ..LoadOptions::default()
+        })
+        .unwrap();
+
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].date, "2025-01-15");
+        assert_eq!(result[0].input_tokens, 200);
+        assert_eq!(result[0].output_tokens, 100);
+    }
+
+    #[test]
+    fn load_usage_entries_dedupes_within_and_across_files() {
+        let fixture = create_fixture();
+        let line = |minute: u32, message: &str, output_tokens: u64| {
+            json!({
+                "timestamp": format!("2025-01-10T10:{minute:02}:00Z"),
+                "message": {
+                    "id": message,
+                    "usage": { "input_tokens": 10, "output_tokens": output_tokens }
+                },
+                "requestId": format!("req_{message}"),
+                "costUSD": 0.001
+            })
+            .to_string()
+        };
+        // The resumed session repeats msg_2 with its final token count.
+        let first = [
+            line(0, "msg_1", 5),
+            line(1, "msg_2", 5),
+            line(2, "msg_1", 9),
+        ];
+        let resumed = [
+            line(1, "msg_2", 7),
+            line(3, "msg_3", 5),
+            line(3, "msg_3", 6),
+        ];
+        write_file(fixture.path(), "projects/app/a.jsonl", &first.join("\n"));
+        write_file(fixture.path(), "projects/app/b.jsonl", &resumed.join("\n"));
+
+        let entries = |dedup| {
+            load_usage_entries(&LoadOptions {
+                claude_paths: vec![fixture.path().to_path_buf()],
+                mode: CostMode::Display,
+                dedup,
+                ..LoadOptions::default()
+            })
+            .unwrap()
+            .into_iter()
+            .map(|entry| (entry.source_file, entry.source_line))
+            .collect::<BTreeSet<_>>()
+        };
+        let at = |file: &str, line| {
+            (
+                Some(fixture.path().join("projects/app").join(file)),
+                Some(line),
+            )
+        };
+        assert_eq!(
+            entries(DedupPolicy::Auto),
+            BTreeSet::from([at("a.jsonl", 3), at("b.jsonl", 1), at("b.jsonl", 3)])
+        );
+        assert_eq!(
+            entries(DedupPolicy::KeepFirst),
+            BTreeSet::from([at("a.jsonl", 1), at("a.jsonl", 2), at("b.jsonl", 2)])
+        );
+    }
+
+    #[test]
+    fn load_daily_usage_merges_aggregates_of_many_chunks() {
+        let fixture = create_fixture();
+        let messages = AGGREGATION_CHUNK_RECORDS * 2 + 100;
+        for project in ["app", "lib"] {
+            let lines = (0..messages)
+                .map(|index| {
+                    json!({
+                        "timestamp": format!("2025-01-{:02}T10:00:00Z", 10 + index % 3),
+                        "sessionId": format!("{project}-{}", index % 5),
+                        "message": {
+                            "id": format!("{project}_{index}"),
+                            "model": if index < messages / 2 { "claude-opus-4" } else { "claude-haiku-4" },
+                            "usage": { "input_tokens": 10, "output_tokens": 1 }
+                        },
+                        "requestId": format!("req_{project}_{index}"),
+                        "costUSD": 0.25
+                    })
+                    .to_string()
+                })
+                .collect::<Vec<_>>();
+            write_file(
+                fixture.path(),
+                &format!("projects/{project}/session.jsonl"),
+                &lines.join("\n"),
+            );
+        }
+
+        let daily = load_daily_usage_data(LoadOptions {
+            claude_paths: vec![fixture.path().to_path_buf()],
+            group_by_project: true,
+            mode: CostMode::Display,
+            ..LoadOptions::default()
+        })
+        .unwrap();
+        assert_eq!(daily.len(), 6);
+        for day in &daily {
+            let day_of_month = day.date[8..].parse::<usize>().unwrap();
+            let count = (0..messages)
+                .filter(|index| 10 + index % 3 == day_of_month)
+                .count() as u64;
+            assert_eq!(day.message_count, count);
+            assert_eq!(day.input_tokens, count * 10);
+            assert_eq!(day.total_cost, count as f64 * 0.25);
+            assert_eq!(day.models_used, ["claude-opus-4", "claude-haiku-4"]);
+            assert_eq!(day.model_breakdowns.len(), 2);
+            assert_eq!(day.session_ids.len(), 5);
+        }
+    }
+
+    #[test]
+    fn process_jsonl_file_by_line_skips_empty() {
+        let fixture = create_fixture();
+        write_file(
+            fixture.path(),
+            "test.jsonl",
+            "{\"line\": 1}\n\n{\"line\": 2}\n  \n{\"line\": 3}\n",
+        );
+        let mut lines = Vec::new();
+        process_jsonl_file_by_line(
+            &fixture.path().join("test.jsonl"),
+            ReadLimits::default(),
+            |line, _| {
+                lines.push(line.to_string());
+                Ok(())
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            lines,
+            vec!["{\"line\": 1}", "{\"line\": 2}", "{\"line\": 3}"]
+        );
+    }
+
+    #[test]
+    fn process_jsonl_file_by_line_bytes_skips_lines_over_the_limit() {
+        let fixture = create_fixture();
+        let long_line = format!("{{\"line\": \"{}\"}}", "x".repeat(100));
+        write_file(
+            fixture.path(),
+            "test.jsonl",
+            &format!("{{\"line\": 1}}\n{long_line}\n{{\"line\": 3}}"),
+        );
+        let file = fixture.path().join("test.jsonl");
+        let limits = ReadLimits {
+            max_line_bytes: 32,
+            ..ReadLimits::default()
+        };
+        let mut lines = Vec::new();
+        process_jsonl_file_by_line_bytes(&file, limits, |line, number| {
+            lines.push((String::from_utf8_lossy(line).to_string(), number));
+            Ok(())
+        })
+        .unwrap();
+
+        assert_eq!(
+            lines,
+            vec![
+                ("{\"line\": 1}".to_string(), 1),
+                ("{\"line\": 3}".to_string(), 3),
+            ]
+        );
+        let expected = format!("skipped line 2 of {}", file.display());
+        assert!(
+            warnings::recorded_warnings()
+                .iter()
+                .any(|warning| warning.starts_with(&expected))
+        );
+    }
+
+    #[test]
+    fn process_jsonl_file_by_line_decodes_invalid_utf8_lossily() {
+        let fixture = create_fixture();
+        let file = fixture.path().join("test.jsonl");
+        std::fs::write(
+            &file,
+            b"{\"line\": 1}\n{\"text\": \"bad \xff byte\"}\n{\"line\": 3}\n",
+        )
+        .unwrap();
+        let mut lines = Vec::new();
+        process_jsonl_file_by_line(&file, ReadLimits::default(), |line, number| {
+            lines.push((line.to_string(), number));
+            Ok(())
         })
         .unwrap();
 
-        assert_eq!(result.len(), 1);
-        assert_eq!(result[0].input_tokens, 100);
-        assert_eq!(result[0].output_tokens, 50);
-        assert_eq!(result[0].cache_creation_tokens, 20);
-        assert_eq!(result[0].cache_read_tokens, 30);
-        assert_eq!(result[0].total_cost, 0.05);
+        assert_eq!(
+            lines,
+            vec![
+                ("{\"line\": 1}".to_string(), 1),
+                ("{\"text\": \"bad \u{fffd} byte\"}".to_string(), 2),
+                ("{\"line\": 3}".to_string(), 3),
+            ]
+        );
+        let expected = format!("{}: 1 line(s) contained invalid UTF-8", file.display());
+        assert!(
+            warnings::recorded_warnings()
+                .iter()
+                .any(|warning| warning.starts_with(&expected))
+        );
     }
 
     #[test]
-    fn load_daily_usage_prefers_parent_over_sidechain_replay() {
+    fn load_daily_usage_keeps_records_with_invalid_utf8() {
         let fixture = create_fixture();
-        let parent = json!({
-            "timestamp": "2025-01-10T10:00:00Z",
-            "message": { "id": "msg_123", "usage": { "input_tokens": 100, "output_tokens": 50 } },
-            "requestId": "req_parent",
-            "isSidechain": false,
-            "costUSD": 0.001
-        });
-        let sidechain = json!({
+        let path = fixture.path().join("projects/app/session.jsonl");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let mut content = json!({
             "timestamp": "2025-01-15T10:00:00Z",
-            "message": { "id": "msg_123", "usage": { "input_tokens": 500, "output_tokens": 250 } },
-            "requestId": "req_sidechain",
-            "isSidechain": true,
-            "costUSD": 0.005
-        });
-        write_file(fixture.path(), "projects/parent.jsonl", &parent.to_string());
-        write_file(
-            fixture.path(),
-            "projects/sidechain.jsonl",
-            &sidechain.to_string(),
+            "message": { "usage": { "input_tokens": 100, "output_tokens": 50 } },
+            "costUSD": 0.01
+        })
+        .to_string()
+        .into_bytes();
+        content.push(b'\n');
+        content.extend_from_slice(
+            b"{\"timestamp\":\"2025-01-15T11:00:00Z\",\"note\":\"\xc3\x28\",\"message\":{\"usage\":{\"input_tokens\":200,\"output_tokens\":100}},\"costUSD\":0.02}\n",
         );
+        std::fs::write(&path, content).unwrap();
 
         let result = load_daily_usage_data(LoadOptions {
-            claude_path: Some(fixture.path().to_path_buf()),
+            claude_paths: vec![fixture.path().to_path_buf()],
             mode: CostMode::Display,
             ..LoadOptions::default()
         })
         .unwrap();
 
         assert_eq!(result.len(), 1);
-        assert_eq!(result[0].date, "2025-01-10");
-        assert_eq!(result[0].input_tokens, 100);
-        assert_eq!(result[0].output_tokens, 50);
+        assert_eq!(result[0].input_tokens, 300);
+        assert_eq!(result[0].output_tokens, 150);
     }
 
     #[test]
-    fn load_daily_usage_keeps_larger_entry_for_dedup() {
+    fn process_jsonl_file_by_line_skips_truncated_trailing_line() {
         let fixture = create_fixture();
-        let newer = json!({
-            "timestamp": "2025-01-15T10:00:00Z",
-            "message": { "id": "msg_123", "usage": { "input_tokens": 200, "output_tokens": 100 } },
-            "requestId": "req_456",
-            "costUSD": 0.002
-        });
-        let older = json!({
-            "timestamp": "2025-01-10T10:00:00Z",
-            "message": { "id": "msg_123", "usage": { "input_tokens": 100, "output_tokens": 50 } },
-            "requestId": "req_456",
-            "costUSD": 0.001
-        });
-        write_file(fixture.path(), "projects/newer.jsonl", &newer.to_string());
-        write_file(fixture.path(), "projects/older.jsonl", &older.to_string());
-
-        let result = load_daily_usage_data(LoadOptions {
-            claude_path: Some(fixture.path().to_path_buf()),
-            mode: CostMode::Display,
-            ..LoadOptions::default()
+        let file = fixture.path().join("test.jsonl");
+        std::fs::write(&file, b"{\"line\": 1}\n{\"line\": 2, \"text\": \"\xe3\x81").unwrap();
+        let mut lines = Vec::new();
+        process_jsonl_file_by_line(&file, ReadLimits::default(), |line, _| {
+            lines.push(line.to_string());
+            Ok(())
         })
         .unwrap();
 
-        assert_eq!(result.len(), 1);
-        assert_eq!(result[0].date, "2025-01-15");
-        assert_eq!(result[0].input_tokens, 200);
-        assert_eq!(result[0].output_tokens, 100);
+        assert_eq!(lines, vec!["{\"line\": 1}"]);
+        let path = file.display().to_string();
+        assert!(
+            !warnings::recorded_warnings()
+                .iter()
+                .any(|warning| warning.contains(&path))
+        );
+
+        std::fs::write(&file, "{\"line\": 1}\n{\"line\": 2}").unwrap();
+        let mut count = 0;
+        process_jsonl_file_by_line(&file, ReadLimits::default(), |_, _| {
+            count += 1;
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(count, 2);
     }
 
     #[test]
-    fn process_jsonl_file_by_line_skips_empty() {
+    fn process_jsonl_file_by_line_bytes_skips_files_over_the_limit() {
         let fixture = create_fixture();
-        write_file(
-            fixture.path(),
-            "test.jsonl",
-            "{\"line\": 1}\n\n{\"line\": 2}\n  \n{\"line\": 3}\n",
-        );
-        let mut lines = Vec::new();
-        process_jsonl_file_by_line(&fixture.path().join("test.jsonl"), |line, _| {
-            lines.push(line.to_string());
+        write_file(fixture.path(), "test.jsonl", "{\"line\": 1}\n");
+        let file = fixture.path().join("test.jsonl");
+        let limits = ReadLimits {
+            max_file_bytes: 4,
+            ..ReadLimits::default()
+        };
+        let mut count = 0;
+        process_jsonl_file_by_line_bytes(&file, limits, |_, _| {
+            count += 1;
             Ok(())
         })
         .unwrap();
-        assert_eq!(
-            lines,
-            vec!["{\"line\": 1}", "{\"line\": 2}", "{\"line\": 3}"]
+
+        assert_eq!(count, 0);
+        let expected = format!("skipped {}: 12 bytes", file.display());
+        assert!(
+            warnings::recorded_warnings()
+                .iter()
+                .any(|warning| warning.starts_with(&expected))
         );
     }
 
+    #[test]
+    fn parse_byte_size_accepts_binary_units() {
+        assert_eq!(parse_byte_size("512").unwrap(), 512);
+        assert_eq!(parse_byte_size("64K").unwrap(), 64 * 1024);
+        assert_eq!(parse_byte_size("16MB").unwrap(), 16 << 20);
+        assert_eq!(parse_byte_size("2 GiB").unwrap(), 2 << 30);
+        assert!(parse_byte_size("").is_err());
+        assert!(parse_byte_size("10T").is_err());
+    }
+
     #[test]
     fn process_jsonl_file_by_line_reports_line_numbers() {
         let fixture = create_fixture();
@@ -3501,10 +6513,14 @@ mod tests {
             "{\"line\": 1}\n{\"line\": 2}\n{\"line\": 3}\n",
         );
         let mut lines = Vec::new();
-        process_jsonl_file_by_line(&fixture.path().join("test.jsonl"), |line, number| {
-            lines.push((line.to_string(), number));
-            Ok(())
-        })
+        process_jsonl_file_by_line(
+            &fixture.path().join("test.jsonl"),
+            ReadLimits::default(),
+            |line, number| {
+                lines.push((line.to_string(), number));
+                Ok(())
+            },
+        )
         .unwrap();
         assert_eq!(
             lines,
@@ -3518,8 +6534,11 @@ mod tests {
 
     #[test]
     fn process_jsonl_file_by_line_errors_on_missing_file() {
-        let result =
-            process_jsonl_file_by_line(Path::new("/nonexistent/file.jsonl"), |_, _| Ok(()));
+        let result = process_jsonl_file_by_line(
+            Path::new("/nonexistent/file.jsonl"),
+            ReadLimits::default(),
+            |_, _| Ok(()),
+        );
         assert!(result.is_err());
     }
 
@@ -3548,7 +6567,7 @@ mod tests {
             fixture.path().join("path3"),
         ];
 
-        let results = glob_usage_files(&paths);
+        let results = glob_usage_files(&paths, &LoadOptions::default());
         assert_eq!(results.len(), 3);
         assert!(
             results
@@ -3580,20 +6599,224 @@ mod tests {
             fixture.path().join("valid"),
             fixture.path().join("nonexistent"),
         ];
-        let results = glob_usage_files(&paths);
+        let results = glob_usage_files(&paths, &LoadOptions::default());
         assert_eq!(results.len(), 1);
         assert!(results[0].file.to_string_lossy().contains("project1"));
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn glob_usage_files_dedupes_symlinks_and_skips_cycles() {
+        let fixture = create_fixture();
+        write_file(fixture.path(), "projects/real/session.jsonl", "data");
+        let projects = fixture.path().join("projects");
+        std::os::unix::fs::symlink(projects.join("real"), projects.join("alias")).unwrap();
+        std::os::unix::fs::symlink(&projects, projects.join("real").join("loop")).unwrap();
+        let paths = vec![fixture.path().to_path_buf()];
+
+        let followed = glob_usage_files(&paths, &LoadOptions::default());
+        assert_eq!(followed.len(), 1);
+
+        let options = LoadOptions {
+            follow_symlinks: false,
+            ..LoadOptions::default()
+        };
+        let unfollowed = glob_usage_files(&paths, &options);
+        assert_eq!(unfollowed.len(), 1);
+        assert!(unfollowed[0].file.ends_with("real/session.jsonl"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn glob_usage_files_dedupes_the_same_file_across_roots() {
+        let fixture = create_fixture();
+        write_file(fixture.path(), "a/projects/app/session.jsonl", "data");
+        std::fs::create_dir_all(fixture.path().join("b/projects/app")).unwrap();
+        std::fs::hard_link(
+            fixture.path().join("a/projects/app/session.jsonl"),
+            fixture.path().join("b/projects/app/session.jsonl"),
+        )
+        .unwrap();
+        let paths = vec![fixture.path().join("a"), fixture.path().join("b")];
+        let options = LoadOptions {
+            follow_symlinks: false,
+            ..LoadOptions::default()
+        };
+
+        let results = glob_usage_files(&paths, &options);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].base_dir, fixture.path().join("a/projects"));
+    }
+
+    #[test]
+    fn glob_usage_files_skips_ignored_entries() {
+        let fixture = create_fixture();
+        write_file(fixture.path(), "projects/app/keep.jsonl", "data");
+        write_file(fixture.path(), "projects/app/skip.jsonl", "data");
+        write_file(fixture.path(), "projects/archived/old.jsonl", "data");
+        write_file(fixture.path(), "projects/app/node_modules/x.jsonl", "data");
+        let paths = vec![fixture.path().to_path_buf()];
+        let options = LoadOptions {
+            ignore: IgnorePatterns::new(&["/archived/", "node_modules/", "app/skip.jsonl"])
+                .unwrap(),
+            ..LoadOptions::default()
+        };
+
+        let results = glob_usage_files(&paths, &options);
+        assert_eq!(results.len(), 1);
+        assert!(results[0].file.ends_with("app/keep.jsonl"));
+    }
+
     #[test]
     fn glob_usage_files_returns_empty_when_no_files() {
         let fixture = create_fixture();
         write_file(fixture.path(), "empty/projects", "");
         let paths = vec![fixture.path().join("empty")];
-        let results = glob_usage_files(&paths);
+        let results = glob_usage_files(&paths, &LoadOptions::default());
         assert!(results.is_empty());
     }
 
+    #[test]
+    fn extract_project_from_path_handles_unix_and_windows_separators() {
+        assert_eq!(
+            extract_project_from_path(Path::new(
+                "/home/me/.claude/projects/-home-me-app/session.jsonl"
+            )),
+            "-home-me-app"
+        );
+        assert_eq!(
+            extract_project_from_path(Path::new(
+                r"C:\Users\me\.claude\projects\C--Users-me-app\session.jsonl"
+            )),
+            "C--Users-me-app"
+        );
+        assert_eq!(
+            extract_project_from_path(Path::new(
+                r"C:\Users\me\AppData\Roaming\claude/projects\app/session.jsonl"
+            )),
+            "app"
+        );
+        assert_eq!(
+            extract_project_from_path(Path::new(r"C:\Users\me\.claude\projects\")),
+            "unknown"
+        );
+        assert_eq!(
+            extract_project_from_path(Path::new("/tmp/usage.jsonl")),
+            "unknown"
+        );
+    }
+
+    #[test]
+    fn windows_claude_paths_use_user_profile_and_app_data() {
+        let paths = windows_claude_paths(|name| match name {
+            "USERPROFILE" => Some(r"C:\Users\me".into()),
+            "APPDATA" => Some(r"C:\Users\me\AppData\Roaming".into()),
+            _ => None,
+        });
+        assert_eq!(
+            paths,
+            vec![
+                PathBuf::from(r"C:\Users\me").join(".claude"),
+                PathBuf::from(r"C:\Users\me\AppData\Roaming").join("claude"),
+            ]
+        );
+
+        let paths = windows_claude_paths(|name| match name {
+            "USERPROFILE" => Some("".into()),
+            _ => None,
+        });
+        assert!(paths.is_empty());
+    }
+
+    #[test]
+    fn load_daily_usage_combines_roots_without_double_counting() {
+        let fixture = create_fixture();
+        write_file(
+            fixture.path(),
+            "a/projects/app/session.jsonl",
+            &json!({
+                "timestamp": "2025-01-15T10:00:00Z",
+                "message": { "usage": { "input_tokens": 100, "output_tokens": 50 } },
+                "costUSD": 0.01
+            })
+            .to_string(),
+        );
+        write_file(
+            fixture.path(),
+            "b/projects/app/session.jsonl",
+            &json!({
+                "timestamp": "2025-01-15T11:00:00Z",
+                "message": { "usage": { "input_tokens": 200, "output_tokens": 100 } },
+                "costUSD": 0.02
+            })
+            .to_string(),
+        );
+        let a = fixture.path().join("a");
+        let result = load_daily_usage_data(LoadOptions {
+            claude_paths: vec![
+                a.clone(),
+                fixture.path().join("b"),
+                fixture.path().join("b/../a"),
+            ],
+            mode: CostMode::Display,
+            dedup: DedupPolicy::Off,
+            ..LoadOptions::default()
+        })
+        .unwrap();
+
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].input_tokens, 300);
+        assert_eq!(result[0].output_tokens, 150);
+    }
+
+    #[test]
+    fn find_claude_paths_probes_candidates_in_order() {
+        let fixture = create_fixture();
+        write_file(fixture.path(), "second/projects/app/session.jsonl", "data");
+        write_file(fixture.path(), "first/projects/app/session.jsonl", "data");
+        std::fs::create_dir_all(fixture.path().join("empty")).unwrap();
+        let candidates = vec![
+            fixture.path().join("missing"),
+            fixture.path().join("first"),
+            fixture.path().join("empty"),
+            fixture.path().join("second"),
+            fixture.path().join("first"),
+        ];
+        let paths = resolve_claude_paths(None, &candidates, false).unwrap();
+        assert_eq!(
+            paths,
+            vec![
+                fixture.path().join("first").canonicalize().unwrap(),
+                fixture.path().join("second").canonicalize().unwrap(),
+            ]
+        );
+        assert!(resolve_claude_paths(None, &candidates[..1], false).is_err());
+        assert!(resolve_claude_paths(Some(" "), &candidates[..1], false).is_err());
+    }
+
+    #[test]
+    fn find_claude_desktop_roots_finds_nested_claude_code_roots() {
+        let fixture = create_fixture();
+        write_file(
+            fixture.path(),
+            "sessions/workspace-a/projects/app/session.jsonl",
+            "",
+        );
+        write_file(
+            fixture.path(),
+            "sessions/workspace-a/projects/app/nested/projects/x.jsonl",
+            "",
+        );
+        write_file(fixture.path(), "a/b/c/d/projects/app/session.jsonl", "");
+        write_file(fixture.path(), "IndexedDB/data.ldb", "");
+
+        assert_eq!(
+            find_claude_desktop_roots(fixture.path()),
+            vec![fixture.path().join("sessions/workspace-a")]
+        );
+        assert!(find_claude_desktop_roots(&fixture.path().join("missing")).is_empty());
+    }
+
     #[test]
     fn get_claude_paths_from_env() {
         let fixture1 = create_fixture();
@@ -4272,7 +7495,7 @@ mod tests {
         let result = load_daily_usage_data(LoadOptions {
             claudecode: true,
             codex: true,
-            claude_path: Some(fixture.path().join("claude")),
+            claude_paths: vec![fixture.path().join("claude")],
             codex_path: Some(fixture.path().join("codex").join("sessions")),
             timezone: Some("UTC".to_string()),
             mode: CostMode::Auto,
@@ -4515,7 +7738,7 @@ mod tests {
             claudecode: true,
             codex: false,
             opencode: true,
-            claude_path: Some(fixture.path().join("claude")),
+            claude_paths: vec![fixture.path().join("claude")],
             opencode_path: Some(
                 fixture
                     .path()
@@ -4544,4 +7767,113 @@ mod tests {
         );
         assert!(result[0].models_used.iter().any(|m| m == "gpt-5"));
     }
+
+    #[test]
+    fn load_usage_entries_filters_by_session_prefix() {
+        let fixture = create_fixture();
+        let entry = |id: &str, session: &str, timestamp: &str| {
+            json!({
+                "timestamp": timestamp,
+                "sessionId": session,
+                "message": { "id": id, "model": "claude-sonnet-4-20250514", "usage": { "input_tokens": 100, "output_tokens": 50 } },
+                "requestId": format!("req_{id}"),
+                "costUSD": 0.5
+            })
+            .to_string()
+        };
+        write_file(
+            fixture.path(),
+            "projects/project1/abc123.jsonl",
+            &[
+                entry("msg_1", "abc123", "2025-01-10T10:00:00Z"),
+                entry("msg_2", "abc123", "2025-01-10T09:00:00Z"),
+            ]
+            .join("\n"),
+        );
+        write_file(
+            fixture.path(),
+            "projects/project1/def456.jsonl",
+            &entry("msg_3", "def456", "2025-01-10T11:00:00Z"),
+        );
+
+        let entries = load_usage_entries(&LoadOptions {
+            claude_paths: vec![fixture.path().to_path_buf()],
+            mode: CostMode::Display,
+            order: SortOrder::Asc,
+            session: Some("abc".to_string()),
+            timezone: Some("UTC".to_string()),
+            ..LoadOptions::default()
+        })
+        .unwrap();
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(
+            entries[0].timestamp.to_rfc3339(),
+            "2025-01-10T09:00:00+00:00"
+        );
+        assert_eq!(entries[0].session_id.as_deref(), Some("abc123"));
+        assert_eq!(entries[0].project.as_deref(), Some("project1"));
+        assert_eq!(calculate_totals_entries(&entries).total_cost, 1.0);
+    }
+
+    #[test]
+    fn weekday_hour_costs_uses_local_date_and_hour() {
+        let entry = |date: &str, local_hour: u32, cost: f64| UsageEntry {
+            timestamp: Utc::now(),
+            date: date.to_string(),
+            cost,
+            local_hour,
+            ..Default::default()
+        };
+        // 2025-01-06 is a Monday and 2025-01-12 a Sunday.
+        let matrix = weekday_hour_costs(&[
+            entry("2025-01-06", 9, 1.0),
+            entry("2025-01-13", 9, 0.5),
+            entry("2025-01-12", 23, 2.0),
+        ]);
+
+        assert_eq!(matrix[0][9], 1.5);
+        assert_eq!(matrix[6][23], 2.0);
+        assert_eq!(matrix.iter().flatten().sum::<f64>(), 3.5);
+    }
+
+    #[test]
+    fn group_entries_by_hour_buckets_by_local_day_and_hour() {
+        let entry = |date: &str, local_hour: u32, model: &str, cost: f64| UsageEntry {
+            timestamp: Utc::now(),
+            date: date.to_string(),
+            session_id: Some(format!("{date}-{local_hour}")),
+            model: Some(model.to_string()),
+            input_tokens: 10,
+            output_tokens: 5,
+            total_tokens: 15,
+            cost,
+            local_hour,
+            ..Default::default()
+        };
+        let hours = group_entries_by_hour(&[
+            entry("2025-01-06", 14, "claude-sonnet-4", 1.0),
+            entry("2025-01-06", 9, "claude-opus-4", 2.0),
+            entry("2025-01-06", 14, "claude-opus-4", 3.0),
+            entry("2025-01-05", 23, "claude-opus-4", 0.5),
+        ]);
+
+        assert_eq!(
+            hours
+                .iter()
+                .map(|hour| (hour.date.as_str(), hour.hour, hour.total_cost))
+                .collect::<Vec<_>>(),
+            vec![
+                ("2025-01-05", 23, 0.5),
+                ("2025-01-06", 9, 2.0),
+                ("2025-01-06", 14, 4.0),
+            ]
+        );
+        let busy = &hours[2];
+        assert_eq!(busy.message_count, 2);
+        assert_eq!(busy.total_tokens, 30);
+        assert_eq!(busy.session_count, 1);
+        assert_eq!(busy.models_used, vec!["claude-sonnet-4", "claude-opus-4"]);
+        assert_eq!(busy.model_breakdowns[0].model_name, "claude-opus-4");
+    }
 }
@@ -0,0 +1,151 @@
+use crate::data_loader::UsageEntry;
+use chrono::{Duration, NaiveDate};
+use std::collections::HashMap;
+
+const UNKNOWN_MODEL: &str = "unknown";
+
+/// Tokens and cost summed over one period.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PeriodUsage {
+    pub total_tokens: u64,
+    pub cost: f64,
+}
+
+impl PeriodUsage {
+    fn add(&mut self, entry: &UsageEntry) {
+        self.total_tokens += entry.total_tokens;
+        self.cost += entry.cost;
+    }
+}
+
+/// One model's usage (or every model's, for the totals) in the baseline and current period.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UsageChange {
+    pub model: String,
+    pub baseline: PeriodUsage,
+    pub current: PeriodUsage,
+}
+
+impl UsageChange {
+    pub fn token_change(&self) -> i64 {
+        self.current.total_tokens as i64 - self.baseline.total_tokens as i64
+    }
+
+    pub fn cost_change(&self) -> f64 {
+        self.current.cost - self.baseline.cost
+    }
+
+    /// Relative token change, or `None` when the baseline had no tokens.
+    pub fn token_change_ratio(&self) -> Option<f64> {
+        (self.baseline.total_tokens > 0)
+            .then(|| self.token_change() as f64 / self.baseline.total_tokens as f64)
+    }
+
+    /// Relative cost change, or `None` when the baseline cost nothing.
+    pub fn cost_change_ratio(&self) -> Option<f64> {
+        (self.baseline.cost > 0.0).then(|| self.cost_change() / self.baseline.cost)
+    }
+}
+
+/// Per-model changes from `baseline` to `current`, the largest cost change first, and the
+/// change across all models.
+pub fn compare_periods(
+    baseline: &[UsageEntry],
+    current: &[UsageEntry],
+) -> (Vec<UsageChange>, UsageChange) {
+    let mut models: HashMap<&str, UsageChange> = HashMap::new();
+    let mut totals = UsageChange {
+        model: "Total".to_string(),
+        ..Default::default()
+    };
+    let tagged = baseline
+        .iter()
+        .map(|entry| (false, entry))
+        .chain(current.iter().map(|entry| (true, entry)));
+    for (is_current, entry) in tagged {
+        let model = entry.model.as_deref().unwrap_or(UNKNOWN_MODEL);
+        let change = models.entry(model).or_insert_with(|| UsageChange {
+            model: model.to_string(),
+            ..Default::default()
+        });
+        if is_current {
+            change.current.add(entry);
+            totals.current.add(entry);
+        } else {
+            change.baseline.add(entry);
+            totals.baseline.add(entry);
+        }
+    }
+    let mut models = models.into_values().collect::<Vec<_>>();
+    models.sort_by(|a, b| {
+        b.cost_change()
+            .abs()
+            .total_cmp(&a.cost_change().abs())
+            .then_with(|| a.model.cmp(&b.model))
+    });
+    (models, totals)
+}
+
+/// The days just before `since..=until`, as many as it spans.
+pub fn preceding_period(since: NaiveDate, until: NaiveDate) -> (NaiveDate, NaiveDate) {
+    let days = (until - since).num_days().max(0) + 1;
+    let end = since - Duration::days(1);
+    (end - Duration::days(days - 1), end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn entry(model: &str, tokens: u64, cost: f64) -> UsageEntry {
+        UsageEntry {
+            timestamp: Utc.with_ymd_and_hms(2026, 1, 2, 3, 4, 5).unwrap(),
+            date: "2026-01-02".to_string(),
+            model: Some(model.to_string()),
+            input_tokens: tokens,
+            total_tokens: tokens,
+            cost,
+            local_hour: 3,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn compare_periods_orders_models_by_the_size_of_the_cost_change() {
+        let baseline = vec![entry("opus", 100, 4.0), entry("sonnet", 50, 1.0)];
+        let current = vec![
+            entry("opus", 80, 3.5),
+            entry("sonnet", 100, 2.0),
+            entry("haiku", 10, 0.25),
+        ];
+
+        let (models, totals) = compare_periods(&baseline, &current);
+        assert_eq!(
+            models
+                .iter()
+                .map(|change| (change.model.as_str(), change.cost_change()))
+                .collect::<Vec<_>>(),
+            vec![("sonnet", 1.0), ("opus", -0.5), ("haiku", 0.25)]
+        );
+        assert_eq!(models[0].cost_change_ratio(), Some(1.0));
+        assert_eq!(models[1].token_change(), -20);
+        assert_eq!(models[2].token_change_ratio(), None);
+        assert_eq!(totals.baseline.cost, 5.0);
+        assert_eq!(totals.current.total_tokens, 190);
+        assert_eq!(totals.cost_change_ratio(), Some(0.15));
+    }
+
+    #[test]
+    fn preceding_period_has_the_same_length() {
+        let day = |month, day| NaiveDate::from_ymd_opt(2026, month, day).unwrap();
+        assert_eq!(
+            preceding_period(day(3, 1), day(3, 31)),
+            (day(1, 29), day(2, 28))
+        );
+        assert_eq!(
+            preceding_period(day(3, 9), day(3, 15)),
+            (day(3, 2), day(3, 8))
+        );
+    }
+}
@@ -0,0 +1,101 @@
+use crate::data_loader::UsageEntry;
+use std::collections::BTreeMap;
+
+/// Directory shown for entries that weren't read from a data directory, such as imports.
+pub const UNKNOWN_DIRECTORY: &str = "unknown";
+
+/// Usage read from one data directory.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DirectoryUsage {
+    pub directory: String,
+    pub request_count: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub total_tokens: u64,
+    pub total_cost: f64,
+}
+
+/// Totals per data directory (Claude data root, Codex home, or OpenCode directory), most
+/// expensive first.
+pub fn directory_usage(entries: &[UsageEntry]) -> Vec<DirectoryUsage> {
+    let mut directories: BTreeMap<String, DirectoryUsage> = BTreeMap::new();
+    for entry in entries {
+        let directory = entry.data_dir.as_ref().map_or_else(
+            || UNKNOWN_DIRECTORY.to_string(),
+            |dir| dir.display().to_string(),
+        );
+        let usage = directories
+            .entry(directory)
+            .or_insert_with_key(|directory| DirectoryUsage {
+                directory: directory.clone(),
+                ..Default::default()
+            });
+        usage.request_count += 1;
+        usage.input_tokens += entry.input_tokens;
+        usage.output_tokens += entry.output_tokens;
+        usage.cache_creation_tokens += entry.cache_creation_tokens;
+        usage.cache_read_tokens += entry.cache_read_tokens;
+        usage.total_tokens += entry.total_tokens;
+        usage.total_cost += entry.cost;
+    }
+    let mut directories = directories.into_values().collect::<Vec<_>>();
+    directories.sort_by(|a, b| {
+        b.total_cost
+            .total_cmp(&a.total_cost)
+            .then_with(|| a.directory.cmp(&b.directory))
+    });
+    directories
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use std::path::PathBuf;
+
+    fn entry(data_dir: Option<&str>, cost: f64) -> UsageEntry {
+        UsageEntry {
+            timestamp: Utc.with_ymd_and_hms(2026, 1, 2, 3, 4, 5).unwrap(),
+            date: "2026-01-02".to_string(),
+            model: Some("claude-sonnet-4".to_string()),
+            input_tokens: 10,
+            output_tokens: 5,
+            cache_creation_tokens: 2,
+            cache_read_tokens: 3,
+            total_tokens: 20,
+            cost,
+            local_hour: 3,
+            data_dir: data_dir.map(PathBuf::from),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn directory_usage_totals_each_directory_by_cost() {
+        let entries = vec![
+            entry(Some("/home/me/.claude"), 1.0),
+            entry(Some("/mnt/work/.claude"), 2.0),
+            entry(Some("/mnt/work/.claude"), 0.5),
+            entry(None, 0.25),
+        ];
+
+        let directories = directory_usage(&entries);
+        assert_eq!(
+            directories
+                .iter()
+                .map(|usage| (usage.directory.as_str(), usage.request_count))
+                .collect::<Vec<_>>(),
+            vec![
+                ("/mnt/work/.claude", 2),
+                ("/home/me/.claude", 1),
+                (UNKNOWN_DIRECTORY, 1)
+            ]
+        );
+        assert_eq!(directories[0].total_cost, 2.5);
+        assert_eq!(directories[0].input_tokens, 20);
+        assert_eq!(directories[0].cache_read_tokens, 6);
+        assert_eq!(directories[0].total_tokens, 40);
+    }
+}
@@ -0,0 +1,166 @@
+use crate::data_loader::UsageEntry;
+
+/// Upper bounds (exclusive) of the histogram buckets; the last bucket has no upper bound.
+const BUCKET_BOUNDS: [u64; 10] = [
+    1_000, 2_000, 5_000, 10_000, 20_000, 50_000, 100_000, 200_000, 500_000, 1_000_000,
+];
+
+/// Requests whose token count falls in `min..max`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TokenBucket {
+    pub min: u64,
+    /// `None` for the open-ended last bucket.
+    pub max: Option<u64>,
+    pub request_count: u64,
+    pub total_tokens: u64,
+    pub total_cost: f64,
+}
+
+impl TokenBucket {
+    /// Range such as `<1K`, `10K-20K`, or `1M+`.
+    pub fn label(&self) -> String {
+        match (self.min, self.max) {
+            (0, Some(max)) => format!("<{}", short_count(max)),
+            (min, Some(max)) => format!("{}-{}", short_count(min), short_count(max)),
+            (min, None) => format!("{}+", short_count(min)),
+        }
+    }
+}
+
+/// Histogram of one per-request token count.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TokenHistogram {
+    /// Buckets from the smallest to the largest non-empty one, including empty ones between.
+    pub buckets: Vec<TokenBucket>,
+    pub request_count: u64,
+    pub median: u64,
+    pub p90: u64,
+    pub max: u64,
+}
+
+/// How prompt and output sizes of individual requests are spread.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RequestSizeDistribution {
+    /// Prompt tokens per request: input plus cache creation and cache read tokens.
+    pub input: TokenHistogram,
+    pub output: TokenHistogram,
+}
+
+/// Histograms of per-request prompt and output tokens; `<synthetic>` entries are left out.
+pub fn request_size_distribution(entries: &[UsageEntry]) -> RequestSizeDistribution {
+    let requests = entries
+        .iter()
+        .filter(|entry| entry.model.as_deref() != Some("<synthetic>"))
+        .collect::<Vec<_>>();
+    RequestSizeDistribution {
+        input: token_histogram(requests.iter().map(|entry| {
+            (
+                entry.input_tokens + entry.cache_creation_tokens + entry.cache_read_tokens,
+                entry.cost,
+            )
+        })),
+        output: token_histogram(
+            requests
+                .iter()
+                .map(|entry| (entry.output_tokens, entry.cost)),
+        ),
+    }
+}
+
+/// Histogram of `(tokens, cost)` pairs.
+fn token_histogram(requests: impl Iterator<Item = (u64, f64)>) -> TokenHistogram {
+    let mut buckets = std::iter::once(0)
+        .chain(BUCKET_BOUNDS)
+        .zip(BUCKET_BOUNDS.map(Some).into_iter().chain([None]))
+        .map(|(min, max)| TokenBucket {
+            min,
+            max,
+            ..Default::default()
+        })
+        .collect::<Vec<_>>();
+    let mut sizes = Vec::new();
+    for (tokens, cost) in requests {
+        let index = BUCKET_BOUNDS.partition_point(|&bound| bound <= tokens);
+        let bucket = &mut buckets[index];
+        bucket.request_count += 1;
+        bucket.total_tokens += tokens;
+        bucket.total_cost += cost;
+        sizes.push(tokens);
+    }
+    sizes.sort_unstable();
+
+    let first = buckets.iter().position(|bucket| bucket.request_count > 0);
+    let last = buckets.iter().rposition(|bucket| bucket.request_count > 0);
+    let buckets = match (first, last) {
+        (Some(first), Some(last)) => buckets.drain(first..=last).collect(),
+        _ => Vec::new(),
+    };
+    TokenHistogram {
+        buckets,
+        request_count: sizes.len() as u64,
+        median: percentile(&sizes, 50),
+        p90: percentile(&sizes, 90),
+        max: sizes.last().copied().unwrap_or(0),
+    }
+}
+
+/// Nearest-rank percentile of sorted `values`, or 0 when there are none.
+fn percentile(values: &[u64], percent: usize) -> u64 {
+    if values.is_empty() {
+        return 0;
+    }
+    let rank = (values.len() * percent).div_ceil(100).max(1);
+    values[rank - 1]
+}
+
+fn short_count(count: u64) -> String {
+    if count >= 1_000_000 && count.is_multiple_of(1_000_000) {
+        format!("{}M", count / 1_000_000)
+    } else if count >= 1_000 && count.is_multiple_of(1_000) {
+        format!("{}K", count / 1_000)
+    } else {
+        count.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_histogram_buckets_requests_and_trims_empty_ends() {
+        let histogram =
+            token_histogram([(1_500, 0.1), (1_999, 0.1), (7_000, 0.5), (60_000, 2.0)].into_iter());
+        assert_eq!(
+            histogram
+                .buckets
+                .iter()
+                .map(|bucket| (bucket.label(), bucket.request_count))
+                .collect::<Vec<_>>(),
+            vec![
+                ("1K-2K".to_string(), 2),
+                ("2K-5K".to_string(), 0),
+                ("5K-10K".to_string(), 1),
+                ("10K-20K".to_string(), 0),
+                ("20K-50K".to_string(), 0),
+                ("50K-100K".to_string(), 1),
+            ]
+        );
+        assert_eq!(histogram.buckets[0].total_tokens, 3_499);
+        assert!((histogram.buckets[0].total_cost - 0.2).abs() < 1e-9);
+        assert_eq!(histogram.request_count, 4);
+        assert_eq!(histogram.median, 1_999);
+        assert_eq!(histogram.p90, 60_000);
+        assert_eq!(histogram.max, 60_000);
+
+        let edges = token_histogram([(0, 0.0), (2_000_000, 1.0)].into_iter());
+        assert_eq!(edges.buckets.first().unwrap().label(), "<1K");
+        assert_eq!(edges.buckets.last().unwrap().label(), "1M+");
+        assert_eq!(edges.buckets.len(), 11);
+
+        assert_eq!(
+            token_histogram(std::iter::empty()),
+            TokenHistogram::default()
+        );
+    }
+}
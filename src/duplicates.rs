@@ -0,0 +1,89 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// A skipped record: its file, line, and timestamp. Reports that load the logs more than once
+/// skip the same records again, so they are collected as a set.
+type SkippedRecord = (Option<PathBuf>, Option<usize>, String);
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+static SKIPPED: Mutex<BTreeSet<SkippedRecord>> = Mutex::new(BTreeSet::new());
+
+pub fn enable_duplicate_report() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+pub fn duplicate_report_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Notes a record dropped by deduplication when `--report-duplicates` is on.
+pub fn record_duplicate(file: Option<&Path>, line: Option<usize>, timestamp: &str) {
+    if !duplicate_report_enabled() {
+        return;
+    }
+    if let Ok(mut skipped) = SKIPPED.lock() {
+        skipped.insert((file.map(Path::to_path_buf), line, timestamp.to_string()));
+    }
+}
+
+/// Prints the number of skipped duplicates per file to stderr.
+pub fn print_duplicate_report() {
+    if !duplicate_report_enabled() {
+        return;
+    }
+    let skipped = SKIPPED
+        .lock()
+        .map(|skipped| skipped.clone())
+        .unwrap_or_default();
+    eprint!("{}", duplicate_report(&skipped));
+}
+
+fn duplicate_report(skipped: &BTreeSet<SkippedRecord>) -> String {
+    if skipped.is_empty() {
+        return "Duplicates skipped: none\n".to_string();
+    }
+    let mut files: BTreeMap<Option<&Path>, usize> = BTreeMap::new();
+    for (file, _, _) in skipped {
+        *files.entry(file.as_deref()).or_default() += 1;
+    }
+    let mut files = files.into_iter().collect::<Vec<_>>();
+    files.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    let width = skipped.len().to_string().len();
+
+    let mut output = String::from("Duplicates skipped:\n");
+    for (file, count) in files {
+        let file = file.map_or_else(|| "(unknown file)".into(), Path::to_string_lossy);
+        output.push_str(&format!("  {count:>width$}  {file}\n"));
+    }
+    output.push_str(&format!("  {:>width$}  total\n", skipped.len()));
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn duplicate_report_counts_each_file_most_duplicates_first() {
+        let skipped = [
+            (Some("/logs/a.jsonl"), Some(3), "t1"),
+            (Some("/logs/b.jsonl"), Some(1), "t1"),
+            (Some("/logs/b.jsonl"), Some(2), "t2"),
+            (None, None, "t3"),
+        ]
+        .into_iter()
+        .map(|(file, line, timestamp)| (file.map(PathBuf::from), line, timestamp.to_string()))
+        .collect::<BTreeSet<_>>();
+        assert_eq!(
+            duplicate_report(&skipped),
+            "Duplicates skipped:\n  2  /logs/b.jsonl\n  1  (unknown file)\n  1  /logs/a.jsonl\n  4  total\n"
+        );
+        assert_eq!(
+            duplicate_report(&BTreeSet::new()),
+            "Duplicates skipped: none\n"
+        );
+    }
+}
@@ -0,0 +1,142 @@
+use crate::notify::run_curl;
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Utc};
+
+/// Environment variable holding the SMTP password, so it stays out of shell history.
+pub const SMTP_PASSWORD_ENV: &str = "CCOST_SMTP_PASSWORD";
+
+pub struct EmailMessage<'a> {
+    pub from: &'a str,
+    pub to: &'a [String],
+    pub subject: &'a str,
+    pub body: &'a str,
+    pub html: bool,
+}
+
+/// Renders `message` as an RFC 5322 message with CRLF line endings. HTML mail wraps the
+/// report in `<pre>` so the table keeps its alignment.
+pub fn compose_message(message: &EmailMessage, date: DateTime<Utc>) -> Result<String> {
+    for value in [message.from, message.subject]
+        .into_iter()
+        .chain(message.to.iter().map(String::as_str))
+    {
+        if value.contains(['\r', '\n']) {
+            return Err(anyhow!("Email headers must not contain line breaks"));
+        }
+    }
+
+    let (content_type, body) = if message.html {
+        (
+            "text/html",
+            format!(
+                "<html><body><pre style=\"font-family: monospace\">{}</pre></body></html>",
+                escape_html(message.body)
+            ),
+        )
+    } else {
+        ("text/plain", message.body.to_string())
+    };
+    let headers = [
+        format!("From: {}", message.from),
+        format!("To: {}", message.to.join(", ")),
+        format!("Subject: {}", message.subject),
+        format!("Date: {}", date.to_rfc2822()),
+        "MIME-Version: 1.0".to_string(),
+        format!("Content-Type: {content_type}; charset=utf-8"),
+        "Content-Transfer-Encoding: 8bit".to_string(),
+    ];
+    let body = body.lines().collect::<Vec<_>>().join("\r\n");
+    Ok(format!("{}\r\n\r\n{body}\r\n", headers.join("\r\n")))
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Sends a composed message through `smtp_url` (`smtp://host:587` must upgrade with STARTTLS,
+/// `smtps://host:465` uses implicit TLS) using `curl`. The URL and credentials go in curl's
+/// private config file, so they never show up in `ps`, and curl refuses to send them unencrypted.
+pub fn send_message(
+    smtp_url: &str,
+    from: &str,
+    to: &[String],
+    user: Option<&str>,
+    message: &str,
+) -> Result<()> {
+    let mut args = vec!["--ssl-reqd", "--mail-from", from];
+    for recipient in to {
+        args.extend(["--mail-rcpt", recipient.as_str()]);
+    }
+    args.extend(["--upload-file", "-"]);
+    let credentials = user.map(|user| {
+        let password = std::env::var(SMTP_PASSWORD_ENV).unwrap_or_default();
+        format!("{user}:{password}")
+    });
+    let mut config = vec![("url", smtp_url)];
+    if let Some(credentials) = &credentials {
+        config.push(("user", credentials.as_str()));
+    }
+    run_curl(&args, &config, message.as_bytes())
+        .map_err(|error| anyhow!("sending email failed: {error}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn recipients() -> Vec<String> {
+        vec![
+            "team@example.com".to_string(),
+            "lead@example.com".to_string(),
+        ]
+    }
+
+    #[test]
+    fn compose_message_writes_headers_and_crlf_body() {
+        let to = recipients();
+        let message = EmailMessage {
+            from: "ccost@example.com",
+            to: &to,
+            subject: "ccost monthly report",
+            body: "Month  Cost\n2026-01  $1.00",
+            html: false,
+        };
+        let date = Utc.with_ymd_and_hms(2026, 2, 1, 9, 0, 0).unwrap();
+
+        assert_eq!(
+            compose_message(&message, date).unwrap(),
+            "From: ccost@example.com\r\n\
+             To: team@example.com, lead@example.com\r\n\
+             Subject: ccost monthly report\r\n\
+             Date: Sun, 1 Feb 2026 09:00:00 +0000\r\n\
+             MIME-Version: 1.0\r\n\
+             Content-Type: text/plain; charset=utf-8\r\n\
+             Content-Transfer-Encoding: 8bit\r\n\
+             \r\n\
+             Month  Cost\r\n\
+             2026-01  $1.00\r\n"
+        );
+    }
+
+    #[test]
+    fn compose_message_escapes_html_and_rejects_header_injection() {
+        let to = recipients();
+        let mut message = EmailMessage {
+            from: "ccost@example.com",
+            to: &to,
+            subject: "report",
+            body: "a < b & c",
+            html: true,
+        };
+        let date = Utc.with_ymd_and_hms(2026, 2, 1, 9, 0, 0).unwrap();
+        let composed = compose_message(&message, date).unwrap();
+        assert!(composed.contains("Content-Type: text/html; charset=utf-8"));
+        assert!(composed.contains("a &lt; b &amp; c"));
+
+        message.subject = "report\r\nBcc: someone@example.com";
+        assert!(compose_message(&message, date).is_err());
+    }
+}
@@ -0,0 +1,79 @@
+use serde::Serialize;
+use serde_json::{Value, json};
+use std::fmt;
+
+/// Stable identifier of a failure, reported as `error.code` when a command runs with `--json`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    InvalidArgument,
+    InvalidTimezone,
+    InvalidConfig,
+    NoDataDirectory,
+    NoData,
+    /// A report row exceeded its budget under `--fail-on-over-budget`.
+    OverBudget,
+    /// Any failure without a more specific code.
+    Error,
+}
+
+/// Error carrying an [`ErrorCode`]; displays as its message alone.
+#[derive(Debug)]
+pub struct CodedError {
+    pub code: ErrorCode,
+    pub message: String,
+}
+
+impl fmt::Display for CodedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for CodedError {}
+
+pub fn coded_error(code: ErrorCode, message: impl Into<String>) -> anyhow::Error {
+    CodedError {
+        code,
+        message: message.into(),
+    }
+    .into()
+}
+
+/// Code of the first coded error in the chain, or [`ErrorCode::Error`].
+pub fn error_code(error: &anyhow::Error) -> ErrorCode {
+    error
+        .chain()
+        .find_map(|cause| cause.downcast_ref::<CodedError>())
+        .map_or(ErrorCode::Error, |coded| coded.code)
+}
+
+/// `{"error": {"code": ..., "message": ...}}` for `error`.
+pub fn json_error(error: &anyhow::Error) -> Value {
+    json!({
+        "error": {
+            "code": error_code(error),
+            "message": error.to_string(),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+
+    #[test]
+    fn json_error_reports_the_code_and_message() {
+        let error = coded_error(ErrorCode::InvalidTimezone, "Invalid timezone: Foo/Bar");
+        assert_eq!(
+            json_error(&error),
+            json!({"error": {"code": "invalid_timezone", "message": "Invalid timezone: Foo/Bar"}})
+        );
+
+        let wrapped = coded_error(ErrorCode::NoDataDirectory, "missing").context("loading");
+        assert_eq!(error_code(&wrapped), ErrorCode::NoDataDirectory);
+        assert_eq!(error_code(&anyhow!("boom")), ErrorCode::Error);
+        assert_eq!(json_error(&anyhow!("boom"))["error"]["code"], "error");
+    }
+}
@@ -0,0 +1,157 @@
+use crate::data_loader::DailyUsage;
+use chrono::{Datelike, Duration, NaiveDate};
+use std::collections::HashMap;
+
+/// Days averaged for the run-rate when none is given.
+pub const DEFAULT_WINDOW_DAYS: u32 = 7;
+
+/// A month's spend projected from the average daily cost of recent days.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonthForecast {
+    /// Month being projected, as `YYYY-MM`.
+    pub month: String,
+    pub days_in_month: u32,
+    /// Days of the month up to and including the forecast day.
+    pub days_elapsed: u32,
+    pub month_to_date_cost: f64,
+    /// Calendar days averaged for the run-rate, ending on the forecast day.
+    pub window_days: u32,
+    /// Mean daily cost over the window, counting days without usage as zero.
+    pub daily_run_rate: f64,
+    /// Sample standard deviation of daily cost over the window.
+    pub daily_std_dev: f64,
+    /// Month-to-date cost plus the run-rate for each day left in the month.
+    pub projected_cost: f64,
+    /// One standard deviation of the remaining days' total either side of `projected_cost`,
+    /// treating days as independent. The low end never drops below the month-to-date cost.
+    pub projected_low: f64,
+    pub projected_high: f64,
+}
+
+impl MonthForecast {
+    /// Days after the forecast day until the month ends.
+    pub fn days_remaining(&self) -> u32 {
+        self.days_in_month - self.days_elapsed
+    }
+}
+
+/// Projects the month containing `today` from daily cost (summed across projects). The
+/// forecast day counts as a full day both in the month-to-date cost and in the run-rate.
+pub fn forecast_month(daily: &[DailyUsage], today: NaiveDate, window_days: u32) -> MonthForecast {
+    let mut costs: HashMap<NaiveDate, f64> = HashMap::new();
+    for entry in daily {
+        if let Ok(date) = NaiveDate::parse_from_str(&entry.date, "%Y-%m-%d") {
+            *costs.entry(date).or_default() += entry.total_cost;
+        }
+    }
+    let window_days = window_days.max(1);
+    let window = (0..window_days)
+        .map(|back| {
+            let date = today - Duration::days(i64::from(back));
+            costs.get(&date).copied().unwrap_or(0.0)
+        })
+        .collect::<Vec<_>>();
+    let (daily_run_rate, daily_std_dev) = mean_and_std_dev(&window);
+
+    let month_start = today.with_day(1).unwrap_or(today);
+    let month_to_date_cost = costs
+        .iter()
+        .filter(|(date, _)| (month_start..=today).contains(date))
+        .map(|(_, cost)| cost)
+        .sum::<f64>();
+    let days_in_month = days_in_month(today);
+    let remaining = f64::from(days_in_month - today.day());
+    let projected_cost = month_to_date_cost + daily_run_rate * remaining;
+    let spread = daily_std_dev * remaining.sqrt();
+    MonthForecast {
+        month: today.format("%Y-%m").to_string(),
+        days_in_month,
+        days_elapsed: today.day(),
+        month_to_date_cost,
+        window_days,
+        daily_run_rate,
+        daily_std_dev,
+        projected_cost,
+        projected_low: (projected_cost - spread).max(month_to_date_cost),
+        projected_high: projected_cost + spread,
+    }
+}
+
+/// Mean and sample standard deviation; the deviation is zero for fewer than two values.
+fn mean_and_std_dev(values: &[f64]) -> (f64, f64) {
+    if values.is_empty() {
+        return (0.0, 0.0);
+    }
+    let count = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / count;
+    if values.len() < 2 {
+        return (mean, 0.0);
+    }
+    let variance = values
+        .iter()
+        .map(|value| (value - mean).powi(2))
+        .sum::<f64>()
+        / (count - 1.0);
+    (mean, variance.sqrt())
+}
+
+fn days_in_month(date: NaiveDate) -> u32 {
+    let (year, month) = match date.month() {
+        12 => (date.year() + 1, 1),
+        month => (date.year(), month + 1),
+    };
+    NaiveDate::from_ymd_opt(year, month, 1)
+        .and_then(|next| next.pred_opt())
+        .map_or(31, |last| last.day())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(date: &str, cost: f64) -> DailyUsage {
+        DailyUsage {
+            date: date.to_string(),
+            total_cost: cost,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn forecast_adds_the_run_rate_for_each_remaining_day() {
+        let daily = vec![
+            day("2026-01-28", 9.0),
+            day("2026-02-01", 2.0),
+            day("2026-02-02", 4.0),
+            day("2026-02-02", 2.0),
+            day("2026-02-04", 4.0),
+        ];
+        let today = NaiveDate::from_ymd_opt(2026, 2, 4).unwrap();
+
+        let forecast = forecast_month(&daily, today, 4);
+        assert_eq!(forecast.month, "2026-02");
+        assert_eq!(forecast.days_in_month, 28);
+        assert_eq!(forecast.days_remaining(), 24);
+        assert_eq!(forecast.month_to_date_cost, 12.0);
+        // Feb 1-4 cost 2, 6, 0, and 4.
+        assert_eq!(forecast.daily_run_rate, 3.0);
+        assert_eq!(forecast.projected_cost, 12.0 + 3.0 * 24.0);
+        assert!(forecast.projected_low < forecast.projected_cost);
+        assert!(forecast.projected_high > forecast.projected_cost);
+
+        // The window reaches back into January; the month-to-date cost does not.
+        let forecast = forecast_month(&daily, today, 8);
+        assert_eq!(forecast.daily_run_rate, 21.0 / 8.0);
+        assert_eq!(forecast.month_to_date_cost, 12.0);
+    }
+
+    #[test]
+    fn forecast_on_the_last_day_is_the_month_to_date_cost() {
+        let today = NaiveDate::from_ymd_opt(2025, 12, 31).unwrap();
+        let forecast = forecast_month(&[day("2025-12-31", 5.0)], today, 1);
+        assert_eq!(forecast.days_remaining(), 0);
+        assert_eq!(forecast.projected_cost, 5.0);
+        assert_eq!(forecast.projected_low, 5.0);
+        assert_eq!(forecast.projected_high, 5.0);
+    }
+}
@@ -0,0 +1,200 @@
+use anyhow::{Result, anyhow};
+use regex::Regex;
+use std::path::Path;
+
+/// Gitignore-style patterns matched against paths relative to a scan root.
+///
+/// Supported syntax: `*`, `?`, `[...]`, `**`, a trailing `/` for directories only, a leading or
+/// inner `/` to anchor the pattern to the root, and `!` to re-include. The last matching pattern
+/// wins, and an ignored directory is never descended into.
+#[derive(Debug, Clone, Default)]
+pub struct IgnorePatterns {
+    rules: Vec<IgnoreRule>,
+}
+
+#[derive(Debug, Clone)]
+struct IgnoreRule {
+    regex: Regex,
+    negated: bool,
+    directory_only: bool,
+}
+
+impl IgnorePatterns {
+    pub fn new<S: AsRef<str>>(patterns: &[S]) -> Result<Self> {
+        let mut rules = Vec::new();
+        for pattern in patterns {
+            if let Some(rule) = compile_rule(pattern.as_ref())? {
+                rules.push(rule);
+            }
+        }
+        Ok(Self { rules })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    pub fn is_ignored(&self, relative: &Path, is_dir: bool) -> bool {
+        let path = relative.to_string_lossy().replace('\\', "/");
+        let mut ignored = false;
+        for rule in &self.rules {
+            if rule.directory_only && !is_dir {
+                continue;
+            }
+            if rule.regex.is_match(&path) {
+                ignored = !rule.negated;
+            }
+        }
+        ignored
+    }
+}
+
+fn compile_rule(pattern: &str) -> Result<Option<IgnoreRule>> {
+    let trimmed = pattern.trim();
+    if trimmed.is_empty() || trimmed.starts_with('#') {
+        return Ok(None);
+    }
+    let (negated, body) = match trimmed.strip_prefix('!') {
+        Some(rest) => (true, rest),
+        None => (false, trimmed),
+    };
+    let (directory_only, body) = match body.strip_suffix('/') {
+        Some(rest) => (true, rest),
+        None => (false, body),
+    };
+    let anchored = body.contains('/');
+    let body = body.trim_start_matches('/');
+    if body.is_empty() {
+        return Err(anyhow!("Invalid ignore pattern: {pattern}"));
+    }
+
+    let mut expression = String::from("^");
+    if !anchored {
+        expression.push_str("(?:.*/)?");
+    }
+    expression.push_str(&glob_to_regex(body));
+    expression.push('$');
+    let regex = Regex::new(&expression)
+        .map_err(|error| anyhow!("Invalid ignore pattern {pattern}: {error}"))?;
+    Ok(Some(IgnoreRule {
+        regex,
+        negated,
+        directory_only,
+    }))
+}
+
+fn glob_to_regex(glob: &str) -> String {
+    let chars = glob.chars().collect::<Vec<_>>();
+    let mut output = String::new();
+    let mut index = 0;
+    while index < chars.len() {
+        match chars[index] {
+            '*' if chars.get(index + 1) == Some(&'*') => {
+                if chars.get(index + 2) == Some(&'/') {
+                    output.push_str("(?:.*/)?");
+                    index += 3;
+                } else {
+                    output.push_str(".*");
+                    index += 2;
+                }
+                continue;
+            }
+            '*' => output.push_str("[^/]*"),
+            '?' => output.push_str("[^/]"),
+            '[' => {
+                if let Some(end) = class_end(&chars, index) {
+                    output.push_str(&class_to_regex(&chars[index + 1..end]));
+                    index = end + 1;
+                    continue;
+                }
+                output.push_str("\\[");
+            }
+            '\\' if index + 1 < chars.len() => {
+                output.push_str(&regex::escape(&chars[index + 1].to_string()));
+                index += 2;
+                continue;
+            }
+            other => output.push_str(&regex::escape(&other.to_string())),
+        }
+        index += 1;
+    }
+    output
+}
+
+/// Index of the `]` closing the class opened at `start`; a `]` right after `[` or `[!` is literal.
+fn class_end(chars: &[char], start: usize) -> Option<usize> {
+    let mut index = start + 1;
+    if matches!(chars.get(index), Some('!' | '^')) {
+        index += 1;
+    }
+    if chars.get(index) == Some(&']') {
+        index += 1;
+    }
+    chars[index.min(chars.len())..]
+        .iter()
+        .position(|&c| c == ']')
+        .map(|offset| index + offset)
+}
+
+fn class_to_regex(content: &[char]) -> String {
+    let mut output = String::from("[");
+    let mut content = content;
+    if let Some(('!' | '^', rest)) = content.split_first() {
+        output.push('^');
+        content = rest;
+    }
+    for &c in content {
+        if matches!(c, '\\' | '[' | ']' | '&' | '~' | '^') {
+            output.push('\\');
+        }
+        output.push(c);
+    }
+    output.push(']');
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn patterns(values: &[&str]) -> IgnorePatterns {
+        IgnorePatterns::new(values).unwrap()
+    }
+
+    #[test]
+    fn unanchored_patterns_match_at_any_depth() {
+        let ignore = patterns(&["node_modules", "*.bak.jsonl"]);
+        assert!(ignore.is_ignored(Path::new("node_modules"), true));
+        assert!(ignore.is_ignored(Path::new("project/node_modules"), true));
+        assert!(ignore.is_ignored(Path::new("project/session.bak.jsonl"), false));
+        assert!(!ignore.is_ignored(Path::new("project/session.jsonl"), false));
+    }
+
+    #[test]
+    fn anchored_and_directory_patterns() {
+        let ignore = patterns(&["/archived/", "project/old-*.jsonl"]);
+        assert!(ignore.is_ignored(Path::new("archived"), true));
+        assert!(!ignore.is_ignored(Path::new("archived"), false));
+        assert!(!ignore.is_ignored(Path::new("nested/archived"), true));
+        assert!(ignore.is_ignored(Path::new("project/old-1.jsonl"), false));
+        assert!(!ignore.is_ignored(Path::new("other/project/old-1.jsonl"), false));
+    }
+
+    #[test]
+    fn double_star_classes_and_negation() {
+        let ignore = patterns(&["**/tmp/**", "session-[0-9].jsonl", "!session-7.jsonl"]);
+        assert!(ignore.is_ignored(Path::new("a/b/tmp/c.jsonl"), false));
+        assert!(ignore.is_ignored(Path::new("tmp/c.jsonl"), false));
+        assert!(ignore.is_ignored(Path::new("p/session-3.jsonl"), false));
+        assert!(!ignore.is_ignored(Path::new("p/session-7.jsonl"), false));
+        assert!(!ignore.is_ignored(Path::new("p/session-x.jsonl"), false));
+    }
+
+    #[test]
+    fn windows_separators_and_blank_or_comment_lines() {
+        let ignore = patterns(&["", "# comment", "project/skip.jsonl"]);
+        assert!(ignore.is_ignored(Path::new(r"project\skip.jsonl"), false));
+        assert!(IgnorePatterns::new(&["/"]).is_err());
+        assert!(patterns(&["", "# only comments"]).is_empty());
+    }
+}
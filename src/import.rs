@@ -0,0 +1,711 @@
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Deserialize;
+use serde_json::{Map, Value, json};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+/// Project directory used for imported records that don't name a project.
+pub const DEFAULT_IMPORT_PROJECT: &str = "imported";
+
+/// One usage record read from another tool's export.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedRecord {
+    pub timestamp: DateTime<Utc>,
+    pub project: Option<String>,
+    pub model: Option<String>,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub cost: Option<f64>,
+    /// The exporting tool's id of the request, such as an OpenRouter generation id.
+    pub id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CcusageExport {
+    daily: Option<Vec<CcusageDay>>,
+    projects: Option<HashMap<String, Vec<CcusageDay>>>,
+    monthly: Option<serde_json::Value>,
+    weekly: Option<serde_json::Value>,
+    sessions: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CcusageDay {
+    date: String,
+    #[serde(default)]
+    input_tokens: u64,
+    #[serde(default)]
+    output_tokens: u64,
+    #[serde(default)]
+    cache_creation_tokens: u64,
+    #[serde(default)]
+    cache_read_tokens: u64,
+    total_cost: Option<f64>,
+    #[serde(default)]
+    models_used: Vec<String>,
+    #[serde(default)]
+    model_breakdowns: Vec<CcusageModelBreakdown>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CcusageModelBreakdown {
+    model_name: String,
+    #[serde(default)]
+    input_tokens: u64,
+    #[serde(default)]
+    output_tokens: u64,
+    #[serde(default)]
+    cache_creation_tokens: u64,
+    #[serde(default)]
+    cache_read_tokens: u64,
+    cost: Option<f64>,
+}
+
+/// Reads a `ccusage daily --json` export (with or without `--instances`). Each model breakdown
+/// becomes one record at noon UTC of its day, so per-model costs survive the import.
+pub fn parse_ccusage_export(content: &str) -> Result<Vec<ImportedRecord>> {
+    let export: CcusageExport = serde_json::from_str(content)
+        .map_err(|error| anyhow!("not a ccusage JSON export: {error}"))?;
+    let mut days = Vec::new();
+    if let Some(daily) = export.daily {
+        days.extend(daily.into_iter().map(|day| (None, day)));
+    }
+    if let Some(projects) = export.projects {
+        for (project, daily) in projects {
+            days.extend(daily.into_iter().map(|day| (Some(project.clone()), day)));
+        }
+    }
+    if days.is_empty() {
+        if export.monthly.is_some() || export.weekly.is_some() || export.sessions.is_some() {
+            return Err(anyhow!(
+                "only daily ccusage exports can be imported (run `ccusage daily --json`)"
+            ));
+        }
+        return Err(anyhow!(
+            "ccusage export has no `daily` or `projects` entries"
+        ));
+    }
+
+    let mut records = Vec::new();
+    for (project, day) in days {
+        let timestamp = parse_timestamp(&day.date)?;
+        if day.model_breakdowns.is_empty() {
+            records.push(ImportedRecord {
+                timestamp,
+                project,
+                model: day.models_used.first().cloned(),
+                input_tokens: day.input_tokens,
+                output_tokens: day.output_tokens,
+                cache_creation_tokens: day.cache_creation_tokens,
+                cache_read_tokens: day.cache_read_tokens,
+                cost: day.total_cost,
+                id: None,
+            });
+            continue;
+        }
+        for breakdown in day.model_breakdowns {
+            records.push(ImportedRecord {
+                timestamp,
+                project: project.clone(),
+                model: Some(breakdown.model_name),
+                input_tokens: breakdown.input_tokens,
+                output_tokens: breakdown.output_tokens,
+                cache_creation_tokens: breakdown.cache_creation_tokens,
+                cache_read_tokens: breakdown.cache_read_tokens,
+                cost: breakdown.cost,
+                id: None,
+            });
+        }
+    }
+    records.sort_by_key(|record| record.timestamp);
+    Ok(records)
+}
+
+/// Record fields a CSV column can map to, with the column name used when unmapped.
+pub const CSV_FIELDS: [&str; 8] = [
+    "date",
+    "project",
+    "model",
+    "input_tokens",
+    "output_tokens",
+    "cache_creation_tokens",
+    "cache_read_tokens",
+    "cost",
+];
+
+/// Parses `FIELD=COLUMN` mappings for [`parse_csv_export`].
+pub fn parse_csv_mapping(entries: &[String]) -> Result<HashMap<String, String>> {
+    let mut mapping = HashMap::new();
+    for entry in entries {
+        let (field, column) = entry
+            .split_once('=')
+            .ok_or_else(|| anyhow!("Invalid mapping: {entry} (expected FIELD=COLUMN)"))?;
+        let field = field.trim();
+        if !CSV_FIELDS.contains(&field) {
+            return Err(anyhow!(
+                "Unknown field in mapping: {field} (expected one of {})",
+                CSV_FIELDS.join(", ")
+            ));
+        }
+        mapping.insert(field.to_string(), column.trim().to_string());
+    }
+    Ok(mapping)
+}
+
+/// Reads a CSV export with a header row. Columns are matched by name: each field in
+/// [`CSV_FIELDS`] uses the column of the same name unless `mapping` renames it. Only `date` is
+/// required; it may be a `YYYY-MM-DD` date or an RFC 3339 timestamp.
+pub fn parse_csv_export(
+    content: &str,
+    mapping: &HashMap<String, String>,
+) -> Result<Vec<ImportedRecord>> {
+    let mut rows = parse_csv(content)?.into_iter();
+    let header = rows.next().ok_or_else(|| anyhow!("CSV file is empty"))?;
+    let column = |field: &str| -> Option<usize> {
+        let name = mapping.get(field).map_or(field, String::as_str);
+        header
+            .iter()
+            .position(|column| column.trim().eq_ignore_ascii_case(name))
+    };
+    let date_column = column("date").ok_or_else(|| {
+        anyhow!(
+            "CSV has no `{}` column (use --map date=COLUMN)",
+            mapping.get("date").map_or("date", String::as_str)
+        )
+    })?;
+    let project_column = column("project");
+    let model_column = column("model");
+    let token_columns = [
+        column("input_tokens"),
+        column("output_tokens"),
+        column("cache_creation_tokens"),
+        column("cache_read_tokens"),
+    ];
+    let cost_column = column("cost");
+
+    let mut records = Vec::new();
+    for (index, row) in rows.enumerate() {
+        let row_number = index + 2;
+        if row.iter().all(|cell| cell.trim().is_empty()) {
+            continue;
+        }
+        let cell = |column: Option<usize>| {
+            column
+                .and_then(|column| row.get(column))
+                .map(|cell| cell.trim())
+                .filter(|cell| !cell.is_empty())
+        };
+        let tokens = |column: Option<usize>| -> Result<u64> {
+            cell(column).map_or(Ok(0), |value| {
+                value
+                    .replace(',', "")
+                    .parse::<u64>()
+                    .map_err(|_| anyhow!("row {row_number}: invalid token count: {value}"))
+            })
+        };
+        let date =
+            cell(Some(date_column)).ok_or_else(|| anyhow!("row {row_number}: missing date"))?;
+        records.push(ImportedRecord {
+            timestamp: parse_timestamp(date)
+                .map_err(|error| anyhow!("row {row_number}: {error}"))?,
+            project: cell(project_column).map(str::to_string),
+            model: cell(model_column).map(str::to_string),
+            input_tokens: tokens(token_columns[0])?,
+            output_tokens: tokens(token_columns[1])?,
+            cache_creation_tokens: tokens(token_columns[2])?,
+            cache_read_tokens: tokens(token_columns[3])?,
+            cost: cell(cost_column)
+                .map(|value| {
+                    value
+                        .trim_start_matches('$')
+                        .parse::<f64>()
+                        .map_err(|_| anyhow!("row {row_number}: invalid cost: {value}"))
+                })
+                .transpose()?,
+            id: None,
+        });
+    }
+    Ok(records)
+}
+
+/// Field names tried in order for each value of an OpenRouter export row. Activity exports
+/// use `created_at`/`tokens_prompt`/`cost_total`, the generation API `native_tokens_*` and
+/// `total_cost`, and the per-day activity API `date`/`prompt_tokens`/`usage`.
+const OPENROUTER_TIMESTAMP: [&str; 3] = ["created_at", "createdAt", "date"];
+const OPENROUTER_MODEL: [&str; 3] = ["model_permaslug", "model", "model_id"];
+const OPENROUTER_PROMPT_TOKENS: [&str; 3] =
+    ["native_tokens_prompt", "tokens_prompt", "prompt_tokens"];
+const OPENROUTER_OUTPUT_TOKENS: [&str; 3] = [
+    "native_tokens_completion",
+    "tokens_completion",
+    "completion_tokens",
+];
+const OPENROUTER_CACHED_TOKENS: [&str; 3] =
+    ["native_tokens_cached", "tokens_cached", "cached_tokens"];
+const OPENROUTER_COST: [&str; 4] = ["cost_total", "total_cost", "usage", "cost"];
+const OPENROUTER_PROJECT: [&str; 2] = ["app_name", "api_key_name"];
+const OPENROUTER_ID: [&str; 2] = ["generation_id", "id"];
+
+/// Reads an OpenRouter activity export (CSV or JSON) or saved generation API responses. JSON
+/// may be an array of rows or an object whose `data` is a row or an array of rows. Cached
+/// prompt tokens become cache reads; the provider prefix (`anthropic/`) is dropped from model
+/// names so they match Claude Code's.
+pub fn parse_openrouter_export(content: &str) -> Result<Vec<ImportedRecord>> {
+    let content = content.trim_start_matches('\u{feff}').trim_start();
+    let rows = if content.starts_with('[') || content.starts_with('{') {
+        let value: Value = serde_json::from_str(content)
+            .map_err(|error| anyhow!("not an OpenRouter JSON export: {error}"))?;
+        let rows = match value {
+            Value::Object(mut object) => object.remove("data").unwrap_or(Value::Object(object)),
+            other => other,
+        };
+        match rows {
+            Value::Array(rows) => rows
+                .into_iter()
+                .map(|row| match row {
+                    Value::Object(row) => Ok(row),
+                    _ => Err(anyhow!("OpenRouter export rows must be objects")),
+                })
+                .collect::<Result<Vec<_>>>()?,
+            Value::Object(row) => vec![row],
+            _ => return Err(anyhow!("OpenRouter export has no rows")),
+        }
+    } else {
+        let mut rows = parse_csv(content)?.into_iter();
+        let header = rows.next().ok_or_else(|| anyhow!("CSV file is empty"))?;
+        rows.filter(|row| row.iter().any(|cell| !cell.trim().is_empty()))
+            .map(|row| {
+                header
+                    .iter()
+                    .map(|column| column.trim().to_string())
+                    .zip(row.into_iter().map(Value::String))
+                    .collect::<Map<_, _>>()
+            })
+            .collect()
+    };
+
+    let mut records = rows
+        .iter()
+        .enumerate()
+        .map(|(index, row)| {
+            openrouter_record(row).map_err(|error| anyhow!("row {}: {error}", index + 1))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    records.sort_by_key(|record| record.timestamp);
+    Ok(records)
+}
+
+fn openrouter_record(row: &Map<String, Value>) -> Result<ImportedRecord> {
+    let field = |names: &[&str]| {
+        names.iter().find_map(|name| match row.get(*name)? {
+            Value::Null => None,
+            Value::String(value) if value.trim().is_empty() => None,
+            value => Some(value),
+        })
+    };
+    let text = |names: &[&str]| {
+        field(names).map(|value| match value {
+            Value::String(value) => value.trim().to_string(),
+            other => other.to_string(),
+        })
+    };
+    let number = |names: &[&str]| -> Result<Option<f64>> {
+        let Some(value) = field(names) else {
+            return Ok(None);
+        };
+        value
+            .as_f64()
+            .or_else(|| value.as_str()?.trim().trim_start_matches('$').parse().ok())
+            .filter(|number| number.is_finite() && *number >= 0.0)
+            .map(Some)
+            .ok_or_else(|| anyhow!("invalid number: {value}"))
+    };
+    let tokens = |names: &[&str]| -> Result<u64> {
+        Ok(number(names)?.map_or(0, |tokens| tokens.round() as u64))
+    };
+
+    let timestamp = text(&OPENROUTER_TIMESTAMP)
+        .ok_or_else(|| anyhow!("missing {}", OPENROUTER_TIMESTAMP[0]))?;
+    let cache_read_tokens = tokens(&OPENROUTER_CACHED_TOKENS)?;
+    Ok(ImportedRecord {
+        timestamp: parse_openrouter_timestamp(&timestamp)?,
+        project: text(&OPENROUTER_PROJECT),
+        model: text(&OPENROUTER_MODEL).map(|model| match model.split_once('/') {
+            Some((_, name)) => name.to_string(),
+            None => model,
+        }),
+        input_tokens: tokens(&OPENROUTER_PROMPT_TOKENS)?.saturating_sub(cache_read_tokens),
+        output_tokens: tokens(&OPENROUTER_OUTPUT_TOKENS)?,
+        cache_creation_tokens: 0,
+        cache_read_tokens,
+        cost: number(&OPENROUTER_COST)?,
+        id: text(&OPENROUTER_ID),
+    })
+}
+
+/// Like [`parse_timestamp`], but also accepts the `YYYY-MM-DD HH:MM:SS[.fff]` UTC timestamps
+/// of OpenRouter's CSV export.
+fn parse_openrouter_timestamp(value: &str) -> Result<DateTime<Utc>> {
+    parse_timestamp(value).or_else(|error| {
+        chrono::NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S%.f")
+            .map(|timestamp| timestamp.and_utc())
+            .map_err(|_| error)
+    })
+}
+
+/// Splits RFC 4180 CSV into rows of cells (quoted cells may contain commas, `""`, and newlines).
+fn parse_csv(content: &str) -> Result<Vec<Vec<String>>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut cell = String::new();
+    let mut in_quotes = false;
+    let mut chars = content.trim_start_matches('\u{feff}').chars().peekable();
+    while let Some(character) = chars.next() {
+        match (in_quotes, character) {
+            (true, '"') if chars.peek() == Some(&'"') => {
+                chars.next();
+                cell.push('"');
+            }
+            (true, '"') => in_quotes = false,
+            (true, _) => cell.push(character),
+            (false, '"') if cell.is_empty() => in_quotes = true,
+            (false, ',') => row.push(std::mem::take(&mut cell)),
+            (false, '\r') => {}
+            (false, '\n') => {
+                row.push(std::mem::take(&mut cell));
+                rows.push(std::mem::take(&mut row));
+            }
+            (false, _) => cell.push(character),
+        }
+    }
+    if in_quotes {
+        return Err(anyhow!("CSV has an unterminated quoted field"));
+    }
+    if !cell.is_empty() || !row.is_empty() {
+        row.push(cell);
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+fn parse_timestamp(value: &str) -> Result<DateTime<Utc>> {
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(value) {
+        return Ok(timestamp.with_timezone(&Utc));
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(12, 0, 0))
+        .map(|timestamp| timestamp.and_utc())
+        .ok_or_else(|| anyhow!("invalid date: {value} (expected YYYY-MM-DD or RFC 3339)"))
+}
+
+/// Message and request ids for `records`: the exporting tool's id when the record has one,
+/// otherwise the first 16 hex digits of the SHA-256 of its time, model, token counts, and cost.
+/// A record therefore gets the same id in every export it appears in, so overlapping imports
+/// count it once; records repeated within one export get a `-N` suffix and are all kept.
+pub fn record_ids(records: &[ImportedRecord]) -> Vec<String> {
+    let mut seen = HashMap::<String, usize>::new();
+    records
+        .iter()
+        .map(|record| {
+            let id = match &record.id {
+                Some(id) => format!("import-{id}"),
+                None => format!("import-{}", content_hash(record)),
+            };
+            let repeats = seen.entry(id.clone()).or_default();
+            *repeats += 1;
+            match *repeats {
+                1 => id,
+                repeat => format!("{id}-{}", repeat - 1),
+            }
+        })
+        .collect()
+}
+
+fn content_hash(record: &ImportedRecord) -> String {
+    let content = json!([
+        record
+            .timestamp
+            .to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+        record.model,
+        record.input_tokens,
+        record.output_tokens,
+        record.cache_creation_tokens,
+        record.cache_read_tokens,
+        record.cost,
+    ]);
+    Sha256::digest(content.to_string().as_bytes())[..8]
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// The request ids in the imported file at `path`.
+pub fn imported_ids(path: &std::path::Path) -> Vec<String> {
+    std::fs::read_to_string(path)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| {
+            let line = serde_json::from_str::<Value>(line).ok()?;
+            Some(line["requestId"].as_str()?.to_string())
+        })
+        .collect()
+}
+
+/// Renders `record` as a Claude Code usage line in the session named after `source`, with `id`
+/// (from [`record_ids`]) as its message and request id.
+pub fn claude_usage_line(record: &ImportedRecord, source: &str, id: &str) -> String {
+    let mut line = json!({
+        "timestamp": record.timestamp.to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+        "sessionId": format!("import-{source}"),
+        "requestId": id,
+        "message": {
+            "id": id,
+            "model": record.model,
+            "usage": {
+                "input_tokens": record.input_tokens,
+                "output_tokens": record.output_tokens,
+                "cache_creation_input_tokens": record.cache_creation_tokens,
+                "cache_read_input_tokens": record.cache_read_tokens,
+            },
+        },
+    });
+    if let Some(cost) = record.cost {
+        line["costUSD"] = json!(cost);
+    }
+    line.to_string()
+}
+
+/// Directory-safe project name for `project`.
+pub fn project_dir_name(project: Option<&str>) -> String {
+    let name = project
+        .unwrap_or(DEFAULT_IMPORT_PROJECT)
+        .chars()
+        .map(|character| match character {
+            '/' | '\\' | ':' | '\0' => '-',
+            other => other,
+        })
+        .collect::<String>();
+    match name.trim() {
+        "" | "." | ".." => DEFAULT_IMPORT_PROJECT.to_string(),
+        trimmed => trimmed.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ccusage_daily_export_splits_model_breakdowns() {
+        let export = r#"{
+            "daily": [{
+                "date": "2025-05-01",
+                "inputTokens": 30, "outputTokens": 3, "cacheCreationTokens": 0,
+                "cacheReadTokens": 0, "totalTokens": 33, "totalCost": 1.5,
+                "modelsUsed": ["claude-sonnet-4-20250514", "claude-opus-4-20250514"],
+                "modelBreakdowns": [
+                    {"modelName": "claude-sonnet-4-20250514", "inputTokens": 10,
+                     "outputTokens": 1, "cacheCreationTokens": 0, "cacheReadTokens": 0,
+                     "cost": 0.5},
+                    {"modelName": "claude-opus-4-20250514", "inputTokens": 20,
+                     "outputTokens": 2, "cacheCreationTokens": 0, "cacheReadTokens": 0,
+                     "cost": 1.0}
+                ]
+            }],
+            "totals": {}
+        }"#;
+        let records = parse_ccusage_export(export).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[1].model.as_deref(), Some("claude-opus-4-20250514"));
+        assert_eq!(records[1].input_tokens, 20);
+        assert_eq!(records[1].cost, Some(1.0));
+        assert_eq!(
+            records[0].timestamp.to_rfc3339(),
+            "2025-05-01T12:00:00+00:00"
+        );
+        assert_eq!(records[0].project, None);
+
+        let grouped = r#"{"projects": {"app": [{"date": "2025-05-02", "inputTokens": 5,
+            "totalCost": 0.25, "modelsUsed": ["claude-haiku-4-5"]}]}}"#;
+        let records = parse_ccusage_export(grouped).unwrap();
+        assert_eq!(records[0].project.as_deref(), Some("app"));
+        assert_eq!(records[0].model.as_deref(), Some("claude-haiku-4-5"));
+        assert_eq!(records[0].cost, Some(0.25));
+
+        assert!(parse_ccusage_export(r#"{"monthly": []}"#).is_err());
+    }
+
+    #[test]
+    fn csv_export_uses_header_names_and_mapping() {
+        let csv = "Day,Model,Input,Output,Spend\n\
+                   2025-05-01,claude-sonnet-4,\"1,000\",20,$0.75\n\
+                   \n\
+                   2025-05-02T08:30:00Z,\"gpt, \"\"custom\"\"\",5,,\n";
+        let mapping = parse_csv_mapping(&[
+            "date=Day".to_string(),
+            "model=Model".to_string(),
+            "input_tokens=Input".to_string(),
+            "output_tokens=Output".to_string(),
+            "cost=Spend".to_string(),
+        ])
+        .unwrap();
+        let records = parse_csv_export(csv, &mapping).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].input_tokens, 1_000);
+        assert_eq!(records[0].cost, Some(0.75));
+        assert_eq!(records[1].model.as_deref(), Some("gpt, \"custom\""));
+        assert_eq!(records[1].output_tokens, 0);
+        assert_eq!(records[1].cost, None);
+        assert_eq!(
+            records[1].timestamp.to_rfc3339(),
+            "2025-05-02T08:30:00+00:00"
+        );
+
+        assert!(parse_csv_export("when,cost\n2025-05-01,1\n", &HashMap::new()).is_err());
+        assert!(parse_csv_mapping(&["price=Spend".to_string()]).is_err());
+        assert!(parse_csv_export("date,input_tokens\n2025-05-01,lots\n", &HashMap::new()).is_err());
+    }
+
+    #[test]
+    fn openrouter_exports_map_tokens_cost_and_models() {
+        let csv = "generation_id,created_at,cost_total,tokens_prompt,tokens_completion,tokens_cached,model_permaslug,app_name\n\
+                   gen-2,2025-05-02 08:30:00.123,0.0125,1200,300,1000,anthropic/claude-4-sonnet-20250522,\n\
+                   gen-1,2025-05-01 10:00:00,0.5,100,20,,anthropic/claude-opus-4,my-proxy\n";
+        let records = parse_openrouter_export(csv).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].model.as_deref(), Some("claude-opus-4"));
+        assert_eq!(records[0].project.as_deref(), Some("my-proxy"));
+        assert_eq!(records[0].id.as_deref(), Some("gen-1"));
+        assert_eq!(records[0].cost, Some(0.5));
+        assert_eq!(
+            records[1].timestamp.to_rfc3339(),
+            "2025-05-02T08:30:00.123+00:00"
+        );
+        assert_eq!(records[1].input_tokens, 200);
+        assert_eq!(records[1].cache_read_tokens, 1_000);
+        assert_eq!(records[1].output_tokens, 300);
+        assert_eq!(records[1].project, None);
+
+        let generation = r#"{"data": {"id": "gen-3", "created_at": "2025-05-03T09:00:00Z",
+            "model": "anthropic/claude-haiku-4.5", "total_cost": 0.002,
+            "native_tokens_prompt": 50, "native_tokens_completion": 5,
+            "native_tokens_cached": null}}"#;
+        let records = parse_openrouter_export(generation).unwrap();
+        assert_eq!(records[0].model.as_deref(), Some("claude-haiku-4.5"));
+        assert_eq!(records[0].input_tokens, 50);
+        assert_eq!(records[0].cost, Some(0.002));
+
+        let activity = r#"[{"date": "2025-05-04", "model": "openai/gpt-5", "usage": "1.25",
+            "prompt_tokens": 10, "completion_tokens": 1}]"#;
+        let records = parse_openrouter_export(activity).unwrap();
+        assert_eq!(records[0].cost, Some(1.25));
+        assert_eq!(
+            records[0].timestamp.to_rfc3339(),
+            "2025-05-04T12:00:00+00:00"
+        );
+
+        assert!(parse_openrouter_export("created_at,cost_total\nyesterday,1\n").is_err());
+        assert!(parse_openrouter_export(r#"[{"date": "2025-05-04", "usage": "lots"}]"#).is_err());
+    }
+
+    #[test]
+    fn claude_usage_line_carries_ids_usage_and_cost() {
+        let record = ImportedRecord {
+            timestamp: parse_timestamp("2025-05-01").unwrap(),
+            project: None,
+            model: Some("claude-sonnet-4-20250514".to_string()),
+            input_tokens: 10,
+            output_tokens: 1,
+            cache_creation_tokens: 2,
+            cache_read_tokens: 3,
+            cost: Some(0.5),
+            id: None,
+        };
+        let usage_line = claude_usage_line(&record, "ccusage", "import-0123456789abcdef");
+        let line: serde_json::Value = serde_json::from_str(&usage_line).unwrap();
+        assert_eq!(line["timestamp"], "2025-05-01T12:00:00.000Z");
+        assert_eq!(line["sessionId"], "import-ccusage");
+        assert_eq!(line["requestId"], "import-0123456789abcdef");
+        assert_eq!(line["message"]["id"], "import-0123456789abcdef");
+        assert_eq!(line["message"]["usage"]["cache_read_input_tokens"], 3);
+        assert_eq!(line["costUSD"], 0.5);
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ccusage.jsonl");
+        std::fs::write(&path, format!("{usage_line}\nnot json\n")).unwrap();
+        assert_eq!(imported_ids(&path), ["import-0123456789abcdef"]);
+        assert_eq!(project_dir_name(Some("a/b")), "a-b");
+        assert_eq!(project_dir_name(Some("..")), DEFAULT_IMPORT_PROJECT);
+    }
+
+    #[test]
+    fn record_ids_follow_record_content_not_the_export() {
+        let csv = |rows: &str| {
+            parse_csv_export(
+                &format!("date,model,input_tokens,cost\n{rows}"),
+                &HashMap::new(),
+            )
+            .unwrap()
+        };
+        let first = record_ids(&csv("2025-05-01,opus,10,1\n2025-05-02,opus,20,2\n"));
+        let second = record_ids(&csv("2025-05-02,opus,20,2\n2025-05-03,opus,30,3\n"));
+        assert_eq!(first[1], second[0]);
+        assert_ne!(first[0], first[1]);
+        assert_eq!(first[0].len(), "import-".len() + 16);
+
+        let repeated = record_ids(&csv("2025-05-01,opus,10,1\n2025-05-01,opus,10,1\n"));
+        assert_eq!(repeated, [first[0].clone(), format!("{}-1", first[0])]);
+
+        let generation = parse_openrouter_export(
+            r#"{"data": {"id": "gen-3",
+            "created_at": "2025-05-03T09:00:00Z", "total_cost": 0.002}}"#,
+        )
+        .unwrap();
+        assert_eq!(record_ids(&generation), ["import-gen-3"]);
+    }
+
+    #[test]
+    fn overlapping_exports_are_counted_once() {
+        use crate::data_loader::{LoadOptions, load_usage_entries};
+        use crate::pricing::CostMode;
+
+        let dir = tempfile::tempdir().unwrap();
+        let import = |name: &str, rows: &str| {
+            let records = parse_csv_export(
+                &format!("date,model,input_tokens,cost\n{rows}"),
+                &HashMap::new(),
+            )
+            .unwrap();
+            let lines = records
+                .iter()
+                .zip(record_ids(&records))
+                .map(|(record, id)| claude_usage_line(record, name, &id) + "\n")
+                .collect::<String>();
+            let project = dir.path().join("projects").join(DEFAULT_IMPORT_PROJECT);
+            std::fs::create_dir_all(&project).unwrap();
+            std::fs::write(project.join(format!("{name}.jsonl")), lines).unwrap();
+        };
+        // April and May, then May again with June added.
+        import("spring", "2025-04-01,opus,10,1\n2025-05-01,opus,20,2\n");
+        import("summer", "2025-05-01,opus,20,2\n2025-06-01,opus,30,4\n");
+
+        let entries = load_usage_entries(&LoadOptions {
+            claude_paths: vec![dir.path().to_path_buf()],
+            mode: CostMode::Display,
+            ..LoadOptions::default()
+        })
+        .unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries.iter().map(|entry| entry.cost).sum::<f64>(), 7.0);
+        assert_eq!(
+            entries.iter().map(|entry| entry.input_tokens).sum::<u64>(),
+            60
+        );
+    }
+}
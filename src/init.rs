@@ -0,0 +1,246 @@
+use crate::config::validate_currency;
+use anyhow::{Result, anyhow};
+use chrono_tz::Tz;
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// Settings collected by `ccost init`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InitAnswers {
+    /// Claude data roots to probe, set only when the user adds directories to the detected ones.
+    pub claude_search_paths: Vec<String>,
+    pub timezone: Option<String>,
+    /// Currency code and the units of it one US dollar buys, when costs are not shown in USD.
+    pub currency: Option<(String, f64)>,
+    /// Monthly budget in USD per project.
+    pub project_budgets: Vec<(String, f64)>,
+}
+
+/// Asks for each setting on `output`, reading answers from `input`. Invalid answers are asked
+/// again; a blank answer (or end of input) keeps the default.
+pub fn ask_init_answers(
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+    detected: &[PathBuf],
+) -> Result<InitAnswers> {
+    let mut answers = InitAnswers::default();
+
+    if detected.is_empty() {
+        writeln!(output, "No Claude data directories found.")?;
+    } else {
+        writeln!(output, "Detected Claude data directories:")?;
+        for path in detected {
+            writeln!(output, "  {}", path.display())?;
+        }
+    }
+    let extra = prompt(
+        input,
+        output,
+        "Additional Claude data directories (comma-separated, blank for none)",
+        |answer| Ok(split_list(answer)),
+    )?
+    .unwrap_or_default();
+    if !extra.is_empty() {
+        answers.claude_search_paths = detected
+            .iter()
+            .map(|path| path.display().to_string())
+            .chain(extra)
+            .collect();
+    }
+
+    answers.timezone = prompt(
+        input,
+        output,
+        "Timezone for dates (IANA name such as Asia/Tokyo, blank for the system's)",
+        |answer| {
+            Tz::from_str(answer)
+                .map(|_| answer.to_string())
+                .map_err(|_| anyhow!("Unknown timezone: {answer}"))
+        },
+    )?;
+
+    if let Some(code) = prompt(
+        input,
+        output,
+        "Currency for costs (ISO code such as EUR, blank for USD)",
+        |answer| {
+            let code = answer.to_ascii_uppercase();
+            validate_currency(&code, 1.0)?;
+            Ok(code)
+        },
+    )?
+    .filter(|code| code != "USD")
+    {
+        let rate = prompt(
+            input,
+            output,
+            &format!("How many {code} one US dollar buys"),
+            |answer| {
+                let rate = answer
+                    .parse::<f64>()
+                    .map_err(|_| anyhow!("Invalid rate: {answer}"))?;
+                validate_currency(&code, rate)?;
+                Ok(rate)
+            },
+        )?;
+        match rate {
+            Some(rate) => answers.currency = Some((code, rate)),
+            None => writeln!(output, "No rate given; costs stay in USD.")?,
+        }
+    }
+
+    writeln!(output, "Budgets are set in USD.")?;
+    answers.project_budgets = prompt(
+        input,
+        output,
+        "Monthly budgets per project as NAME=USD (comma-separated, blank for none)",
+        parse_budgets,
+    )?
+    .unwrap_or_default();
+
+    Ok(answers)
+}
+
+/// Config file contents for `answers`, in the format read by `load_config`.
+pub fn init_config_toml(answers: &InitAnswers) -> String {
+    let quote = |value: &str| toml::Value::String(value.to_string()).to_string();
+    let mut output = String::from("# Written by `ccost init`; see the README for every setting.\n");
+    if let Some(timezone) = &answers.timezone {
+        output.push_str(&format!("timezone = {}\n", quote(timezone)));
+    }
+    if !answers.claude_search_paths.is_empty() {
+        let paths = answers
+            .claude_search_paths
+            .iter()
+            .map(|path| quote(path))
+            .collect::<Vec<_>>();
+        output.push_str(&format!("claude_search_paths = [{}]\n", paths.join(", ")));
+    }
+    if let Some((code, rate)) = &answers.currency {
+        output.push_str(&format!(
+            "\n[currency]\ncode = {}\nrate = {rate}\n",
+            quote(code)
+        ));
+    }
+    if !answers.project_budgets.is_empty() {
+        output.push_str("\n[budgets.projects]\n");
+        for (project, amount) in &answers.project_budgets {
+            output.push_str(&format!("{} = {amount}\n", quote(project)));
+        }
+    }
+    output
+}
+
+fn prompt<T>(
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+    question: &str,
+    parse: impl Fn(&str) -> Result<T>,
+) -> Result<Option<T>> {
+    loop {
+        write!(output, "{question}: ")?;
+        output.flush()?;
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            writeln!(output)?;
+            return Ok(None);
+        }
+        let answer = line.trim();
+        if answer.is_empty() {
+            return Ok(None);
+        }
+        match parse(answer) {
+            Ok(value) => return Ok(Some(value)),
+            Err(error) => writeln!(output, "{error}")?,
+        }
+    }
+}
+
+fn split_list(answer: &str) -> Vec<String> {
+    answer
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn parse_budgets(answer: &str) -> Result<Vec<(String, f64)>> {
+    split_list(answer)
+        .into_iter()
+        .map(|item| {
+            let (project, amount) = item
+                .split_once('=')
+                .map(|(project, amount)| (project.trim(), amount.trim()))
+                .ok_or_else(|| anyhow!("Invalid budget: {item} (expected NAME=USD)"))?;
+            let amount = amount
+                .trim_start_matches('$')
+                .parse::<f64>()
+                .ok()
+                .filter(|amount| amount.is_finite() && *amount > 0.0)
+                .ok_or_else(|| anyhow!("Invalid budget amount: {amount}"))?;
+            if project.is_empty() {
+                return Err(anyhow!("Invalid budget: {item} (expected NAME=USD)"));
+            }
+            Ok((project.to_string(), amount))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn wizard_collects_answers_and_asks_again_on_invalid_input() {
+        let mut input = Cursor::new(
+            "/mnt/claude\nMars/Base\nAsia/Tokyo\neuro\neur\n-1\n0.92\nacme-api=nope\nacme-api=200, side=$25\n",
+        );
+        let mut output = Vec::new();
+        let answers = ask_init_answers(
+            &mut input,
+            &mut output,
+            &[PathBuf::from("/home/me/.claude")],
+        )
+        .unwrap();
+
+        assert_eq!(
+            answers,
+            InitAnswers {
+                claude_search_paths: vec![
+                    "/home/me/.claude".to_string(),
+                    "/mnt/claude".to_string()
+                ],
+                timezone: Some("Asia/Tokyo".to_string()),
+                currency: Some(("EUR".to_string(), 0.92)),
+                project_budgets: vec![("acme-api".to_string(), 200.0), ("side".to_string(), 25.0)],
+            }
+        );
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("Unknown timezone: Mars/Base"));
+        assert!(output.contains("currency.code must be a three-letter ISO code"));
+        assert!(output.contains("currency.rate must be a positive number of EUR"));
+        assert!(output.contains("Invalid budget amount: nope"));
+
+        let blank = ask_init_answers(&mut Cursor::new(""), &mut Vec::new(), &[]).unwrap();
+        assert_eq!(blank, InitAnswers::default());
+    }
+
+    #[test]
+    fn init_config_toml_round_trips_through_the_config_parser() {
+        let answers = InitAnswers {
+            claude_search_paths: vec!["C:\\Users\\me\\.claude".to_string()],
+            timezone: Some("Europe/Berlin".to_string()),
+            currency: Some(("EUR".to_string(), 0.92)),
+            project_budgets: vec![("my \"side\" project".to_string(), 12.5)],
+        };
+        let config = crate::config::parse_config(&init_config_toml(&answers)).unwrap();
+        assert_eq!(config.timezone.as_deref(), Some("Europe/Berlin"));
+        assert_eq!(config.claude_search_paths, answers.claude_search_paths);
+        assert_eq!(config.budgets.projects["my \"side\" project"], 12.5);
+        let currency = config.currency.unwrap();
+        assert_eq!((currency.code.as_str(), currency.rate), ("EUR", 0.92));
+    }
+}
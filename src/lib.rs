@@ -1,6 +1,50 @@
-pub mod cli;
-pub mod data_loader;
-pub mod pricing;
-pub mod table;
-pub mod time_utils;
-pub mod token_utils;
+//! Token usage and cost reports for Claude Code, Codex, and OpenCode logs.
+//!
+//! The `ccost` binary is a thin wrapper around [`run`]. To embed the reports, use
+//! [`report::UsageReport`]; [`report`] also exports the row types it returns. Everything else
+//! is internal to the binary.
+
+pub(crate) mod archive;
+pub(crate) mod blocks;
+pub(crate) mod cli;
+pub(crate) mod config;
+pub(crate) mod data_loader;
+pub(crate) mod diff;
+pub(crate) mod directories;
+pub(crate) mod distribution;
+pub(crate) mod duplicates;
+pub(crate) mod email;
+pub(crate) mod error;
+pub(crate) mod forecast;
+pub(crate) mod ignore;
+pub(crate) mod import;
+pub(crate) mod init;
+pub(crate) mod model_filter;
+pub(crate) mod models;
+pub(crate) mod notify;
+#[cfg(feature = "otlp")]
+pub(crate) mod otlp;
+pub(crate) mod pager;
+pub(crate) mod pdf;
+pub(crate) mod pricing;
+pub(crate) mod projects;
+pub(crate) mod query;
+pub(crate) mod recap;
+pub mod report;
+pub(crate) mod schedule;
+pub(crate) mod sessions;
+pub(crate) mod snapshot;
+pub(crate) mod stop_reasons;
+pub(crate) mod table;
+pub(crate) mod template;
+pub(crate) mod tiers;
+pub(crate) mod time_utils;
+pub(crate) mod timings;
+pub(crate) mod trend;
+#[cfg(feature = "tui")]
+pub(crate) mod tui;
+pub(crate) mod warnings;
+pub(crate) mod watch;
+pub(crate) mod whatif;
+
+pub use cli::{print_error, run};
@@ -1,6 +1,6 @@
 fn main() {
-    if let Err(err) = ccost::cli::run() {
-        eprintln!("{err}");
+    if let Err(err) = ccost::run() {
+        ccost::print_error(&err);
         std::process::exit(1);
     }
 }
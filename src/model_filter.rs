@@ -0,0 +1,120 @@
+use crate::table::format_model_name;
+use anyhow::{Result, anyhow};
+use regex::{Regex, RegexBuilder};
+
+/// Model names kept by `--model` and dropped by `--exclude-model`.
+///
+/// A pattern containing `*` or `?` is a glob that must match the whole model name, either as
+/// logged (`claude-opus-4-1-20250805`) or as shown in reports (`opus-4-1`); any other pattern
+/// matches anywhere in the logged name. Matching ignores case.
+#[derive(Debug, Clone, Default)]
+pub struct ModelFilter {
+    include: Vec<ModelPattern>,
+    exclude: Vec<ModelPattern>,
+}
+
+#[derive(Debug, Clone)]
+enum ModelPattern {
+    Glob(Regex),
+    Substring(String),
+}
+
+impl ModelFilter {
+    pub fn new<S: AsRef<str>>(include: &[S], exclude: &[S]) -> Result<Self> {
+        let compile = |patterns: &[S]| {
+            patterns
+                .iter()
+                .map(|pattern| ModelPattern::new(pattern.as_ref()))
+                .collect::<Result<Vec<_>>>()
+        };
+        Ok(Self {
+            include: compile(include)?,
+            exclude: compile(exclude)?,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty()
+    }
+
+    /// Whether usage of `model` passes the filter. Entries without a model only pass when no
+    /// `--model` pattern is given.
+    pub fn matches(&self, model: Option<&str>) -> bool {
+        let Some(model) = model else {
+            return self.include.is_empty();
+        };
+        let display = format_model_name(model);
+        let hit = |pattern: &ModelPattern| pattern.is_match(model, &display);
+        (self.include.is_empty() || self.include.iter().any(hit)) && !self.exclude.iter().any(hit)
+    }
+}
+
+impl ModelPattern {
+    fn new(pattern: &str) -> Result<Self> {
+        let pattern = pattern.trim();
+        if pattern.is_empty() {
+            return Err(anyhow!("Invalid model pattern: empty"));
+        }
+        if !pattern.contains(['*', '?']) {
+            return Ok(Self::Substring(pattern.to_lowercase()));
+        }
+        let expression = pattern
+            .split_inclusive(['*', '?'])
+            .map(|part| match part.strip_suffix('*') {
+                Some(literal) => format!("{}.*", regex::escape(literal)),
+                None => match part.strip_suffix('?') {
+                    Some(literal) => format!("{}.", regex::escape(literal)),
+                    None => regex::escape(part),
+                },
+            })
+            .collect::<String>();
+        RegexBuilder::new(&format!("^{expression}$"))
+            .case_insensitive(true)
+            .build()
+            .map(Self::Glob)
+            .map_err(|error| anyhow!("Invalid model pattern {pattern}: {error}"))
+    }
+
+    fn is_match(&self, model: &str, display: &str) -> bool {
+        match self {
+            Self::Glob(regex) => regex.is_match(model) || regex.is_match(display),
+            Self::Substring(needle) => model.to_lowercase().contains(needle.as_str()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(include: &[&str], exclude: &[&str]) -> ModelFilter {
+        ModelFilter::new(include, exclude).unwrap()
+    }
+
+    #[test]
+    fn globs_match_logged_or_display_names_and_plain_patterns_match_substrings() {
+        let opus = filter(&["opus*"], &[]);
+        assert!(opus.matches(Some("claude-opus-4-1-20250805")));
+        assert!(!opus.matches(Some("claude-sonnet-4-20250514")));
+        assert!(!opus.matches(None));
+
+        let sonnet = filter(&["SONNET"], &[]);
+        assert!(sonnet.matches(Some("claude-sonnet-4-20250514")));
+        assert!(filter(&["gpt-5?"], &[]).matches(Some("gpt-5o")));
+        assert!(!filter(&["gpt-5?"], &[]).matches(Some("gpt-5")));
+    }
+
+    #[test]
+    fn exclusions_apply_after_inclusions() {
+        let claude = filter(&["claude"], &["haiku"]);
+        assert!(claude.matches(Some("claude-sonnet-4-20250514")));
+        assert!(!claude.matches(Some("claude-haiku-4-5-20251001")));
+        assert!(!claude.matches(Some("gpt-5")));
+
+        let exclude_only = filter(&[], &["haiku*"]);
+        assert!(exclude_only.matches(None));
+        assert!(exclude_only.matches(Some("gpt-5")));
+        assert!(!exclude_only.matches(Some("claude-haiku-4-5-20251001")));
+        assert!(ModelFilter::new(&[" "], &[]).is_err());
+    }
+}
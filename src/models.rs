@@ -0,0 +1,207 @@
+use crate::data_loader::UsageEntry;
+use std::collections::{BTreeMap, HashMap};
+
+const UNKNOWN_MODEL: &str = "unknown";
+
+/// Usage of one model across all entries, with derived cost-efficiency figures.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ModelUsage {
+    pub model: String,
+    pub message_count: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub total_tokens: u64,
+    pub total_cost: f64,
+}
+
+impl ModelUsage {
+    pub fn cost_per_message(&self) -> Option<f64> {
+        (self.message_count > 0).then(|| self.total_cost / self.message_count as f64)
+    }
+
+    pub fn cost_per_1k_output(&self) -> Option<f64> {
+        (self.output_tokens > 0).then(|| self.total_cost / self.output_tokens as f64 * 1_000.0)
+    }
+
+    /// Fraction of prompt tokens (input, cache writes, and cache reads) served from the cache.
+    pub fn cache_read_share(&self) -> Option<f64> {
+        let prompt = self.input_tokens + self.cache_creation_tokens + self.cache_read_tokens;
+        (prompt > 0).then(|| self.cache_read_tokens as f64 / prompt as f64)
+    }
+}
+
+/// Sums entries per model, most expensive first. `<synthetic>` entries are left out.
+pub fn group_entries_by_model(entries: &[UsageEntry]) -> Vec<ModelUsage> {
+    let mut models: HashMap<&str, ModelUsage> = HashMap::new();
+    for entry in entries {
+        let name = entry.model.as_deref().unwrap_or(UNKNOWN_MODEL);
+        if name == "<synthetic>" {
+            continue;
+        }
+        let usage = models.entry(name).or_insert_with(|| ModelUsage {
+            model: name.to_string(),
+            ..Default::default()
+        });
+        usage.message_count += 1;
+        usage.input_tokens += entry.input_tokens;
+        usage.output_tokens += entry.output_tokens;
+        usage.cache_creation_tokens += entry.cache_creation_tokens;
+        usage.cache_read_tokens += entry.cache_read_tokens;
+        usage.total_tokens += entry.total_tokens;
+        usage.total_cost += entry.cost;
+    }
+    let mut models = models.into_values().collect::<Vec<_>>();
+    models.sort_by(|a, b| {
+        b.total_cost
+            .total_cmp(&a.total_cost)
+            .then_with(|| a.model.cmp(&b.model))
+    });
+    models
+}
+
+/// Price class used by the premium mix metric.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModelTier {
+    /// Opus-class models.
+    Premium,
+    /// Sonnet- and Haiku-class models.
+    Standard,
+}
+
+/// Tier of a Claude model name; other providers' models have none.
+pub fn model_tier(model: &str) -> Option<ModelTier> {
+    let model = model.to_ascii_lowercase();
+    if model.contains("opus") {
+        Some(ModelTier::Premium)
+    } else if model.contains("sonnet") || model.contains("haiku") {
+        Some(ModelTier::Standard)
+    } else {
+        None
+    }
+}
+
+/// Premium and standard token volumes of one day or month.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MixPeriod {
+    pub period: String,
+    pub premium_tokens: u64,
+    pub standard_tokens: u64,
+}
+
+impl MixPeriod {
+    /// Premium tokens per standard token.
+    pub fn ratio(&self) -> Option<f64> {
+        (self.standard_tokens > 0).then(|| self.premium_tokens as f64 / self.standard_tokens as f64)
+    }
+
+    /// Fraction of tiered tokens that went to premium models.
+    pub fn premium_share(&self) -> Option<f64> {
+        let tiered = self.premium_tokens + self.standard_tokens;
+        (tiered > 0).then(|| self.premium_tokens as f64 / tiered as f64)
+    }
+}
+
+/// Premium and standard tokens per day, or per month when `monthly`, oldest first. Periods
+/// without any tiered model usage are left out.
+pub fn premium_mix(entries: &[UsageEntry], monthly: bool) -> Vec<MixPeriod> {
+    let mut periods: BTreeMap<&str, MixPeriod> = BTreeMap::new();
+    for entry in entries {
+        let Some(tier) = entry.model.as_deref().and_then(model_tier) else {
+            continue;
+        };
+        let period = if monthly {
+            entry.date.get(..7).unwrap_or(&entry.date)
+        } else {
+            entry.date.as_str()
+        };
+        let mix = periods.entry(period).or_insert_with(|| MixPeriod {
+            period: period.to_string(),
+            ..Default::default()
+        });
+        match tier {
+            ModelTier::Premium => mix.premium_tokens += entry.total_tokens,
+            ModelTier::Standard => mix.standard_tokens += entry.total_tokens,
+        }
+    }
+    periods.into_values().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn entry(model: &str, input: u64, output: u64, cache_read: u64, cost: f64) -> UsageEntry {
+        UsageEntry {
+            timestamp: Utc.with_ymd_and_hms(2026, 1, 2, 3, 4, 5).unwrap(),
+            date: "2026-01-02".to_string(),
+            model: Some(model.to_string()),
+            input_tokens: input,
+            output_tokens: output,
+            cache_read_tokens: cache_read,
+            total_tokens: input + output + cache_read,
+            cost,
+            local_hour: 3,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn group_entries_by_model_sums_and_derives_efficiency() {
+        let entries = vec![
+            entry("claude-sonnet-4", 100, 1_000, 300, 0.5),
+            entry("claude-sonnet-4", 100, 1_000, 500, 0.5),
+            entry("claude-opus-4", 200, 500, 0, 3.0),
+            entry("<synthetic>", 0, 0, 0, 0.0),
+        ];
+        let models = group_entries_by_model(&entries);
+
+        assert_eq!(
+            models.iter().map(|m| m.model.as_str()).collect::<Vec<_>>(),
+            vec!["claude-opus-4", "claude-sonnet-4"]
+        );
+        let sonnet = &models[1];
+        assert_eq!(sonnet.message_count, 2);
+        assert_eq!(sonnet.cost_per_message(), Some(0.5));
+        assert_eq!(sonnet.cost_per_1k_output(), Some(0.5));
+        assert_eq!(sonnet.cache_read_share(), Some(0.8));
+        assert_eq!(models[0].cost_per_1k_output(), Some(6.0));
+        assert_eq!(models[0].cache_read_share(), Some(0.0));
+        assert_eq!(ModelUsage::default().cost_per_message(), None);
+    }
+
+    #[test]
+    fn premium_mix_splits_tiers_per_period() {
+        let mut entries = vec![
+            entry("claude-opus-4-20250514", 100, 0, 0, 1.0),
+            entry("claude-sonnet-4-20250514", 300, 0, 0, 1.0),
+            entry("claude-3-5-haiku-20241022", 100, 0, 0, 1.0),
+            entry("gpt-5", 1_000, 0, 0, 1.0),
+        ];
+        let mut later = entry("claude-opus-4-1", 50, 0, 0, 1.0);
+        later.date = "2026-02-01".to_string();
+        entries.push(later);
+
+        let daily = premium_mix(&entries, false);
+        assert_eq!(daily.len(), 2);
+        assert_eq!(daily[0].period, "2026-01-02");
+        assert_eq!(daily[0].premium_tokens, 100);
+        assert_eq!(daily[0].standard_tokens, 400);
+        assert_eq!(daily[0].ratio(), Some(0.25));
+        assert_eq!(daily[0].premium_share(), Some(0.2));
+        assert_eq!(daily[1].ratio(), None);
+        assert_eq!(daily[1].premium_share(), Some(1.0));
+
+        let monthly = premium_mix(&entries, true);
+        assert_eq!(
+            monthly
+                .iter()
+                .map(|m| m.period.as_str())
+                .collect::<Vec<_>>(),
+            vec!["2026-01", "2026-02"]
+        );
+        assert_eq!(model_tier("gpt-5"), None);
+    }
+}
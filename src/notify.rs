@@ -0,0 +1,273 @@
+use crate::data_loader::DailyUsage;
+use crate::table::{TokenFormat, format_currency, format_tokens};
+use anyhow::{Result, anyhow};
+use serde_json::json;
+use std::collections::HashMap;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+const CURL_COMMAND: &str = "curl";
+const DISCORD_HOSTS: &[&str] = &["discord.com", "discordapp.com"];
+
+/// Today's and this month's totals posted by `ccost notify`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NotifySummary {
+    pub date: String,
+    pub today_tokens: u64,
+    pub today_cost: f64,
+    pub month: String,
+    pub month_tokens: u64,
+    pub month_cost: f64,
+    pub top_projects: Vec<(String, f64)>,
+    /// `budgets.daily` from the config.
+    pub daily_budget: Option<f64>,
+    /// `budgets.monthly` from the config.
+    pub monthly_budget: Option<f64>,
+}
+
+/// Projects with the highest cost across `daily`, most expensive first.
+pub fn top_projects_by_cost(daily: &[DailyUsage], limit: usize) -> Vec<(String, f64)> {
+    let mut costs: HashMap<&str, f64> = HashMap::new();
+    for day in daily {
+        if let Some(project) = day.project.as_deref() {
+            *costs.entry(project).or_default() += day.total_cost;
+        }
+    }
+    let mut projects = costs
+        .into_iter()
+        .map(|(project, cost)| (project.to_string(), cost))
+        .collect::<Vec<_>>();
+    projects.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    projects.truncate(limit);
+    projects
+}
+
+/// Plain-text message understood by both Slack and Discord.
+pub fn summary_text(summary: &NotifySummary) -> String {
+    let mut lines = vec![
+        format!("ccost summary for {}", summary.date),
+        format!(
+            "Today: {} ({} tokens){}",
+            format_currency(summary.today_cost),
+            format_tokens(summary.today_tokens, TokenFormat::HumanReadable),
+            budget_status(summary.today_cost, summary.daily_budget, "daily")
+        ),
+        format!(
+            "This month ({}): {} ({} tokens){}",
+            summary.month,
+            format_currency(summary.month_cost),
+            format_tokens(summary.month_tokens, TokenFormat::HumanReadable),
+            budget_status(summary.month_cost, summary.monthly_budget, "monthly")
+        ),
+    ];
+    if !summary.top_projects.is_empty() {
+        lines.push("Top projects this month:".to_string());
+        for (project, cost) in &summary.top_projects {
+            lines.push(format!("• {project}: {}", format_currency(*cost)));
+        }
+    }
+    lines.join("\n")
+}
+
+/// How `cost` compares with `budget`, appended to a total; empty without a budget.
+fn budget_status(cost: f64, budget: Option<f64>, period: &str) -> String {
+    match budget {
+        Some(budget) if cost > budget => format!(
+            ", over the {} {period} budget by {}",
+            format_currency(budget),
+            format_currency(cost - budget)
+        ),
+        Some(budget) if budget > 0.0 => format!(
+            ", {:.0}% of the {} {period} budget",
+            cost / budget * 100.0,
+            format_currency(budget)
+        ),
+        Some(budget) => format!(", within the {} {period} budget", format_currency(budget)),
+        None => String::new(),
+    }
+}
+
+/// Discord webhooks expect `content`; Slack incoming webhooks (and most others) expect `text`.
+pub fn webhook_payload(url: &str, text: &str) -> serde_json::Value {
+    if is_discord_webhook(url) {
+        json!({ "content": text })
+    } else {
+        json!({ "text": text })
+    }
+}
+
+fn is_discord_webhook(url: &str) -> bool {
+    let host = url
+        .split_once("://")
+        .map_or(url, |(_, rest)| rest)
+        .split(['/', '?'])
+        .next()
+        .unwrap_or_default()
+        .rsplit('@')
+        .next()
+        .unwrap_or_default()
+        .split(':')
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    DISCORD_HOSTS
+        .iter()
+        .any(|discord| host == *discord || host.ends_with(&format!(".{discord}")))
+}
+
+/// Posts `payload` with `curl`, which handles TLS and proxies the same way users' cron jobs do.
+/// The webhook URL is a secret, so it goes in curl's config file rather than on its command line.
+pub fn post_webhook(url: &str, payload: &serde_json::Value) -> Result<()> {
+    run_curl(
+        &[
+            "--fail",
+            "--request",
+            "POST",
+            "--header",
+            "Content-Type: application/json",
+            "--data-binary",
+            "@-",
+        ],
+        &[("url", url)],
+        payload.to_string().as_bytes(),
+    )
+    .map_err(|error| anyhow!("webhook request failed: {error}"))
+}
+
+/// Runs `curl` quietly with `args`, feeding `input` on stdin. `config` holds the options that
+/// must not show up in `ps`, such as webhook URLs, passwords, and API keys: they are written to
+/// a config file only the current user can read, which curl loads with `--config`.
+pub(crate) fn run_curl(args: &[&str], config: &[(&str, &str)], input: &[u8]) -> Result<()> {
+    let mut config_file = tempfile::Builder::new().prefix("ccost-curl-").tempfile()?;
+    config_file.write_all(curl_config(config).as_bytes())?;
+    config_file.flush()?;
+    let mut child = Command::new(CURL_COMMAND)
+        .args(["--silent", "--show-error", "--config"])
+        .arg(config_file.path())
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .spawn()
+        .map_err(|error| anyhow!("failed to run {CURL_COMMAND}: {error}"))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(input)?;
+    }
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(anyhow!("{CURL_COMMAND} exited with {status}"));
+    }
+    Ok(())
+}
+
+/// `options` as curl config file lines, with each value quoted so it is never read as a flag.
+fn curl_config(options: &[(&str, &str)]) -> String {
+    options
+        .iter()
+        .map(|(name, value)| {
+            let value = value
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n")
+                .replace('\r', "\\r")
+                .replace('\t', "\\t");
+            format!("{name} = \"{value}\"\n")
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(project: Option<&str>, cost: f64) -> DailyUsage {
+        DailyUsage {
+            date: "2026-01-01".to_string(),
+            project: project.map(str::to_string),
+            total_cost: cost,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn top_projects_by_cost_sums_and_limits() {
+        let daily = vec![
+            day(Some("a"), 1.0),
+            day(Some("b"), 3.0),
+            day(Some("a"), 2.5),
+            day(None, 10.0),
+            day(Some("c"), 0.5),
+        ];
+        assert_eq!(
+            top_projects_by_cost(&daily, 2),
+            vec![("a".to_string(), 3.5), ("b".to_string(), 3.0)]
+        );
+    }
+
+    #[test]
+    fn summary_text_lists_totals_and_projects() {
+        let summary = NotifySummary {
+            date: "2026-01-15".to_string(),
+            today_tokens: 12_345,
+            today_cost: 1.5,
+            month: "2026-01".to_string(),
+            month_tokens: 2_000_000,
+            month_cost: 42.0,
+            top_projects: vec![("app".to_string(), 30.0)],
+            daily_budget: None,
+            monthly_budget: None,
+        };
+        assert_eq!(
+            summary_text(&summary),
+            "ccost summary for 2026-01-15\n\
+             Today: $1.50 (12.35K tokens)\n\
+             This month (2026-01): $42.00 (2M tokens)\n\
+             Top projects this month:\n\
+             • app: $30.00"
+        );
+
+        let summary = NotifySummary {
+            daily_budget: Some(5.0),
+            monthly_budget: Some(40.0),
+            ..summary
+        };
+        assert_eq!(
+            summary_text(&summary).lines().take(3).collect::<Vec<_>>(),
+            vec![
+                "ccost summary for 2026-01-15",
+                "Today: $1.50 (12.35K tokens), 30% of the $5.00 daily budget",
+                "This month (2026-01): $42.00 (2M tokens), over the $40.00 monthly budget by $2.00",
+            ]
+        );
+    }
+
+    #[test]
+    fn curl_config_quotes_values() {
+        assert_eq!(
+            curl_config(&[
+                ("url", "-https://hooks.example.com/a?b=\"c\""),
+                ("user", "me:p\\w"),
+            ]),
+            "url = \"-https://hooks.example.com/a?b=\\\"c\\\"\"\nuser = \"me:p\\\\w\"\n"
+        );
+    }
+
+    #[test]
+    fn webhook_payload_matches_the_service() {
+        assert_eq!(
+            webhook_payload("https://hooks.slack.com/services/T/B/X", "hi"),
+            json!({ "text": "hi" })
+        );
+        assert_eq!(
+            webhook_payload("https://discord.com/api/webhooks/1/abc", "hi"),
+            json!({ "content": "hi" })
+        );
+        assert_eq!(
+            webhook_payload("https://ptb.discordapp.com/api/webhooks/1/abc", "hi"),
+            json!({ "content": "hi" })
+        );
+        assert_eq!(
+            webhook_payload("https://example.com/?next=discord.com", "hi"),
+            json!({ "text": "hi" })
+        );
+    }
+}
@@ -0,0 +1,325 @@
+//! OTLP/HTTP JSON payloads for `ccost export otlp`, sent to a collector with `curl`.
+
+use crate::data_loader::UsageEntry;
+use crate::notify::run_curl;
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Duration, DurationRound, Utc};
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+
+/// Collector address used when neither `--endpoint` nor `OTEL_EXPORTER_OTLP_ENDPOINT` is set.
+pub const DEFAULT_ENDPOINT: &str = "http://localhost:4318";
+/// Usage entries (logs) or data points (metrics) per request.
+pub const BATCH_SIZE: usize = 1000;
+
+const SCOPE_NAME: &str = "ccost";
+const TOKEN_TYPES: [&str; 4] = ["input", "output", "cache_creation", "cache_read"];
+
+/// OTLP signal the usage entries are converted to.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Signal {
+    /// One log record per request.
+    Logs,
+    /// `ccost.tokens` and `ccost.cost` delta sums per hour, source, project, and model.
+    Metrics,
+}
+
+impl Signal {
+    /// Path appended to the collector base URL, as OTLP/HTTP exporters do.
+    pub fn path(self) -> &'static str {
+        match self {
+            Self::Logs => "/v1/logs",
+            Self::Metrics => "/v1/metrics",
+        }
+    }
+}
+
+/// Request bodies for `entries`, each holding at most [`BATCH_SIZE`] records or data points.
+pub fn payloads(entries: &[UsageEntry], signal: Signal, service_name: &str) -> Vec<Value> {
+    match signal {
+        Signal::Logs => entries
+            .chunks(BATCH_SIZE)
+            .map(|chunk| logs_payload(chunk, service_name))
+            .collect(),
+        Signal::Metrics => {
+            let points = hourly_points(entries);
+            points
+                .chunks(BATCH_SIZE)
+                .map(|chunk| metrics_payload(chunk, service_name))
+                .collect()
+        }
+    }
+}
+
+/// Posts one payload to `endpoint` (the collector base URL) for `signal`. `headers` are
+/// `Name: value` lines, e.g. for an API key, so they go in curl's private config file rather
+/// than on its command line.
+pub fn push(endpoint: &str, signal: Signal, headers: &[String], payload: &Value) -> Result<()> {
+    let url = format!("{}{}", endpoint.trim_end_matches('/'), signal.path());
+    let mut config = vec![("url", url.as_str())];
+    config.extend(headers.iter().map(|header| ("header", header.as_str())));
+    run_curl(
+        &[
+            "--fail",
+            "--request",
+            "POST",
+            "--header",
+            "Content-Type: application/json",
+            "--data-binary",
+            "@-",
+        ],
+        &config,
+        payload.to_string().as_bytes(),
+    )
+    .map_err(|error| anyhow!("OTLP export to {url} failed: {error}"))
+}
+
+/// `key=value` pairs separated by commas, as in `OTEL_EXPORTER_OTLP_HEADERS`, as
+/// `Name: value` header lines.
+pub fn parse_headers(value: &str) -> Result<Vec<String>> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| match pair.split_once('=') {
+            Some((name, value)) if !name.trim().is_empty() => {
+                Ok(format!("{}: {}", name.trim(), value.trim()))
+            }
+            _ => Err(anyhow!("Invalid OTLP header {pair}: expected name=value")),
+        })
+        .collect()
+}
+
+fn logs_payload(entries: &[UsageEntry], service_name: &str) -> Value {
+    let records = entries
+        .iter()
+        .map(|entry| {
+            let mut attributes = entry_attributes(entry);
+            if let Some(session) = &entry.session_id {
+                attributes.push(string_attribute("session.id", session));
+            }
+            attributes.extend([
+                int_attribute("gen_ai.usage.input_tokens", entry.input_tokens),
+                int_attribute("gen_ai.usage.output_tokens", entry.output_tokens),
+                int_attribute("ccost.cache_creation_tokens", entry.cache_creation_tokens),
+                int_attribute("ccost.cache_read_tokens", entry.cache_read_tokens),
+                int_attribute("ccost.total_tokens", entry.total_tokens),
+                json!({ "key": "ccost.cost_usd", "value": { "doubleValue": entry.cost } }),
+            ]);
+            json!({
+                "timeUnixNano": unix_nanos(entry.timestamp),
+                "observedTimeUnixNano": unix_nanos(entry.timestamp),
+                "severityNumber": 9,
+                "severityText": "INFO",
+                "body": { "stringValue": "ccost.usage" },
+                "attributes": attributes,
+            })
+        })
+        .collect::<Vec<_>>();
+    json!({
+        "resourceLogs": [{
+            "resource": resource(service_name),
+            "scopeLogs": [{ "scope": scope(), "logRecords": records }],
+        }]
+    })
+}
+
+/// Usage summed per hour, source, project, and model.
+#[derive(Debug, Default)]
+struct HourlyPoint<'a> {
+    start: DateTime<Utc>,
+    entry: Option<&'a UsageEntry>,
+    tokens: [u64; 4],
+    cost: f64,
+}
+
+fn hourly_points(entries: &[UsageEntry]) -> Vec<HourlyPoint<'_>> {
+    let mut points: BTreeMap<_, HourlyPoint> = BTreeMap::new();
+    for entry in entries {
+        let start = entry
+            .timestamp
+            .duration_trunc(Duration::hours(1))
+            .unwrap_or(entry.timestamp);
+        let key = (
+            start,
+            entry.source.as_str(),
+            entry.project.as_deref(),
+            entry.model.as_deref(),
+        );
+        let point = points.entry(key).or_insert_with(|| HourlyPoint {
+            start,
+            entry: Some(entry),
+            ..Default::default()
+        });
+        point.tokens[0] += entry.input_tokens;
+        point.tokens[1] += entry.output_tokens;
+        point.tokens[2] += entry.cache_creation_tokens;
+        point.tokens[3] += entry.cache_read_tokens;
+        point.cost += entry.cost;
+    }
+    points.into_values().collect()
+}
+
+fn metrics_payload(points: &[HourlyPoint], service_name: &str) -> Value {
+    let mut token_points = Vec::new();
+    let mut cost_points = Vec::new();
+    for point in points {
+        let attributes = point.entry.map(entry_attributes).unwrap_or_default();
+        let start = unix_nanos(point.start);
+        let end = unix_nanos(point.start + Duration::hours(1));
+        for (token_type, tokens) in TOKEN_TYPES.iter().zip(point.tokens) {
+            let mut attributes = attributes.clone();
+            attributes.push(string_attribute("ccost.token_type", token_type));
+            token_points.push(json!({
+                "startTimeUnixNano": start,
+                "timeUnixNano": end,
+                "asInt": tokens.to_string(),
+                "attributes": attributes,
+            }));
+        }
+        cost_points.push(json!({
+            "startTimeUnixNano": start,
+            "timeUnixNano": end,
+            "asDouble": point.cost,
+            "attributes": attributes,
+        }));
+    }
+    let sum = |name: &str, description: &str, unit: &str, points: Vec<Value>| {
+        json!({
+            "name": name,
+            "description": description,
+            "unit": unit,
+            "sum": {
+                "dataPoints": points,
+                // Delta: each point covers only its own hour.
+                "aggregationTemporality": 1,
+                "isMonotonic": true,
+            },
+        })
+    };
+    json!({
+        "resourceMetrics": [{
+            "resource": resource(service_name),
+            "scopeMetrics": [{
+                "scope": scope(),
+                "metrics": [
+                    sum("ccost.tokens", "Tokens used, by token type", "{token}", token_points),
+                    sum("ccost.cost", "Cost in US dollars", "USD", cost_points),
+                ],
+            }],
+        }]
+    })
+}
+
+/// Attributes shared by log records and data points.
+fn entry_attributes(entry: &UsageEntry) -> Vec<Value> {
+    let mut attributes = vec![string_attribute("ccost.source", entry.source.as_str())];
+    if let Some(project) = &entry.project {
+        attributes.push(string_attribute("ccost.project", project));
+    }
+    if let Some(model) = &entry.model {
+        attributes.push(string_attribute("gen_ai.request.model", model));
+    }
+    attributes
+}
+
+fn resource(service_name: &str) -> Value {
+    json!({ "attributes": [string_attribute("service.name", service_name)] })
+}
+
+fn scope() -> Value {
+    json!({ "name": SCOPE_NAME, "version": env!("CARGO_PKG_VERSION") })
+}
+
+fn string_attribute(key: &str, value: &str) -> Value {
+    json!({ "key": key, "value": { "stringValue": value } })
+}
+
+/// OTLP JSON carries 64-bit integers as strings.
+fn int_attribute(key: &str, value: u64) -> Value {
+    json!({ "key": key, "value": { "intValue": value.to_string() } })
+}
+
+fn unix_nanos(timestamp: DateTime<Utc>) -> String {
+    timestamp
+        .timestamp_nanos_opt()
+        .unwrap_or_default()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn entry(minute: u32, model: &str, cost: f64) -> UsageEntry {
+        UsageEntry {
+            timestamp: Utc.with_ymd_and_hms(2026, 1, 2, 3, minute, 0).unwrap(),
+            date: "2026-01-02".to_string(),
+            session_id: Some("session-1".to_string()),
+            project: Some("app".to_string()),
+            model: Some(model.to_string()),
+            input_tokens: 10,
+            output_tokens: 5,
+            cache_read_tokens: 20,
+            total_tokens: 35,
+            cost,
+            local_hour: 3,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn logs_carry_one_record_per_entry() {
+        let entries = vec![
+            entry(1, "claude-opus-4", 1.5),
+            entry(2, "claude-opus-4", 0.5),
+        ];
+        let payloads = payloads(&entries, Signal::Logs, "team-a");
+        assert_eq!(payloads.len(), 1);
+        let logs = &payloads[0]["resourceLogs"][0];
+        assert_eq!(
+            logs["resource"]["attributes"][0]["value"]["stringValue"],
+            "team-a"
+        );
+        let records = logs["scopeLogs"][0]["logRecords"].as_array().unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0]["timeUnixNano"], "1767322860000000000");
+        let attributes = records[0]["attributes"].as_array().unwrap();
+        assert!(attributes.contains(&int_attribute("gen_ai.usage.input_tokens", 10)));
+        assert!(attributes.contains(&string_attribute("session.id", "session-1")));
+    }
+
+    #[test]
+    fn metrics_sum_each_hour_and_model() {
+        let entries = vec![
+            entry(1, "claude-opus-4", 1.5),
+            entry(2, "claude-opus-4", 0.5),
+            entry(3, "claude-sonnet-4", 0.25),
+        ];
+        let payloads = payloads(&entries, Signal::Metrics, "ccost");
+        let metrics = &payloads[0]["resourceMetrics"][0]["scopeMetrics"][0]["metrics"];
+        let cost = metrics[1]["sum"]["dataPoints"].as_array().unwrap();
+        assert_eq!(
+            cost.iter()
+                .map(|point| point["asDouble"].as_f64().unwrap())
+                .collect::<Vec<_>>(),
+            vec![2.0, 0.25]
+        );
+        assert_eq!(cost[0]["startTimeUnixNano"], "1767322800000000000");
+        assert_eq!(cost[0]["timeUnixNano"], "1767326400000000000");
+        // Four token types per hour and model.
+        let tokens = metrics[0]["sum"]["dataPoints"].as_array().unwrap();
+        assert_eq!(tokens.len(), 8);
+        assert_eq!(tokens[0]["asInt"], "20");
+    }
+
+    #[test]
+    fn headers_parse_like_the_otel_environment_variable() {
+        assert_eq!(
+            parse_headers("api-key=secret, x-team = a").unwrap(),
+            vec!["api-key: secret", "x-team: a"]
+        );
+        assert!(parse_headers("api-key").is_err());
+    }
+}
@@ -0,0 +1,86 @@
+use anyhow::Result;
+use std::io::{IsTerminal, Write};
+use std::process::{Command, Stdio};
+use terminal_size::terminal_size;
+
+const PAGER_ENV: &str = "PAGER";
+const DEFAULT_PAGER: &str = "less";
+const LESS_ENV: &str = "LESS";
+const DEFAULT_LESS_FLAGS: &str = "FRX";
+
+pub fn write_paged(output: &str, allow_pager: bool) -> Result<()> {
+    if allow_pager
+        && std::io::stdout().is_terminal()
+        && let Some((_, height)) = terminal_size()
+        && exceeds_height(output, height.0 as usize)
+        && let Some(command) = pager_command(std::env::var(PAGER_ENV).ok().as_deref())
+        && spawn_pager(&command, output).is_ok()
+    {
+        return Ok(());
+    }
+
+    let mut stdout = std::io::stdout().lock();
+    stdout.write_all(output.as_bytes())?;
+    stdout.flush()?;
+    Ok(())
+}
+
+fn exceeds_height(output: &str, height: usize) -> bool {
+    output.lines().count() >= height
+}
+
+fn pager_command(env_value: Option<&str>) -> Option<Vec<String>> {
+    let value = match env_value {
+        Some(value) => value.trim(),
+        None => DEFAULT_PAGER,
+    };
+    let parts = value
+        .split_whitespace()
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    match parts.first().map(String::as_str) {
+        None | Some("cat") => None,
+        Some(_) => Some(parts),
+    }
+}
+
+fn spawn_pager(command: &[String], output: &str) -> Result<()> {
+    let mut process = Command::new(&command[0]);
+    process.args(&command[1..]).stdin(Stdio::piped());
+    if std::env::var_os(LESS_ENV).is_none() {
+        process.env(LESS_ENV, DEFAULT_LESS_FLAGS);
+    }
+    let mut child = process.spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // The user may quit the pager before reading everything; a broken pipe is expected then.
+        let _ = stdin.write_all(output.as_bytes());
+    }
+    child.wait()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pager_command_defaults_to_less() {
+        assert_eq!(pager_command(None), Some(vec!["less".to_string()]));
+    }
+
+    #[test]
+    fn pager_command_splits_arguments_and_disables_for_cat_or_empty() {
+        assert_eq!(
+            pager_command(Some("less -S")),
+            Some(vec!["less".to_string(), "-S".to_string()])
+        );
+        assert_eq!(pager_command(Some("cat")), None);
+        assert_eq!(pager_command(Some("  ")), None);
+    }
+
+    #[test]
+    fn exceeds_height_counts_lines() {
+        assert!(!exceeds_height("a\nb\n", 3));
+        assert!(exceeds_height("a\nb\nc\n", 3));
+    }
+}
@@ -0,0 +1,503 @@
+use crate::data_loader::{MonthlyUsage, UsageTotals};
+use crate::table::{TokenFormat, cost_header, format_currency, format_model_name, format_tokens};
+use crate::warnings::warn;
+use std::fmt::Write as _;
+
+// A4 landscape, in PDF points.
+const PAGE_WIDTH: f64 = 842.0;
+const PAGE_HEIGHT: f64 = 595.0;
+const MARGIN: f64 = 40.0;
+const TABLE_FONT_SIZE: f64 = 9.0;
+const ROW_HEIGHT: f64 = 13.0;
+const CHART_TOP: f64 = 520.0;
+const CHART_HEIGHT: f64 = 150.0;
+const FIRST_PAGE_TABLE_TOP: f64 = 330.0;
+const TABLE_TOP: f64 = 535.0;
+const TABLE_BOTTOM: f64 = 55.0;
+/// Character widths of the report columns; the table is set in Courier so widths are exact.
+const COLUMN_WIDTHS: [usize; 7] = [30, 13, 13, 14, 14, 15, 12];
+const COLUMN_HEADERS: [&str; 7] = [
+    "Month",
+    "Input",
+    "Output",
+    "Cache Create",
+    "Cache Read",
+    "Total Tokens",
+    "Cost",
+];
+
+enum Row {
+    Month(Vec<String>),
+    Breakdown(Vec<String>),
+    Totals(Vec<String>),
+}
+
+/// Renders the monthly report as a paginated PDF with a cost-per-month bar chart on the first
+/// page. Only the standard Helvetica and Courier fonts are used, so text is limited to what
+/// WinAnsiEncoding covers (Western European scripts); other characters are printed as `?`, with
+/// a warning.
+pub fn monthly_report_pdf(
+    title: &str,
+    generated: &str,
+    monthly: &[MonthlyUsage],
+    totals: &UsageTotals,
+    breakdown: bool,
+) -> Vec<u8> {
+    let rows = report_rows(monthly, totals, breakdown);
+    let unencodable = std::iter::once(title)
+        .chain(
+            rows.iter()
+                .flat_map(|row| row.cells().iter().map(String::as_str)),
+        )
+        .flat_map(str::chars)
+        .filter(|c| win_ansi_byte(*c).is_none())
+        .count();
+    if unencodable > 0 {
+        warn(format!(
+            "The PDF fonts only cover Western European text; \
+             {unencodable} other characters were printed as ?"
+        ));
+    }
+    let pages = paginate(&rows);
+    let page_count = pages.len();
+    let mut document = PdfDocument::default();
+    for (index, page_rows) in pages.into_iter().enumerate() {
+        let mut content = String::new();
+        let table_top = if index == 0 {
+            text(&mut content, "F2", 16.0, MARGIN, PAGE_HEIGHT - 40.0, title);
+            text(
+                &mut content,
+                "F1",
+                9.0,
+                MARGIN,
+                PAGE_HEIGHT - 56.0,
+                &format!("Generated {generated}"),
+            );
+            cost_chart(&mut content, monthly);
+            FIRST_PAGE_TABLE_TOP
+        } else {
+            TABLE_TOP
+        };
+        table(&mut content, table_top, page_rows);
+        text(
+            &mut content,
+            "F1",
+            8.0,
+            PAGE_WIDTH - MARGIN - 60.0,
+            25.0,
+            &format!("Page {} of {page_count}", index + 1),
+        );
+        document.pages.push(content);
+    }
+    document.finish()
+}
+
+impl Row {
+    fn cells(&self) -> &[String] {
+        match self {
+            Row::Month(cells) | Row::Breakdown(cells) | Row::Totals(cells) => cells,
+        }
+    }
+}
+
+fn report_rows(monthly: &[MonthlyUsage], totals: &UsageTotals, breakdown: bool) -> Vec<Row> {
+    let tokens = |value: u64| format_tokens(value, TokenFormat::Exact);
+    let mut rows = Vec::new();
+    for entry in monthly {
+        rows.push(Row::Month(vec![
+            entry.month.clone(),
+            tokens(entry.input_tokens),
+            tokens(entry.output_tokens),
+            tokens(entry.cache_creation_tokens),
+            tokens(entry.cache_read_tokens),
+            tokens(entry.total_tokens),
+            format_currency(entry.total_cost),
+        ]));
+        if breakdown {
+            for model in &entry.model_breakdowns {
+                rows.push(Row::Breakdown(vec![
+                    format!("  {}", format_model_name(&model.model_name)),
+                    tokens(model.input_tokens),
+                    tokens(model.output_tokens),
+                    tokens(model.cache_creation_tokens),
+                    tokens(model.cache_read_tokens),
+                    tokens(model.total_tokens),
+                    format_currency(model.cost),
+                ]));
+            }
+        }
+    }
+    rows.push(Row::Totals(vec![
+        "Total".to_string(),
+        tokens(totals.input_tokens),
+        tokens(totals.output_tokens),
+        tokens(totals.cache_creation_tokens),
+        tokens(totals.cache_read_tokens),
+        tokens(totals.total_tokens()),
+        format_currency(totals.total_cost),
+    ]));
+    rows
+}
+
+fn rows_per_page(table_top: f64) -> usize {
+    // One row for the header.
+    (((table_top - TABLE_BOTTOM) / ROW_HEIGHT) as usize)
+        .saturating_sub(1)
+        .max(1)
+}
+
+fn paginate(rows: &[Row]) -> Vec<&[Row]> {
+    let mut pages = Vec::new();
+    let mut remaining = rows;
+    let mut capacity = rows_per_page(FIRST_PAGE_TABLE_TOP);
+    while !remaining.is_empty() {
+        let (page, rest) = remaining.split_at(capacity.min(remaining.len()));
+        pages.push(page);
+        remaining = rest;
+        capacity = rows_per_page(TABLE_TOP);
+    }
+    pages
+}
+
+fn format_cells(cells: &[String]) -> String {
+    cells
+        .iter()
+        .zip(COLUMN_WIDTHS)
+        .enumerate()
+        .map(|(index, (cell, width))| {
+            if index == 0 {
+                format!("{cell:<width$}")
+            } else {
+                format!("{cell:>width$}")
+            }
+        })
+        .collect()
+}
+
+fn table(content: &mut String, top: f64, rows: &[Row]) {
+    let width = COLUMN_WIDTHS.iter().sum::<usize>() as f64 * TABLE_FONT_SIZE * 0.6;
+    let mut headers = COLUMN_HEADERS.map(str::to_string);
+    headers[COLUMN_HEADERS.len() - 1] = cost_header("Cost");
+    text(
+        content,
+        "F4",
+        TABLE_FONT_SIZE,
+        MARGIN,
+        top,
+        &format_cells(&headers),
+    );
+    line(content, MARGIN, top - 4.0, MARGIN + width, top - 4.0);
+    let mut y = top - ROW_HEIGHT;
+    for row in rows {
+        let (font, cells) = match row {
+            Row::Month(cells) | Row::Breakdown(cells) => ("F3", cells),
+            Row::Totals(cells) => {
+                line(content, MARGIN, y + 9.0, MARGIN + width, y + 9.0);
+                ("F4", cells)
+            }
+        };
+        if matches!(row, Row::Breakdown(_)) {
+            content.push_str("0.4 g\n");
+            text(
+                content,
+                font,
+                TABLE_FONT_SIZE,
+                MARGIN,
+                y,
+                &format_cells(cells),
+            );
+            content.push_str("0 g\n");
+        } else {
+            text(
+                content,
+                font,
+                TABLE_FONT_SIZE,
+                MARGIN,
+                y,
+                &format_cells(cells),
+            );
+        }
+        y -= ROW_HEIGHT;
+    }
+}
+
+fn cost_chart(content: &mut String, monthly: &[MonthlyUsage]) {
+    let left = MARGIN;
+    let bottom = CHART_TOP - CHART_HEIGHT;
+    let width = PAGE_WIDTH - 2.0 * MARGIN;
+    text(content, "F2", 10.0, left, CHART_TOP + 8.0, "Cost per month");
+    line(content, left, bottom, left + width, bottom);
+    let max_cost = monthly
+        .iter()
+        .map(|entry| entry.total_cost)
+        .fold(0.0, f64::max);
+    if monthly.is_empty() || max_cost <= 0.0 {
+        return;
+    }
+
+    let slot = width / monthly.len() as f64;
+    let bar_width = (slot * 0.6).min(40.0);
+    // Thin out month labels so they never overlap (each is about 40pt wide).
+    let label_every = (45.0 / slot).ceil().max(1.0) as usize;
+    for (index, entry) in monthly.iter().enumerate() {
+        let x = left + slot * index as f64 + (slot - bar_width) / 2.0;
+        let height = (entry.total_cost / max_cost) * (CHART_HEIGHT - 20.0);
+        let _ = writeln!(
+            content,
+            "0.2 0.45 0.75 rg {x:.2} {bottom:.2} {bar_width:.2} {height:.2} re f 0 g"
+        );
+        if index % label_every == 0 {
+            text(content, "F1", 7.0, x, bottom - 10.0, &entry.month);
+            text(
+                content,
+                "F1",
+                7.0,
+                x,
+                bottom + height + 3.0,
+                &format_currency(entry.total_cost),
+            );
+        }
+    }
+}
+
+fn text(content: &mut String, font: &str, size: f64, x: f64, y: f64, value: &str) {
+    let _ = writeln!(
+        content,
+        "BT /{font} {size} Tf {x:.2} {y:.2} Td ({}) Tj ET",
+        escape_text(value)
+    );
+}
+
+fn line(content: &mut String, x1: f64, y1: f64, x2: f64, y2: f64) {
+    let _ = writeln!(content, "0.5 w {x1:.2} {y1:.2} m {x2:.2} {y2:.2} l S");
+}
+
+/// Escapes a PDF literal string for the WinAnsiEncoding fonts: other characters the encoding
+/// has are written as octal escapes, and those it lacks become `?`.
+fn escape_text(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match (c, win_ansi_byte(c)) {
+            ('(' | ')' | '\\', _) => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            (' '..='~', _) => escaped.push(c),
+            (_, Some(byte)) => {
+                let _ = write!(escaped, "\\{byte:03o}");
+            }
+            (_, None) => escaped.push('?'),
+        }
+    }
+    escaped
+}
+
+/// The WinAnsiEncoding (Windows-1252) byte of a printable character, if it has one.
+fn win_ansi_byte(c: char) -> Option<u8> {
+    const CP1252_EXTRAS: [(char, u8); 27] = [
+        ('€', 0x80),
+        ('‚', 0x82),
+        ('ƒ', 0x83),
+        ('„', 0x84),
+        ('…', 0x85),
+        ('†', 0x86),
+        ('‡', 0x87),
+        ('ˆ', 0x88),
+        ('‰', 0x89),
+        ('Š', 0x8A),
+        ('‹', 0x8B),
+        ('Œ', 0x8C),
+        ('Ž', 0x8E),
+        ('‘', 0x91),
+        ('’', 0x92),
+        ('“', 0x93),
+        ('”', 0x94),
+        ('•', 0x95),
+        ('–', 0x96),
+        ('—', 0x97),
+        ('˜', 0x98),
+        ('™', 0x99),
+        ('š', 0x9A),
+        ('›', 0x9B),
+        ('œ', 0x9C),
+        ('ž', 0x9E),
+        ('Ÿ', 0x9F),
+    ];
+    match c {
+        ' '..='~' | '\u{A0}'..='\u{FF}' => Some(c as u8),
+        _ => CP1252_EXTRAS
+            .iter()
+            .find(|(extra, _)| *extra == c)
+            .map(|(_, byte)| *byte),
+    }
+}
+
+/// Minimal PDF 1.4 writer: uncompressed page content streams and the four standard fonts used
+/// by the report.
+#[derive(Default)]
+struct PdfDocument {
+    pages: Vec<String>,
+}
+
+impl PdfDocument {
+    fn finish(self) -> Vec<u8> {
+        const FONTS: [(&str, &str); 4] = [
+            ("F1", "Helvetica"),
+            ("F2", "Helvetica-Bold"),
+            ("F3", "Courier"),
+            ("F4", "Courier-Bold"),
+        ];
+        // Object ids: 1 catalog, 2 page tree, 3..=6 fonts, then a page and its content per page.
+        let first_page_id = 3 + FONTS.len();
+        let page_ids = (0..self.pages.len())
+            .map(|index| first_page_id + index * 2)
+            .collect::<Vec<_>>();
+
+        let mut objects = vec![
+            "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+            format!(
+                "<< /Type /Pages /Kids [{}] /Count {} >>",
+                page_ids
+                    .iter()
+                    .map(|id| format!("{id} 0 R"))
+                    .collect::<Vec<_>>()
+                    .join(" "),
+                page_ids.len()
+            ),
+        ];
+        for (_, base_font) in FONTS {
+            objects.push(format!(
+                "<< /Type /Font /Subtype /Type1 /BaseFont /{base_font} /Encoding /WinAnsiEncoding >>"
+            ));
+        }
+        let font_resources = FONTS
+            .iter()
+            .enumerate()
+            .map(|(index, (name, _))| format!("/{name} {} 0 R", 3 + index))
+            .collect::<Vec<_>>()
+            .join(" ");
+        for (content, page_id) in self.pages.iter().zip(&page_ids) {
+            objects.push(format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {PAGE_WIDTH} {PAGE_HEIGHT}] \
+                 /Resources << /Font << {font_resources} >> >> /Contents {} 0 R >>",
+                page_id + 1
+            ));
+            objects.push(format!(
+                "<< /Length {} >>\nstream\n{content}endstream",
+                content.len()
+            ));
+        }
+
+        let mut output = String::from("%PDF-1.4\n");
+        let mut offsets = Vec::with_capacity(objects.len());
+        for (index, object) in objects.iter().enumerate() {
+            offsets.push(output.len());
+            let _ = write!(output, "{} 0 obj\n{object}\nendobj\n", index + 1);
+        }
+        let xref_offset = output.len();
+        let _ = write!(
+            output,
+            "xref\n0 {}\n0000000000 65535 f \n",
+            objects.len() + 1
+        );
+        for offset in offsets {
+            let _ = writeln!(output, "{offset:010} 00000 n ");
+        }
+        let _ = write!(
+            output,
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref_offset}\n%%EOF\n",
+            objects.len() + 1
+        );
+        output.into_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn month(month: &str, cost: f64) -> MonthlyUsage {
+        MonthlyUsage {
+            month: month.to_string(),
+            input_tokens: 1_000,
+            output_tokens: 500,
+            total_tokens: 1_500,
+            total_cost: cost,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn escape_text_encodes_western_european_text_and_replaces_the_rest() {
+        assert_eq!(escape_text(r"a (b) \ c"), r"a \(b\) \\ c");
+        assert_eq!(escape_text("café – €5"), r"caf\351 \226 \2005");
+        assert_eq!(escape_text("Ωmega 日本"), "?mega ??");
+    }
+
+    #[test]
+    fn paginate_moves_overflow_to_later_pages() {
+        let rows = (0..100)
+            .map(|index| Row::Month(vec![index.to_string()]))
+            .collect::<Vec<_>>();
+        let pages = paginate(&rows);
+        assert_eq!(pages[0].len(), rows_per_page(FIRST_PAGE_TABLE_TOP));
+        assert!(
+            pages[1..]
+                .iter()
+                .all(|page| page.len() <= rows_per_page(TABLE_TOP))
+        );
+        assert_eq!(pages.iter().map(|page| page.len()).sum::<usize>(), 100);
+    }
+
+    #[test]
+    fn monthly_report_pdf_writes_a_well_formed_document() {
+        let monthly = vec![month("2026-01", 12.5), month("2026-02", 30.0)];
+        let totals = UsageTotals {
+            input_tokens: 2_000,
+            output_tokens: 1_000,
+            total_cost: 42.5,
+            ..Default::default()
+        };
+        let pdf = monthly_report_pdf(
+            "ccost monthly report",
+            "2026-03-01",
+            &monthly,
+            &totals,
+            false,
+        );
+        let pdf = String::from_utf8(pdf).unwrap();
+
+        assert!(pdf.starts_with("%PDF-1.4\n"));
+        assert!(pdf.ends_with("%%EOF\n"));
+        assert!(pdf.contains("/Count 1"));
+        assert!(pdf.contains("(2026-02) Tj"));
+        assert!(pdf.contains("$42.50"));
+
+        let xref_offset = pdf
+            .rsplit("startxref\n")
+            .next()
+            .and_then(|tail| tail.lines().next())
+            .and_then(|value| value.parse::<usize>().ok())
+            .unwrap();
+        assert!(pdf[xref_offset..].starts_with("xref\n"));
+        let first_entry = pdf[xref_offset..].lines().nth(3).unwrap();
+        let offset = first_entry[..10].parse::<usize>().unwrap();
+        assert!(pdf[offset..].starts_with("1 0 obj"));
+    }
+
+    #[test]
+    fn monthly_report_pdf_warns_about_text_the_fonts_lack() {
+        let pdf = monthly_report_pdf(
+            "Rapport für プロジェクト",
+            "2026-03-01",
+            &[month("2026-01", 1.0)],
+            &UsageTotals::default(),
+            false,
+        );
+        let pdf = String::from_utf8(pdf).unwrap();
+        assert!(pdf.contains(r"(Rapport f\374r ??????) Tj"));
+        assert!(crate::warnings::recorded_warnings().iter().any(|warning| {
+            warning.contains("only cover Western European text; 6 other characters")
+        }));
+    }
+}
@@ -1,13 +1,17 @@
-use serde::Deserialize;
+use crate::error::{ErrorCode, coded_error};
+use crate::warnings;
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::Path;
 use std::str::FromStr;
-use std::sync::OnceLock;
+use std::sync::{OnceLock, RwLock};
 
 const DEFAULT_TIERED_THRESHOLD: u64 = 200_000;
 const MILLION: f64 = 1_000_000.0;
 const DEFAULT_CODEX_FAST_MULTIPLIER: f64 = 2.0;
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Default, Deserialize)]
 pub struct LiteLLMModelPricing {
     pub input_cost_per_token: Option<f64>,
     pub output_cost_per_token: Option<f64>,
@@ -17,7 +21,6 @@ pub struct LiteLLMModelPricing {
     pub output_cost_per_token_above_200k_tokens: Option<f64>,
     pub cache_creation_input_token_cost_above_200k_tokens: Option<f64>,
     pub cache_read_input_token_cost_above_200k_tokens: Option<f64>,
-    pub max_input_tokens: Option<u64>,
     pub provider_specific_entry: Option<ProviderSpecificEntry>,
 }
 
@@ -26,11 +29,134 @@ pub struct ProviderSpecificEntry {
     pub fast: Option<f64>,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Rates in USD per million tokens for one model from the user's pricing file; unset rates
+/// keep the embedded dataset's value.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PricingOverride {
+    pub input: Option<f64>,
+    pub output: Option<f64>,
+    pub cache_creation: Option<f64>,
+    pub cache_read: Option<f64>,
+}
+
+impl PricingOverride {
+    fn apply(&self, base: Option<LiteLLMModelPricing>) -> LiteLLMModelPricing {
+        let per_token = |rate: Option<f64>| rate.map(|rate| rate / MILLION);
+        let mut pricing = base.unwrap_or_default();
+        // A user rate replaces the tiered rate too, so a flat contract price stays flat.
+        if let Some(rate) = per_token(self.input) {
+            pricing.input_cost_per_token = Some(rate);
+            pricing.input_cost_per_token_above_200k_tokens = None;
+        }
+        if let Some(rate) = per_token(self.output) {
+            pricing.output_cost_per_token = Some(rate);
+            pricing.output_cost_per_token_above_200k_tokens = None;
+        }
+        if let Some(rate) = per_token(self.cache_creation) {
+            pricing.cache_creation_input_token_cost = Some(rate);
+            pricing.cache_creation_input_token_cost_above_200k_tokens = None;
+        }
+        if let Some(rate) = per_token(self.cache_read) {
+            pricing.cache_read_input_token_cost = Some(rate);
+            pricing.cache_read_input_token_cost_above_200k_tokens = None;
+        }
+        pricing
+    }
+
+    fn rates(&self) -> [Option<f64>; 4] {
+        [
+            self.input,
+            self.output,
+            self.cache_creation,
+            self.cache_read,
+        ]
+    }
+}
+
+/// User pricing installed at startup and picked up by every [`PricingFetcher`].
+static PRICING_OVERRIDES: OnceLock<HashMap<String, PricingOverride>> = OnceLock::new();
+
+/// Installs the user's pricing overrides; later calls are ignored.
+pub fn set_pricing_overrides(overrides: HashMap<String, PricingOverride>) {
+    let _ = PRICING_OVERRIDES.set(overrides);
+}
+
+/// Identifies the installed overrides, so costs calculated under different prices can be
+/// told apart.
+pub(crate) fn pricing_overrides_key() -> String {
+    let Some(overrides) = PRICING_OVERRIDES.get() else {
+        return String::new();
+    };
+    let mut models = overrides
+        .iter()
+        .map(|(model, rates)| format!("{model}={rates:?}"))
+        .collect::<Vec<_>>();
+    models.sort();
+    models.join(";")
+}
+
+pub fn load_pricing_overrides(path: &Path) -> Result<HashMap<String, PricingOverride>> {
+    let content = std::fs::read_to_string(path).map_err(|error| {
+        coded_error(
+            ErrorCode::InvalidConfig,
+            format!("failed to read pricing file {}: {error}", path.display()),
+        )
+    })?;
+    parse_pricing_overrides(&content).map_err(|error| {
+        coded_error(
+            ErrorCode::InvalidConfig,
+            format!("invalid pricing file {}: {error}", path.display()),
+        )
+    })
+}
+
+/// Reads `[models."name"]` tables of per-million-token rates. Entries that don't parse or
+/// carry negative rates are skipped with a warning rather than failing the whole file.
+pub fn parse_pricing_overrides(content: &str) -> Result<HashMap<String, PricingOverride>> {
+    let mut document: toml::Table = toml::from_str(content)?;
+    let models = match document.remove("models") {
+        Some(toml::Value::Table(models)) => models,
+        Some(_) => return Err(anyhow!("`models` must be a table")),
+        None => toml::Table::new(),
+    };
+    for key in document.keys() {
+        warnings::warn(format!("pricing file: ignored unknown key `{key}`"));
+    }
+    let mut overrides = HashMap::new();
+    for (model, value) in models {
+        match value.try_into::<PricingOverride>() {
+            Ok(rates)
+                if rates
+                    .rates()
+                    .iter()
+                    .flatten()
+                    .all(|rate| rate.is_finite() && *rate >= 0.0) =>
+            {
+                overrides.insert(model, rates);
+            }
+            Ok(_) => warnings::warn(format!(
+                "pricing file: skipped {model}: rates must be non-negative numbers"
+            )),
+            Err(error) => warnings::warn(format!(
+                "pricing file: skipped {model}: {}",
+                error.message().trim()
+            )),
+        }
+    }
+    Ok(overrides)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
 pub enum CostMode {
+    /// Use costUSD from the logs when present, otherwise calculate from tokens
     Auto,
+    /// Always calculate from tokens and pricing data
     Calculate,
+    /// Only use costUSD from the logs
     Display,
+    /// Use the larger of costUSD and the calculated cost, for a conservative estimate
+    Max,
 }
 
 impl FromStr for CostMode {
@@ -41,6 +167,7 @@ impl FromStr for CostMode {
             "auto" => Ok(Self::Auto),
             "calculate" => Ok(Self::Calculate),
             "display" => Ok(Self::Display),
+            "max" => Ok(Self::Max),
             _ => Err(format!("Invalid cost mode: {value}")),
         }
     }
@@ -52,7 +179,7 @@ pub(crate) struct CacheCreationTokens {
     pub ephemeral_1h_input_tokens: u64,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UsageTokens {
     pub input_tokens: u64,
     pub output_tokens: u64,
@@ -77,6 +204,11 @@ fn pricing_dataset() -> &'static HashMap<String, LiteLLMModelPricing> {
 pub struct PricingFetcher {
     provider_prefixes: Vec<String>,
     model_aliases: HashMap<String, String>,
+    /// User rates layered over the dataset, looked up by model name or its alias.
+    overrides: HashMap<String, PricingOverride>,
+    /// Pricing resolved per model name (including misses), so the prefix and substring scans
+    /// run once per distinct name rather than once per record.
+    resolved: RwLock<HashMap<String, Option<LiteLLMModelPricing>>>,
 }
 
 impl Default for PricingFetcher {
@@ -177,6 +309,17 @@ impl PricingFetcher {
                 ("sonnet-4-6".to_string(), "claude-sonnet-4-6".to_string()),
                 ("sonnet-4-5".to_string(), "claude-sonnet-4-5".to_string()),
             ]),
+            overrides: PRICING_OVERRIDES.get().cloned().unwrap_or_default(),
+            resolved: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// A fetcher using `overrides` instead of the ones installed at startup.
+    #[cfg(test)]
+    fn with_overrides(overrides: HashMap<String, PricingOverride>) -> Self {
+        Self {
+            overrides,
+            ..Self::new()
         }
     }
 
@@ -190,6 +333,32 @@ impl PricingFetcher {
     }
 
     pub fn get_model_pricing(&self, model_name: &str) -> Option<LiteLLMModelPricing> {
+        if let Ok(resolved) = self.resolved.read()
+            && let Some(pricing) = resolved.get(model_name)
+        {
+            return pricing.clone();
+        }
+        let pricing = self.resolve_model_pricing(model_name);
+        if let Ok(mut resolved) = self.resolved.write() {
+            resolved.insert(model_name.to_string(), pricing.clone());
+        }
+        pricing
+    }
+
+    fn resolve_model_pricing(&self, model_name: &str) -> Option<LiteLLMModelPricing> {
+        let dataset = self.resolve_dataset_pricing(model_name);
+        let user = self.overrides.get(model_name).or_else(|| {
+            self.model_aliases
+                .get(model_name)
+                .and_then(|alias| self.overrides.get(alias))
+        });
+        match user {
+            Some(user) => Some(user.apply(dataset)),
+            None => dataset,
+        }
+    }
+
+    fn resolve_dataset_pricing(&self, model_name: &str) -> Option<LiteLLMModelPricing> {
         let pricing = pricing_dataset();
         let mut names = vec![model_name.to_string()];
         if let Some(alias) = self.model_aliases.get(model_name) {
@@ -385,6 +554,73 @@ mod tests {
         assert_eq!(fetcher.calculate_cost_from_tokens(&tokens, None), 0.0);
     }
 
+    #[test]
+    fn get_model_pricing_resolves_each_model_name_once() {
+        let fetcher = PricingFetcher::new();
+        let first = fetcher.get_model_pricing("claude-sonnet-4").unwrap();
+        let second = fetcher.get_model_pricing("claude-sonnet-4").unwrap();
+        assert_eq!(first.input_cost_per_token, second.input_cost_per_token);
+        assert!(fetcher.get_model_pricing("no-such-model-zzz").is_none());
+
+        let resolved = fetcher.resolved.read().unwrap();
+        assert_eq!(resolved.len(), 2);
+        assert!(resolved["claude-sonnet-4"].is_some());
+        assert!(resolved["no-such-model-zzz"].is_none());
+    }
+
+    #[test]
+    fn pricing_overrides_replace_dataset_rates_and_price_unknown_models() {
+        let overrides = parse_pricing_overrides(
+            r#"
+[models."claude-sonnet-4-20250514"]
+input = 2.0
+
+[models.acme-internal]
+input = 1.0
+output = 4.0
+
+[models.broken]
+input = "cheap"
+
+[models.negative]
+output = -1.0
+"#,
+        )
+        .unwrap();
+        assert_eq!(overrides.len(), 2);
+        let warnings = crate::warnings::recorded_warnings();
+        assert!(
+            warnings
+                .iter()
+                .any(|warning| warning.contains("skipped broken"))
+        );
+        assert!(
+            warnings
+                .iter()
+                .any(|warning| warning.contains("skipped negative"))
+        );
+
+        let fetcher = PricingFetcher::with_overrides(overrides);
+        let sonnet = fetcher.get_model_pricing("claude-sonnet-4").unwrap();
+        let dataset = PricingFetcher::with_overrides(HashMap::new())
+            .get_model_pricing("claude-sonnet-4")
+            .unwrap();
+        assert_eq!(sonnet.input_cost_per_token, Some(2.0 / MILLION));
+        assert_eq!(sonnet.output_cost_per_token, dataset.output_cost_per_token);
+
+        let tokens = UsageTokens {
+            input_tokens: 1_000_000,
+            output_tokens: 500_000,
+            cache_creation_input_tokens: 0,
+            cache_read_input_tokens: 0,
+        };
+        let cost = fetcher.calculate_cost_from_tokens(&tokens, Some("acme-internal"));
+        assert!((cost - 3.0).abs() < 1e-9);
+
+        assert!(parse_pricing_overrides("models = 3").is_err());
+        assert!(parse_pricing_overrides("[models\n").is_err());
+    }
+
     #[test]
     fn calculate_cost_from_tokens_uses_pricing() {
         let fetcher = PricingFetcher::new();
@@ -420,7 +656,6 @@ mod tests {
             output_cost_per_token_above_200k_tokens: None,
             cache_creation_input_token_cost_above_200k_tokens: None,
             cache_read_input_token_cost_above_200k_tokens: None,
-            max_input_tokens: None,
             provider_specific_entry: None,
         };
 
@@ -451,7 +686,6 @@ mod tests {
             output_cost_per_token_above_200k_tokens: None,
             cache_creation_input_token_cost_above_200k_tokens: None,
             cache_read_input_token_cost_above_200k_tokens: None,
-            max_input_tokens: None,
             provider_specific_entry: None,
         };
 
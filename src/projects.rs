@@ -0,0 +1,129 @@
+use crate::data_loader::UsageEntry;
+use std::collections::{BTreeSet, HashMap};
+
+/// Project shown for entries without one, such as Codex and OpenCode usage.
+pub const UNKNOWN_PROJECT: &str = "unknown";
+
+/// Usage of one project across all entries.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProjectUsage {
+    pub project: String,
+    /// First and last day with usage, as `YYYY-MM-DD`.
+    pub first_date: String,
+    pub last_date: String,
+    pub request_count: u64,
+    pub session_count: usize,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub total_tokens: u64,
+    pub total_cost: f64,
+    /// Models in order of first use; `<synthetic>` is left out.
+    pub models_used: Vec<String>,
+}
+
+/// Sums entries per project, most expensive first.
+pub fn group_entries_by_project(entries: &[UsageEntry]) -> Vec<ProjectUsage> {
+    let mut projects: HashMap<&str, (ProjectUsage, BTreeSet<&str>)> = HashMap::new();
+    let mut sorted = entries.iter().collect::<Vec<_>>();
+    sorted.sort_by_key(|entry| entry.timestamp);
+    for entry in sorted {
+        let name = entry.project.as_deref().unwrap_or(UNKNOWN_PROJECT);
+        let (usage, sessions) = projects.entry(name).or_insert_with(|| {
+            (
+                ProjectUsage {
+                    project: name.to_string(),
+                    first_date: entry.date.clone(),
+                    last_date: entry.date.clone(),
+                    ..Default::default()
+                },
+                BTreeSet::new(),
+            )
+        });
+        if entry.date < usage.first_date {
+            usage.first_date = entry.date.clone();
+        }
+        if entry.date > usage.last_date {
+            usage.last_date = entry.date.clone();
+        }
+        usage.request_count += 1;
+        usage.input_tokens += entry.input_tokens;
+        usage.output_tokens += entry.output_tokens;
+        usage.cache_creation_tokens += entry.cache_creation_tokens;
+        usage.cache_read_tokens += entry.cache_read_tokens;
+        usage.total_tokens += entry.total_tokens;
+        usage.total_cost += entry.cost;
+        if let Some(model) = entry.model.as_deref()
+            && model != "<synthetic>"
+            && !usage.models_used.iter().any(|used| used == model)
+        {
+            usage.models_used.push(model.to_string());
+        }
+        if let Some(session) = entry.session_id.as_deref() {
+            sessions.insert(session);
+        }
+    }
+    let mut projects = projects
+        .into_values()
+        .map(|(usage, sessions)| ProjectUsage {
+            session_count: sessions.len(),
+            ..usage
+        })
+        .collect::<Vec<_>>();
+    projects.sort_by(|a, b| {
+        b.total_cost
+            .total_cmp(&a.total_cost)
+            .then_with(|| a.project.cmp(&b.project))
+    });
+    projects
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn entry(project: Option<&str>, date: &str, model: &str, cost: f64) -> UsageEntry {
+        UsageEntry {
+            timestamp: Utc.with_ymd_and_hms(2026, 1, 2, 3, 4, 5).unwrap(),
+            date: date.to_string(),
+            session_id: Some(format!("{date}-session")),
+            project: project.map(str::to_string),
+            model: Some(model.to_string()),
+            input_tokens: 10,
+            output_tokens: 5,
+            total_tokens: 15,
+            cost,
+            local_hour: 3,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn group_entries_by_project_totals_each_project_by_cost() {
+        let entries = vec![
+            entry(Some("api"), "2026-01-05", "claude-sonnet-4", 1.0),
+            entry(Some("web"), "2026-01-03", "claude-opus-4", 4.0),
+            entry(Some("api"), "2026-01-02", "claude-opus-4", 2.0),
+            entry(Some("api"), "2026-01-05", "<synthetic>", 0.0),
+            entry(None, "2026-01-04", "gpt-5", 0.5),
+        ];
+
+        let projects = group_entries_by_project(&entries);
+        assert_eq!(
+            projects
+                .iter()
+                .map(|usage| (usage.project.as_str(), usage.total_cost))
+                .collect::<Vec<_>>(),
+            vec![("web", 4.0), ("api", 3.0), (UNKNOWN_PROJECT, 0.5)]
+        );
+        let api = &projects[1];
+        assert_eq!(api.first_date, "2026-01-02");
+        assert_eq!(api.last_date, "2026-01-05");
+        assert_eq!(api.request_count, 3);
+        assert_eq!(api.session_count, 2);
+        assert_eq!(api.total_tokens, 45);
+        assert_eq!(api.models_used.len(), 2);
+    }
+}
@@ -0,0 +1,151 @@
+use crate::data_loader::UsageEntry;
+use anyhow::{Result, anyhow};
+use rusqlite::Connection;
+use rusqlite::types::ValueRef;
+use serde_json::Value;
+
+/// Schema of the `entries` table that `ccost query` runs against, one row per usage entry.
+pub const ENTRIES_SCHEMA: &str = "CREATE TABLE entries (
+    source TEXT NOT NULL,
+    timestamp TEXT NOT NULL,
+    date TEXT NOT NULL,
+    hour INTEGER NOT NULL,
+    session_id TEXT,
+    project TEXT,
+    model TEXT,
+    input_tokens INTEGER NOT NULL,
+    output_tokens INTEGER NOT NULL,
+    cache_creation_tokens INTEGER NOT NULL,
+    cache_read_tokens INTEGER NOT NULL,
+    total_tokens INTEGER NOT NULL,
+    cost REAL NOT NULL,
+    is_sidechain INTEGER NOT NULL,
+    tool_calls INTEGER NOT NULL,
+    source_file TEXT,
+    source_line INTEGER
+)";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryResult {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<Value>>,
+}
+
+/// Loads `entries` into an in-memory SQLite database and runs a single `sql` statement.
+pub fn run_query(entries: &[UsageEntry], sql: &str) -> Result<QueryResult> {
+    let mut connection = Connection::open_in_memory()?;
+    connection.execute(ENTRIES_SCHEMA, [])?;
+    let transaction = connection.transaction()?;
+    {
+        let mut insert = transaction.prepare(
+            "INSERT INTO entries VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17)",
+        )?;
+        // SQLite integers are signed 64-bit.
+        let int = |value: u64| i64::try_from(value).unwrap_or(i64::MAX);
+        for entry in entries {
+            insert.execute(rusqlite::params![
+                entry.source.as_str(),
+                entry.timestamp.to_rfc3339(),
+                entry.date,
+                entry.local_hour,
+                entry.session_id,
+                entry.project,
+                entry.model,
+                int(entry.input_tokens),
+                int(entry.output_tokens),
+                int(entry.cache_creation_tokens),
+                int(entry.cache_read_tokens),
+                int(entry.total_tokens),
+                entry.cost,
+                entry.is_sidechain,
+                int(entry.tool_calls),
+                entry
+                    .source_file
+                    .as_ref()
+                    .map(|file| file.display().to_string()),
+                entry.source_line.map(|line| int(line as u64)),
+            ])?;
+        }
+    }
+    transaction.commit()?;
+
+    let mut statement = connection
+        .prepare(sql)
+        .map_err(|error| anyhow!("Invalid query: {error}"))?;
+    let columns = statement
+        .column_names()
+        .into_iter()
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    let mut rows = Vec::new();
+    let mut results = statement.query([])?;
+    while let Some(row) = results.next()? {
+        let values = (0..columns.len())
+            .map(|index| row.get_ref(index).map(json_value))
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        rows.push(values);
+    }
+    Ok(QueryResult { columns, rows })
+}
+
+fn json_value(value: ValueRef) -> Value {
+    match value {
+        ValueRef::Null => Value::Null,
+        ValueRef::Integer(number) => Value::from(number),
+        ValueRef::Real(number) => Value::from(number),
+        ValueRef::Text(text) => Value::String(String::from_utf8_lossy(text).into_owned()),
+        ValueRef::Blob(bytes) => Value::String(format!("<{} bytes>", bytes.len())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use serde_json::json;
+
+    fn entry(project: &str, model: &str, cost: f64) -> UsageEntry {
+        UsageEntry {
+            timestamp: Utc.with_ymd_and_hms(2026, 1, 2, 3, 4, 5).unwrap(),
+            date: "2026-01-02".to_string(),
+            session_id: Some("s1".to_string()),
+            project: Some(project.to_string()),
+            model: Some(model.to_string()),
+            input_tokens: 10,
+            output_tokens: 5,
+            total_tokens: 15,
+            cost,
+            tool_calls: 1,
+            local_hour: 3,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn run_query_groups_entries() {
+        let entries = vec![
+            entry("app", "claude-sonnet-4", 1.0),
+            entry("app", "claude-opus-4", 2.5),
+            entry("api", "claude-sonnet-4", 0.5),
+        ];
+        let result = run_query(
+            &entries,
+            "SELECT project, sum(cost) AS cost, sum(total_tokens) FROM entries GROUP BY 1 ORDER BY 2 DESC",
+        )
+        .unwrap();
+        assert_eq!(result.columns, vec!["project", "cost", "sum(total_tokens)"]);
+        assert_eq!(
+            result.rows,
+            vec![
+                vec![json!("app"), json!(3.5), json!(30)],
+                vec![json!("api"), json!(0.5), json!(15)],
+            ]
+        );
+    }
+
+    #[test]
+    fn run_query_reports_invalid_sql() {
+        let error = run_query(&[], "SELECT nope FROM entries").unwrap_err();
+        assert!(error.to_string().starts_with("Invalid query:"));
+    }
+}
@@ -0,0 +1,235 @@
+use crate::data_loader::{DailyUsage, calculate_totals_daily};
+use crate::notify::top_projects_by_cost;
+use crate::table::{TokenFormat, format_currency, format_model_name, format_tokens};
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+
+const TOP_ENTRIES: usize = 5;
+const BUSIEST_DAYS: usize = 3;
+/// A day is a spike when its cost is this many standard deviations above the monthly mean.
+const SPIKE_STDDEVS: f64 = 2.0;
+/// Fewer active days than this make the mean and deviation meaningless.
+const MIN_DAYS_FOR_SPIKES: usize = 5;
+
+/// Usage of one calendar day summed across projects.
+#[derive(Debug, Clone, PartialEq)]
+struct DayTotal {
+    date: String,
+    tokens: u64,
+    cost: f64,
+}
+
+/// Builds the Markdown recap for `month` (YYYY-MM) from per-project daily usage.
+pub fn recap_markdown(month: &str, daily: &[DailyUsage]) -> String {
+    let mut output = String::new();
+    let _ = writeln!(output, "# Usage recap: {month}\n");
+    let totals = calculate_totals_daily(daily);
+    let days = day_totals(daily);
+    if days.is_empty() {
+        let _ = writeln!(output, "No usage was recorded this month.");
+        return output;
+    }
+
+    let projects = top_projects_by_cost(daily, usize::MAX);
+    let average = totals.total_cost / days.len() as f64;
+    let _ = writeln!(
+        output,
+        "Spent **{}** on **{}** tokens across {} active {} and {} {}, \
+         averaging {} per active day.\n",
+        format_currency(totals.total_cost),
+        format_tokens(totals.total_tokens(), TokenFormat::HumanReadable),
+        days.len(),
+        plural(days.len(), "day", "days"),
+        projects.len(),
+        plural(projects.len(), "project", "projects"),
+        format_currency(average),
+    );
+
+    if !projects.is_empty() {
+        let _ = writeln!(output, "## Top projects\n");
+        for (project, cost) in projects.iter().take(TOP_ENTRIES) {
+            let _ = writeln!(
+                output,
+                "- **{project}**: {} ({})",
+                format_currency(*cost),
+                share(*cost, totals.total_cost)
+            );
+        }
+        let _ = writeln!(output);
+    }
+
+    let models = top_models_by_cost(daily);
+    if !models.is_empty() {
+        let _ = writeln!(output, "## Top models\n");
+        for (model, cost) in models.iter().take(TOP_ENTRIES) {
+            let _ = writeln!(
+                output,
+                "- **{}**: {} ({})",
+                format_model_name(model),
+                format_currency(*cost),
+                share(*cost, totals.total_cost)
+            );
+        }
+        let _ = writeln!(output);
+    }
+
+    let mut busiest = days.clone();
+    busiest.sort_by(|a, b| b.cost.total_cmp(&a.cost).then_with(|| a.date.cmp(&b.date)));
+    let _ = writeln!(output, "## Busiest days\n");
+    for day in busiest.iter().take(BUSIEST_DAYS) {
+        let _ = writeln!(
+            output,
+            "- {}: {} ({} tokens)",
+            day.date,
+            format_currency(day.cost),
+            format_tokens(day.tokens, TokenFormat::HumanReadable)
+        );
+    }
+
+    let spikes = spikes(&days);
+    if !spikes.is_empty() {
+        let _ = writeln!(output, "\n## Notable spikes\n");
+        for day in spikes {
+            let _ = writeln!(
+                output,
+                "- {}: {}, {:.1}x the daily average",
+                day.date,
+                format_currency(day.cost),
+                day.cost / average
+            );
+        }
+    }
+    output
+}
+
+fn day_totals(daily: &[DailyUsage]) -> Vec<DayTotal> {
+    let mut days: BTreeMap<&str, (u64, f64)> = BTreeMap::new();
+    for entry in daily {
+        let day = days.entry(entry.date.as_str()).or_default();
+        day.0 += entry.total_tokens;
+        day.1 += entry.total_cost;
+    }
+    days.into_iter()
+        .map(|(date, (tokens, cost))| DayTotal {
+            date: date.to_string(),
+            tokens,
+            cost,
+        })
+        .collect()
+}
+
+fn top_models_by_cost(daily: &[DailyUsage]) -> Vec<(String, f64)> {
+    let mut costs: BTreeMap<&str, f64> = BTreeMap::new();
+    for breakdown in daily.iter().flat_map(|entry| &entry.model_breakdowns) {
+        *costs.entry(breakdown.model_name.as_str()).or_default() += breakdown.cost;
+    }
+    let mut models = costs
+        .into_iter()
+        .map(|(model, cost)| (model.to_string(), cost))
+        .collect::<Vec<_>>();
+    models.sort_by(|a, b| b.1.total_cmp(&a.1));
+    models
+}
+
+/// Days whose cost is more than [`SPIKE_STDDEVS`] standard deviations above the mean.
+fn spikes(days: &[DayTotal]) -> Vec<&DayTotal> {
+    if days.len() < MIN_DAYS_FOR_SPIKES {
+        return Vec::new();
+    }
+    let count = days.len() as f64;
+    let mean = days.iter().map(|day| day.cost).sum::<f64>() / count;
+    let variance = days
+        .iter()
+        .map(|day| (day.cost - mean).powi(2))
+        .sum::<f64>()
+        / count;
+    let threshold = mean + SPIKE_STDDEVS * variance.sqrt();
+    days.iter()
+        .filter(|day| day.cost > threshold && day.cost > mean)
+        .collect()
+}
+
+fn share(cost: f64, total: f64) -> String {
+    if total > 0.0 {
+        format!("{:.0}%", cost / total * 100.0)
+    } else {
+        "0%".to_string()
+    }
+}
+
+fn plural<'a>(count: usize, one: &'a str, many: &'a str) -> &'a str {
+    if count == 1 { one } else { many }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_loader::ModelBreakdown;
+
+    fn day(date: &str, project: &str, model: &str, cost: f64) -> DailyUsage {
+        DailyUsage {
+            date: date.to_string(),
+            project: Some(project.to_string()),
+            total_tokens: 1_000,
+            total_cost: cost,
+            model_breakdowns: vec![ModelBreakdown {
+                model_name: model.to_string(),
+                input_tokens: 0,
+                output_tokens: 0,
+                cache_creation_tokens: 0,
+                cache_read_tokens: 0,
+                total_tokens: 1_000,
+                cost,
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn recap_lists_totals_projects_models_days_and_spikes() {
+        let mut daily = (1..=9)
+            .map(|n| {
+                day(
+                    &format!("2026-05-0{n}"),
+                    "app",
+                    "claude-sonnet-4-20250514",
+                    1.0,
+                )
+            })
+            .collect::<Vec<_>>();
+        daily.push(day("2026-05-10", "api", "claude-opus-4-20250514", 20.0));
+        daily.push(day("2026-05-10", "app", "claude-sonnet-4-20250514", 1.0));
+
+        assert_eq!(
+            recap_markdown("2026-05", &daily),
+            "# Usage recap: 2026-05\n\n\
+             Spent **$30.00** on **11K** tokens across 10 active days and 2 projects, \
+             averaging $3.00 per active day.\n\n\
+             ## Top projects\n\n\
+             - **api**: $20.00 (67%)\n\
+             - **app**: $10.00 (33%)\n\n\
+             ## Top models\n\n\
+             - **opus-4**: $20.00 (67%)\n\
+             - **sonnet-4**: $10.00 (33%)\n\n\
+             ## Busiest days\n\n\
+             - 2026-05-10: $21.00 (2K tokens)\n\
+             - 2026-05-01: $1.00 (1K tokens)\n\
+             - 2026-05-02: $1.00 (1K tokens)\n\n\
+             ## Notable spikes\n\n\
+             - 2026-05-10: $21.00, 7.0x the daily average\n"
+        );
+    }
+
+    #[test]
+    fn recap_handles_empty_months_and_skips_spikes_for_few_days() {
+        assert_eq!(
+            recap_markdown("2026-05", &[]),
+            "# Usage recap: 2026-05\n\nNo usage was recorded this month.\n"
+        );
+        let daily = vec![
+            day("2026-05-01", "app", "m", 1.0),
+            day("2026-05-02", "app", "m", 50.0),
+        ];
+        assert!(!recap_markdown("2026-05", &daily).contains("Notable spikes"));
+    }
+}
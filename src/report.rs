@@ -0,0 +1,210 @@
+//! Builder for loading usage reports from Rust code instead of parsing the CLI's JSON.
+//!
+//! ```no_run
+//! use ccost::report::UsageReport;
+//! use chrono::NaiveDate;
+//!
+//! let report = UsageReport::new()
+//!     .claude_path("/home/me/.claude")
+//!     .since(NaiveDate::from_ymd_opt(2026, 1, 1).unwrap())
+//!     .timezone(chrono_tz::Europe::Berlin);
+//! for day in report.daily()? {
+//!     println!("{} ${:.2}", day.date, day.total_cost);
+//! }
+//! println!("total ${:.2}", report.totals()?.total_cost);
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+
+pub use crate::data_loader::{
+    DailyUsage, HourlyUsage, ModelBreakdown, MonthlyUsage, UsageEntry, UsageSource, UsageTotals,
+};
+pub use crate::model_filter::ModelFilter;
+pub use crate::pricing::CostMode;
+pub use crate::projects::ProjectUsage;
+pub use crate::sessions::SessionUsage;
+pub use crate::time_utils::SortOrder;
+
+use crate::data_loader::{
+    LoadOptions, calculate_totals_entries, group_entries_by_hour, load_daily_usage_data,
+    load_monthly_usage_data, load_usage_entries,
+};
+use crate::projects::group_entries_by_project;
+use crate::sessions::group_entries_by_session;
+use anyhow::Result;
+use chrono::NaiveDate;
+use chrono_tz::Tz;
+use std::path::PathBuf;
+
+/// What to load and how to price it; each report method reads the logs again.
+///
+/// Defaults match `ccost` with no flags, except that only Claude Code logs are read until
+/// [`Self::sources`] says otherwise. Prices come from the bundled dataset.
+#[derive(Debug, Clone, Default)]
+pub struct UsageReport {
+    options: LoadOptions,
+}
+
+impl UsageReport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Scans this Claude data directory (the one containing `projects/`) instead of the
+    /// default locations; call again to add more.
+    pub fn claude_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.options.claude_paths.push(path.into());
+        self
+    }
+
+    pub fn codex_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.options.codex_path = Some(path.into());
+        self
+    }
+
+    pub fn opencode_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.options.opencode_path = Some(path.into());
+        self
+    }
+
+    /// Tools whose logs are read.
+    pub fn sources(mut self, sources: &[UsageSource]) -> Self {
+        self.options.claudecode = sources.contains(&UsageSource::ClaudeCode);
+        self.options.codex = sources.contains(&UsageSource::Codex);
+        self.options.opencode = sources.contains(&UsageSource::OpenCode);
+        self
+    }
+
+    /// First day to include, in [`Self::timezone`].
+    pub fn since(mut self, date: NaiveDate) -> Self {
+        self.options.since = Some(date.format("%Y%m%d").to_string());
+        self
+    }
+
+    /// Last day to include, in [`Self::timezone`].
+    pub fn until(mut self, date: NaiveDate) -> Self {
+        self.options.until = Some(date.format("%Y%m%d").to_string());
+        self
+    }
+
+    /// Timezone that assigns entries to days; the system timezone when unset.
+    pub fn timezone(mut self, timezone: Tz) -> Self {
+        self.options.timezone = Some(timezone.name().to_string());
+        self
+    }
+
+    /// Keeps only this Claude Code project, named as in `ccost daily --instances`.
+    pub fn project(mut self, project: impl Into<String>) -> Self {
+        self.options.project = Some(project.into());
+        self
+    }
+
+    /// Splits daily and monthly rows per project.
+    pub fn group_by_project(mut self, group: bool) -> Self {
+        self.options.group_by_project = group;
+        self
+    }
+
+    pub fn models(mut self, filter: ModelFilter) -> Self {
+        self.options.models = filter;
+        self
+    }
+
+    pub fn cost_mode(mut self, mode: CostMode) -> Self {
+        self.options.mode = mode;
+        self
+    }
+
+    pub fn order(mut self, order: SortOrder) -> Self {
+        self.options.order = order;
+        self
+    }
+
+    /// Keeps parsed Claude logs in this SQLite file between runs, as the CLI does; nothing is
+    /// cached by default.
+    pub fn cache_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.options.cache_path = Some(path.into());
+        self
+    }
+
+    /// Deduplicated per-request entries.
+    pub fn entries(&self) -> Result<Vec<UsageEntry>> {
+        load_usage_entries(&self.options)
+    }
+
+    pub fn daily(&self) -> Result<Vec<DailyUsage>> {
+        load_daily_usage_data(self.options.clone())
+    }
+
+    pub fn monthly(&self) -> Result<Vec<MonthlyUsage>> {
+        load_monthly_usage_data(self.options.clone())
+    }
+
+    /// Hours with usage, oldest first.
+    pub fn hourly(&self) -> Result<Vec<HourlyUsage>> {
+        Ok(group_entries_by_hour(&self.entries()?))
+    }
+
+    pub fn sessions(&self) -> Result<Vec<SessionUsage>> {
+        Ok(group_entries_by_session(&self.entries()?))
+    }
+
+    /// Projects, most expensive first.
+    pub fn projects(&self) -> Result<Vec<ProjectUsage>> {
+        Ok(group_entries_by_project(&self.entries()?))
+    }
+
+    pub fn totals(&self) -> Result<UsageTotals> {
+        Ok(calculate_totals_entries(&self.entries()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn builder_loads_reports_from_a_claude_directory() {
+        let dir = TempDir::new().unwrap();
+        let log = dir.path().join("projects/-home-me-app/session-1.jsonl");
+        std::fs::create_dir_all(log.parent().unwrap()).unwrap();
+        let line = |timestamp: &str, id: &str, model: &str| {
+            format!(
+                r#"{{"timestamp":"{timestamp}","sessionId":"session-1","requestId":"{id}","message":{{"id":"{id}","model":"{model}","usage":{{"input_tokens":100,"output_tokens":50}}}},"costUSD":0.5}}"#
+            )
+        };
+        std::fs::write(
+            &log,
+            [
+                line("2026-01-01T10:00:00Z", "a", "claude-sonnet-4-20250514"),
+                line("2026-01-02T10:00:00Z", "b", "claude-opus-4-20250514"),
+                line("2026-01-03T10:00:00Z", "c", "claude-opus-4-20250514"),
+            ]
+            .join("\n"),
+        )
+        .unwrap();
+
+        let report = UsageReport::new()
+            .claude_path(dir.path())
+            .timezone(chrono_tz::UTC)
+            .since(NaiveDate::from_ymd_opt(2026, 1, 2).unwrap())
+            .order(SortOrder::Asc);
+        let daily = report.daily().unwrap();
+        assert_eq!(
+            daily
+                .iter()
+                .map(|day| day.date.as_str())
+                .collect::<Vec<_>>(),
+            vec!["2026-01-02", "2026-01-03"]
+        );
+        assert_eq!(report.totals().unwrap().total_cost, 1.0);
+        assert_eq!(report.sessions().unwrap().len(), 1);
+        assert_eq!(report.projects().unwrap()[0].request_count, 2);
+
+        let sonnet = report
+            .clone()
+            .models(ModelFilter::new(&["sonnet"], &[]).unwrap());
+        assert!(sonnet.daily().unwrap().is_empty());
+        assert_eq!(report.monthly().unwrap()[0].total_cost, 1.0);
+    }
+}
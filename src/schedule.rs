@@ -0,0 +1,116 @@
+use crate::config::ScheduleEntry;
+use anyhow::{Result, anyhow};
+use chrono::{Datelike, Duration, NaiveDateTime, NaiveTime};
+
+/// Longest gap between two runs of a valid entry (a day-31 entry skips short months).
+const MAX_SEARCH_DAYS: i64 = 62;
+
+/// A validated `[[schedule]]` entry: run `command` at `at` local time, every day or only on
+/// day `day` of the month.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScheduledReport {
+    pub at: NaiveTime,
+    pub day: Option<u32>,
+    pub command: Vec<String>,
+}
+
+impl ScheduledReport {
+    pub fn from_entry(entry: &ScheduleEntry) -> Result<Self> {
+        let at = NaiveTime::parse_from_str(entry.at.trim(), "%H:%M")
+            .map_err(|_| anyhow!("Invalid schedule time: {} (expected HH:MM)", entry.at))?;
+        if let Some(day) = entry.day
+            && !(1..=31).contains(&day)
+        {
+            return Err(anyhow!("Invalid schedule day: {day} (expected 1-31)"));
+        }
+        if entry.command.is_empty() {
+            return Err(anyhow!("Schedule at {} has an empty command", entry.at));
+        }
+        Ok(Self {
+            at,
+            day: entry.day,
+            command: entry.command.clone(),
+        })
+    }
+
+    /// First run strictly after `after`.
+    pub fn next_run(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        (0..=MAX_SEARCH_DAYS)
+            .filter_map(|offset| after.date().checked_add_signed(Duration::days(offset)))
+            .filter(|date| self.day.is_none_or(|day| date.day() == day))
+            .map(|date| date.and_time(self.at))
+            .find(|candidate| *candidate > after)
+    }
+
+    pub fn describe(&self) -> String {
+        let when = match self.day {
+            Some(day) => format!("day {day} of each month at {}", self.at.format("%H:%M")),
+            None => format!("daily at {}", self.at.format("%H:%M")),
+        };
+        format!("{when}: ccost {}", self.command.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn entry(at: &str, day: Option<u32>) -> ScheduleEntry {
+        ScheduleEntry {
+            at: at.to_string(),
+            day,
+            command: vec!["summary".to_string()],
+        }
+    }
+
+    fn datetime(date: (i32, u32, u32), time: (u32, u32)) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(date.0, date.1, date.2)
+            .unwrap()
+            .and_hms_opt(time.0, time.1, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn daily_schedule_runs_today_or_tomorrow() {
+        let report = ScheduledReport::from_entry(&entry("18:00", None)).unwrap();
+        assert_eq!(
+            report.next_run(datetime((2026, 1, 15), (9, 0))),
+            Some(datetime((2026, 1, 15), (18, 0)))
+        );
+        assert_eq!(
+            report.next_run(datetime((2026, 1, 15), (18, 0))),
+            Some(datetime((2026, 1, 16), (18, 0)))
+        );
+    }
+
+    #[test]
+    fn monthly_schedule_skips_months_without_the_day() {
+        let first = ScheduledReport::from_entry(&entry("09:00", Some(1))).unwrap();
+        assert_eq!(
+            first.next_run(datetime((2026, 1, 15), (9, 0))),
+            Some(datetime((2026, 2, 1), (9, 0)))
+        );
+
+        let last = ScheduledReport::from_entry(&entry("09:00", Some(31))).unwrap();
+        assert_eq!(
+            last.next_run(datetime((2026, 1, 31), (10, 0))),
+            Some(datetime((2026, 3, 31), (9, 0)))
+        );
+    }
+
+    #[test]
+    fn from_entry_validates_time_day_and_command() {
+        assert!(ScheduledReport::from_entry(&entry("6pm", None)).is_err());
+        assert!(ScheduledReport::from_entry(&entry("18:00", Some(0))).is_err());
+        let mut empty = entry("18:00", None);
+        empty.command.clear();
+        assert!(ScheduledReport::from_entry(&empty).is_err());
+        assert_eq!(
+            ScheduledReport::from_entry(&entry("9:05", Some(1)))
+                .unwrap()
+                .describe(),
+            "day 1 of each month at 09:05: ccost summary"
+        );
+    }
+}
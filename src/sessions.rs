@@ -0,0 +1,493 @@
+use crate::data_loader::UsageEntry;
+use chrono::{DateTime, Duration, Utc};
+use std::collections::{HashMap, HashSet};
+
+const UNKNOWN_SESSION: &str = "unknown";
+
+#[derive(Debug, Clone)]
+pub struct SessionUsage {
+    pub session_id: String,
+    /// Human-readable title from the session's summary or first prompt, filled in by the
+    /// caller.
+    pub title: Option<String>,
+    pub project: Option<String>,
+    pub first_timestamp: DateTime<Utc>,
+    pub last_timestamp: DateTime<Utc>,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub total_tokens: u64,
+    pub total_cost: f64,
+    /// Portion of `total_cost` spent in subagent (Task tool) sidechains.
+    pub subagent_cost: f64,
+    pub tool_calls: u64,
+    pub models_used: Vec<String>,
+    pub last_model: Option<String>,
+    pub entry_count: usize,
+}
+
+impl SessionUsage {
+    fn new(session_id: String, entry: &UsageEntry) -> Self {
+        Self {
+            session_id,
+            title: None,
+            project: entry.project.clone(),
+            first_timestamp: entry.timestamp,
+            last_timestamp: entry.timestamp,
+            input_tokens: 0,
+            output_tokens: 0,
+            cache_creation_tokens: 0,
+            cache_read_tokens: 0,
+            total_tokens: 0,
+            total_cost: 0.0,
+            subagent_cost: 0.0,
+            tool_calls: 0,
+            models_used: Vec::new(),
+            last_model: None,
+            entry_count: 0,
+        }
+    }
+
+    fn add_entry(&mut self, entry: &UsageEntry) {
+        if entry.timestamp >= self.last_timestamp && entry.model.is_some() {
+            self.last_model = entry.model.clone();
+        }
+        self.first_timestamp = self.first_timestamp.min(entry.timestamp);
+        self.last_timestamp = self.last_timestamp.max(entry.timestamp);
+        if self.project.is_none() {
+            self.project = entry.project.clone();
+        }
+        self.input_tokens += entry.input_tokens;
+        self.output_tokens += entry.output_tokens;
+        self.cache_creation_tokens += entry.cache_creation_tokens;
+        self.cache_read_tokens += entry.cache_read_tokens;
+        self.total_tokens += entry.total_tokens;
+        self.total_cost += entry.cost;
+        if entry.is_sidechain {
+            self.subagent_cost += entry.cost;
+        }
+        self.tool_calls += entry.tool_calls;
+        if let Some(model) = entry.model.as_deref()
+            && model != "<synthetic>"
+            && !self.models_used.iter().any(|used| used == model)
+        {
+            self.models_used.push(model.to_string());
+        }
+        self.entry_count += 1;
+    }
+
+    /// Wall-clock time between the first and last entry of the session.
+    pub fn duration(&self) -> Duration {
+        self.last_timestamp - self.first_timestamp
+    }
+
+    pub fn cost_per_hour(&self) -> Option<f64> {
+        self.hours().map(|hours| self.total_cost / hours)
+    }
+
+    pub fn tokens_per_hour(&self) -> Option<f64> {
+        self.hours().map(|hours| self.total_tokens as f64 / hours)
+    }
+
+    fn hours(&self) -> Option<f64> {
+        let seconds = self.duration().num_seconds();
+        (seconds > 0).then(|| seconds as f64 / 3600.0)
+    }
+}
+
+/// Groups entries by session id, ordered by each session's last activity (oldest first).
+pub fn group_entries_by_session(entries: &[UsageEntry]) -> Vec<SessionUsage> {
+    let mut sessions: HashMap<&str, SessionUsage> = HashMap::new();
+    for entry in entries {
+        let session_id = entry.session_id.as_deref().unwrap_or(UNKNOWN_SESSION);
+        sessions
+            .entry(session_id)
+            .or_insert_with(|| SessionUsage::new(session_id.to_string(), entry))
+            .add_entry(entry);
+    }
+
+    let mut sessions = sessions.into_values().collect::<Vec<_>>();
+    sessions.sort_by(|a, b| {
+        a.last_timestamp
+            .cmp(&b.last_timestamp)
+            .then_with(|| a.session_id.cmp(&b.session_id))
+    });
+    sessions
+}
+
+/// Keeps sessions whose most recent entry falls within `window` before `now`.
+pub fn active_sessions(
+    sessions: Vec<SessionUsage>,
+    now: DateTime<Utc>,
+    window: Duration,
+) -> Vec<SessionUsage> {
+    let cutoff = now - window;
+    sessions
+        .into_iter()
+        .filter(|session| session.last_timestamp >= cutoff)
+        .collect()
+}
+
+/// Switches from one model to another within sessions, with the cost of the contiguous runs
+/// on either side of each switch.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelSwitchSummary {
+    pub from_model: String,
+    pub to_model: String,
+    pub switches: usize,
+    pub sessions: usize,
+    pub cost_before: f64,
+    pub messages_before: u64,
+    pub cost_after: f64,
+    pub messages_after: u64,
+}
+
+impl ModelSwitchSummary {
+    pub fn cost_per_message_before(&self) -> Option<f64> {
+        (self.messages_before > 0).then(|| self.cost_before / self.messages_before as f64)
+    }
+
+    pub fn cost_per_message_after(&self) -> Option<f64> {
+        (self.messages_after > 0).then(|| self.cost_after / self.messages_after as f64)
+    }
+}
+
+struct ModelRun<'a> {
+    model: &'a str,
+    cost: f64,
+    messages: u64,
+}
+
+/// Detects model switches on each session's main thread. Subagent sidechains are ignored
+/// since they routinely run on a different model than the conversation that spawned them.
+pub fn summarize_model_switches(entries: &[UsageEntry]) -> Vec<ModelSwitchSummary> {
+    let mut by_session: HashMap<&str, Vec<&UsageEntry>> = HashMap::new();
+    for entry in entries {
+        if entry.is_sidechain
+            || entry
+                .model
+                .as_deref()
+                .is_none_or(|model| model == "<synthetic>")
+        {
+            continue;
+        }
+        let session_id = entry.session_id.as_deref().unwrap_or(UNKNOWN_SESSION);
+        by_session.entry(session_id).or_default().push(entry);
+    }
+
+    let mut summaries: HashMap<(&str, &str), (ModelSwitchSummary, HashSet<&str>)> = HashMap::new();
+    for (session_id, mut session_entries) in by_session {
+        session_entries.sort_by_key(|entry| entry.timestamp);
+        let mut runs: Vec<ModelRun> = Vec::new();
+        for entry in session_entries {
+            let model = entry.model.as_deref().unwrap_or_default();
+            match runs.last_mut() {
+                Some(run) if run.model == model => {
+                    run.cost += entry.cost;
+                    run.messages += 1;
+                }
+                _ => runs.push(ModelRun {
+                    model,
+                    cost: entry.cost,
+                    messages: 1,
+                }),
+            }
+        }
+
+        for pair in runs.windows(2) {
+            let (before, after) = (&pair[0], &pair[1]);
+            let (summary, sessions) =
+                summaries
+                    .entry((before.model, after.model))
+                    .or_insert_with(|| {
+                        (
+                            ModelSwitchSummary {
+                                from_model: before.model.to_string(),
+                                to_model: after.model.to_string(),
+                                switches: 0,
+                                sessions: 0,
+                                cost_before: 0.0,
+                                messages_before: 0,
+                                cost_after: 0.0,
+                                messages_after: 0,
+                            },
+                            HashSet::new(),
+                        )
+                    });
+            summary.switches += 1;
+            summary.cost_before += before.cost;
+            summary.messages_before += before.messages;
+            summary.cost_after += after.cost;
+            summary.messages_after += after.messages;
+            sessions.insert(session_id);
+        }
+    }
+
+    let mut summaries = summaries
+        .into_values()
+        .map(|(mut summary, sessions)| {
+            summary.sessions = sessions.len();
+            summary
+        })
+        .collect::<Vec<_>>();
+    summaries.sort_by(|a, b| {
+        b.switches
+            .cmp(&a.switches)
+            .then_with(|| a.from_model.cmp(&b.from_model))
+            .then_with(|| a.to_model.cmp(&b.to_model))
+    });
+    summaries
+}
+
+/// How well a session's prompt-cache writes were reused.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CacheEfficiency {
+    pub session_id: String,
+    pub project: Option<String>,
+    pub first_timestamp: DateTime<Utc>,
+    pub cache_creation_tokens: u64,
+    pub cache_read_tokens: u64,
+    /// Requests that wrote to the cache.
+    pub writes: u64,
+    /// Writes not followed by a cache read on the same thread before the cache expired.
+    pub unread_writes: u64,
+    pub wasted_creation_tokens: u64,
+    /// Cache-write cost of the wasted tokens, when the model's price is known.
+    pub wasted_cost: f64,
+}
+
+impl CacheEfficiency {
+    /// Cache-read tokens per cache-write token; `None` when nothing was written.
+    pub fn read_ratio(&self) -> Option<f64> {
+        (self.cache_creation_tokens > 0)
+            .then(|| self.cache_read_tokens as f64 / self.cache_creation_tokens as f64)
+    }
+}
+
+/// Estimates wasted cache writes per session. A write counts as wasted when the next request on
+/// the same thread (main conversation or subagent sidechain) reads nothing from the cache or
+/// comes more than `ttl` later, after the cached prefix expired. `write_price` returns the
+/// per-token cache write price of a model.
+pub fn analyze_cache_efficiency(
+    entries: &[UsageEntry],
+    ttl: Duration,
+    write_price: impl Fn(&str) -> Option<f64>,
+) -> Vec<CacheEfficiency> {
+    let mut threads: HashMap<(&str, bool), Vec<&UsageEntry>> = HashMap::new();
+    for entry in entries {
+        let session_id = entry.session_id.as_deref().unwrap_or(UNKNOWN_SESSION);
+        threads
+            .entry((session_id, entry.is_sidechain))
+            .or_default()
+            .push(entry);
+    }
+
+    let mut sessions: HashMap<&str, CacheEfficiency> = HashMap::new();
+    for ((session_id, _), mut thread) in threads {
+        thread.sort_by_key(|entry| entry.timestamp);
+        let session = sessions
+            .entry(session_id)
+            .or_insert_with(|| CacheEfficiency {
+                session_id: session_id.to_string(),
+                project: thread[0].project.clone(),
+                first_timestamp: thread[0].timestamp,
+                cache_creation_tokens: 0,
+                cache_read_tokens: 0,
+                writes: 0,
+                unread_writes: 0,
+                wasted_creation_tokens: 0,
+                wasted_cost: 0.0,
+            });
+        session.first_timestamp = session.first_timestamp.min(thread[0].timestamp);
+        for (index, entry) in thread.iter().enumerate() {
+            session.cache_creation_tokens += entry.cache_creation_tokens;
+            session.cache_read_tokens += entry.cache_read_tokens;
+            if entry.cache_creation_tokens == 0 {
+                continue;
+            }
+            session.writes += 1;
+            let reused = thread.get(index + 1).is_some_and(|next| {
+                next.cache_read_tokens > 0 && next.timestamp - entry.timestamp <= ttl
+            });
+            if !reused {
+                session.unread_writes += 1;
+                session.wasted_creation_tokens += entry.cache_creation_tokens;
+                let price = entry.model.as_deref().and_then(&write_price).unwrap_or(0.0);
+                session.wasted_cost += entry.cache_creation_tokens as f64 * price;
+            }
+        }
+    }
+
+    let mut sessions = sessions
+        .into_values()
+        .filter(|session| session.writes > 0)
+        .collect::<Vec<_>>();
+    sessions.sort_by(|a, b| {
+        b.wasted_creation_tokens
+            .cmp(&a.wasted_creation_tokens)
+            .then_with(|| a.session_id.cmp(&b.session_id))
+    });
+    sessions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(session: &str, timestamp: &str, total_tokens: u64, cost: f64) -> UsageEntry {
+        UsageEntry {
+            timestamp: DateTime::parse_from_rfc3339(timestamp)
+                .unwrap()
+                .with_timezone(&Utc),
+            date: timestamp[..10].to_string(),
+            session_id: Some(session.to_string()),
+            project: Some("project1".to_string()),
+            model: Some("claude-sonnet-4-20250514".to_string()),
+            input_tokens: total_tokens,
+            total_tokens,
+            cost,
+            local_hour: timestamp[11..13].parse().unwrap(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn group_entries_by_session_computes_duration_and_rates() {
+        let entries = vec![
+            entry("a", "2025-01-10T10:00:00Z", 1_000, 1.0),
+            entry("b", "2025-01-10T09:00:00Z", 500, 0.5),
+            entry("a", "2025-01-10T12:00:00Z", 3_000, 3.0),
+        ];
+
+        let sessions = group_entries_by_session(&entries);
+
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0].session_id, "b");
+        assert_eq!(sessions[1].session_id, "a");
+        assert_eq!(sessions[1].entry_count, 2);
+        assert_eq!(sessions[1].duration(), Duration::hours(2));
+        assert_eq!(sessions[1].cost_per_hour(), Some(2.0));
+        assert_eq!(sessions[1].tokens_per_hour(), Some(2_000.0));
+    }
+
+    #[test]
+    fn analyze_cache_efficiency_counts_unread_and_expired_writes() {
+        let cache = |timestamp: &str, created: u64, read: u64| {
+            let mut entry = entry("a", timestamp, created + read, 0.1);
+            entry.cache_creation_tokens = created;
+            entry.cache_read_tokens = read;
+            entry
+        };
+        let mut sidechain = cache("2025-01-10T10:01:00Z", 400, 0);
+        sidechain.is_sidechain = true;
+        let entries = vec![
+            cache("2025-01-10T10:00:00Z", 1_000, 0),
+            // Read within the TTL: the first write was reused.
+            cache("2025-01-10T10:02:00Z", 200, 1_000),
+            // Next request comes after the cache expired: the 200-token write was wasted.
+            cache("2025-01-10T10:30:00Z", 300, 0),
+            // Last request of the thread: nothing ever reads this write.
+            sidechain,
+            cache("2025-01-10T09:00:00Z", 0, 0),
+        ];
+
+        let sessions = analyze_cache_efficiency(&entries, Duration::minutes(5), |_| Some(0.001));
+
+        assert_eq!(sessions.len(), 1);
+        let session = &sessions[0];
+        assert_eq!(session.writes, 4);
+        assert_eq!(session.unread_writes, 3);
+        assert_eq!(session.cache_creation_tokens, 1_900);
+        assert_eq!(session.cache_read_tokens, 1_000);
+        assert_eq!(session.wasted_creation_tokens, 900);
+        assert!((session.wasted_cost - 0.9).abs() < 1e-9);
+        assert_eq!(session.read_ratio(), Some(1_000.0 / 1_900.0));
+        assert_eq!(
+            session.first_timestamp,
+            DateTime::parse_from_rfc3339("2025-01-10T09:00:00Z").unwrap()
+        );
+    }
+
+    #[test]
+    fn group_entries_by_session_tracks_subagent_cost() {
+        let mut subagent = entry("a", "2025-01-10T10:05:00Z", 100, 0.25);
+        subagent.is_sidechain = true;
+        let entries = vec![entry("a", "2025-01-10T10:00:00Z", 100, 1.0), subagent];
+
+        let sessions = group_entries_by_session(&entries);
+
+        assert_eq!(sessions[0].total_cost, 1.25);
+        assert_eq!(sessions[0].subagent_cost, 0.25);
+    }
+
+    #[test]
+    fn active_sessions_keeps_recent_sessions_with_current_model() {
+        let mut latest = entry("a", "2025-01-10T11:50:00Z", 10, 1.0);
+        latest.model = Some("claude-opus-4-20250514".to_string());
+        let entries = vec![
+            entry("a", "2025-01-10T10:00:00Z", 10, 1.0),
+            latest,
+            entry("b", "2025-01-10T11:00:00Z", 10, 1.0),
+        ];
+        let now = DateTime::parse_from_rfc3339("2025-01-10T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        let active = active_sessions(
+            group_entries_by_session(&entries),
+            now,
+            Duration::minutes(30),
+        );
+
+        assert_eq!(active.len(), 1);
+        assert_eq!(active[0].session_id, "a");
+        assert_eq!(active[0].total_cost, 2.0);
+        assert_eq!(
+            active[0].last_model.as_deref(),
+            Some("claude-opus-4-20250514")
+        );
+    }
+
+    #[test]
+    fn summarize_model_switches_compares_runs_around_each_switch() {
+        let with_model = |session: &str, timestamp: &str, model: &str, cost: f64| {
+            let mut entry = entry(session, timestamp, 10, cost);
+            entry.model = Some(model.to_string());
+            entry
+        };
+        let mut subagent = with_model("a", "2025-01-10T10:02:00Z", "haiku", 0.1);
+        subagent.is_sidechain = true;
+        let entries = vec![
+            with_model("a", "2025-01-10T10:00:00Z", "opus", 4.0),
+            with_model("a", "2025-01-10T10:01:00Z", "opus", 4.0),
+            subagent,
+            with_model("a", "2025-01-10T10:03:00Z", "sonnet", 1.0),
+            with_model("b", "2025-01-10T11:00:00Z", "opus", 2.0),
+            with_model("b", "2025-01-10T11:01:00Z", "sonnet", 1.0),
+            with_model("b", "2025-01-10T11:02:00Z", "sonnet", 1.0),
+            with_model("c", "2025-01-10T12:00:00Z", "sonnet", 1.0),
+        ];
+
+        let summaries = summarize_model_switches(&entries);
+
+        assert_eq!(summaries.len(), 1);
+        let summary = &summaries[0];
+        assert_eq!(summary.from_model, "opus");
+        assert_eq!(summary.to_model, "sonnet");
+        assert_eq!(summary.switches, 2);
+        assert_eq!(summary.sessions, 2);
+        assert_eq!(summary.cost_before, 10.0);
+        assert_eq!(summary.cost_per_message_before(), Some(10.0 / 3.0));
+        assert_eq!(summary.cost_after, 3.0);
+        assert_eq!(summary.cost_per_message_after(), Some(1.0));
+    }
+
+    #[test]
+    fn single_entry_sessions_have_no_hourly_rate() {
+        let sessions = group_entries_by_session(&[entry("a", "2025-01-10T10:00:00Z", 10, 1.0)]);
+
+        assert_eq!(sessions[0].duration(), Duration::zero());
+        assert_eq!(sessions[0].cost_per_hour(), None);
+    }
+}
@@ -0,0 +1,224 @@
+use crate::data_loader::DailyUsage;
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Costs closer than this are treated as unchanged, absorbing float noise from re-aggregation.
+const COST_EPSILON: f64 = 1e-9;
+
+/// Saved daily aggregates used by `ccost drift` to spot retroactive changes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Snapshot {
+    pub created: DateTime<Utc>,
+    /// Local date the snapshot was taken; this day and later ones were still in progress, so
+    /// only earlier days are compared.
+    pub as_of: String,
+    pub days: Vec<SnapshotDay>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotDay {
+    pub date: String,
+    pub project: Option<String>,
+    pub total_tokens: u64,
+    pub total_cost: f64,
+    pub message_count: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DriftKind {
+    /// The day is in the snapshot but no longer in the data (e.g. deleted logs).
+    Removed,
+    /// The day's totals differ from the snapshot (e.g. a re-synced session).
+    Changed,
+    /// The day predates the snapshot but wasn't in it (e.g. restored or imported logs).
+    Added,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Drift {
+    pub kind: DriftKind,
+    pub date: String,
+    pub project: Option<String>,
+    pub before: Option<SnapshotDay>,
+    pub after: Option<SnapshotDay>,
+}
+
+impl Snapshot {
+    pub fn from_daily(daily: &[DailyUsage], created: DateTime<Utc>, as_of: String) -> Self {
+        let mut days = daily
+            .iter()
+            .map(|entry| SnapshotDay {
+                date: entry.date.clone(),
+                project: entry.project.clone(),
+                total_tokens: entry.total_tokens,
+                total_cost: entry.total_cost,
+                message_count: entry.message_count,
+            })
+            .collect::<Vec<_>>();
+        days.sort_by(|a, b| (&a.date, &a.project).cmp(&(&b.date, &b.project)));
+        Self {
+            created,
+            as_of,
+            days,
+        }
+    }
+
+    /// File name that sorts snapshots chronologically.
+    pub fn file_name(&self) -> String {
+        format!("{}.json", self.created.format("%Y%m%dT%H%M%SZ"))
+    }
+}
+
+/// Days before `snapshot.as_of` whose totals in `live` differ from the snapshot.
+pub fn detect_drift(snapshot: &Snapshot, live: &Snapshot) -> Vec<Drift> {
+    let key = |day: &SnapshotDay| (day.date.clone(), day.project.clone());
+    let settled = |day: &&SnapshotDay| day.date < snapshot.as_of;
+    let before = snapshot
+        .days
+        .iter()
+        .filter(settled)
+        .map(|day| (key(day), day))
+        .collect::<BTreeMap<_, _>>();
+    let after = live
+        .days
+        .iter()
+        .filter(settled)
+        .map(|day| (key(day), day))
+        .collect::<BTreeMap<_, _>>();
+
+    let mut keys = before.keys().chain(after.keys()).collect::<Vec<_>>();
+    keys.sort();
+    keys.dedup();
+    keys.into_iter()
+        .filter_map(|key| {
+            let old = before.get(key).copied();
+            let new = after.get(key).copied();
+            let kind = match (old, new) {
+                (Some(_), None) => DriftKind::Removed,
+                (None, Some(_)) => DriftKind::Added,
+                (Some(old), Some(new))
+                    if old.total_tokens != new.total_tokens
+                        || (old.total_cost - new.total_cost).abs() > COST_EPSILON =>
+                {
+                    DriftKind::Changed
+                }
+                _ => return None,
+            };
+            Some(Drift {
+                kind,
+                date: key.0.clone(),
+                project: key.1.clone(),
+                before: old.cloned(),
+                after: new.cloned(),
+            })
+        })
+        .collect()
+}
+
+pub fn save_snapshot(dir: &Path, snapshot: &Snapshot) -> Result<PathBuf> {
+    std::fs::create_dir_all(dir)
+        .map_err(|error| anyhow!("failed to create {}: {error}", dir.display()))?;
+    let path = dir.join(snapshot.file_name());
+    std::fs::write(&path, serde_json::to_string_pretty(snapshot)?)
+        .map_err(|error| anyhow!("failed to write {}: {error}", path.display()))?;
+    Ok(path)
+}
+
+pub fn load_snapshot(path: &Path) -> Result<Snapshot> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|error| anyhow!("failed to read {}: {error}", path.display()))?;
+    serde_json::from_str(&content)
+        .map_err(|error| anyhow!("invalid snapshot {}: {error}", path.display()))
+}
+
+/// Most recent snapshot file in `dir`, if any.
+pub fn latest_snapshot_path(dir: &Path) -> Option<PathBuf> {
+    std::fs::read_dir(dir)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "json")
+        })
+        .max()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use tempfile::TempDir;
+
+    fn day(date: &str, project: &str, tokens: u64, cost: f64) -> SnapshotDay {
+        SnapshotDay {
+            date: date.to_string(),
+            project: Some(project.to_string()),
+            total_tokens: tokens,
+            total_cost: cost,
+            message_count: 1,
+        }
+    }
+
+    fn snapshot(days: Vec<SnapshotDay>) -> Snapshot {
+        Snapshot {
+            created: Utc.with_ymd_and_hms(2026, 1, 10, 9, 0, 0).unwrap(),
+            as_of: "2026-01-10".to_string(),
+            days,
+        }
+    }
+
+    #[test]
+    fn detect_drift_flags_removed_changed_and_added_days_before_as_of() {
+        let saved = snapshot(vec![
+            day("2026-01-01", "app", 100, 1.0),
+            day("2026-01-02", "app", 100, 1.0),
+            day("2026-01-03", "app", 100, 1.0),
+            day("2026-01-10", "app", 100, 1.0),
+        ]);
+        let live = snapshot(vec![
+            day("2026-01-01", "app", 100, 1.0),
+            day("2026-01-03", "app", 150, 1.5),
+            day("2026-01-05", "api", 10, 0.1),
+            day("2026-01-10", "app", 900, 9.0),
+            day("2026-01-11", "app", 100, 1.0),
+        ]);
+
+        let drift = detect_drift(&saved, &live);
+        assert_eq!(
+            drift
+                .iter()
+                .map(|d| (d.kind, d.date.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                (DriftKind::Removed, "2026-01-02"),
+                (DriftKind::Changed, "2026-01-03"),
+                (DriftKind::Added, "2026-01-05"),
+            ]
+        );
+        assert_eq!(drift[1].before.as_ref().unwrap().total_tokens, 100);
+        assert_eq!(drift[1].after.as_ref().unwrap().total_tokens, 150);
+        assert!(detect_drift(&saved, &saved).is_empty());
+    }
+
+    #[test]
+    fn snapshots_round_trip_and_latest_is_newest() {
+        let dir = TempDir::new().unwrap();
+        let older = snapshot(vec![day("2026-01-01", "app", 1, 0.5)]);
+        let mut newer = older.clone();
+        newer.created = Utc.with_ymd_and_hms(2026, 2, 1, 0, 0, 0).unwrap();
+
+        save_snapshot(dir.path(), &newer).unwrap();
+        save_snapshot(dir.path(), &older).unwrap();
+        let latest = latest_snapshot_path(dir.path()).unwrap();
+        assert_eq!(load_snapshot(&latest).unwrap(), newer);
+        assert!(latest_snapshot_path(&dir.path().join("missing")).is_none());
+    }
+}
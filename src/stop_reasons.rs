@@ -0,0 +1,162 @@
+use crate::data_loader::UsageEntry;
+use std::collections::BTreeMap;
+
+/// Stop reason shown for entries whose log doesn't record one.
+pub const UNKNOWN_STOP_REASON: &str = "unknown";
+
+/// Requests that ended for one reason, with the output they produced.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StopReasonCount {
+    pub reason: String,
+    pub request_count: u64,
+    pub output_tokens: u64,
+    pub total_cost: f64,
+}
+
+/// Stop reasons of one day or month.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StopReasonPeriod {
+    pub period: String,
+    /// Reasons by request count, most common first.
+    pub reasons: Vec<StopReasonCount>,
+}
+
+impl StopReasonPeriod {
+    pub fn request_count(&self) -> u64 {
+        self.reasons.iter().map(|reason| reason.request_count).sum()
+    }
+
+    /// Requests cut off by the output token limit.
+    pub fn max_tokens_count(&self) -> u64 {
+        self.reasons
+            .iter()
+            .filter(|reason| reason.reason == "max_tokens")
+            .map(|reason| reason.request_count)
+            .sum()
+    }
+
+    /// Fraction of the period's requests that ended for `reason`.
+    pub fn request_share(&self, reason: &StopReasonCount) -> Option<f64> {
+        let total = self.request_count();
+        (total > 0).then(|| reason.request_count as f64 / total as f64)
+    }
+}
+
+/// Requests, output tokens, and cost per stop reason per day, or per month when `monthly`,
+/// oldest first. `<synthetic>` entries are left out.
+pub fn stop_reason_counts(entries: &[UsageEntry], monthly: bool) -> Vec<StopReasonPeriod> {
+    let mut periods: BTreeMap<&str, BTreeMap<&str, StopReasonCount>> = BTreeMap::new();
+    for entry in entries {
+        if entry.model.as_deref() == Some("<synthetic>") {
+            continue;
+        }
+        let period = if monthly {
+            entry.date.get(..7).unwrap_or(&entry.date)
+        } else {
+            entry.date.as_str()
+        };
+        let reason = entry.stop_reason.as_deref().unwrap_or(UNKNOWN_STOP_REASON);
+        let count = periods
+            .entry(period)
+            .or_default()
+            .entry(reason)
+            .or_insert_with(|| StopReasonCount {
+                reason: reason.to_string(),
+                ..Default::default()
+            });
+        count.request_count += 1;
+        count.output_tokens += entry.output_tokens;
+        count.total_cost += entry.cost;
+    }
+    periods
+        .into_iter()
+        .map(|(period, reasons)| StopReasonPeriod {
+            period: period.to_string(),
+            reasons: sorted_reasons(reasons.into_values().collect()),
+        })
+        .collect()
+}
+
+/// All periods combined into one labeled `Total`.
+pub fn stop_reason_totals(periods: &[StopReasonPeriod]) -> StopReasonPeriod {
+    let mut reasons: BTreeMap<&str, StopReasonCount> = BTreeMap::new();
+    for count in periods.iter().flat_map(|period| &period.reasons) {
+        let total = reasons
+            .entry(count.reason.as_str())
+            .or_insert_with(|| StopReasonCount {
+                reason: count.reason.clone(),
+                ..Default::default()
+            });
+        total.request_count += count.request_count;
+        total.output_tokens += count.output_tokens;
+        total.total_cost += count.total_cost;
+    }
+    StopReasonPeriod {
+        period: "Total".to_string(),
+        reasons: sorted_reasons(reasons.into_values().collect()),
+    }
+}
+
+fn sorted_reasons(mut reasons: Vec<StopReasonCount>) -> Vec<StopReasonCount> {
+    reasons.sort_by(|a, b| {
+        b.request_count
+            .cmp(&a.request_count)
+            .then_with(|| a.reason.cmp(&b.reason))
+    });
+    reasons
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn entry(date: &str, reason: Option<&str>, output_tokens: u64) -> UsageEntry {
+        UsageEntry {
+            timestamp: Utc.with_ymd_and_hms(2026, 1, 2, 3, 4, 5).unwrap(),
+            date: date.to_string(),
+            model: Some("claude-sonnet-4".to_string()),
+            input_tokens: 10,
+            output_tokens,
+            total_tokens: 10 + output_tokens,
+            cost: 1.0,
+            local_hour: 3,
+            stop_reason: reason.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn stop_reason_counts_split_each_period_by_reason() {
+        let entries = vec![
+            entry("2026-01-01", Some("end_turn"), 100),
+            entry("2026-01-01", Some("max_tokens"), 8_192),
+            entry("2026-01-01", Some("max_tokens"), 8_192),
+            entry("2026-01-01", None, 5),
+            entry("2026-01-02", Some("refusal"), 20),
+        ];
+
+        let daily = stop_reason_counts(&entries, false);
+        assert_eq!(daily.len(), 2);
+        assert_eq!(
+            daily[0]
+                .reasons
+                .iter()
+                .map(|reason| (reason.reason.as_str(), reason.request_count))
+                .collect::<Vec<_>>(),
+            vec![("max_tokens", 2), ("end_turn", 1), (UNKNOWN_STOP_REASON, 1)]
+        );
+        assert_eq!(daily[0].reasons[0].output_tokens, 16_384);
+        assert_eq!(daily[0].max_tokens_count(), 2);
+        assert_eq!(daily[0].request_share(&daily[0].reasons[0]), Some(0.5));
+        assert_eq!(daily[1].max_tokens_count(), 0);
+
+        let monthly = stop_reason_counts(&entries, true);
+        assert_eq!(monthly.len(), 1);
+        assert_eq!(monthly[0].request_count(), 5);
+
+        let totals = stop_reason_totals(&daily);
+        assert_eq!(totals.request_count(), 5);
+        assert_eq!(totals.reasons[0].reason, "max_tokens");
+    }
+}
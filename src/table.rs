@@ -1,6 +1,8 @@
+use crate::time_utils::format_activity_span;
+use chrono::{DateTime, Utc};
 use num_format::{Locale, ToFormattedString};
 use regex::Regex;
-use std::sync::LazyLock;
+use std::sync::{LazyLock, OnceLock};
 
 static PI_MODEL_RE: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"^\[pi\] (.+)$").expect("valid pi model regex"));
@@ -21,6 +23,17 @@ pub struct UsageDataRow {
     pub total_tokens: u64,
     pub total_cost: f64,
     pub models_used: Vec<String>,
+    pub message_count: u64,
+    pub subagent_cost: f64,
+    pub tool_calls: u64,
+    pub api_errors: u64,
+    /// Distinct sessions in the row.
+    pub sessions: u64,
+    pub first_timestamp: Option<DateTime<Utc>>,
+    pub last_timestamp: Option<DateTime<Utc>>,
+    pub active_hours: u32,
+    /// Standard score of the row's cost within the report, when computed.
+    pub z_score: Option<f64>,
 }
 
 #[derive(Debug, Clone)]
@@ -46,6 +59,100 @@ pub enum TokenFormat {
     HumanReadable,
 }
 
+/// Optional columns appended after the standard usage columns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ExtraColumn {
+    /// Message count and average cost per message
+    Messages,
+    /// Cost split between the main thread and subagent (Task tool) sidechains
+    Subagents,
+    /// Tool invocation count and average cost per tool call
+    Tools,
+    /// Failed API requests (Claude Code placeholder entries) and their share of all requests
+    Errors,
+    /// Distinct sessions and average cost per session
+    Sessions,
+    /// Time of the first and last entry in the row
+    Activity,
+    /// Distinct hours with usage and average cost per active hour
+    Hours,
+    /// Standard deviations of the row's cost from the mean of all rows in the report
+    ZScore,
+}
+
+pub fn extra_column_headers(columns: &[ExtraColumn]) -> Vec<&'static str> {
+    columns
+        .iter()
+        .flat_map(|column| match column {
+            ExtraColumn::Messages => vec!["Messages", "Cost/Msg"],
+            ExtraColumn::Subagents => vec!["Main Cost", "Subagent Cost"],
+            ExtraColumn::Tools => vec!["Tool Calls", "Cost/Tool"],
+            ExtraColumn::Errors => vec!["API Errors", "Error Rate"],
+            ExtraColumn::Sessions => vec!["Sessions", "Cost/Session"],
+            ExtraColumn::Activity => vec!["First", "Last"],
+            ExtraColumn::Hours => vec!["Active Hours", "Cost/Active Hour"],
+            ExtraColumn::ZScore => vec!["Z-Score"],
+        })
+        .collect()
+}
+
+pub fn build_extra_cells(
+    data: &UsageDataRow,
+    columns: &[ExtraColumn],
+    timezone: Option<&str>,
+) -> Vec<String> {
+    columns
+        .iter()
+        .flat_map(|column| match column {
+            ExtraColumn::Messages => vec![
+                data.message_count.to_formatted_string(&Locale::en),
+                cost_per_unit(data.total_cost, data.message_count),
+            ],
+            ExtraColumn::Subagents => vec![
+                format_currency(data.total_cost - data.subagent_cost),
+                format_currency(data.subagent_cost),
+            ],
+            ExtraColumn::Tools => vec![
+                data.tool_calls.to_formatted_string(&Locale::en),
+                cost_per_unit(data.total_cost, data.tool_calls),
+            ],
+            ExtraColumn::Errors => vec![
+                data.api_errors.to_formatted_string(&Locale::en),
+                match data.message_count + data.api_errors {
+                    0 => "-".to_string(),
+                    requests => format!("{:.1}%", data.api_errors as f64 / requests as f64 * 100.0),
+                },
+            ],
+            ExtraColumn::Sessions => vec![
+                data.sessions.to_formatted_string(&Locale::en),
+                cost_per_unit(data.total_cost, data.sessions),
+            ],
+            ExtraColumn::Activity => match (data.first_timestamp, data.last_timestamp) {
+                (Some(first), Some(last)) => {
+                    let (first, last) = format_activity_span(first, last, timezone);
+                    vec![first, last]
+                }
+                _ => vec!["-".to_string(), "-".to_string()],
+            },
+            ExtraColumn::Hours => vec![
+                data.active_hours.to_string(),
+                cost_per_unit(data.total_cost, data.active_hours.into()),
+            ],
+            ExtraColumn::ZScore => vec![
+                data.z_score
+                    .map_or_else(|| "-".to_string(), |score| format!("{score:+.2}")),
+            ],
+        })
+        .collect()
+}
+
+fn cost_per_unit(cost: f64, units: u64) -> String {
+    if units == 0 {
+        return "-".to_string();
+    }
+    format_currency(cost / units as f64)
+}
+
 pub fn format_number(num: f64) -> String {
     if num.is_nan() || num.is_infinite() {
         return num.to_string();
@@ -98,25 +205,121 @@ fn rounded_hundredths(tokens: u64, divisor: u64) -> u64 {
     whole * 100 + (remainder * 100 + divisor / 2) / divisor
 }
 
+/// Currency costs are shown in, from the config's `[currency]` table. Costs are calculated in
+/// US dollars and converted at `rate` when shown; JSON output stays in US dollars.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DisplayCurrency {
+    pub code: String,
+    pub symbol: String,
+    /// Units of the currency one US dollar buys.
+    pub rate: f64,
+    pub decimals: usize,
+}
+
+static DISPLAY_CURRENCY: OnceLock<DisplayCurrency> = OnceLock::new();
+
+/// Installs the config's display currency; later calls are ignored.
+pub fn set_display_currency(currency: DisplayCurrency) {
+    let _ = DISPLAY_CURRENCY.set(currency);
+}
+
+/// ISO code of the currency costs are shown in: `USD` unless the config sets another.
+pub fn currency_code() -> &'static str {
+    DISPLAY_CURRENCY
+        .get()
+        .map_or("USD", |currency| currency.code.as_str())
+}
+
+/// Header of a cost column, e.g. `Cost (USD)`.
+pub fn cost_header(label: &str) -> String {
+    format!("{label} ({})", currency_code())
+}
+
+/// Formats a cost in US dollars in the display currency.
 pub fn format_currency(amount: f64) -> String {
+    match DISPLAY_CURRENCY.get() {
+        Some(currency) => {
+            format_amount(amount * currency.rate, &currency.symbol, currency.decimals)
+        }
+        None => format_amount(amount, "$", 2),
+    }
+}
+
+/// Formats a small cost in US dollars, such as a per-message rate, with two more decimals
+/// than [`format_currency`].
+pub fn format_currency_rate(amount: f64) -> String {
+    match DISPLAY_CURRENCY.get() {
+        Some(currency) => format_amount(
+            amount * currency.rate,
+            &currency.symbol,
+            currency.decimals + 2,
+        ),
+        None => format_amount(amount, "$", 4),
+    }
+}
+
+fn format_amount(amount: f64, symbol: &str, decimals: usize) -> String {
     if !amount.is_finite() {
-        return format!("${amount:.2}");
+        return format!("{symbol}{amount:.decimals$}");
     }
 
-    let rounded = format!("{amount:.2}");
+    let rounded = format!("{amount:.decimals$}");
     let (sign, rest) = rounded
         .strip_prefix('-')
         .map_or(("", rounded.as_str()), |value| ("-", value));
-    let (int_part, frac_part) = rest.split_once('.').expect("currency has two decimals");
+    let (int_part, frac_part) = rest.split_once('.').unwrap_or((rest, ""));
     let grouped = int_part.parse::<u128>().map_or_else(
         |_| int_part.to_string(),
         |value| value.to_formatted_string(&Locale::en),
     );
 
-    format!("${sign}{grouped}.{frac_part}")
+    if frac_part.is_empty() {
+        format!("{symbol}{sign}{grouped}")
+    } else {
+        format!("{symbol}{sign}{grouped}.{frac_part}")
+    }
+}
+
+pub fn format_duration(duration: chrono::Duration) -> String {
+    let minutes = duration.num_minutes().max(0);
+    let (hours, minutes) = (minutes / 60, minutes % 60);
+    if hours > 0 {
+        format!("{hours}h {minutes:02}m")
+    } else {
+        format!("{minutes}m")
+    }
+}
+
+/// Display rules from the config, tried before the built-in ones.
+static MODEL_NAME_RULES: OnceLock<Vec<(Regex, String)>> = OnceLock::new();
+
+/// Installs the config's model-name display rules; later calls are ignored.
+pub fn set_model_name_rules(rules: Vec<(Regex, String)>) {
+    let _ = MODEL_NAME_RULES.set(rules);
+}
+
+pub fn format_model_name(model_name: &str) -> String {
+    format_model_name_with(
+        MODEL_NAME_RULES.get().map_or(&[], Vec::as_slice),
+        model_name,
+    )
 }
 
-fn format_model_name(model_name: &str) -> String {
+/// Rewrites `model_name` with the first matching rule, then shortens the result with the
+/// built-in Claude rules.
+fn format_model_name_with(rules: &[(Regex, String)], model_name: &str) -> String {
+    match rules
+        .iter()
+        .find(|(pattern, _)| pattern.is_match(model_name))
+    {
+        Some((pattern, replacement)) => {
+            format_builtin_model_name(&pattern.replace(model_name, replacement.as_str()))
+        }
+        None => format_builtin_model_name(model_name),
+    }
+}
+
+fn format_builtin_model_name(model_name: &str) -> String {
     if let Some(caps) = PI_MODEL_RE.captures(model_name) {
         return format!("[pi] {}", format_model_name(&caps[1]));
     }
@@ -136,6 +339,7 @@ fn format_model_name(model_name: &str) -> String {
     model_name.to_string()
 }
 
+#[cfg(feature = "tui")]
 pub fn format_models_display(models: &[String]) -> String {
     let mut unique = models
         .iter()
@@ -146,18 +350,28 @@ pub fn format_models_display(models: &[String]) -> String {
     unique.join(", ")
 }
 
-pub fn format_models_display_multiline(models: &[String]) -> String {
+#[cfg(test)]
+fn format_models_display_multiline(models: &[String]) -> String {
+    format_models_display_truncated(models, None)
+}
+
+pub fn format_models_display_truncated(models: &[String], max_models: Option<usize>) -> String {
     let mut unique = models
         .iter()
         .map(|m| format_model_name(m))
         .collect::<Vec<_>>();
     unique.sort();
     unique.dedup();
-    unique
+    let hidden = max_models.map_or(0, |max| unique.len().saturating_sub(max));
+    let mut lines = unique
         .into_iter()
+        .take(max_models.unwrap_or(usize::MAX))
         .map(|model| format!("- {model}"))
-        .collect::<Vec<_>>()
-        .join("\n")
+        .collect::<Vec<_>>();
+    if hidden > 0 {
+        lines.push(format!("+{hidden} more"));
+    }
+    lines.join("\n")
 }
 
 pub fn build_usage_row(
@@ -165,11 +379,13 @@ pub fn build_usage_row(
     data: &UsageDataRow,
     mode: TableMode,
     token_format: TokenFormat,
+    max_models: Option<usize>,
 ) -> Vec<String> {
+    let models = format_models_display_truncated(&data.models_used, max_models);
     match mode {
         TableMode::Full => vec![
             first_column_value.to_string(),
-            format_models_display_multiline(&data.models_used),
+            models,
             format_tokens(data.input_tokens, token_format),
             format_tokens(data.output_tokens, token_format),
             format_tokens(data.cache_creation_tokens, token_format),
@@ -179,7 +395,7 @@ pub fn build_usage_row(
         ],
         TableMode::Compact => vec![
             first_column_value.to_string(),
-            format_models_display_multiline(&data.models_used),
+            models,
             format_tokens(data.input_tokens, token_format),
             format_tokens(data.output_tokens, token_format),
             format_currency(data.total_cost),
@@ -213,6 +429,9 @@ pub fn build_totals_row(
     }
 }
 
+/// Marks a per-model row under its day or month in the first column.
+pub const BREAKDOWN_PREFIX: &str = "  |- ";
+
 pub fn build_breakdown_rows(
     breakdowns: &[ModelBreakdownRow],
     mode: TableMode,
@@ -222,7 +441,10 @@ pub fn build_breakdown_rows(
     for breakdown in breakdowns {
         match mode {
             TableMode::Full => rows.push(vec![
-                format!("  |- {}", format_model_name(&breakdown.model_name)),
+                format!(
+                    "{BREAKDOWN_PREFIX}{}",
+                    format_model_name(&breakdown.model_name)
+                ),
                 String::new(),
                 format_tokens(breakdown.input_tokens, token_format),
                 format_tokens(breakdown.output_tokens, token_format),
@@ -232,7 +454,10 @@ pub fn build_breakdown_rows(
                 format_currency(breakdown.cost),
             ]),
             TableMode::Compact => rows.push(vec![
-                format!("  |- {}", format_model_name(&breakdown.model_name)),
+                format!(
+                    "{BREAKDOWN_PREFIX}{}",
+                    format_model_name(&breakdown.model_name)
+                ),
                 String::new(),
                 format_tokens(breakdown.input_tokens, token_format),
                 format_tokens(breakdown.output_tokens, token_format),
@@ -254,6 +479,32 @@ mod tests {
         assert_eq!(format_number(1234567.89), "1,234,567.89");
     }
 
+    #[test]
+    fn format_model_name_applies_config_rules_before_builtin_ones() {
+        let rules = vec![
+            (
+                Regex::new(r"^bedrock/us\.anthropic\.(claude-.+)-v\d+:\d+$").unwrap(),
+                "$1".to_string(),
+            ),
+            (
+                Regex::new(r"^ft:([^:]+):.+$").unwrap(),
+                "$1 (tuned)".to_string(),
+            ),
+        ];
+        assert_eq!(
+            format_model_name_with(&rules, "bedrock/us.anthropic.claude-sonnet-4-20250514-v1:0"),
+            "sonnet-4"
+        );
+        assert_eq!(
+            format_model_name_with(&rules, "ft:gpt-4o:acme:run1:abc"),
+            "gpt-4o (tuned)"
+        );
+        assert_eq!(
+            format_model_name_with(&rules, "claude-opus-4-1-20250805"),
+            "opus-4-1"
+        );
+    }
+
     #[test]
     fn format_number_handles_small_values() {
         assert_eq!(format_number(0.0), "0");
@@ -309,9 +560,19 @@ mod tests {
                 total_tokens: 3_002_001_233,
                 total_cost: 12.34,
                 models_used: Vec::new(),
+                message_count: 0,
+                subagent_cost: 0.0,
+                tool_calls: 0,
+                api_errors: 0,
+                sessions: 0,
+                first_timestamp: None,
+                last_timestamp: None,
+                active_hours: 0,
+                z_score: None,
             },
             TableMode::Full,
             TokenFormat::HumanReadable,
+            None,
         );
 
         assert_eq!(
@@ -320,6 +581,95 @@ mod tests {
         );
     }
 
+    #[test]
+    fn extra_cells_report_messages_and_cost_per_message() {
+        let row = UsageDataRow {
+            input_tokens: 0,
+            output_tokens: 0,
+            cache_creation_tokens: 0,
+            cache_read_tokens: 0,
+            total_tokens: 0,
+            total_cost: 12.0,
+            models_used: Vec::new(),
+            message_count: 1_200,
+            subagent_cost: 3.0,
+            tool_calls: 0,
+            api_errors: 0,
+            sessions: 0,
+            first_timestamp: None,
+            last_timestamp: None,
+            active_hours: 0,
+            z_score: None,
+        };
+
+        assert_eq!(
+            extra_column_headers(&[ExtraColumn::Messages]),
+            vec!["Messages", "Cost/Msg"]
+        );
+        assert_eq!(
+            build_extra_cells(&row, &[ExtraColumn::Messages], None),
+            vec!["1,200", "$0.01"]
+        );
+        assert_eq!(
+            build_extra_cells(
+                &UsageDataRow {
+                    message_count: 0,
+                    ..row
+                },
+                &[ExtraColumn::Messages],
+                None
+            ),
+            vec!["0", "-"]
+        );
+    }
+
+    #[test]
+    fn extra_cells_split_main_and_subagent_cost() {
+        let row = UsageDataRow {
+            input_tokens: 0,
+            output_tokens: 0,
+            cache_creation_tokens: 0,
+            cache_read_tokens: 0,
+            total_tokens: 0,
+            total_cost: 10.0,
+            models_used: Vec::new(),
+            message_count: 4,
+            subagent_cost: 2.5,
+            tool_calls: 8,
+            api_errors: 0,
+            sessions: 0,
+            first_timestamp: None,
+            last_timestamp: None,
+            active_hours: 0,
+            z_score: None,
+        };
+
+        assert_eq!(
+            extra_column_headers(&[ExtraColumn::Subagents, ExtraColumn::Messages]),
+            vec!["Main Cost", "Subagent Cost", "Messages", "Cost/Msg"]
+        );
+        assert_eq!(
+            build_extra_cells(&row, &[ExtraColumn::Subagents], None),
+            vec!["$7.50", "$2.50"]
+        );
+        assert_eq!(
+            build_extra_cells(&row, &[ExtraColumn::Tools], None),
+            vec!["8", "$1.25"]
+        );
+        assert_eq!(
+            build_extra_cells(
+                &UsageDataRow {
+                    active_hours: 4,
+                    z_score: None,
+                    ..row
+                },
+                &[ExtraColumn::Hours],
+                None
+            ),
+            vec!["4", "$2.50"]
+        );
+    }
+
     #[test]
     fn format_currency_formats_amounts() {
         assert_eq!(format_currency(10.0), "$10.00");
@@ -346,6 +696,20 @@ mod tests {
         assert_eq!(format_currency(0.009), "$0.01");
     }
 
+    #[test]
+    fn format_amount_uses_the_symbol_and_decimals() {
+        assert_eq!(format_amount(1234.5, "€", 2), "€1,234.50");
+        assert_eq!(format_amount(-1234.5, "¥", 0), "¥-1,234");
+        assert_eq!(format_amount(0.01234, "$", 4), "$0.0123");
+    }
+
+    #[test]
+    fn format_duration_uses_hours_and_minutes() {
+        assert_eq!(format_duration(chrono::Duration::seconds(59)), "0m");
+        assert_eq!(format_duration(chrono::Duration::minutes(45)), "45m");
+        assert_eq!(format_duration(chrono::Duration::minutes(125)), "2h 05m");
+    }
+
     #[test]
     fn format_models_display_multiline_formats_single_model() {
         let models = vec!["claude-sonnet-4-20250514".to_string()];
@@ -414,6 +778,24 @@ mod tests {
         );
     }
 
+    #[test]
+    fn format_models_display_truncated_caps_list_with_remainder() {
+        let models = vec![
+            "claude-sonnet-4-20250514".to_string(),
+            "claude-opus-4-20250514".to_string(),
+            "claude-haiku-4-5-20251001".to_string(),
+            "gpt-5".to_string(),
+        ];
+        assert_eq!(
+            format_models_display_truncated(&models, Some(2)),
+            "- gpt-5\n- haiku-4-5\n+2 more"
+        );
+        assert_eq!(
+            format_models_display_truncated(&models, Some(4)),
+            format_models_display_multiline(&models)
+        );
+    }
+
     #[test]
     fn format_models_display_multiline_formats_pi_models() {
         let models = vec!["[pi] claude-opus-4-5".to_string()];
@@ -0,0 +1,147 @@
+use crate::table::{TokenFormat, format_currency, format_model_name, format_tokens};
+use anyhow::{Result, anyhow};
+use serde_json::Value;
+use std::collections::HashMap;
+use tera::{Context, Tera};
+
+const TEMPLATE_NAME: &str = "report";
+
+/// A report template in [Tera](https://keats.github.io/tera/docs/) syntax, with ccost's
+/// `currency`, `tokens`, `kmb`, and `model` filters added to Tera's built-in ones.
+pub struct Template {
+    tera: Tera,
+}
+
+impl Template {
+    pub fn parse(source: &str) -> Result<Self> {
+        let mut tera = Tera::default();
+        tera.add_raw_template(TEMPLATE_NAME, source)
+            .map_err(describe)?;
+        tera.register_filter("currency", currency);
+        tera.register_filter("tokens", tokens);
+        tera.register_filter("kmb", kmb);
+        tera.register_filter("model", model);
+        Ok(Self { tera })
+    }
+
+    pub fn render(&self, context: &Value) -> Result<String> {
+        let context = Context::from_value(context.clone()).map_err(describe)?;
+        self.tera.render(TEMPLATE_NAME, &context).map_err(describe)
+    }
+}
+
+/// Tera's error followed by its causes, which say what went wrong and where.
+fn describe(error: tera::Error) -> anyhow::Error {
+    let mut message = error.to_string();
+    let mut cause = std::error::Error::source(&error);
+    while let Some(error) = cause {
+        message.push_str(&format!(": {error}"));
+        cause = error.source();
+    }
+    anyhow!(message)
+}
+
+fn number(value: &Value, filter: &str) -> tera::Result<f64> {
+    value
+        .as_f64()
+        .ok_or_else(|| expected(filter, "a number", value))
+}
+
+fn expected(filter: &str, kind: &str, value: &Value) -> tera::Error {
+    tera::Error::msg(format!("Filter `{filter}` expects {kind}, got {value}"))
+}
+
+fn currency(value: &Value, _: &HashMap<String, Value>) -> tera::Result<Value> {
+    Ok(Value::String(format_currency(number(value, "currency")?)))
+}
+
+fn tokens(value: &Value, _: &HashMap<String, Value>) -> tera::Result<Value> {
+    Ok(Value::String(format_tokens(
+        number(value, "tokens")? as u64,
+        TokenFormat::Exact,
+    )))
+}
+
+fn kmb(value: &Value, _: &HashMap<String, Value>) -> tera::Result<Value> {
+    Ok(Value::String(format_tokens(
+        number(value, "kmb")? as u64,
+        TokenFormat::HumanReadable,
+    )))
+}
+
+fn model(value: &Value, _: &HashMap<String, Value>) -> tera::Result<Value> {
+    let name = value
+        .as_str()
+        .ok_or_else(|| expected("model", "a string", value))?;
+    Ok(Value::String(format_model_name(name)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn render(source: &str, context: Value) -> String {
+        Template::parse(source).unwrap().render(&context).unwrap()
+    }
+
+    #[test]
+    fn renders_values_filters_and_loops() {
+        let context = json!({
+            "title": "Report",
+            "monthly": [
+                { "month": "2026-01", "totalCost": 12.5, "totalTokens": 1234567 },
+                { "month": "2026-02", "totalCost": 0.5, "totalTokens": 900 },
+            ],
+        });
+        let source = "# {{ title | upper }}\n\
+                       {% for m in monthly -%}\n\
+                       - {{ m.month }}: {{ m.totalCost | currency }} ({{ m.totalTokens | kmb }})\
+                       {% if not loop.last %}\n{% endif %}\
+                       {%- endfor %}";
+        assert_eq!(
+            render(source, context),
+            "# REPORT\n- 2026-01: $12.50 (1.23M)\n- 2026-02: $0.50 (900)"
+        );
+    }
+
+    #[test]
+    fn supports_tera_conditions_and_builtin_filters() {
+        let source = "{% if cost > 10 %}high{% elif cost >= 1 %}mid{% else %}low{% endif %}";
+        assert_eq!(render(source, json!({ "cost": 25 })), "high");
+        assert_eq!(render(source, json!({ "cost": 1 })), "mid");
+        assert_eq!(render(source, json!({ "cost": 0.2 })), "low");
+        assert_eq!(
+            render(
+                "{{ project | default(value='(none)') }} {{ tokens | tokens }} {{ name | model }}",
+                json!({ "tokens": 1234567, "name": "claude-sonnet-4-20250514" })
+            ),
+            "(none) 1,234,567 sonnet-4"
+        );
+        assert_eq!(
+            render(
+                "{{ models | join(sep=' + ') }} {{ models | length }} {{ cost | round(precision=1) }}{# note #}",
+                json!({ "models": ["a", "b"], "cost": 2.46 })
+            ),
+            "a + b 2 2.5"
+        );
+    }
+
+    #[test]
+    fn parse_and_render_report_errors() {
+        assert!(Template::parse("{% for x in xs %}").is_err());
+        assert!(Template::parse("{% endif %}").is_err());
+        assert!(Template::parse("{{ title").is_err());
+        let template = Template::parse("{{ x | nope }}").unwrap();
+        assert!(template.render(&json!({ "x": 1 })).is_err());
+        let error = Template::parse("{{ x | currency }}")
+            .unwrap()
+            .render(&json!({ "x": "a" }))
+            .unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .contains("Filter `currency` expects a number")
+        );
+    }
+}
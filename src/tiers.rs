@@ -0,0 +1,159 @@
+use crate::data_loader::UsageEntry;
+use std::collections::BTreeMap;
+
+/// Tier shown for entries whose log doesn't record one.
+pub const UNKNOWN_TIER: &str = "unknown";
+
+/// Requests and usage on one service tier.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TierUsage {
+    pub tier: String,
+    pub request_count: u64,
+    pub total_tokens: u64,
+    pub total_cost: f64,
+}
+
+/// Service tier mix of one day or month.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TierPeriod {
+    pub period: String,
+    /// Tiers by request count, most used first.
+    pub tiers: Vec<TierUsage>,
+}
+
+impl TierPeriod {
+    pub fn request_count(&self) -> u64 {
+        self.tiers.iter().map(|tier| tier.request_count).sum()
+    }
+
+    /// Fraction of the period's requests that ran on `tier`.
+    pub fn request_share(&self, tier: &TierUsage) -> Option<f64> {
+        let total = self.request_count();
+        (total > 0).then(|| tier.request_count as f64 / total as f64)
+    }
+}
+
+/// All periods combined into one labeled `Total`.
+pub fn tier_totals(periods: &[TierPeriod]) -> TierPeriod {
+    let mut tiers: BTreeMap<&str, TierUsage> = BTreeMap::new();
+    for usage in periods.iter().flat_map(|period| &period.tiers) {
+        let total = tiers
+            .entry(usage.tier.as_str())
+            .or_insert_with(|| TierUsage {
+                tier: usage.tier.clone(),
+                ..Default::default()
+            });
+        total.request_count += usage.request_count;
+        total.total_tokens += usage.total_tokens;
+        total.total_cost += usage.total_cost;
+    }
+    let mut tiers = tiers.into_values().collect::<Vec<_>>();
+    sort_tiers(&mut tiers);
+    TierPeriod {
+        period: "Total".to_string(),
+        tiers,
+    }
+}
+
+fn sort_tiers(tiers: &mut [TierUsage]) {
+    tiers.sort_by(|a, b| {
+        b.request_count
+            .cmp(&a.request_count)
+            .then_with(|| a.tier.cmp(&b.tier))
+    });
+}
+
+/// Requests, tokens, and cost per service tier per day, or per month when `monthly`, oldest
+/// first. `<synthetic>` entries are left out.
+pub fn service_tier_usage(entries: &[UsageEntry], monthly: bool) -> Vec<TierPeriod> {
+    let mut periods: BTreeMap<&str, BTreeMap<&str, TierUsage>> = BTreeMap::new();
+    for entry in entries {
+        if entry.model.as_deref() == Some("<synthetic>") {
+            continue;
+        }
+        let period = if monthly {
+            entry.date.get(..7).unwrap_or(&entry.date)
+        } else {
+            entry.date.as_str()
+        };
+        let tier = entry.service_tier.as_deref().unwrap_or(UNKNOWN_TIER);
+        let usage = periods
+            .entry(period)
+            .or_default()
+            .entry(tier)
+            .or_insert_with(|| TierUsage {
+                tier: tier.to_string(),
+                ..Default::default()
+            });
+        usage.request_count += 1;
+        usage.total_tokens += entry.total_tokens;
+        usage.total_cost += entry.cost;
+    }
+    periods
+        .into_iter()
+        .map(|(period, tiers)| {
+            let mut tiers = tiers.into_values().collect::<Vec<_>>();
+            sort_tiers(&mut tiers);
+            TierPeriod {
+                period: period.to_string(),
+                tiers,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn entry(date: &str, tier: Option<&str>, cost: f64) -> UsageEntry {
+        UsageEntry {
+            timestamp: Utc.with_ymd_and_hms(2026, 1, 2, 3, 4, 5).unwrap(),
+            date: date.to_string(),
+            model: Some("claude-sonnet-4".to_string()),
+            input_tokens: 10,
+            output_tokens: 5,
+            total_tokens: 15,
+            cost,
+            local_hour: 3,
+            service_tier: tier.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn service_tier_usage_splits_each_period_by_tier() {
+        let entries = vec![
+            entry("2026-01-01", Some("standard"), 1.0),
+            entry("2026-01-01", Some("priority"), 2.0),
+            entry("2026-01-01", Some("priority"), 2.0),
+            entry("2026-01-01", None, 0.5),
+            entry("2026-02-03", Some("batch"), 0.25),
+        ];
+
+        let daily = service_tier_usage(&entries, false);
+        assert_eq!(daily.len(), 2);
+        assert_eq!(
+            daily[0]
+                .tiers
+                .iter()
+                .map(|tier| (tier.tier.as_str(), tier.request_count))
+                .collect::<Vec<_>>(),
+            vec![("priority", 2), ("standard", 1), (UNKNOWN_TIER, 1)]
+        );
+        assert_eq!(daily[0].tiers[0].total_tokens, 30);
+        assert_eq!(daily[0].tiers[0].total_cost, 4.0);
+        assert_eq!(daily[0].request_share(&daily[0].tiers[0]), Some(0.5));
+
+        let monthly = service_tier_usage(&entries, true);
+        assert_eq!(monthly[0].period, "2026-01");
+        assert_eq!(monthly[1].period, "2026-02");
+        assert_eq!(monthly[1].request_count(), 1);
+
+        let totals = tier_totals(&daily);
+        assert_eq!(totals.request_count(), 5);
+        assert_eq!(totals.tiers.len(), 4);
+        assert_eq!(totals.tiers[0].tier, "priority");
+    }
+}
@@ -1,4 +1,4 @@
-use chrono::{DateTime, Datelike, Local, NaiveDate, TimeZone};
+use chrono::{DateTime, Datelike, Local, NaiveDate, TimeZone, Timelike, Utc};
 use chrono_tz::Tz;
 use std::str::FromStr;
 use std::sync::LazyLock;
@@ -6,9 +6,11 @@ use std::sync::LazyLock;
 static SIMPLE_DATE_RE: LazyLock<regex::Regex> =
     LazyLock::new(|| regex::Regex::new(r"^\d{4}-\d{2}-\d{2}$").expect("valid date regex"));
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
 pub enum SortOrder {
+    /// Oldest first
     Asc,
+    /// Newest first
     Desc,
 }
 
@@ -24,7 +26,8 @@ impl FromStr for SortOrder {
     }
 }
 
-pub fn format_date(timestamp: &str, timezone: Option<&str>) -> Option<String> {
+#[cfg(test)]
+fn format_date(timestamp: &str, timezone: Option<&str>) -> Option<String> {
     let tz = match timezone {
         Some(tz_str) => Some(Tz::from_str(tz_str).ok()?),
         None => None,
@@ -46,6 +49,58 @@ pub fn format_date_with_tz(timestamp: &str, timezone: Option<Tz>) -> Option<Stri
     ))
 }
 
+/// Hour of day (0-23) of `timestamp` in the grouping timezone.
+pub fn local_hour(timestamp: DateTime<Utc>, timezone: Option<Tz>) -> u32 {
+    match timezone {
+        Some(tz) => timestamp.with_timezone(&tz).hour(),
+        None => timestamp.with_timezone(&Local).hour(),
+    }
+}
+
+pub fn format_timestamp(timestamp: DateTime<Utc>, timezone: Option<&str>) -> String {
+    const FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+    match timezone.and_then(|tz_str| Tz::from_str(tz_str).ok()) {
+        Some(tz) => timestamp.with_timezone(&tz).format(FORMAT).to_string(),
+        None => timestamp.with_timezone(&Local).format(FORMAT).to_string(),
+    }
+}
+
+/// Formats the first and last activity of a period: times only when both fall on the same
+/// local day, otherwise `MM-DD HH:MM`.
+pub fn format_activity_span(
+    first: DateTime<Utc>,
+    last: DateTime<Utc>,
+    timezone: Option<&str>,
+) -> (String, String) {
+    match timezone.and_then(|tz_str| Tz::from_str(tz_str).ok()) {
+        Some(tz) => format_local_span(first.with_timezone(&tz), last.with_timezone(&tz)),
+        None => format_local_span(first.with_timezone(&Local), last.with_timezone(&Local)),
+    }
+}
+
+fn format_local_span<Z: TimeZone>(first: DateTime<Z>, last: DateTime<Z>) -> (String, String)
+where
+    Z::Offset: std::fmt::Display,
+{
+    let pattern = if first.date_naive() == last.date_naive() {
+        "%H:%M"
+    } else {
+        "%m-%d %H:%M"
+    };
+    (
+        first.format(pattern).to_string(),
+        last.format(pattern).to_string(),
+    )
+}
+
+pub fn today_in_timezone(timezone: Option<&str>) -> Option<NaiveDate> {
+    let now = Utc::now();
+    match timezone {
+        Some(tz_str) => Some(now.with_timezone(&Tz::from_str(tz_str).ok()?).date_naive()),
+        None => Some(now.with_timezone(&Local).date_naive()),
+    }
+}
+
 pub fn format_month(date_str: &str) -> Option<String> {
     if date_str.len() >= 7 {
         Some(date_str[..7].to_string())
@@ -62,7 +117,7 @@ pub fn format_date_compact(date_str: &str, timezone: Option<&str>) -> Option<Str
         match timezone {
             Some(tz_str) => {
                 let tz = Tz::from_str(tz_str).ok()?;
-                let utc_dt = chrono::Utc.from_utc_datetime(&naive.and_hms_opt(0, 0, 0)?);
+                let utc_dt = Utc.from_utc_datetime(&naive.and_hms_opt(0, 0, 0)?);
                 utc_dt.with_timezone(&tz).date_naive()
             }
             None => {
@@ -91,6 +146,57 @@ pub fn format_date_compact(date_str: &str, timezone: Option<&str>) -> Option<Str
     ))
 }
 
+/// Days relative to today, picked by `--last`, `--yesterday`, `--this-week`, or `--this-month`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RelativeRange {
+    /// The last N days, including today.
+    LastDays(u32),
+    Yesterday,
+    /// Monday of the current week through today.
+    ThisWeek,
+    /// The first of the current month through today.
+    ThisMonth,
+}
+
+impl RelativeRange {
+    /// First and last day of the range, given today's date in the report timezone.
+    pub fn bounds(self, today: NaiveDate) -> (NaiveDate, NaiveDate) {
+        match self {
+            Self::LastDays(days) => (
+                today - chrono::Duration::days(i64::from(days.max(1)) - 1),
+                today,
+            ),
+            Self::Yesterday => {
+                let yesterday = today.pred_opt().unwrap_or(today);
+                (yesterday, yesterday)
+            }
+            Self::ThisWeek => (
+                today - chrono::Duration::days(i64::from(today.weekday().num_days_from_monday())),
+                today,
+            ),
+            Self::ThisMonth => (today.with_day(1).unwrap_or(today), today),
+        }
+    }
+}
+
+/// Parses a `--last` span into days: a count followed by `d` (days) or `w` (weeks), such as
+/// `7d` or `2w`; a bare count is days.
+pub fn parse_last_days(value: &str) -> Result<u32, String> {
+    let value = value.trim().to_ascii_lowercase();
+    let (count, unit) = match value.strip_suffix('w') {
+        Some(count) => (count, 7),
+        None => (value.strip_suffix('d').unwrap_or(&value), 1),
+    };
+    match count.parse::<u32>() {
+        Ok(count) if count > 0 => count
+            .checked_mul(unit)
+            .ok_or_else(|| format!("Span too long: {value}")),
+        _ => Err(format!(
+            "Invalid span: {value} (expected a count of days or weeks, such as 7d or 2w)"
+        )),
+    }
+}
+
 pub fn filter_by_date_range<T, F>(
     items: Vec<T>,
     get_date: F,
@@ -138,6 +244,27 @@ where
 mod tests {
     use super::*;
 
+    #[test]
+    fn relative_ranges_end_today_or_yesterday() {
+        // A Thursday.
+        let today = NaiveDate::from_ymd_opt(2026, 10, 15).unwrap();
+        let day = |d| NaiveDate::from_ymd_opt(2026, 10, d).unwrap();
+        assert_eq!(RelativeRange::LastDays(7).bounds(today), (day(9), today));
+        assert_eq!(RelativeRange::LastDays(1).bounds(today), (today, today));
+        assert_eq!(RelativeRange::Yesterday.bounds(today), (day(14), day(14)));
+        assert_eq!(RelativeRange::ThisWeek.bounds(today), (day(12), today));
+        assert_eq!(RelativeRange::ThisMonth.bounds(today), (day(1), today));
+    }
+
+    #[test]
+    fn last_spans_count_days_or_weeks() {
+        assert_eq!(parse_last_days("7d"), Ok(7));
+        assert_eq!(parse_last_days("2W"), Ok(14));
+        assert_eq!(parse_last_days("30"), Ok(30));
+        assert!(parse_last_days("0d").is_err());
+        assert!(parse_last_days("1m").is_err());
+    }
+
     #[test]
     fn format_date_to_yyyy_mm_dd() {
         let result = format_date("2024-08-04T12:00:00Z", None).unwrap();
@@ -166,6 +293,57 @@ mod tests {
         assert_eq!(result, "2024\n08-04");
     }
 
+    #[test]
+    fn format_timestamp_uses_timezone() {
+        let timestamp = DateTime::parse_from_rfc3339("2024-08-04T23:30:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(
+            format_timestamp(timestamp, Some("Asia/Tokyo")),
+            "2024-08-05 08:30:00"
+        );
+    }
+
+    #[test]
+    fn format_activity_span_shows_dates_only_across_days() {
+        let parse = |value: &str| {
+            DateTime::parse_from_rfc3339(value)
+                .unwrap()
+                .with_timezone(&Utc)
+        };
+        assert_eq!(
+            format_activity_span(
+                parse("2024-08-04T00:15:00Z"),
+                parse("2024-08-04T14:45:00Z"),
+                Some("Asia/Tokyo")
+            ),
+            ("09:15".to_string(), "23:45".to_string())
+        );
+        assert_eq!(
+            format_activity_span(
+                parse("2024-08-01T00:15:00Z"),
+                parse("2024-08-04T14:45:00Z"),
+                Some("UTC")
+            ),
+            ("08-01 00:15".to_string(), "08-04 14:45".to_string())
+        );
+    }
+
+    #[test]
+    fn local_hour_uses_timezone() {
+        let timestamp = DateTime::parse_from_rfc3339("2024-08-04T23:30:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(local_hour(timestamp, Some(chrono_tz::UTC)), 23);
+        assert_eq!(local_hour(timestamp, Some(chrono_tz::Asia::Tokyo)), 8);
+    }
+
+    #[test]
+    fn today_in_timezone_rejects_unknown_timezone() {
+        assert!(today_in_timezone(Some("UTC")).is_some());
+        assert!(today_in_timezone(Some("Not/AZone")).is_none());
+    }
+
     #[test]
     fn filter_by_date_range_filters_items() {
         let items = vec!["2024-01-01", "2024-01-02", "2024-01-03", "2024-01-04"];
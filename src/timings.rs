@@ -0,0 +1,139 @@
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// Stages of loading and reporting usage, in pipeline order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Phase {
+    /// Finding data roots and listing log files.
+    Discovery,
+    /// Ordering parsed files so duplicates resolve the same way on every run.
+    Sorting,
+    /// Reading and parsing log files.
+    Parsing,
+    Dedup,
+    /// Grouping entries into days, months, or report rows.
+    Aggregation,
+    /// Everything outside the other phases, mostly building and writing the report.
+    Rendering,
+}
+
+impl Phase {
+    fn label(self) -> &'static str {
+        match self {
+            Phase::Discovery => "discovery",
+            Phase::Sorting => "sorting",
+            Phase::Parsing => "parsing",
+            Phase::Dedup => "dedup",
+            Phase::Aggregation => "aggregation",
+            Phase::Rendering => "rendering",
+        }
+    }
+
+    fn unit(self) -> &'static str {
+        match self {
+            Phase::Discovery | Phase::Sorting => "files",
+            Phase::Parsing => "entries",
+            Phase::Dedup => "entries kept",
+            Phase::Aggregation | Phase::Rendering => "",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct PhaseTotal {
+    elapsed: Duration,
+    count: Option<usize>,
+}
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Time and item count per phase, summed over every data source.
+static PHASES: Mutex<BTreeMap<Phase, PhaseTotal>> = Mutex::new(BTreeMap::new());
+
+pub fn enable_timings() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// Start of a phase, or `None` when `--timings` is off.
+pub fn phase_start() -> Option<Instant> {
+    ENABLED.load(Ordering::Relaxed).then(Instant::now)
+}
+
+/// Adds the time since `started` (and `count` items) to `phase`.
+pub fn record_phase(phase: Phase, started: Option<Instant>, count: Option<usize>) {
+    let Some(started) = started else {
+        return;
+    };
+    if let Ok(mut phases) = PHASES.lock() {
+        let total = phases.entry(phase).or_default();
+        total.elapsed += started.elapsed();
+        if let Some(count) = count {
+            *total.count.get_or_insert(0) += count;
+        }
+    }
+}
+
+/// Prints the recorded phases to stderr. Rendering is whatever part of `total` (the whole
+/// command) the loading phases don't account for.
+pub fn print_timings(total: Duration) {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+    let phases = PHASES
+        .lock()
+        .map(|phases| phases.clone())
+        .unwrap_or_default();
+    eprint!("{}", timings_report(&phases, total));
+}
+
+fn timings_report(phases: &BTreeMap<Phase, PhaseTotal>, total: Duration) -> String {
+    let loading = phases.values().map(|phase| phase.elapsed).sum::<Duration>();
+    let mut phases = phases.clone();
+    phases.entry(Phase::Rendering).or_default().elapsed += total.saturating_sub(loading);
+
+    let mut output = String::from("Timings:\n");
+    for (phase, PhaseTotal { elapsed, count }) in &phases {
+        let count = count.map_or_else(String::new, |count| format!("  {count} {}", phase.unit()));
+        output.push_str(&format!(
+            "  {:<12}{:>10.1} ms{count}\n",
+            phase.label(),
+            elapsed.as_secs_f64() * 1000.0
+        ));
+    }
+    output.push_str(&format!(
+        "  {:<12}{:>10.1} ms\n",
+        "total",
+        total.as_secs_f64() * 1000.0
+    ));
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timings_report_lists_phases_in_order_with_rendering_as_the_rest() {
+        let mut phases = BTreeMap::new();
+        phases.insert(
+            Phase::Parsing,
+            PhaseTotal {
+                elapsed: Duration::from_millis(30),
+                count: Some(1_200),
+            },
+        );
+        phases.insert(
+            Phase::Discovery,
+            PhaseTotal {
+                elapsed: Duration::from_millis(5),
+                count: Some(12),
+            },
+        );
+        assert_eq!(
+            timings_report(&phases, Duration::from_millis(50)),
+            "Timings:\n  discovery          5.0 ms  12 files\n  parsing           30.0 ms  1200 entries\n  rendering         15.0 ms\n  total             50.0 ms\n"
+        );
+    }
+}
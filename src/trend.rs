@@ -0,0 +1,154 @@
+use crate::data_loader::DailyUsage;
+use chrono::NaiveDate;
+use std::collections::BTreeMap;
+
+/// Days projected past the last day of data.
+pub const PROJECTION_DAYS: u32 = 30;
+
+/// Least-squares line through daily cost, with a projection of the days that follow.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CostTrend {
+    /// Calendar days covered, counting days without usage as zero cost.
+    pub days: u32,
+    /// Change in daily cost per day, in USD.
+    pub slope_per_day: f64,
+    /// Fitted daily cost on the last day.
+    pub current_daily_cost: f64,
+    /// Sum of the fitted daily costs over the next [`PROJECTION_DAYS`] days (never negative).
+    pub projected_cost: f64,
+    /// How much of the day-to-day variation the line explains (0 to 1).
+    pub r_squared: f64,
+}
+
+/// Fits daily cost (summed across projects) against the day number. Needs at least two
+/// calendar days between the first and last dates.
+pub fn cost_trend(daily: &[DailyUsage]) -> Option<CostTrend> {
+    let mut costs: BTreeMap<NaiveDate, f64> = BTreeMap::new();
+    for entry in daily {
+        let date = NaiveDate::parse_from_str(&entry.date, "%Y-%m-%d").ok()?;
+        *costs.entry(date).or_default() += entry.total_cost;
+    }
+    let (first, last) = (*costs.keys().next()?, *costs.keys().next_back()?);
+    let days = u32::try_from((last - first).num_days() + 1).ok()?;
+    if days < 2 {
+        return None;
+    }
+
+    let count = f64::from(days);
+    let points = (0..days)
+        .map(|day| {
+            let date = first + chrono::Duration::days(i64::from(day));
+            (f64::from(day), costs.get(&date).copied().unwrap_or(0.0))
+        })
+        .collect::<Vec<_>>();
+    let mean_x = (count - 1.0) / 2.0;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / count;
+    let sxx = points
+        .iter()
+        .map(|(x, _)| (x - mean_x).powi(2))
+        .sum::<f64>();
+    let sxy = points
+        .iter()
+        .map(|(x, y)| (x - mean_x) * (y - mean_y))
+        .sum::<f64>();
+    let syy = points
+        .iter()
+        .map(|(_, y)| (y - mean_y).powi(2))
+        .sum::<f64>();
+    let slope = sxy / sxx;
+    let intercept = mean_y - slope * mean_x;
+    let fitted = |day: f64| intercept + slope * day;
+
+    Some(CostTrend {
+        days,
+        slope_per_day: slope,
+        current_daily_cost: fitted(count - 1.0),
+        projected_cost: (0..PROJECTION_DAYS)
+            .map(|ahead| fitted(count + f64::from(ahead)).max(0.0))
+            .sum(),
+        r_squared: if syy > 0.0 {
+            sxy * sxy / (sxx * syy)
+        } else {
+            0.0
+        },
+    })
+}
+
+/// Standard score of each value against the mean and (population) standard deviation of
+/// all of them; `None` when there are fewer than two values or they are all equal.
+pub fn z_scores(values: &[f64]) -> Vec<Option<f64>> {
+    let count = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / count;
+    let deviation = (values
+        .iter()
+        .map(|value| (value - mean).powi(2))
+        .sum::<f64>()
+        / count)
+        .sqrt();
+    values
+        .iter()
+        .map(|value| (values.len() >= 2 && deviation > 0.0).then(|| (value - mean) / deviation))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(date: &str, cost: f64) -> DailyUsage {
+        DailyUsage {
+            date: date.to_string(),
+            total_cost: cost,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn cost_trend_fits_a_line_and_projects_it() {
+        let daily = vec![
+            day("2026-01-01", 1.0),
+            day("2026-01-02", 2.0),
+            day("2026-01-03", 3.0),
+            day("2026-01-04", 4.0),
+        ];
+        let trend = cost_trend(&daily).unwrap();
+        assert_eq!(trend.days, 4);
+        assert!((trend.slope_per_day - 1.0).abs() < 1e-9);
+        assert!((trend.current_daily_cost - 4.0).abs() < 1e-9);
+        // Days 5 through 34: 30 * (5 + 34) / 2.
+        assert!((trend.projected_cost - 585.0).abs() < 1e-9);
+        assert!((trend.r_squared - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn cost_trend_counts_gaps_as_zero_and_never_projects_below_zero() {
+        let daily = vec![day("2026-01-01", 10.0), day("2026-01-03", 0.0)];
+        let trend = cost_trend(&daily).unwrap();
+        assert_eq!(trend.days, 3);
+        assert!((trend.slope_per_day + 5.0).abs() < 1e-9);
+        assert_eq!(trend.projected_cost, 0.0);
+
+        assert!(cost_trend(&[day("2026-01-01", 1.0)]).is_none());
+        assert!(cost_trend(&[]).is_none());
+    }
+
+    #[test]
+    fn z_scores_measure_distance_from_the_mean() {
+        assert_eq!(
+            z_scores(&[2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0]),
+            vec![
+                Some(-1.5),
+                Some(-0.5),
+                Some(-0.5),
+                Some(-0.5),
+                Some(0.0),
+                Some(0.0),
+                Some(1.0),
+                Some(2.0)
+            ]
+        );
+        assert_eq!(z_scores(&[3.0, 3.0]), vec![None, None]);
+        assert_eq!(z_scores(&[3.0]), vec![None]);
+        assert!(z_scores(&[]).is_empty());
+    }
+}
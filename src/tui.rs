@@ -0,0 +1,434 @@
+//! Interactive dashboard for `ccost tui`, built with ratatui behind the `tui` feature.
+
+use crate::data_loader::{DailyUsage, MonthlyUsage};
+use crate::projects::ProjectUsage;
+use crate::sessions::SessionUsage;
+use crate::table::{
+    TokenFormat, cost_header, format_currency, format_models_display, format_tokens,
+};
+use anyhow::Result;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::widgets::{Block, Cell, Paragraph, Row, Sparkline, Table, TableState, Tabs};
+use ratatui::{DefaultTerminal, Frame};
+use std::cmp::Reverse;
+
+const TABS: [&str; 4] = ["Daily", "Monthly", "Projects", "Sessions"];
+
+/// Row order picked with `s`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DashboardSort {
+    /// The order the report was loaded in: by date, or by cost for projects and sessions.
+    Default,
+    /// Most expensive first.
+    Cost,
+    /// Most tokens first.
+    Tokens,
+}
+
+impl DashboardSort {
+    fn next(self) -> Self {
+        match self {
+            Self::Default => Self::Cost,
+            Self::Cost => Self::Tokens,
+            Self::Tokens => Self::Default,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Default => "default",
+            Self::Cost => "cost",
+            Self::Tokens => "tokens",
+        }
+    }
+}
+
+/// One table row: display cells plus the values rows are sorted by.
+#[derive(Debug, Clone)]
+struct DashboardRow {
+    cells: Vec<String>,
+    cost: f64,
+    tokens: u64,
+}
+
+/// Loaded reports and the dashboard's navigation state.
+pub struct Dashboard {
+    title: String,
+    /// Daily cost in date order, for the sparkline.
+    daily_costs: Vec<f64>,
+    headers: [Vec<String>; 4],
+    rows: [Vec<DashboardRow>; 4],
+    tab: usize,
+    sort: DashboardSort,
+    state: TableState,
+}
+
+impl Dashboard {
+    pub fn new(
+        title: impl Into<String>,
+        daily: &[DailyUsage],
+        monthly: &[MonthlyUsage],
+        projects: &[ProjectUsage],
+        sessions: &[SessionUsage],
+    ) -> Self {
+        let mut dated = daily
+            .iter()
+            .map(|day| (day.date.as_str(), day.total_cost))
+            .collect::<Vec<_>>();
+        dated.sort_by(|a, b| a.0.cmp(b.0));
+        let usage_header = |first: &[&str]| {
+            first
+                .iter()
+                .chain(&["Models", "Total Tokens"])
+                .map(|header| header.to_string())
+                .chain([cost_header("Cost")])
+                .collect::<Vec<_>>()
+        };
+        let usage_cells = |models: &[String], tokens: u64, cost: f64| {
+            vec![
+                format_models_display(models),
+                format_tokens(tokens, TokenFormat::HumanReadable),
+                format_currency(cost),
+            ]
+        };
+        Self {
+            title: title.into(),
+            daily_costs: dated.into_iter().map(|(_, cost)| cost).collect(),
+            headers: [
+                usage_header(&["Date"]),
+                usage_header(&["Month"]),
+                usage_header(&["Project", "Last Activity", "Sessions"]),
+                usage_header(&["Session", "Project", "Last Activity"]),
+            ],
+            rows: [
+                daily
+                    .iter()
+                    .map(|day| DashboardRow {
+                        cells: [
+                            vec![day.date.clone()],
+                            usage_cells(&day.models_used, day.total_tokens, day.total_cost),
+                        ]
+                        .concat(),
+                        cost: day.total_cost,
+                        tokens: day.total_tokens,
+                    })
+                    .collect(),
+                monthly
+                    .iter()
+                    .map(|month| DashboardRow {
+                        cells: [
+                            vec![month.month.clone()],
+                            usage_cells(&month.models_used, month.total_tokens, month.total_cost),
+                        ]
+                        .concat(),
+                        cost: month.total_cost,
+                        tokens: month.total_tokens,
+                    })
+                    .collect(),
+                projects
+                    .iter()
+                    .map(|project| DashboardRow {
+                        cells: [
+                            vec![
+                                project.project.clone(),
+                                project.last_date.clone(),
+                                project.session_count.to_string(),
+                            ],
+                            usage_cells(
+                                &project.models_used,
+                                project.total_tokens,
+                                project.total_cost,
+                            ),
+                        ]
+                        .concat(),
+                        cost: project.total_cost,
+                        tokens: project.total_tokens,
+                    })
+                    .collect(),
+                sessions
+                    .iter()
+                    .map(|session| DashboardRow {
+                        cells: [
+                            vec![
+                                session
+                                    .title
+                                    .clone()
+                                    .unwrap_or_else(|| session.session_id.clone()),
+                                session.project.clone().unwrap_or_default(),
+                                session.last_timestamp.format("%Y-%m-%d %H:%M").to_string(),
+                            ],
+                            usage_cells(
+                                &session.models_used,
+                                session.total_tokens,
+                                session.total_cost,
+                            ),
+                        ]
+                        .concat(),
+                        cost: session.total_cost,
+                        tokens: session.total_tokens,
+                    })
+                    .collect(),
+            ],
+            tab: 0,
+            sort: DashboardSort::Default,
+            state: TableState::default().with_selected(Some(0)),
+        }
+    }
+
+    /// Rows of the current tab in the current sort order.
+    fn sorted_rows(&self) -> Vec<&DashboardRow> {
+        let mut rows = self.rows[self.tab].iter().collect::<Vec<_>>();
+        match self.sort {
+            DashboardSort::Default => {}
+            DashboardSort::Cost => rows.sort_by(|a, b| b.cost.total_cmp(&a.cost)),
+            DashboardSort::Tokens => rows.sort_by_key(|row| Reverse(row.tokens)),
+        }
+        rows
+    }
+
+    /// Applies a key press; returns `false` when the dashboard should close.
+    pub fn handle_key(&mut self, key: KeyCode) -> bool {
+        let row_count = self.rows[self.tab].len();
+        match key {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Tab | KeyCode::Right | KeyCode::Char('l') => {
+                self.select_tab((self.tab + 1) % TABS.len());
+            }
+            KeyCode::BackTab | KeyCode::Left | KeyCode::Char('h') => {
+                self.select_tab((self.tab + TABS.len() - 1) % TABS.len());
+            }
+            KeyCode::Char(digit @ '1'..='4') => {
+                self.select_tab(digit as usize - '1' as usize);
+            }
+            KeyCode::Down | KeyCode::Char('j') if row_count > 0 => {
+                let next = self.state.selected().map_or(0, |index| index + 1);
+                self.state.select(Some(next.min(row_count - 1)));
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                let previous = self
+                    .state
+                    .selected()
+                    .map_or(0, |index| index.saturating_sub(1));
+                self.state.select(Some(previous));
+            }
+            KeyCode::Home | KeyCode::Char('g') => self.state.select(Some(0)),
+            KeyCode::End | KeyCode::Char('G') => {
+                self.state.select(Some(row_count.saturating_sub(1)));
+            }
+            KeyCode::Char('s') => {
+                self.sort = self.sort.next();
+                self.state.select(Some(0));
+            }
+            _ => {}
+        }
+        true
+    }
+
+    fn select_tab(&mut self, tab: usize) {
+        self.tab = tab;
+        self.state.select(Some(0));
+    }
+
+    fn render(&mut self, frame: &mut Frame) {
+        let [tabs_area, sparkline_area, table_area, help_area] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Length(6),
+            Constraint::Min(3),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        frame.render_widget(
+            Tabs::new(TABS)
+                .select(self.tab)
+                .highlight_style(Style::new().add_modifier(Modifier::BOLD | Modifier::REVERSED))
+                .block(Block::bordered().title(self.title.as_str())),
+            tabs_area,
+        );
+        self.render_sparkline(frame, sparkline_area);
+        self.render_table(frame, table_area);
+        frame.render_widget(
+            Paragraph::new(format!(
+                "←/→ tab  ↑/↓ move  s sort ({})  q quit",
+                self.sort.label()
+            )),
+            help_area,
+        );
+    }
+
+    fn render_sparkline(&self, frame: &mut Frame, area: Rect) {
+        let total = self.daily_costs.iter().sum::<f64>();
+        // Only the most recent days that fit, in cents so small days still show.
+        let width = usize::from(area.width.saturating_sub(2));
+        let start = self.daily_costs.len().saturating_sub(width);
+        let cents = self.daily_costs[start..]
+            .iter()
+            .map(|cost| (cost * 100.0).round().max(0.0) as u64)
+            .collect::<Vec<_>>();
+        frame.render_widget(
+            Sparkline::default()
+                .block(Block::bordered().title(format!(
+                    "Daily cost ({} days, {})",
+                    self.daily_costs.len(),
+                    format_currency(total)
+                )))
+                .data(&cents),
+            area,
+        );
+    }
+
+    fn render_table(&mut self, frame: &mut Frame, area: Rect) {
+        let header = &self.headers[self.tab];
+        let rows = self.sorted_rows();
+        let widths = header
+            .iter()
+            .enumerate()
+            .map(|(column, title)| {
+                let widest = rows
+                    .iter()
+                    .filter_map(|row| row.cells.get(column))
+                    .map(|cell| cell.chars().count())
+                    .max()
+                    .unwrap_or(0)
+                    .max(title.len());
+                Constraint::Length(widest.min(60) as u16)
+            })
+            .collect::<Vec<_>>();
+        let table = Table::new(
+            rows.iter().map(|row| {
+                Row::new(
+                    row.cells
+                        .iter()
+                        .map(|cell| Cell::from(cell.replace('\n', ", "))),
+                )
+            }),
+            widths,
+        )
+        .header(Row::new(header.clone()).style(Style::new().add_modifier(Modifier::BOLD)))
+        .row_highlight_style(Style::new().add_modifier(Modifier::REVERSED))
+        .block(Block::bordered().title(format!(
+            "{} ({} rows)",
+            TABS[self.tab],
+            rows.len()
+        )));
+        frame.render_stateful_widget(table, area, &mut self.state);
+    }
+}
+
+/// Takes over the terminal and shows `dashboard` until `q` or Esc is pressed.
+pub fn run(mut dashboard: Dashboard) -> Result<()> {
+    let mut terminal = ratatui::init();
+    let result = event_loop(&mut terminal, &mut dashboard);
+    ratatui::restore();
+    result
+}
+
+fn event_loop(terminal: &mut DefaultTerminal, dashboard: &mut Dashboard) -> Result<()> {
+    loop {
+        terminal.draw(|frame| dashboard.render(frame))?;
+        if let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+            && !dashboard.handle_key(key.code)
+        {
+            return Ok(());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::Terminal;
+    use ratatui::backend::TestBackend;
+
+    fn day(date: &str, tokens: u64, cost: f64) -> DailyUsage {
+        DailyUsage {
+            date: date.to_string(),
+            input_tokens: tokens,
+            output_tokens: 0,
+            cache_creation_tokens: 0,
+            cache_read_tokens: 0,
+            total_tokens: tokens,
+            total_cost: cost,
+            models_used: vec!["claude-sonnet-4-20250514".to_string()],
+            model_breakdowns: Vec::new(),
+            project: None,
+            message_count: 1,
+            subagent_cost: 0.0,
+            tool_calls: 0,
+            api_errors: 0,
+            logged_costs: 0,
+            session_ids: Vec::new(),
+            first_timestamp: None,
+            last_timestamp: None,
+            hour_mask: 0,
+        }
+    }
+
+    #[test]
+    fn keys_switch_tabs_move_the_selection_and_cycle_sorting() {
+        let daily = vec![
+            day("2026-01-01", 500, 1.0),
+            day("2026-01-02", 100, 3.0),
+            day("2026-01-03", 300, 2.0),
+        ];
+        let mut dashboard = Dashboard::new("Usage", &daily, &[], &[], &[]);
+
+        let dates = |dashboard: &Dashboard| {
+            dashboard
+                .sorted_rows()
+                .iter()
+                .map(|row| row.cells[0].clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            dates(&dashboard),
+            ["2026-01-01", "2026-01-02", "2026-01-03"]
+        );
+        assert!(dashboard.handle_key(KeyCode::Char('s')));
+        assert_eq!(
+            dates(&dashboard),
+            ["2026-01-02", "2026-01-03", "2026-01-01"]
+        );
+        dashboard.handle_key(KeyCode::Char('s'));
+        assert_eq!(
+            dates(&dashboard),
+            ["2026-01-01", "2026-01-03", "2026-01-02"]
+        );
+
+        for _ in 0..5 {
+            dashboard.handle_key(KeyCode::Down);
+        }
+        assert_eq!(dashboard.state.selected(), Some(2));
+        dashboard.handle_key(KeyCode::Left);
+        assert_eq!(dashboard.tab, 3);
+        assert_eq!(dashboard.state.selected(), Some(0));
+        dashboard.handle_key(KeyCode::Down);
+        assert_eq!(dashboard.state.selected(), Some(0));
+        dashboard.handle_key(KeyCode::Char('2'));
+        assert_eq!(dashboard.tab, 1);
+        assert!(!dashboard.handle_key(KeyCode::Char('q')));
+    }
+
+    #[test]
+    fn render_draws_tabs_sparkline_and_rows() {
+        let daily = vec![day("2026-01-01", 500, 1.0), day("2026-01-02", 100, 3.0)];
+        let mut dashboard = Dashboard::new("Usage", &daily, &[], &[], &[]);
+        let mut terminal = Terminal::new(TestBackend::new(80, 20)).unwrap();
+        terminal.draw(|frame| dashboard.render(frame)).unwrap();
+
+        let screen = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect::<String>();
+        assert!(screen.contains("Daily cost (2 days, $4.00)"));
+        assert!(screen.contains("2026-01-02"));
+        assert!(screen.contains("sonnet-4"));
+        assert!(screen.contains("s sort (default)"));
+    }
+}
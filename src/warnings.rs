@@ -0,0 +1,31 @@
+use std::sync::Mutex;
+
+/// Non-fatal problems found while loading data, printed to stderr after the report.
+static WARNINGS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+pub fn warn(message: impl Into<String>) {
+    if let Ok(mut warnings) = WARNINGS.lock() {
+        warnings.push(message.into());
+    }
+}
+
+pub fn take_warnings() -> Vec<String> {
+    WARNINGS
+        .lock()
+        .map(|mut warnings| std::mem::take(&mut *warnings))
+        .unwrap_or_default()
+}
+
+pub fn print_warnings() {
+    for warning in take_warnings() {
+        eprintln!("Warning: {warning}");
+    }
+}
+
+#[cfg(test)]
+pub(crate) fn recorded_warnings() -> Vec<String> {
+    WARNINGS
+        .lock()
+        .map(|warnings| warnings.clone())
+        .unwrap_or_default()
+}
@@ -0,0 +1,88 @@
+use ::notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use anyhow::{Result, anyhow};
+use std::path::PathBuf;
+use std::sync::mpsc::{Receiver, RecvTimeoutError, channel};
+use std::time::{Duration, Instant};
+
+/// Watches usage log directories through the platform's file system notifications, so
+/// `ccost live` loads the logs again only after one was written, created, or removed.
+pub struct LogWatcher {
+    _watcher: RecommendedWatcher,
+    events: Receiver<::notify::Result<Event>>,
+}
+
+impl LogWatcher {
+    /// Watches each of `roots` recursively. Fails when no root can be watched, e.g. when the
+    /// system's limit on watches is reached.
+    pub fn new(roots: &[PathBuf]) -> Result<Self> {
+        let (sender, events) = channel();
+        let mut watcher = ::notify::recommended_watcher(move |event| {
+            let _ = sender.send(event);
+        })?;
+        let mut watched = 0;
+        let mut last_error = None;
+        for root in roots {
+            match watcher.watch(root, RecursiveMode::Recursive) {
+                Ok(()) => watched += 1,
+                Err(error) => last_error = Some(error),
+            }
+        }
+        if watched == 0 {
+            return Err(
+                last_error.map_or_else(|| anyhow!("no log directories to watch"), Into::into)
+            );
+        }
+        Ok(Self {
+            _watcher: watcher,
+            events,
+        })
+    }
+
+    /// Waits for `timeout` and returns whether a log changed in the meantime. A watcher error
+    /// (such as dropped events) counts as a change, so nothing is missed.
+    pub fn changed_within(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        let mut changed = false;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match self.events.recv_timeout(remaining) {
+                Ok(Ok(event)) => changed |= is_change(&event.kind),
+                Ok(Err(_)) => changed = true,
+                Err(RecvTimeoutError::Timeout) => return changed,
+                Err(RecvTimeoutError::Disconnected) => {
+                    std::thread::sleep(remaining);
+                    return true;
+                }
+            }
+        }
+    }
+}
+
+/// Whether an event can change what the logs hold; reads and opens can't.
+fn is_change(kind: &EventKind) -> bool {
+    !matches!(kind, EventKind::Access(_))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::notify::event::{AccessKind, CreateKind, ModifyKind};
+
+    #[test]
+    fn writes_count_as_changes_but_reads_do_not() {
+        assert!(is_change(&EventKind::Create(CreateKind::File)));
+        assert!(is_change(&EventKind::Modify(ModifyKind::Any)));
+        assert!(!is_change(&EventKind::Access(AccessKind::Any)));
+    }
+
+    #[test]
+    fn reports_a_log_written_while_waiting() {
+        let dir = tempfile::tempdir().unwrap();
+        let watcher = LogWatcher::new(&[dir.path().to_path_buf()]).unwrap();
+        assert!(!watcher.changed_within(Duration::from_millis(50)));
+        std::fs::write(dir.path().join("session.jsonl"), "{}\n").unwrap();
+        assert!(watcher.changed_within(Duration::from_millis(500)));
+        assert!(LogWatcher::new(&[dir.path().join("missing")]).is_err());
+        assert!(LogWatcher::new(&[]).is_err());
+    }
+}
@@ -0,0 +1,212 @@
+use crate::data_loader::UsageEntry;
+use crate::pricing::{LiteLLMModelPricing, PricingFetcher, UsageTokens};
+use anyhow::{Result, anyhow};
+use std::collections::HashMap;
+
+const UNKNOWN_MODEL: &str = "unknown";
+
+/// Changes applied when re-pricing usage for `ccost whatif`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Scenario {
+    /// Bill cache reads as regular input and drop cache writes.
+    pub no_cache: bool,
+    /// `(from, to)` pairs: models whose name starts with `from` are priced as `to`.
+    pub model_map: Vec<(String, String)>,
+}
+
+impl Scenario {
+    /// Model whose prices apply to `model`; the longest matching `from` prefix wins.
+    pub fn priced_as<'a>(&'a self, model: &'a str) -> &'a str {
+        self.model_map
+            .iter()
+            .filter(|(from, _)| model.starts_with(from.as_str()))
+            .max_by_key(|(from, _)| from.len())
+            .map_or(model, |(_, to)| to.as_str())
+    }
+}
+
+/// Parses `FROM=TO` model substitutions for [`Scenario::model_map`].
+pub fn parse_model_map(entries: &[String]) -> Result<Vec<(String, String)>> {
+    entries
+        .iter()
+        .map(|entry| {
+            let (from, to) = entry
+                .split_once('=')
+                .map(|(from, to)| (from.trim(), to.trim()))
+                .filter(|(from, to)| !from.is_empty() && !to.is_empty())
+                .ok_or_else(|| anyhow!("Invalid model mapping: {entry} (expected FROM=TO)"))?;
+            Ok((from.to_string(), to.to_string()))
+        })
+        .collect()
+}
+
+/// Calculated cost of one model's usage as logged and under the scenario.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WhatIfModel {
+    pub model: String,
+    /// Model whose prices the scenario applied, when substituted.
+    pub priced_as: Option<String>,
+    pub message_count: u64,
+    pub actual_cost: f64,
+    pub simulated_cost: f64,
+}
+
+impl WhatIfModel {
+    pub fn difference(&self) -> f64 {
+        self.simulated_cost - self.actual_cost
+    }
+}
+
+/// Token counts an entry would have been billed for under `scenario`.
+pub fn scenario_tokens(entry: &UsageEntry, scenario: &Scenario) -> UsageTokens {
+    if scenario.no_cache {
+        // Without caching, every prompt token is sent (and billed) as fresh input.
+        return UsageTokens {
+            input_tokens: entry.input_tokens
+                + entry.cache_creation_tokens
+                + entry.cache_read_tokens,
+            output_tokens: entry.output_tokens,
+            cache_creation_input_tokens: 0,
+            cache_read_input_tokens: 0,
+        };
+    }
+    UsageTokens {
+        input_tokens: entry.input_tokens,
+        output_tokens: entry.output_tokens,
+        cache_creation_input_tokens: entry.cache_creation_tokens,
+        cache_read_input_tokens: entry.cache_read_tokens,
+    }
+}
+
+/// Re-prices every entry from its tokens, once as logged and once under `scenario` (with its
+/// model substitutions), and sums both per logged model, most expensive first. Both sides use
+/// calculated costs so they stay comparable regardless of the cost mode; models without
+/// pricing data cost nothing.
+pub fn simulate<'a>(
+    entries: &'a [UsageEntry],
+    scenario: &'a Scenario,
+    pricing: &PricingFetcher,
+) -> Vec<WhatIfModel> {
+    let mut prices: HashMap<&str, Option<LiteLLMModelPricing>> = HashMap::new();
+    let mut models: HashMap<&str, WhatIfModel> = HashMap::new();
+    for entry in entries {
+        let name = entry.model.as_deref().unwrap_or(UNKNOWN_MODEL);
+        if name == "<synthetic>" {
+            continue;
+        }
+        let substitute = scenario.priced_as(name);
+        let mut cost = |model: &'a str, tokens: &UsageTokens| {
+            prices
+                .entry(model)
+                .or_insert_with(|| pricing.get_model_pricing(model))
+                .as_ref()
+                .map_or(0.0, |price| {
+                    pricing.calculate_cost_from_pricing(tokens, price)
+                })
+        };
+        let actual = cost(name, &scenario_tokens(entry, &Scenario::default()));
+        let simulated = cost(substitute, &scenario_tokens(entry, scenario));
+        let model = models.entry(name).or_insert_with(|| WhatIfModel {
+            model: name.to_string(),
+            priced_as: (substitute != name).then(|| substitute.to_string()),
+            ..Default::default()
+        });
+        model.message_count += 1;
+        model.actual_cost += actual;
+        model.simulated_cost += simulated;
+    }
+    let mut models = models.into_values().collect::<Vec<_>>();
+    models.sort_by(|a, b| {
+        b.actual_cost
+            .total_cmp(&a.actual_cost)
+            .then_with(|| a.model.cmp(&b.model))
+    });
+    models
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn entry(model: &str) -> UsageEntry {
+        UsageEntry {
+            timestamp: Utc.with_ymd_and_hms(2026, 1, 2, 3, 4, 5).unwrap(),
+            date: "2026-01-02".to_string(),
+            model: Some(model.to_string()),
+            input_tokens: 1_000,
+            output_tokens: 100,
+            cache_creation_tokens: 2_000,
+            cache_read_tokens: 10_000,
+            total_tokens: 13_100,
+            local_hour: 3,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn no_cache_bills_cache_tokens_as_input() {
+        let scenario = Scenario {
+            no_cache: true,
+            ..Default::default()
+        };
+        let tokens = scenario_tokens(&entry("m"), &scenario);
+        assert_eq!(tokens.input_tokens, 13_000);
+        assert_eq!(tokens.output_tokens, 100);
+        assert_eq!(tokens.cache_creation_input_tokens, 0);
+        assert_eq!(tokens.cache_read_input_tokens, 0);
+    }
+
+    #[test]
+    fn simulate_compares_calculated_costs_per_model() {
+        let pricing = PricingFetcher::new();
+        let model = "claude-sonnet-4-20250514";
+        let price = pricing.get_model_pricing(model).unwrap();
+        let input = price.input_cost_per_token.unwrap();
+        let output = price.output_cost_per_token.unwrap();
+        let entries = vec![entry(model), entry(model), entry("not-a-real-model")];
+
+        let scenario = Scenario {
+            no_cache: true,
+            ..Default::default()
+        };
+        let models = simulate(&entries, &scenario, &pricing);
+        assert_eq!(models.len(), 2);
+        assert_eq!(models[0].model, model);
+        assert_eq!(models[0].message_count, 2);
+        let expected = 2.0 * (13_000.0 * input + 100.0 * output);
+        assert!((models[0].simulated_cost - expected).abs() < 1e-9);
+        assert!(models[0].difference() > 0.0);
+        assert_eq!(models[1].actual_cost, 0.0);
+
+        let unchanged = simulate(&entries, &Scenario::default(), &pricing);
+        assert_eq!(unchanged[0].difference(), 0.0);
+    }
+
+    #[test]
+    fn model_map_reprices_matching_models() {
+        let pricing = PricingFetcher::new();
+        let opus = "claude-opus-4-20250514";
+        let sonnet = "claude-sonnet-4-20250514";
+        let scenario = Scenario {
+            model_map: parse_model_map(&[
+                "claude-opus=claude-3-5-haiku-20241022".to_string(),
+                format!("claude-opus-4={sonnet}"),
+            ])
+            .unwrap(),
+            ..Default::default()
+        };
+        assert_eq!(scenario.priced_as(opus), sonnet);
+        assert_eq!(scenario.priced_as(sonnet), sonnet);
+
+        let models = simulate(&[entry(opus)], &scenario, &pricing);
+        let on_sonnet = simulate(&[entry(sonnet)], &Scenario::default(), &pricing);
+        assert_eq!(models[0].model, opus);
+        assert_eq!(models[0].priced_as.as_deref(), Some(sonnet));
+        assert!((models[0].simulated_cost - on_sonnet[0].actual_cost).abs() < 1e-12);
+        assert!(models[0].difference() < 0.0);
+
+        assert!(parse_model_map(&["claude-opus-4".to_string()]).is_err());
+        assert!(parse_model_map(&["=claude-sonnet-4".to_string()]).is_err());
+    }
+}